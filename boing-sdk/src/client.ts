@@ -8,12 +8,25 @@ import type {
   AccountProof,
   AccountState,
   Block,
+  BlockTag,
+  DelegationsResult,
   FaucetResult,
+  IntentInfo,
+  NodeInfo,
+  PeerInfo,
+  PostSignalingResult,
   QaCheckResponse,
   RegisterDappResult,
+  RelayInfo,
+  SignalingMessageInfo,
   SimulateResult,
   SubmitIntentResult,
+  SubmitSolutionResult,
   SubmitTransactionResult,
+  SyncStatus,
+  ValidatorDelegators,
+  ValidatorSlashes,
+  ValidatorStats,
   VerifyProofResult,
 } from './types.js';
 import { ensureHex, validateHex32 } from './hex.js';
@@ -121,38 +134,92 @@ export class BoingClient {
     }
   }
 
+  /** Chain id from genesis; must be set on every transaction and intent. */
+  async chainId(): Promise<number> {
+    return this.request<number>('boing_chainId', []);
+  }
+
   /** Current chain height (tip block number). */
   async chainHeight(): Promise<number> {
     return this.request<number>('boing_chainHeight', []);
   }
 
-  /** Get spendable balance for an account. Params: 32-byte account ID (hex). */
-  async getBalance(hexAccountId: string): Promise<AccountBalance> {
+  /** This node's peer id, chain id and genesis hash. */
+  async nodeInfo(): Promise<NodeInfo> {
+    return this.request<NodeInfo>('boing_nodeInfo', []);
+  }
+
+  /** Connected peers with their addresses and reputation. */
+  async peers(): Promise<PeerInfo[]> {
+    return this.request<PeerInfo[]>('boing_peers', []);
+  }
+
+  /** Sync progress, or false when the node has caught up with its peers. */
+  async syncing(): Promise<SyncStatus | false> {
+    return this.request<SyncStatus | false>('boing_syncing', []);
+  }
+
+  /** Get spendable balance for an account. Params: 32-byte account ID (hex), block tag (default latest). */
+  async getBalance(hexAccountId: string, tag: BlockTag = 'latest'): Promise<AccountBalance> {
     const hex = validateHex32(hexAccountId);
-    return this.request<AccountBalance>('boing_getBalance', [hex]);
+    return this.request<AccountBalance>('boing_getBalance', [hex, tag]);
   }
 
-  /** Get full account state (balance, nonce, stake). Params: 32-byte account ID (hex). */
-  async getAccount(hexAccountId: string): Promise<AccountState> {
+  /** Get full account state (balance, nonce, stake, pending unbonds). Params: 32-byte account ID (hex), block tag (default latest). */
+  async getAccount(hexAccountId: string, tag: BlockTag = 'latest'): Promise<AccountState> {
     const hex = validateHex32(hexAccountId);
-    return this.request<AccountState>('boing_getAccount', [hex]);
+    return this.request<AccountState>('boing_getAccount', [hex, tag]);
+  }
+
+  /** Get a validator's commission, voting power and delegators. Params: 32-byte account ID (hex). */
+  async getDelegators(hexValidatorId: string): Promise<ValidatorDelegators> {
+    const hex = validateHex32(hexValidatorId);
+    return this.request<ValidatorDelegators>('boing_getDelegators', [hex]);
+  }
+
+  /** Get a delegator's positions by validator. Params: 32-byte account ID (hex). */
+  async getDelegations(hexDelegatorId: string): Promise<DelegationsResult> {
+    const hex = validateHex32(hexDelegatorId);
+    return this.request<DelegationsResult>('boing_getDelegations', [hex]);
   }
 
-  /** Get block by height. Returns null if not found. */
-  async getBlockByHeight(height: number): Promise<Block | null> {
+  /** Get a validator's missed proposals and votes and jail status. Params: 32-byte account ID (hex). */
+  async getValidatorStats(hexValidatorId: string): Promise<ValidatorStats> {
+    const hex = validateHex32(hexValidatorId);
+    return this.request<ValidatorStats>('boing_getValidatorStats', [hex]);
+  }
+
+  /** Get a validator's slashes, appeals and jail status. Params: 32-byte account ID (hex). */
+  async getSlashes(hexValidatorId: string): Promise<ValidatorSlashes> {
+    const hex = validateHex32(hexValidatorId);
+    return this.request<ValidatorSlashes>('boing_getSlashes', [hex]);
+  }
+
+  /** List registered STUN/TURN relays, highest reputation first (leaving relays last). */
+  async listRelays(): Promise<RelayInfo[]> {
+    return this.request<RelayInfo[]>('boing_listRelays', []);
+  }
+
+  /** Get block by height or at a block tag. Returns null if not found. */
+  async getBlockByHeight(height: number | BlockTag): Promise<Block | null> {
     return this.request<Block | null>('boing_getBlockByHeight', [height]);
   }
 
+  /** Get the last finalized block (never reverted). */
+  async getFinalizedBlock(): Promise<Block> {
+    return this.request<Block>('boing_getFinalizedBlock', []);
+  }
+
   /** Get block by hash. Params: 32-byte block hash (hex). */
   async getBlockByHash(hexBlockHash: string): Promise<Block | null> {
     const hex = validateHex32(hexBlockHash);
     return this.request<Block | null>('boing_getBlockByHash', [hex]);
   }
 
-  /** Get Merkle proof for an account. Params: 32-byte account ID (hex). */
-  async getAccountProof(hexAccountId: string): Promise<AccountProof> {
+  /** Get Merkle proof for an account. Params: 32-byte account ID (hex), block tag (default latest). */
+  async getAccountProof(hexAccountId: string, tag: BlockTag = 'latest'): Promise<AccountProof> {
     const hex = validateHex32(hexAccountId);
-    return this.request<AccountProof>('boing_getAccountProof', [hex]);
+    return this.request<AccountProof>('boing_getAccountProof', [hex, tag]);
   }
 
   /** Verify an account Merkle proof. Params: hex proof, hex state root. */
@@ -191,6 +258,81 @@ export class BoingClient {
     return this.request<SubmitIntentResult>('boing_submitIntent', [ensureHex(hexSignedIntent)]);
   }
 
+  /** Intents waiting for a solver, soonest deadline first. */
+  async getIntents(): Promise<IntentInfo[]> {
+    return this.request<IntentInfo[]>('boing_getIntents', []);
+  }
+
+  /** Intent with its status (pending, fulfilled, cancelled, expired), or null if unknown. Params: 32-byte intent id (hex). */
+  async getIntent(hexIntentId: string): Promise<IntentInfo | null> {
+    return this.request<IntentInfo | null>('boing_getIntent', [validateHex32(hexIntentId)]);
+  }
+
+  /** Submit a solver's FulfillIntent transaction. Params: hex-encoded signed transaction. */
+  async submitSolution(hexSignedTx: string): Promise<SubmitSolutionResult> {
+    return this.request<SubmitSolutionResult>('boing_submitSolution', [ensureHex(hexSignedTx)]);
+  }
+
+  /**
+   * Follow intents as the node pools them (Server-Sent Events at /intents/subscribe): the
+   * pooled intents first, then each new one, local or gossiped. Runs until `signal` aborts
+   * or the node closes the stream; the same intent may be yielded twice, so dedupe by id.
+   */
+  async *subscribeIntents(signal?: AbortSignal): AsyncGenerator<IntentInfo> {
+    const res = await this.fetchImpl(`${this.baseUrl}/intents/subscribe`, {
+      headers: { Accept: 'text/event-stream' },
+      signal,
+    });
+    if (!res.ok || res.body == null) {
+      throw new BoingRpcError(-32000, `HTTP ${res.status}: ${res.statusText}`, undefined, 'intents/subscribe');
+    }
+    const reader = res.body.getReader();
+    const decoder = new TextDecoder();
+    let buffer = '';
+    try {
+      for (;;) {
+        const { done, value } = await reader.read();
+        if (done) return;
+        buffer += decoder.decode(value, { stream: true });
+        let end: number;
+        while ((end = buffer.indexOf('\n\n')) >= 0) {
+          const data = buffer
+            .slice(0, end)
+            .split('\n')
+            .filter((line) => line.startsWith('data:'))
+            .map((line) => line.slice(5).trimStart())
+            .join('\n');
+          buffer = buffer.slice(end + 2);
+          if (data) yield JSON.parse(data) as IntentInfo;
+        }
+      }
+    } finally {
+      reader.releaseLock();
+    }
+  }
+
+  /**
+   * Post a signed WebRTC offer, answer or ICE candidate. Params: hex-encoded signed
+   * signaling message; deposit (decimal string) reserved by offers.
+   */
+  async postSignalingMessage(hexSignedMessage: string, deposit?: string): Promise<PostSignalingResult> {
+    const params = deposit === undefined ? [ensureHex(hexSignedMessage)] : [ensureHex(hexSignedMessage), deposit];
+    return this.request<PostSignalingResult>('boing_postSignalingMessage', params);
+  }
+
+  /**
+   * Unanswered WebRTC offers for the recipient. Params: hex-encoded signaling query
+   * (recipient, chain id, Unix timestamp) signed by the recipient within the last minute.
+   */
+  async getPendingOffers(hexSignedQuery: string): Promise<SignalingMessageInfo[]> {
+    return this.request<SignalingMessageInfo[]>('boing_getPendingOffers', [ensureHex(hexSignedQuery)]);
+  }
+
+  /** All unexpired signaling messages for the recipient. Params: hex-encoded signed signaling query. */
+  async getSignalingMessages(hexSignedQuery: string): Promise<SignalingMessageInfo[]> {
+    return this.request<SignalingMessageInfo[]>('boing_getSignalingMessages', [ensureHex(hexSignedQuery)]);
+  }
+
   /**
    * Pre-flight QA check for a deployment (no submit).
    * Params: hex bytecode; optionally purpose_category and description_hash.
@@ -27,14 +27,36 @@ export {
 export type {
   AccountBalance,
   AccountState,
+  UnbondingEntry,
+  Delegator,
+  ValidatorDelegators,
+  DelegationPosition,
+  DelegationsResult,
+  SlashAppeal,
+  Slash,
+  ValidatorSlashes,
+  ValidatorStats,
+  RelayInfo,
   Block,
   BlockHeader,
+  BlockTag,
+  SyncStatus,
+  NodeInfo,
+  PeerInfo,
+  PostSignalingResult,
+  SignalingMessageInfo,
+  VrfOutput,
+  QuorumCertificate,
   AccountProof,
   VerifyProofResult,
   SimulateResult,
   SubmitTransactionResult,
   RegisterDappResult,
   SubmitIntentResult,
+  IntentKindInfo,
+  IntentStatus,
+  IntentInfo,
+  SubmitSolutionResult,
   QaCheckResult,
   QaCheckResponse,
   FaucetResult,
@@ -5,29 +5,180 @@
 /** Chain height (block number). */
 export type ChainHeight = number;
 
+/**
+ * Named chain position: the head, the newest certified block, or the last finalized
+ * (never reverted) block.
+ */
+export type BlockTag = 'latest' | 'safe' | 'finalized';
+
+/** Sync progress from boing_syncing while the node catches up to its peers. */
+export interface SyncStatus {
+  /** `headers` while downloading headers, `bodies` while downloading and importing blocks. */
+  phase: 'headers' | 'bodies';
+  starting_block: number;
+  current_block: number;
+  highest_block: number;
+}
+
+/** This node's identity from boing_nodeInfo. */
+export interface NodeInfo {
+  /** libp2p peer id; null when P2P is disabled. */
+  peer_id: string | null;
+  chain_id: number;
+  genesis_hash: string;
+}
+
+/** Connected peer from boing_peers. */
+export interface PeerInfo {
+  peer_id: string;
+  /** Announced listen multiaddrs, or the address of the node's connection to the peer. */
+  addresses: string[];
+  /** Reputation rank; peers below -100 are banned. */
+  score: number;
+  reliability: number;
+}
+
 /** Balance and stake are u128 as decimal strings. */
 export interface AccountBalance {
   balance: string;
 }
 
+/** Unbonded stake waiting out the unbonding delay; claim with a Withdraw tx from `release_height`. */
+export interface UnbondingEntry {
+  /** Validator the stake was bonded or delegated to. */
+  validator: string;
+  amount: string;
+  release_height: number;
+}
+
 export interface AccountState {
   balance: string;
   nonce: number;
   stake: string;
+  /** Pending unbonds, oldest first. */
+  unbonding: UnbondingEntry[];
+}
+
+export interface Delegator {
+  delegator: string;
+  amount: string;
+}
+
+/** Result of boing_getDelegators. Amounts are u128 as decimal strings. */
+export interface ValidatorDelegators {
+  registered: boolean;
+  /** Null when the account is not a registered validator. */
+  commission_bps: number | null;
+  /** Validator's own bonded stake. */
+  stake: string;
+  delegated: string;
+  /** stake + delegated. */
+  voting_power: string;
+  delegators: Delegator[];
+}
+
+export interface DelegationPosition {
+  validator: string;
+  amount: string;
+}
+
+/** Result of boing_getDelegations. */
+export interface DelegationsResult {
+  delegations: DelegationPosition[];
+}
+
+/** Result of boing_getValidatorStats. */
+export interface ValidatorStats {
+  /** Liveness window in blocks. */
+  window: number;
+  /** Misses tolerated in the window before a downtime slash. */
+  max_missed: number;
+  missed_proposals: number;
+  missed_votes: number;
+  jailed: boolean;
+  /** First height an Unjail tx is accepted; null if not jailed or only an appeal releases it. */
+  jailed_until: number | null;
+}
+
+export interface SlashAppeal {
+  id: number;
+  /** "Pending", "Approved" (stake refunded) or "Rejected". */
+  status: string;
+}
+
+export interface Slash {
+  id: number;
+  /** Total taken from the validator, its delegators and unbonding entries (u128 decimal). */
+  amount: string;
+  /** "equivocation", "liveness", "fraud" or "other: ...". */
+  reason: string;
+  block_height: number;
+  /** Last height an appeal is accepted. */
+  appeal_deadline: number;
+  appeal: SlashAppeal | null;
+}
+
+/** Result of boing_getSlashes. */
+export interface ValidatorSlashes {
+  /** Jailed validators are left out of later validator sets. */
+  jailed: boolean;
+  slashes: Slash[];
+}
+
+/** STUN/TURN relay from boing_listRelays. */
+export interface RelayInfo {
+  /** Operator account (hex); it staked for the relay and receives its rewards. */
+  operator: string;
+  multiaddr: string;
+  /** Relay stake (u128 decimal string). */
+  stake: string;
+  /** 0–10000, updated at each epoch boundary from the median of staked reporters' metrics. */
+  reputation: number;
+  registered_at: number;
+  /** Sent DeregisterRelay; removed at the next epoch boundary. */
+  leaving: boolean;
+  /** Metrics reports received this epoch. */
+  reports: number;
 }
 
 export interface BlockHeader {
   parent_hash: string;
   height: number;
+  /** Consensus view the block was proposed in. */
+  round: number;
   timestamp: number;
   proposer: string;
   tx_root: string;
   state_root: string;
+  /** Hash of the validator set of the block's epoch. */
+  validator_set_hash: string;
+  /** Proposer's ECVRF proof over the parent hash and view, and its output (empty for genesis). */
+  vrf: VrfOutput;
+  /** Randomness beacon after this block (parent beacon mixed with the VRF output); what PREVRANDAO returns. */
+  randomness: string;
+}
+
+/** ECVRF-EDWARDS25519-SHA512-TAI proof (80 bytes) and the first 32 bytes of its hash. */
+export interface VrfOutput {
+  proof: number[];
+  output: number[];
+}
+
+/** Quorum certificate: 2f+1 validator vote signatures over the block hash. */
+export interface QuorumCertificate {
+  block_hash: unknown;
+  signatures: unknown[];
 }
 
 export interface Block {
   header: BlockHeader;
   transactions: unknown[];
+  /** Proposer signature over the block hash (null for genesis). */
+  signature: unknown | null;
+  /** QC certifying the parent block (null for children of genesis). */
+  justify: QuorumCertificate | null;
+  /** QC for this block, attached once it commits. */
+  qc: QuorumCertificate | null;
 }
 
 export interface AccountProof {
@@ -56,8 +207,57 @@ export interface RegisterDappResult {
   owner: string;
 }
 
+/** WebRTC signaling message from boing_getSignalingMessages / boing_getPendingOffers. */
+export interface SignalingMessageInfo {
+  message_id: string;
+  kind: 'offer' | 'answer' | 'ice_candidate';
+  sender: string;
+  recipient: string;
+  /** Inline payload (hex), or null when content_pointer is set. */
+  payload: string | null;
+  content_pointer: { cid: string; hash: string | null } | null;
+  posted_at: number;
+  expires_at: number;
+  answered: boolean;
+}
+
+export interface PostSignalingResult {
+  message_id: string;
+}
+
 export interface SubmitIntentResult {
   intent_id: string;
+  status: 'pending';
+}
+
+/** What an intent asks for; amounts are u128 decimal strings. */
+export type IntentKindInfo =
+  | { type: 'transfer'; to: string; amount: string }
+  | { type: 'swap'; from_asset: string; to_asset: string; amount: string; min_out: string | null }
+  | { type: 'custom'; data: string };
+
+/**
+ * pending: waiting for a solver; fulfilled: settled on-chain; cancelled: its nonce was used
+ * up by CancelIntent or a later intent; expired: its deadline passed.
+ */
+export type IntentStatus = 'pending' | 'fulfilled' | 'cancelled' | 'expired';
+
+/** Intent from boing_getIntents or boing_getIntent. */
+export interface IntentInfo {
+  intent_id: string;
+  signer: string;
+  nonce: number;
+  /** Last block that may settle the intent. */
+  deadline_block: number;
+  status: IntentStatus;
+  kind: IntentKindInfo;
+  /** Hex-encoded SignedIntent, to embed in a FulfillIntent transaction. */
+  signed_intent: string;
+}
+
+export interface SubmitSolutionResult {
+  intent_id: string;
+  tx_hash: string;
 }
 
 /** QA pre-flight result. */
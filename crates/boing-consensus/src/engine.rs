@@ -1,140 +1,600 @@
-//! HotStuff-style BFT consensus engine.
+//! Chained HotStuff BFT consensus engine.
 //!
-//! Phases: Propose → Vote → Commit (when 2f+1 votes).
+//! Every block carries a QC for its parent (`justify`), so one round of votes per view
+//! drives the three HotStuff phases along the chain:
+//! - prepare: a block gathers votes from more than two thirds of the stake; its QC becomes
+//!   `high_qc`, which the next leader extends;
+//! - pre-commit: its child is certified, so the validator locks on it (`locked_qc`);
+//! - commit: its grandchild is certified and the three blocks are in consecutive views.
+//!
+//! A validator votes at most once per view, and only for blocks that extend its lock or
+//! justify themselves with a QC newer than the lock, so with less than a third of the stake
+//! Byzantine two conflicting blocks never both commit. A leader that makes no progress is
+//! replaced once a stake quorum signs timeout votes for its view (a timeout certificate).
+//!
+//! The validator set changes at epoch boundaries. The engine keeps a schedule of sets by
+//! the first height they govern; a block's votes, QC, and leader are checked against the
+//! set of its height.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
-use boing_primitives::{AccountId, Block, Hash};
+use boing_primitives::{
+    AccountId, Block, CertificateError, EquivocationEvidence, Hash, QuorumCertificate,
+    TimeoutCertificate, TimeoutVote, ValidatorSet, Vote,
+};
+use ed25519_dalek::SigningKey;
+
+/// Position of a block in the chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockRef {
+    pub hash: Hash,
+    pub height: u64,
+    pub view: u64,
+}
+
+impl BlockRef {
+    pub fn of(block: &Block) -> Self {
+        Self {
+            hash: block.hash(),
+            height: block.header.height,
+            view: block.header.round,
+        }
+    }
+}
+
+/// A block together with the quorum certificate for it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertifiedBlock {
+    pub block: BlockRef,
+    pub qc: QuorumCertificate,
+}
+
+/// Voting state a validator must never lose. Persist it before sending any vote, so a
+/// restarted validator cannot vote twice in a view or against its lock.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyState {
+    /// Highest view we voted or timed out in; we never vote in it or below again.
+    pub last_voted_view: u64,
+    /// Highest two-chain head: the parent of the newest block whose child was certified.
+    pub locked_qc: Option<CertifiedBlock>,
+}
 
 /// Consensus engine — orchestrates BFT consensus rounds.
 pub struct ConsensusEngine {
-    /// Validator set (AccountIds). Must have at least 1.
-    validators: Vec<AccountId>,
-    /// Current round number.
-    round: u64,
-    /// Pending block awaiting votes.
-    pending_block: Option<Block>,
-    /// Votes for pending block: validator → block_hash (to detect equivocation).
-    votes: HashMap<AccountId, Hash>,
+    /// Validator sets by the first height they govern. Always has an entry at or below the
+    /// root height.
+    epochs: BTreeMap<u64, ValidatorSet>,
+    /// Current view. Monotonic across heights; selects the leader. View 0 is genesis.
+    view: u64,
+    /// Last committed block; every block in `tree` descends from it.
+    root: BlockRef,
+    /// QC for `root` (None for genesis).
+    root_qc: Option<QuorumCertificate>,
+    /// Proposed, uncommitted blocks by hash.
+    tree: HashMap<Hash, Block>,
+    /// QCs for certified blocks in `tree`.
+    qcs: HashMap<Hash, QuorumCertificate>,
+    /// Highest certified block; the next proposal extends it.
+    high_qc: Option<CertifiedBlock>,
+    safety: SafetyState,
+    /// Signed votes per block in `tree`, by validator (to detect equivocation).
+    votes: HashMap<Hash, HashMap<AccountId, Vote>>,
+    /// Timeout votes for the current view, by validator.
+    timeout_votes: HashMap<AccountId, TimeoutVote>,
+    /// Blocks committed since the last `take_committed`, oldest first, each with its QC.
+    committed: Vec<Block>,
+    /// Equivocations detected since the last `take_evidence`.
+    evidence: Vec<EquivocationEvidence>,
 }
 
 impl ConsensusEngine {
-    pub fn new(validators: Vec<AccountId>) -> Self {
-        assert!(!validators.is_empty(), "Consensus requires at least 1 validator");
+    /// Engine with `validators` governing every height until another set is scheduled.
+    pub fn new(validators: ValidatorSet) -> Self {
         Self {
-            validators,
-            round: 0,
-            pending_block: None,
+            epochs: BTreeMap::from([(0, validators)]),
+            view: 1,
+            root: BlockRef { hash: Hash::ZERO, height: 0, view: 0 },
+            root_qc: None,
+            tree: HashMap::new(),
+            qcs: HashMap::new(),
+            high_qc: None,
+            safety: SafetyState::default(),
             votes: HashMap::new(),
+            timeout_votes: HashMap::new(),
+            committed: Vec::new(),
+            evidence: Vec::new(),
         }
     }
 
     /// Create a single-validator engine for local testing.
     pub fn single_validator(validator: AccountId) -> Self {
-        Self::new(vec![validator])
+        Self::new(ValidatorSet::equal_weight([validator]))
     }
 
-    /// Number of validators.
+    /// Number of validators in the current set.
     pub fn num_validators(&self) -> usize {
-        self.validators.len()
+        self.validators().len()
+    }
+
+    /// Validator set of the next height to commit. Timeout votes and certificates are
+    /// checked against it.
+    pub fn validators(&self) -> &ValidatorSet {
+        self.validators_at(self.root.height + 1)
+    }
+
+    /// Validator set governing `height`.
+    pub fn validators_at(&self, height: u64) -> &ValidatorSet {
+        self.epochs
+            .range(..=height)
+            .next_back()
+            .map(|(_, set)| set)
+            .expect("epoch schedule covers every height")
+    }
+
+    /// Scheduled validator sets by first height, including the one governing the root.
+    pub fn epochs(&self) -> &BTreeMap<u64, ValidatorSet> {
+        &self.epochs
+    }
+
+    /// Replace the schedule with one saved by a previous run. Ignored if it does not cover
+    /// the root height.
+    pub fn restore_epochs(&mut self, epochs: BTreeMap<u64, ValidatorSet>) {
+        if epochs.range(..=self.root.height).next_back().is_some() {
+            self.epochs = epochs;
+        }
+    }
+
+    /// Schedule `validators` to govern heights from `first_height` on. Ignored for heights
+    /// already committed; a set scheduled again for the same height replaces the old one.
+    pub fn schedule_epoch(&mut self, first_height: u64, validators: ValidatorSet) {
+        if first_height <= self.root.height {
+            return;
+        }
+        info!(
+            "Consensus: {} validators scheduled from height {} (set {})",
+            validators.len(),
+            first_height,
+            validators.hash()
+        );
+        self.epochs.insert(first_height, validators);
+    }
+
+    /// Drop the set scheduled from `first_height` (the boundary block that scheduled it was
+    /// reverted by a reorg). Ignored for heights already committed.
+    pub fn unschedule_epoch(&mut self, first_height: u64) {
+        if first_height > self.root.height && self.epochs.remove(&first_height).is_some() {
+            info!("Consensus: validator set scheduled from height {} unscheduled", first_height);
+        }
+    }
+
+    /// Current view.
+    pub fn view(&self) -> u64 {
+        self.view
+    }
+
+    /// Last committed block.
+    pub fn root(&self) -> BlockRef {
+        self.root
+    }
+
+    /// Highest certified, uncommitted block, if any.
+    pub fn high_qc(&self) -> Option<&CertifiedBlock> {
+        self.high_qc.as_ref()
+    }
+
+    /// Voting state to persist before each vote.
+    pub fn safety_state(&self) -> &SafetyState {
+        &self.safety
+    }
+
+    /// Restore voting state persisted by a previous run.
+    pub fn restore_safety_state(&mut self, safety: SafetyState) {
+        self.safety = safety;
+    }
+
+    /// Proposed, uncommitted block by hash.
+    pub fn get_block(&self, hash: &Hash) -> Option<&Block> {
+        self.tree.get(hash)
+    }
+
+    /// Stake a quorum needs in the current set (more than two thirds).
+    pub fn quorum(&self) -> u128 {
+        self.validators().quorum_stake()
     }
 
-    /// Validator set (for block import validation).
-    pub fn validators(&self) -> &[AccountId] {
-        &self.validators
+    /// Max faulty stake tolerated in the current set.
+    pub fn max_faulty(&self) -> u128 {
+        self.validators().max_faulty_stake()
     }
 
-    /// Max faulty replicas (f). HotStuff tolerates f failures with n = 3f+1.
-    fn f(&self) -> usize {
-        (self.validators.len().saturating_sub(1)) / 3
+    /// Leader of `view` for a block at `height` (drawn by stake from that height's set).
+    pub fn leader(&self, height: u64, view: u64) -> AccountId {
+        self.validators_at(height).leader(view)
     }
 
-    /// Quorum size (2f+1).
-    fn quorum(&self) -> usize {
-        2 * self.f() + 1
+    /// Leader of the current view for the next proposal (extending [`Self::extend_target`]).
+    pub fn current_leader(&self) -> AccountId {
+        self.leader(self.extend_target().0.height + 1, self.view)
     }
 
-    /// Leader for round r (round-robin).
-    pub fn leader(&self, round: u64) -> AccountId {
-        let n = self.validators.len();
-        self.validators[(round as usize) % n]
+    /// What the next proposal extends: the highest certified block (or the committed root)
+    /// and the QC to carry as its `justify`.
+    pub fn extend_target(&self) -> (BlockRef, Option<QuorumCertificate>) {
+        match &self.high_qc {
+            Some(high) => (high.block, Some(high.qc.clone())),
+            None => (self.root, self.root_qc.clone()),
+        }
     }
 
-    /// Propose a block. Enters voting phase. Only the round leader may propose.
+    /// Accept a proposal into the block tree. Its `justify` QC is processed first (possibly
+    /// advancing the view, the lock, and commits); then only the view leader may propose,
+    /// and the block must carry the leader's signature. Does not vote: see [`Self::prepare_vote`].
     pub fn propose(&mut self, block: Block) -> Result<(), ConsensusError> {
-        if block.header.height != self.round {
+        let hash = block.hash();
+        if self.tree.contains_key(&hash) {
+            return Ok(());
+        }
+        let parent = self.block_ref(&block.header.parent_hash).ok_or_else(|| {
+            ConsensusError::InvalidBlock("Parent is not the committed tip or a pending block".into())
+        })?;
+        if block.header.height != parent.height + 1 {
             return Err(ConsensusError::InvalidBlock(format!(
-                "Block height {} != expected round {}",
-                block.header.height, self.round
+                "Block height {} != parent height {} + 1",
+                block.header.height, parent.height
             )));
         }
-        let expected_leader = self.leader(self.round);
+        if block.header.round <= parent.view {
+            return Err(ConsensusError::InvalidBlock("Block view does not follow its parent".into()));
+        }
+        match &block.justify {
+            Some(qc) => {
+                qc.verify(&parent.hash, self.validators_at(parent.height))?;
+                self.process_qc(qc.clone(), parent);
+            }
+            // Only genesis is final without a certificate.
+            None if parent.height == 0 && parent.hash == self.root.hash => {}
+            None => return Err(ConsensusError::InvalidBlock("Missing justify QC for parent".into())),
+        }
+        if block.header.round != self.view {
+            return Err(ConsensusError::WrongView { view: block.header.round });
+        }
+        let expected_leader = self.leader(block.header.height, self.view);
         if block.header.proposer != expected_leader {
             return Err(ConsensusError::InvalidBlock(format!(
-                "Proposer {:?} is not the round leader {:?}",
+                "Proposer {:?} is not the view leader {:?}",
                 block.header.proposer, expected_leader
             )));
         }
-        if !self.validators.contains(&block.header.proposer) {
-            return Err(ConsensusError::InvalidBlock("Proposer not in validator set".into()));
+        if block.verify_signature().is_err() {
+            return Err(ConsensusError::InvalidSignature(block.header.proposer));
         }
 
-        self.pending_block = Some(block.clone());
-        self.votes.clear();
-        info!("Consensus: round {} propose block {}", self.round, block.hash());
+        info!("Consensus: view {} propose block {} at height {}", self.view, hash, block.header.height);
+        self.tree.insert(hash, block);
         Ok(())
     }
 
-    /// Submit a vote from a validator. Returns Some(block_hash) when committed.
-    /// Detects equivocation: validator voting for different blocks in same round.
-    pub fn vote(&mut self, block_hash: Hash, validator: AccountId) -> Result<Option<Hash>, ConsensusError> {
-        if !self.validators.contains(&validator) {
+    /// Apply the voting rules to a pending block before signing a vote for it: it must be
+    /// in the current view, we must not have voted (or timed out) in that view, and it must
+    /// extend our locked block or justify itself with a QC newer than the lock. On success
+    /// the view is recorded as voted; persist [`Self::safety_state`] before sending the vote.
+    pub fn prepare_vote(&mut self, block_hash: &Hash) -> Result<(), ConsensusError> {
+        let block = self
+            .tree
+            .get(block_hash)
+            .ok_or_else(|| ConsensusError::InvalidBlock("Unknown block".into()))?;
+        let view = block.header.round;
+        if view != self.view {
+            return Err(ConsensusError::WrongView { view });
+        }
+        if view <= self.safety.last_voted_view {
+            return Err(ConsensusError::AlreadyVoted { view });
+        }
+        if let Some(locked) = &self.safety.locked_qc {
+            let justify_view = self.block_ref(&block.header.parent_hash).map(|p| p.view).unwrap_or(0);
+            if !self.extends(block_hash, &locked.block) && justify_view <= locked.block.view {
+                return Err(ConsensusError::ConflictsWithLock);
+            }
+        }
+        self.safety.last_voted_view = view;
+        Ok(())
+    }
+
+    /// Submit a signed vote. Returns the quorum certificate once voters hold more than two
+    /// thirds of the block's validator set stake; the view then advances and committed blocks
+    /// become available from [`Self::take_committed`]. Votes arriving after that are added
+    /// to the QC, so the next proposal's `justify` lists them. Detects equivocation: validator
+    /// voting for different blocks in the same view; the conflicting votes become
+    /// available from [`Self::take_evidence`].
+    pub fn vote(&mut self, vote: Vote) -> Result<Option<QuorumCertificate>, ConsensusError> {
+        let validator = vote.validator;
+        let block_hash = vote.block_hash;
+        let block = self
+            .tree
+            .get(&block_hash)
+            .ok_or_else(|| ConsensusError::InvalidBlock("Vote for unknown block".into()))?;
+        let certified = BlockRef::of(block);
+        if !self.validators_at(certified.height).contains(&validator) {
             return Err(ConsensusError::InvalidBlock("Voter not in validator set".into()));
         }
-        let block = self.pending_block.as_ref().ok_or_else(|| {
-            ConsensusError::InvalidBlock("No pending block to vote on".into())
-        })?;
-        if block.hash() != block_hash {
-            if self.votes.contains_key(&validator) {
-                return Err(ConsensusError::Equivocation { validator, round: self.round });
+        if vote.verify().is_err() {
+            return Err(ConsensusError::InvalidSignature(validator));
+        }
+        let earlier = self.votes.iter().find_map(|(other, voters)| {
+            let other_block = self.tree.get(other).filter(|b| b.header.round == certified.view)?;
+            let earlier_vote = voters.get(&validator).filter(|_| *other != block_hash)?;
+            Some((other_block, earlier_vote))
+        });
+        if let Some((other_block, earlier_vote)) = earlier {
+            self.evidence.push(EquivocationEvidence::new(
+                (&other_block.header, earlier_vote),
+                (&block.header, &vote),
+            ));
+            return Err(ConsensusError::Equivocation { validator, round: certified.view });
+        }
+
+        let signature = vote.signature;
+        self.votes.entry(block_hash).or_default().insert(validator, vote);
+        let voters = &self.votes[&block_hash];
+        let set = self.validators_at(certified.height);
+        let order: Vec<AccountId> = set.iter().map(|v| v.id).collect();
+        let (stake, quorum) = (set.stake_of_all(voters.keys()), set.quorum_stake());
+        debug!("Consensus: vote from {:?}, stake {}/{}", validator, stake, quorum);
+
+        if let Some(qc) = self.qcs.get_mut(&block_hash) {
+            // A vote after quorum still goes into the QC, so the next leader's `justify`
+            // credits the voter for liveness.
+            if !qc.signatures.iter().any(|(id, _)| *id == validator) {
+                qc.signatures.push((validator, signature));
+                qc.signatures.sort_by_key(|(id, _)| order.iter().position(|o| o == id));
+            }
+            let qc = qc.clone();
+            if let Some(high) = self.high_qc.as_mut().filter(|h| h.block.hash == block_hash) {
+                high.qc = qc;
             }
-            return Err(ConsensusError::InvalidBlock("Vote for wrong block hash".into()));
+            return Ok(None);
+        }
+        if stake < quorum {
+            return Ok(None);
+        }
+        // Order signatures by validator set position so every node builds the same QC.
+        let voters = &self.votes[&block_hash];
+        let ordered = self.validators_at(certified.height).iter().filter_map(|v| voters.get(&v.id));
+        let qc = QuorumCertificate::from_votes(block_hash, ordered);
+        info!("Consensus: certified block {} at view {}", block_hash, certified.view);
+        self.process_qc(qc.clone(), certified);
+        Ok(Some(qc))
+    }
+
+    /// Blocks committed since the last call, oldest first, each with its QC attached.
+    pub fn take_committed(&mut self) -> Vec<Block> {
+        std::mem::take(&mut self.committed)
+    }
+
+    /// Equivocation evidence detected since the last call, to report on chain.
+    pub fn take_evidence(&mut self) -> Vec<EquivocationEvidence> {
+        std::mem::take(&mut self.evidence)
+    }
+
+    /// Record that we time out the current view: no more votes in it. Persist
+    /// [`Self::safety_state`], then sign and send a timeout vote for the returned view.
+    pub fn prepare_timeout(&mut self) -> u64 {
+        self.safety.last_voted_view = self.safety.last_voted_view.max(self.view);
+        self.view
+    }
+
+    /// Submit a signed timeout vote for the current view. Returns the timeout certificate
+    /// once a stake quorum timed out; the engine then enters the next view.
+    pub fn timeout_vote(&mut self, vote: TimeoutVote) -> Result<Option<TimeoutCertificate>, ConsensusError> {
+        let validator = vote.validator;
+        if !self.validators().contains(&validator) {
+            return Err(ConsensusError::InvalidBlock("Timeout voter not in validator set".into()));
+        }
+        if vote.view != self.view {
+            return Err(ConsensusError::WrongView { view: vote.view });
+        }
+        if vote.verify().is_err() {
+            return Err(ConsensusError::InvalidSignature(validator));
         }
 
-        self.votes.insert(validator, block_hash);
-        debug!("Consensus: vote from {:?}, {}/{}", validator, self.votes.len(), self.quorum());
+        self.timeout_votes.insert(validator, vote);
+        let stake = self.timeout_stake();
+        debug!("Consensus: timeout from {:?} for view {}, stake {}/{}", validator, self.view, stake, self.quorum());
 
-        if self.votes.len() >= self.quorum() {
-            let h = block.hash();
-            info!("Consensus: committed block {} at round {}", h, self.round);
-            self.round += 1;
-            self.pending_block = None;
-            self.votes.clear();
-            return Ok(Some(h));
+        if stake >= self.quorum() {
+            let ordered = self.validators().iter().filter_map(|v| self.timeout_votes.get(&v.id));
+            let tc = TimeoutCertificate::from_votes(self.view, ordered);
+            info!("Consensus: view {} timed out", self.view);
+            self.enter_view(self.view + 1);
+            return Ok(Some(tc));
         }
         Ok(None)
     }
 
-    /// Sync consensus to a given height (e.g. after importing a block from the network).
-    pub fn sync_round(&mut self, height: u64) {
-        self.round = height;
-        self.pending_block = None;
-        self.votes.clear();
+    /// Stake of the validators that timed out the current view.
+    pub fn timeout_stake(&self) -> u128 {
+        self.validators().stake_of_all(self.timeout_votes.keys())
+    }
+
+    /// Whether `validator` already sent a timeout vote for the current view.
+    pub fn has_timed_out(&self, validator: &AccountId) -> bool {
+        self.timeout_votes.contains_key(validator)
     }
 
-    /// Propose and immediately collect votes from all validators (for single-process testing).
-    pub fn propose_and_commit(&mut self, block: Block) -> Result<Hash, ConsensusError> {
+    /// Apply a timeout certificate received from the network (new-view message). Enters view
+    /// `tc.view + 1` and returns true when the certificate is valid and moves us forward;
+    /// certificates for past views are ignored.
+    pub fn new_view(&mut self, tc: &TimeoutCertificate) -> Result<bool, ConsensusError> {
+        if tc.view < self.view {
+            return Ok(false);
+        }
+        tc.verify(self.validators())?;
+        info!("Consensus: new view {}", tc.view + 1);
+        self.enter_view(tc.view + 1);
+        Ok(true)
+    }
+
+    /// Sync consensus after `block` was committed outside the voting pipeline (imported
+    /// from the network or loaded from disk). It becomes the root; pending blocks that do
+    /// not descend from it are dropped. The safety state is kept.
+    pub fn sync_to(&mut self, block: &Block) {
+        self.set_root(BlockRef::of(block), block.qc.clone());
+        if self.view <= block.header.round {
+            self.enter_view(block.header.round + 1);
+        }
+    }
+
+    /// Propose and immediately collect votes from the given validator keys, then commit the
+    /// block (for single-process testing and single-validator chains). Committing on one QC
+    /// is only safe because every signer is local and votes nowhere else. Returns the QC.
+    pub fn propose_and_commit(
+        &mut self,
+        block: Block,
+        signers: &[&SigningKey],
+    ) -> Result<QuorumCertificate, ConsensusError> {
         self.propose(block.clone())?;
         let block_hash = block.hash();
-        for v in &self.validators.clone() {
-            if let Ok(Some(h)) = self.vote(block_hash, *v) {
-                return Ok(h);
+        for key in signers {
+            if let Ok(Some(qc)) = self.vote(Vote::new(block_hash, key)) {
+                self.commit(&block_hash);
+                return Ok(qc);
             }
         }
+        self.tree.remove(&block_hash);
+        self.votes.remove(&block_hash);
         Err(ConsensusError::InsufficientVotes)
     }
+
+    fn block_ref(&self, hash: &Hash) -> Option<BlockRef> {
+        if *hash == self.root.hash {
+            return Some(self.root);
+        }
+        self.tree.get(hash).map(BlockRef::of)
+    }
+
+    /// Whether the block `hash` descends from (or is) `ancestor`. Committed history is
+    /// treated as an ancestor of every pending block.
+    fn extends(&self, hash: &Hash, ancestor: &BlockRef) -> bool {
+        if ancestor.height <= self.root.height {
+            return true;
+        }
+        let mut cur = *hash;
+        while let Some(block) = self.tree.get(&cur) {
+            if cur == ancestor.hash {
+                return true;
+            }
+            if block.header.height <= ancestor.height {
+                return false;
+            }
+            cur = block.header.parent_hash;
+        }
+        false
+    }
+
+    /// A QC for `certified` was formed or received: update high_qc, the view, the lock
+    /// (on its parent), and commit its grandparent on a three-chain in consecutive views.
+    fn process_qc(&mut self, qc: QuorumCertificate, certified: BlockRef) {
+        if certified.hash == self.root.hash {
+            return;
+        }
+        if !self.tree.contains_key(&certified.hash) {
+            return;
+        }
+        self.qcs.entry(certified.hash).or_insert_with(|| qc.clone());
+        if self.high_qc.as_ref().map(|h| certified.view > h.block.view).unwrap_or(true) {
+            self.high_qc = Some(CertifiedBlock { block: certified, qc });
+        }
+        if self.view <= certified.view {
+            self.enter_view(certified.view + 1);
+        }
+
+        let parent_hash = self.tree[&certified.hash].header.parent_hash;
+        let Some(parent) = self.tree.get(&parent_hash).map(BlockRef::of) else {
+            return;
+        };
+        let locked_view = self.safety.locked_qc.as_ref().map(|l| l.block.view).unwrap_or(0);
+        if parent.view > locked_view {
+            if let Some(parent_qc) = self.qcs.get(&parent.hash) {
+                self.safety.locked_qc = Some(CertifiedBlock { block: parent, qc: parent_qc.clone() });
+            }
+        }
+
+        let grandparent_hash = self.tree[&parent.hash].header.parent_hash;
+        let Some(grandparent) = self.tree.get(&grandparent_hash).map(BlockRef::of) else {
+            return;
+        };
+        if parent.view == grandparent.view + 1 && certified.view == parent.view + 1 {
+            self.commit(&grandparent.hash);
+        }
+    }
+
+    /// Commit `hash` and its uncommitted ancestors, oldest first.
+    fn commit(&mut self, hash: &Hash) {
+        let mut chain = Vec::new();
+        let mut cur = *hash;
+        while let Some(block) = self.tree.get(&cur) {
+            chain.push(cur);
+            cur = block.header.parent_hash;
+        }
+        let Some(new_root) = self.tree.get(hash).map(BlockRef::of) else {
+            return;
+        };
+        for h in chain.into_iter().rev() {
+            let mut block = self.tree[&h].clone();
+            block.qc = self.qcs.get(&h).cloned();
+            info!("Consensus: committed block {} at height {}", h, block.header.height);
+            self.committed.push(block);
+        }
+        let root_qc = self.qcs.get(hash).cloned();
+        self.set_root(new_root, root_qc);
+    }
+
+    /// Move the root to `root` and drop pending blocks that do not descend from it.
+    fn set_root(&mut self, root: BlockRef, root_qc: Option<QuorumCertificate>) {
+        let mut keep: HashMap<Hash, bool> = HashMap::new();
+        let hashes: Vec<Hash> = self.tree.keys().copied().collect();
+        for h in hashes {
+            let descends = self.descends_from(&h, &root, &mut keep);
+            keep.insert(h, descends);
+        }
+        self.tree.retain(|h, _| keep.get(h).copied().unwrap_or(false));
+        self.qcs.retain(|h, _| keep.get(h).copied().unwrap_or(false));
+        self.votes.retain(|h, _| keep.get(h).copied().unwrap_or(false));
+        if self.high_qc.as_ref().map(|q| !self.tree.contains_key(&q.block.hash)).unwrap_or(false) {
+            self.high_qc = None;
+        }
+        self.root = root;
+        self.root_qc = root_qc;
+        // Keep the set governing the root (its QC is checked against it) and later ones.
+        let governing = self.epochs.range(..=root.height).next_back().map(|(h, _)| *h).unwrap_or(0);
+        self.epochs.retain(|h, _| *h >= governing);
+    }
+
+    fn descends_from(&self, hash: &Hash, root: &BlockRef, memo: &mut HashMap<Hash, bool>) -> bool {
+        if let Some(known) = memo.get(hash) {
+            return *known;
+        }
+        match self.tree.get(hash) {
+            Some(block) if block.header.height > root.height => {
+                let parent = block.header.parent_hash;
+                if parent == root.hash {
+                    return true;
+                }
+                let descends = self.descends_from(&parent, root, memo);
+                memo.insert(parent, descends);
+                descends
+            }
+            _ => false,
+        }
+    }
+
+    fn enter_view(&mut self, view: u64) {
+        self.view = view;
+        self.timeout_votes.clear();
+    }
 }
 
 impl Default for ConsensusEngine {
@@ -147,108 +607,458 @@ impl Default for ConsensusEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use boing_primitives::{Block, BlockHeader};
+    use boing_primitives::{Block, BlockHeader, Hash, TimeoutCertificate, TimeoutVote, Validator};
+
+    fn key(i: u8) -> SigningKey {
+        SigningKey::from_bytes(&[i; 32])
+    }
+
+    fn id(key: &SigningKey) -> AccountId {
+        AccountId(key.verifying_key().to_bytes())
+    }
+
+    fn keys4() -> (Vec<SigningKey>, ValidatorSet) {
+        let keys: Vec<SigningKey> = (1..=4).map(key).collect();
+        let validators = ValidatorSet::equal_weight(keys.iter().map(id));
+        (keys, validators)
+    }
 
-    fn mk_block(height: u64, proposer: AccountId, parent: Hash) -> Block {
-        Block {
-            header: BlockHeader {
+    fn mk_block(
+        height: u64,
+        view: u64,
+        proposer: &SigningKey,
+        parent: Hash,
+        justify: Option<QuorumCertificate>,
+    ) -> Block {
+        let mut block = Block::new(
+            BlockHeader {
                 parent_hash: parent,
                 height,
+                round: view,
                 timestamp: 0,
-                proposer,
+                proposer: id(proposer),
                 tx_root: Hash::ZERO,
                 state_root: Hash::ZERO,
+                validator_set_hash: Hash::ZERO,
+                vrf: Default::default(),
+                randomness: Hash::ZERO,
+                justify_hash: Hash::ZERO,
             },
-            transactions: vec![],
+            vec![],
+        );
+        block.set_justify(justify);
+        block.sign(proposer);
+        block
+    }
+
+    /// Propose `block` and vote for it with `voters`; returns the QC if one formed.
+    fn certify(engine: &mut ConsensusEngine, block: &Block, voters: &[SigningKey]) -> Option<QuorumCertificate> {
+        engine.propose(block.clone()).unwrap();
+        let mut qc = None;
+        for k in voters {
+            qc = qc.or(engine.vote(Vote::new(block.hash(), k)).unwrap());
+        }
+        qc
+    }
+
+    /// Key of the leader of `view` under `set`.
+    fn leader<'a>(keys: &'a [SigningKey], set: &ValidatorSet, view: u64) -> &'a SigningKey {
+        let leader = set.leader(view);
+        keys.iter().find(|k| id(k) == leader).expect("leader is in the set")
+    }
+
+    /// Extend `parent` (certified by `qc`) with a block by the leader of `view`.
+    fn child(keys: &[SigningKey], set: &ValidatorSet, view: u64, parent: &Block, qc: QuorumCertificate) -> Block {
+        mk_block(parent.header.height + 1, view, leader(keys, set, view), parent.hash(), Some(qc))
+    }
+
+    fn timeout_all(engine: &mut ConsensusEngine, keys: &[SigningKey]) -> TimeoutCertificate {
+        let view = engine.view();
+        for k in keys {
+            if let Some(tc) = engine.timeout_vote(TimeoutVote::new(view, k)).unwrap() {
+                return tc;
+            }
         }
+        panic!("no timeout certificate")
     }
 
     #[test]
     fn test_propose_and_commit_single_validator() {
-        let v = AccountId::from_bytes([1u8; 32]);
-        let mut engine = ConsensusEngine::single_validator(v);
-        let block = mk_block(0, v, Hash::ZERO);
-        let h = engine.propose_and_commit(block.clone()).unwrap();
-        assert_eq!(h, block.hash());
+        let k = key(1);
+        let mut engine = ConsensusEngine::single_validator(id(&k));
+        let block = mk_block(1, 1, &k, Hash::ZERO, None);
+        let qc = engine.propose_and_commit(block.clone(), &[&k]).unwrap();
+        assert_eq!(qc.block_hash, block.hash());
+        assert!(qc.verify(&block.hash(), engine.validators()).is_ok());
+        let committed = engine.take_committed();
+        assert_eq!(committed.len(), 1);
+        assert_eq!(committed[0].qc.as_ref(), Some(&qc));
+        assert_eq!(engine.root().hash, block.hash());
+
+        // The next block extends the committed one, justified by its QC.
+        let (parent, justify) = engine.extend_target();
+        assert_eq!((parent.hash, justify), (block.hash(), Some(qc.clone())));
+        let next = mk_block(2, engine.view(), &k, block.hash(), Some(qc));
+        assert!(engine.propose_and_commit(next, &[&k]).is_ok());
     }
 
     #[test]
     fn test_equivocation_detected() {
-        let v1 = AccountId::from_bytes([1u8; 32]);
-        let v2 = AccountId::from_bytes([2u8; 32]);
-        let v3 = AccountId::from_bytes([3u8; 32]);
-        let v4 = AccountId::from_bytes([4u8; 32]);
-        let validators = vec![v1, v2, v3, v4];
-        let mut engine = ConsensusEngine::new(validators);
-        let block_a = mk_block(0, v1, Hash::ZERO);
-        let block_b = mk_block(0, v1, Hash([1u8; 32])); // different parent -> different hash
+        let (keys, validators) = keys4();
+        let proposer = leader(&keys, &validators, 1);
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let block_a = mk_block(1, 1, proposer, Hash::ZERO, None);
+        let mut block_b = mk_block(1, 1, proposer, Hash::ZERO, None);
+        block_b.header.timestamp = 1; // different hash, same view
+        block_b.sign(proposer);
         engine.propose(block_a.clone()).unwrap();
-        let hash_a = block_a.hash();
-        engine.vote(hash_a, v1).unwrap();
-        let result = engine.vote(block_b.hash(), v1); // v1 votes for different block
-        assert!(matches!(result, Err(ConsensusError::Equivocation { .. })));
+        engine.propose(block_b.clone()).unwrap();
+        engine.vote(Vote::new(block_a.hash(), &keys[0])).unwrap();
+        let result = engine.vote(Vote::new(block_b.hash(), &keys[0])); // votes for different block
+        assert!(matches!(result, Err(ConsensusError::Equivocation { round: 1, .. })));
+
+        let evidence = engine.take_evidence();
+        assert_eq!(evidence.len(), 1);
+        assert_eq!(evidence[0].validator, id(&keys[0]));
+        assert!(evidence[0].verify().is_ok());
+        assert!(engine.take_evidence().is_empty());
+    }
+
+    #[test]
+    fn test_leader_drawn_from_epoch_set() {
+        let ids: Vec<AccountId> = (1..=3).map(|i| AccountId::from_bytes([i; 32])).collect();
+        let old = ValidatorSet::equal_weight(ids.clone());
+        let new = old.clone().with_seed(Hash([7u8; 32]));
+        let mut engine = ConsensusEngine::new(old.clone());
+        engine.schedule_epoch(5, new.clone());
+        for view in 0..30 {
+            assert_eq!(engine.leader(1, view), old.leader(view));
+            assert_eq!(engine.leader(5, view), new.leader(view));
+        }
+        for v in &ids {
+            assert!((0..30).any(|view| engine.leader(1, view) == *v), "every validator leads");
+        }
+    }
+
+    #[test]
+    fn test_quorum_weighted_by_stake() {
+        let keys: Vec<SigningKey> = (1..=4).map(key).collect();
+        let stakes = [700, 100, 100, 100];
+        let set = ValidatorSet::new(
+            keys.iter().zip(stakes).map(|(k, stake)| Validator { id: id(k), stake }).collect(),
+        );
+        let mut engine = ConsensusEngine::new(set.clone());
+        let block = mk_block(1, 1, leader(&keys, &set, 1), Hash::ZERO, None);
+        // Three of four validators, but only 300 of 1000 stake.
+        assert!(certify(&mut engine, &block, &keys[1..]).is_none());
+        let qc = engine.vote(Vote::new(block.hash(), &keys[0])).unwrap().expect("whale completes quorum");
+        assert!(qc.verify(&block.hash(), &set).is_ok());
     }
 
+    /// Blocks past an epoch boundary are led, voted, and certified by the scheduled set.
     #[test]
-    fn test_leader_rotation() {
-        let v1 = AccountId::from_bytes([1u8; 32]);
-        let v2 = AccountId::from_bytes([2u8; 32]);
-        let v3 = AccountId::from_bytes([3u8; 32]);
-        let validators = vec![v1, v2, v3];
-        let engine = ConsensusEngine::new(validators);
-        assert_eq!(engine.leader(0), v1);
-        assert_eq!(engine.leader(1), v2);
-        assert_eq!(engine.leader(2), v3);
-        assert_eq!(engine.leader(3), v1);
+    fn test_epoch_switches_validator_set() {
+        let keys: Vec<SigningKey> = (1..=5).map(key).collect();
+        let old = ValidatorSet::equal_weight(keys[..4].iter().map(id));
+        let new = ValidatorSet::equal_weight(keys[1..].iter().map(id));
+        let mut engine = ConsensusEngine::new(old.clone());
+        engine.schedule_epoch(2, new.clone());
+        assert_eq!(engine.validators_at(1), &old);
+        assert_eq!(engine.validators_at(9), &new);
+
+        let b1 = mk_block(1, 1, leader(&keys, &old, 1), Hash::ZERO, None);
+        let qc1 = certify(&mut engine, &b1, &keys[..3]).unwrap();
+        // View 2 at height 2: led by the new set's leader, certified by the new set only.
+        assert_eq!(engine.current_leader(), new.leader(2));
+        let b2 = mk_block(2, 2, leader(&keys, &new, 2), b1.hash(), Some(qc1));
+        engine.propose(b2.clone()).unwrap();
+        assert!(engine.vote(Vote::new(b2.hash(), &keys[0])).is_err(), "left the set");
+        let mut qc2 = None;
+        for k in &keys[2..] {
+            qc2 = qc2.or(engine.vote(Vote::new(b2.hash(), k)).unwrap());
+        }
+        let qc2 = qc2.expect("new set reaches quorum");
+        assert!(qc2.verify(&b2.hash(), &new).is_ok());
+        assert!(qc2.verify(&b2.hash(), &old).is_err());
     }
 
     #[test]
     fn test_only_leader_can_propose() {
-        let v1 = AccountId::from_bytes([1u8; 32]);
-        let v2 = AccountId::from_bytes([2u8; 32]);
-        let validators = vec![v1, v2];
-        let mut engine = ConsensusEngine::new(validators);
-        let block = mk_block(0, v2, Hash::ZERO); // v2 proposes but v1 is leader for round 0
+        let keys = [key(1), key(2)];
+        let set = ValidatorSet::equal_weight(keys.iter().map(id));
+        let follower = keys.iter().find(|k| id(k) != set.leader(1)).unwrap();
+        let mut engine = ConsensusEngine::new(set);
+        let block = mk_block(1, 1, follower, Hash::ZERO, None);
         let result = engine.propose(block);
         assert!(matches!(result, Err(ConsensusError::InvalidBlock(_))));
     }
 
     #[test]
-    fn test_propose_then_vote_commits() {
-        let v1 = AccountId::from_bytes([1u8; 32]);
-        let v2 = AccountId::from_bytes([2u8; 32]);
-        let v3 = AccountId::from_bytes([3u8; 32]);
-        let v4 = AccountId::from_bytes([4u8; 32]);
-        let validators = vec![v1, v2, v3, v4]; // n=4, f=1, quorum=3
-        let mut engine = ConsensusEngine::new(validators);
-        let block = mk_block(0, v1, Hash::ZERO);
+    fn test_unsigned_or_forged_proposal_rejected() {
+        let keys = [key(1), key(2)];
+        let set = ValidatorSet::equal_weight(keys.iter().map(id));
+        let proposer = leader(&keys, &set, 1);
+        let other = keys.iter().find(|k| id(k) != set.leader(1)).unwrap();
+        let mut engine = ConsensusEngine::new(set.clone());
+        let mut block = mk_block(1, 1, proposer, Hash::ZERO, None);
+        block.signature = None;
+        assert!(matches!(engine.propose(block.clone()), Err(ConsensusError::InvalidSignature(_))));
+        block.sign(other); // signed by a non-proposer key
+        assert!(matches!(engine.propose(block), Err(ConsensusError::InvalidSignature(_))));
+    }
+
+    #[test]
+    fn test_forged_vote_rejected() {
+        let (keys, validators) = keys4();
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let block = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        engine.propose(block.clone()).unwrap();
+        let mut vote = Vote::new(block.hash(), &keys[1]);
+        vote.validator = id(&keys[2]); // claims to be another validator
+        assert!(matches!(engine.vote(vote), Err(ConsensusError::InvalidSignature(_))));
+    }
+
+    #[test]
+    fn test_propose_then_vote_certifies() {
+        let (keys, validators) = keys4(); // n=4, f=1, quorum=3
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let block = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
         engine.propose(block.clone()).unwrap();
         let block_hash = block.hash();
-        assert!(engine.vote(block_hash, v1).unwrap().is_none());
-        assert!(engine.vote(block_hash, v2).unwrap().is_none());
-        let committed = engine.vote(block_hash, v3).unwrap();
-        assert_eq!(committed, Some(block_hash));
+        assert!(engine.vote(Vote::new(block_hash, &keys[0])).unwrap().is_none());
+        assert!(engine.vote(Vote::new(block_hash, &keys[1])).unwrap().is_none());
+        let qc = engine.vote(Vote::new(block_hash, &keys[2])).unwrap().expect("quorum");
+        assert_eq!(qc.block_hash, block_hash);
+        assert_eq!(qc.len(), 3);
+        assert!(qc.verify(&block_hash, engine.validators()).is_ok());
+        // Certified, not committed: it becomes the high QC and the view advances.
+        assert_eq!(engine.high_qc().map(|h| h.block.hash), Some(block_hash));
+        assert_eq!(engine.view(), 2);
+        assert!(engine.take_committed().is_empty());
     }
 
     /// Simulate 4 nodes, 1 Byzantine (v4 never votes). 3 honest nodes reach quorum.
     #[test]
     fn test_simulate_4_nodes_1_byzantine() {
-        let v1 = AccountId::from_bytes([1u8; 32]);
-        let v2 = AccountId::from_bytes([2u8; 32]);
-        let v3 = AccountId::from_bytes([3u8; 32]);
-        let v4 = AccountId::from_bytes([4u8; 32]); // Byzantine: does not vote
-        let validators = vec![v1, v2, v3, v4]; // n=4, f=1, quorum=3
-        let mut engine = ConsensusEngine::new(validators);
-
-        let block = mk_block(0, v1, Hash::ZERO);
+        let (keys, validators) = keys4(); // keys[3] is Byzantine: does not vote
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let block = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        let qc = certify(&mut engine, &block, &keys[..3]);
+        assert_eq!(
+            qc.map(|qc| qc.block_hash),
+            Some(block.hash()),
+            "3 honest nodes should certify despite 1 Byzantine"
+        );
+    }
+
+    #[test]
+    fn test_propose_and_commit_without_quorum_fails() {
+        let (keys, validators) = keys4();
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let block = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        let result = engine.propose_and_commit(block, &[leader(&keys, &validators, 1)]);
+        assert!(matches!(result, Err(ConsensusError::InsufficientVotes)));
+    }
+
+    #[test]
+    fn test_three_chain_commit() {
+        let (keys, validators) = keys4();
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let b1 = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        let qc1 = certify(&mut engine, &b1, &keys[..3]).unwrap();
+        assert!(engine.safety_state().locked_qc.is_none());
+
+        let b2 = child(&keys, &validators, 2, &b1, qc1.clone());
+        let qc2 = certify(&mut engine, &b2, &keys[..3]).unwrap();
+        assert_eq!(engine.safety_state().locked_qc.as_ref().map(|l| l.block.hash), Some(b1.hash()));
+        assert!(engine.take_committed().is_empty(), "two-chain only locks");
+
+        let b3 = child(&keys, &validators, 3, &b2, qc2);
+        certify(&mut engine, &b3, &keys[..3]).unwrap();
+        let committed = engine.take_committed();
+        assert_eq!(committed.len(), 1);
+        assert_eq!(committed[0].hash(), b1.hash());
+        assert_eq!(committed[0].qc.as_ref(), Some(&qc1));
+        assert_eq!(engine.root().hash, b1.hash());
+        assert_eq!(engine.safety_state().locked_qc.as_ref().map(|l| l.block.hash), Some(b2.hash()));
+    }
+
+    #[test]
+    fn test_commit_requires_consecutive_views() {
+        let (keys, validators) = keys4();
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let b1 = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        let qc1 = certify(&mut engine, &b1, &keys[..3]).unwrap();
+        let b2 = child(&keys, &validators, 2, &b1, qc1);
+        let qc2 = certify(&mut engine, &b2, &keys[..3]).unwrap();
+        timeout_all(&mut engine, &keys); // view 3 fails
+        let b3 = child(&keys, &validators, 4, &b2, qc2);
+        let qc3 = certify(&mut engine, &b3, &keys[..3]).unwrap();
+        assert!(engine.take_committed().is_empty(), "views 1, 2, 4 are not consecutive");
+
+        let b4 = child(&keys, &validators, 5, &b3, qc3);
+        let qc4 = certify(&mut engine, &b4, &keys[..3]).unwrap();
+        assert!(engine.take_committed().is_empty(), "views 2, 4, 5 are not consecutive");
+
+        let b5 = child(&keys, &validators, 6, &b4, qc4);
+        certify(&mut engine, &b5, &keys[..3]).unwrap();
+        let committed: Vec<Hash> = engine.take_committed().iter().map(|b| b.hash()).collect();
+        assert_eq!(committed, vec![b1.hash(), b2.hash(), b3.hash()]);
+    }
+
+    #[test]
+    fn test_lock_rejects_conflicting_proposal() {
+        let (keys, validators) = keys4();
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let b1 = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        let qc1 = certify(&mut engine, &b1, &keys[..3]).unwrap();
+        let b2 = child(&keys, &validators, 2, &b1, qc1);
+        let qc2 = certify(&mut engine, &b2, &keys[..3]).unwrap(); // locks b1
+        timeout_all(&mut engine, &keys);
+
+        // View 4's leader forks from genesis: justified by nothing newer than the lock.
+        let fork = mk_block(1, 4, leader(&keys, &validators, 4), Hash::ZERO, None);
+        engine.propose(fork.clone()).unwrap();
+        assert!(matches!(engine.prepare_vote(&fork.hash()), Err(ConsensusError::ConflictsWithLock)));
+
+        // Extending the highest QC is safe.
+        timeout_all(&mut engine, &keys);
+        let b3 = child(&keys, &validators, 5, &b2, qc2);
+        engine.propose(b3.clone()).unwrap();
+        assert!(engine.prepare_vote(&b3.hash()).is_ok());
+    }
+
+    #[test]
+    fn test_restored_safety_state_prevents_double_vote() {
+        let (keys, validators) = keys4();
+        let mut engine = ConsensusEngine::new(validators.clone());
+        let block = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
         engine.propose(block.clone()).unwrap();
-        let block_hash = block.hash();
+        engine.prepare_vote(&block.hash()).unwrap();
+        assert!(matches!(engine.prepare_vote(&block.hash()), Err(ConsensusError::AlreadyVoted { view: 1 })));
+
+        // Restart: the same proposal arrives again, or a conflicting one in the same view.
+        let mut restarted = ConsensusEngine::new(validators.clone());
+        restarted.restore_safety_state(engine.safety_state().clone());
+        let mut other = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        other.header.timestamp = 7;
+        other.sign(leader(&keys, &validators, 1));
+        for b in [&block, &other] {
+            restarted.propose(b.clone()).unwrap();
+            assert!(matches!(restarted.prepare_vote(&b.hash()), Err(ConsensusError::AlreadyVoted { .. })));
+        }
+
+        // Timing out a view also forbids voting in it.
+        let mut timed_out = ConsensusEngine::new(validators.clone());
+        assert_eq!(timed_out.prepare_timeout(), 1);
+        timed_out.propose(block.clone()).unwrap();
+        assert!(matches!(timed_out.prepare_vote(&block.hash()), Err(ConsensusError::AlreadyVoted { .. })));
+    }
+
+    /// One engine per simulated node; hands a timeout vote to every live node.
+    fn deliver_timeout(
+        engines: &mut [ConsensusEngine],
+        vote: &TimeoutVote,
+    ) -> Vec<Option<TimeoutCertificate>> {
+        engines.iter_mut().map(|e| e.timeout_vote(vote.clone()).unwrap()).collect()
+    }
+
+    /// Leader of view 1 crashed: the three live validators time out, form a TC, and the
+    /// view-2 leader gets its block certified.
+    #[test]
+    fn test_crashed_leader_replaced_after_timeout_certificate() {
+        let (keys, validators) = keys4();
+        let crashed = validators.leader(1);
+        assert_ne!(validators.leader(2), crashed, "view 2 needs a live leader");
+        let live: Vec<SigningKey> = keys.iter().filter(|k| id(k) != crashed).cloned().collect();
+        let mut engines: Vec<ConsensusEngine> =
+            live.iter().map(|_| ConsensusEngine::new(validators.clone())).collect();
+
+        let mut tcs = Vec::new();
+        for k in &live {
+            tcs = deliver_timeout(&mut engines, &TimeoutVote::new(1, k));
+        }
+        let tc = tcs[0].clone().expect("2f+1 timeouts form a TC");
+        assert_eq!((tc.view, tc.signatures.len()), (1, 3));
+        assert!(tc.verify(&validators).is_ok());
+        for e in &engines {
+            assert_eq!(e.view(), 2);
+            assert_eq!(e.current_leader(), validators.leader(2));
+        }
+
+        // A late proposal from the crashed leader's view is now stale.
+        let stale = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        assert!(matches!(engines[0].propose(stale), Err(ConsensusError::WrongView { view: 1 })));
+
+        let block = mk_block(1, 2, leader(&keys, &validators, 2), Hash::ZERO, None);
+        for e in engines.iter_mut() {
+            let qc = certify(e, &block, &live).expect("live validators reach quorum");
+            assert!(qc.verify(&block.hash(), &validators).is_ok());
+            assert_eq!(e.view(), 3);
+        }
+    }
+
+    /// A 2/2 partition can neither certify a block nor time out a view. After healing, the
+    /// accumulated timeout votes form a TC and the next leader's block is certified.
+    #[test]
+    fn test_partition_stalls_until_healed() {
+        let (keys, validators) = keys4();
+        let mut engines: Vec<ConsensusEngine> =
+            keys.iter().map(|_| ConsensusEngine::new(validators.clone())).collect();
+        let (side_a, side_b) = engines.split_at_mut(2);
+
+        // Side A holds the view-1 leader: its proposal gets only two votes.
+        assert!(keys[..2].iter().any(|k| id(k) == validators.leader(1)));
+        let block = mk_block(1, 1, leader(&keys, &validators, 1), Hash::ZERO, None);
+        for e in side_a.iter_mut() {
+            assert!(certify(e, &block, &keys[..2]).is_none());
+        }
+
+        // Both sides time out, but neither reaches 2f+1 = 3 timeout votes.
+        let timeouts: Vec<TimeoutVote> = keys.iter().map(|k| TimeoutVote::new(1, k)).collect();
+        for v in &timeouts[..2] {
+            assert!(deliver_timeout(side_a, v).iter().all(Option::is_none));
+        }
+        for v in &timeouts[2..] {
+            assert!(deliver_timeout(side_b, v).iter().all(Option::is_none));
+        }
+        assert!(engines.iter().all(|e| e.view() == 1 && e.timeout_stake() == 2));
+
+        // Heal: the missing votes cross the partition.
+        let (side_a, side_b) = engines.split_at_mut(2);
+        let tc = deliver_timeout(side_a, &timeouts[2])[0].clone().expect("TC after heal");
+        assert!(deliver_timeout(side_b, &timeouts[0]).iter().all(Option::is_some));
+        assert!(engines.iter().all(|e| e.view() == 2));
+
+        // Nodes that missed the votes catch up from the new-view message alone.
+        let mut lagging = ConsensusEngine::new(validators.clone());
+        assert!(lagging.new_view(&tc).unwrap());
+        assert!(!lagging.new_view(&tc).unwrap(), "stale TC is ignored");
+        assert_eq!(lagging.view(), 2);
+
+        let block = mk_block(1, 2, leader(&keys, &validators, 2), Hash::ZERO, None);
+        engines.push(lagging);
+        for e in engines.iter_mut() {
+            assert!(certify(e, &block, &keys[..3]).is_some());
+        }
+    }
+
+    #[test]
+    fn test_invalid_timeouts_rejected() {
+        let (keys, validators) = keys4();
+        let mut engine = ConsensusEngine::new(validators.clone());
+
+        let future = TimeoutVote::new(5, &keys[1]);
+        assert!(matches!(engine.timeout_vote(future), Err(ConsensusError::WrongView { view: 5 })));
+
+        let mut forged = TimeoutVote::new(1, &keys[1]);
+        forged.validator = validators.accounts()[2];
+        assert!(matches!(engine.timeout_vote(forged), Err(ConsensusError::InvalidSignature(_))));
 
-        // Honest: v1, v2, v3 vote. Byzantine v4 never votes.
-        assert!(engine.vote(block_hash, v1).unwrap().is_none());
-        assert!(engine.vote(block_hash, v2).unwrap().is_none());
-        let committed = engine.vote(block_hash, v3).unwrap();
-        assert_eq!(committed, Some(block_hash), "3 honest nodes should commit despite 1 Byzantine");
+        let votes: Vec<TimeoutVote> = keys[..2].iter().map(|k| TimeoutVote::new(1, k)).collect();
+        let short = TimeoutCertificate::from_votes(1, &votes);
+        assert!(matches!(engine.new_view(&short), Err(ConsensusError::InvalidCertificate(_))));
+        assert_eq!(engine.view(), 1);
     }
 }
 
@@ -258,6 +1068,16 @@ pub enum ConsensusError {
     InvalidBlock(String),
     #[error("Not enough votes")]
     InsufficientVotes,
+    #[error("Invalid signature from {0:?}")]
+    InvalidSignature(AccountId),
     #[error("Equivocation: validator {validator:?} voted for different blocks at round {round}")]
     Equivocation { validator: AccountId, round: u64 },
+    #[error("Message for view {view} is not for the current view")]
+    WrongView { view: u64 },
+    #[error("Already voted or timed out in view {view}")]
+    AlreadyVoted { view: u64 },
+    #[error("Block conflicts with the locked block and does not justify a newer one")]
+    ConflictsWithLock,
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(#[from] CertificateError),
 }
@@ -1,8 +1,13 @@
-//! Boing Consensus — PoS + HotStuff-style BFT
+//! Boing Consensus — PoS + chained HotStuff BFT
 //!
 //! Permissionless validator set, deterministic finality.
 
 mod engine;
+mod pacemaker;
 
-pub use engine::{ConsensusEngine, ConsensusError};
-pub use boing_primitives::{Block, BlockHeader, Hash};
+pub use engine::{BlockRef, CertifiedBlock, ConsensusEngine, ConsensusError, SafetyState};
+pub use pacemaker::Pacemaker;
+pub use boing_primitives::{
+    Block, BlockHeader, Hash, QuorumCertificate, TimeoutCertificate, TimeoutVote, Validator,
+    ValidatorSet, Vote,
+};
@@ -0,0 +1,116 @@
+//! Pacemaker — view timers with exponential backoff.
+//!
+//! Each view gets `base_timeout * 2^k`, where k counts consecutive views that ended in a
+//! timeout (capped at `max_timeout`). Progress (a commit) resets the backoff, so a healthy
+//! network runs at the base timeout while a partition or a run of crashed leaders waits
+//! progressively longer, giving slow links time to deliver.
+
+use std::time::{Duration, Instant};
+
+/// Backoff cap relative to the base timeout (2^6).
+const MAX_BACKOFF_FACTOR: u32 = 64;
+
+/// Round timer for the local validator.
+#[derive(Clone, Debug)]
+pub struct Pacemaker {
+    base_timeout: Duration,
+    max_timeout: Duration,
+    /// Views in a row that ended without a commit.
+    consecutive_timeouts: u32,
+    deadline: Instant,
+}
+
+impl Pacemaker {
+    /// Start the first view at `now` with the base timeout.
+    pub fn new(base_timeout: Duration, now: Instant) -> Self {
+        Self {
+            base_timeout,
+            max_timeout: base_timeout * MAX_BACKOFF_FACTOR,
+            consecutive_timeouts: 0,
+            deadline: now + base_timeout,
+        }
+    }
+
+    /// Timeout for the current view: base * 2^k, capped.
+    pub fn timeout(&self) -> Duration {
+        let factor = 1u32.checked_shl(self.consecutive_timeouts).unwrap_or(u32::MAX);
+        self.base_timeout.saturating_mul(factor).min(self.max_timeout)
+    }
+
+    /// Views in a row that ended in a timeout.
+    pub fn consecutive_timeouts(&self) -> u32 {
+        self.consecutive_timeouts
+    }
+
+    /// When the current view times out.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub fn is_expired(&self, now: Instant) -> bool {
+        now >= self.deadline
+    }
+
+    /// A block committed: reset the backoff and start a fresh view timer.
+    pub fn on_progress(&mut self, now: Instant) {
+        self.consecutive_timeouts = 0;
+        self.deadline = now + self.timeout();
+    }
+
+    /// Our view timer fired and we sent a timeout vote. Back off and re-arm so the vote is
+    /// repeated if no timeout certificate forms.
+    pub fn on_local_timeout(&mut self, now: Instant) {
+        self.consecutive_timeouts = self.consecutive_timeouts.saturating_add(1);
+        self.deadline = now + self.timeout();
+    }
+
+    /// Re-arm the timer without changing the backoff: a timeout certificate moved us to a
+    /// new view (its leader gets the current timeout), or there was nothing to wait for.
+    pub fn restart(&mut self, now: Instant) {
+        self.deadline = now + self.timeout();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeouts_double_until_cap() {
+        let base = Duration::from_millis(100);
+        let t0 = Instant::now();
+        let mut pm = Pacemaker::new(base, t0);
+        assert_eq!(pm.timeout(), base);
+        assert!(!pm.is_expired(t0));
+        assert!(pm.is_expired(t0 + base));
+
+        pm.on_local_timeout(t0 + base);
+        assert_eq!(pm.timeout(), base * 2);
+        pm.on_local_timeout(t0 + base * 3);
+        assert_eq!(pm.timeout(), base * 4);
+        assert_eq!(pm.deadline(), t0 + base * 7);
+
+        for _ in 0..40 {
+            pm.on_local_timeout(t0);
+        }
+        assert_eq!(pm.timeout(), base * MAX_BACKOFF_FACTOR);
+    }
+
+    #[test]
+    fn test_progress_resets_backoff() {
+        let base = Duration::from_millis(100);
+        let t0 = Instant::now();
+        let mut pm = Pacemaker::new(base, t0);
+        pm.on_local_timeout(t0);
+        pm.on_local_timeout(t0);
+        assert_eq!(pm.consecutive_timeouts(), 2);
+
+        // A new view keeps the backoff: the network has not recovered yet.
+        pm.restart(t0);
+        assert_eq!(pm.timeout(), base * 4);
+
+        pm.on_progress(t0);
+        assert_eq!(pm.consecutive_timeouts(), 0);
+        assert_eq!(pm.deadline(), t0 + base);
+    }
+}
@@ -0,0 +1,227 @@
+//! Property tests for chained HotStuff safety under Byzantine vote patterns.
+//!
+//! Four validators, one Byzantine. Each view the leader proposes; a Byzantine leader proposes
+//! two conflicting blocks on arbitrary certified ancestors, and the Byzantine validator votes
+//! for everything it sees. An adversarial schedule decides which proposals and votes reach
+//! each honest node. No two honest nodes may ever commit different blocks at the same height.
+
+use std::collections::HashMap;
+
+use boing_consensus::ConsensusEngine;
+use boing_primitives::{
+    AccountId, Block, BlockHeader, Hash, QuorumCertificate, TimeoutCertificate, TimeoutVote,
+    ValidatorSet, Vote,
+};
+use ed25519_dalek::SigningKey;
+use proptest::prelude::*;
+
+const N: usize = 4;
+const BYZANTINE: usize = 3;
+const VIEWS: u64 = 24;
+
+/// Adversarial delivery choices for one view.
+#[derive(Clone, Debug)]
+struct ViewSchedule {
+    /// Per honest node: bit j set = receives proposal j.
+    proposals: [u8; 3],
+    /// Bit (vote index * 3 + recipient) % 32 set = vote delivered to that honest node.
+    votes: u32,
+    /// Parents the Byzantine leader forks from (indices into certified blocks).
+    byzantine_parents: (u8, u8),
+}
+
+/// Mostly-delivered schedules: each message is dropped with probability 1/8, so blocks
+/// still get certified and committed often enough for forks to matter.
+fn schedule() -> impl Strategy<Value = ViewSchedule> {
+    (any::<[[u8; 3]; 3]>(), any::<[u32; 3]>(), any::<(u8, u8)>()).prop_map(|(p, v, byzantine_parents)| {
+        ViewSchedule {
+            proposals: [0, 1, 2].map(|i| !(p[0][i] & p[1][i] & p[2][i])),
+            votes: !(v[0] & v[1] & v[2]),
+            byzantine_parents,
+        }
+    })
+}
+
+struct Sim {
+    keys: Vec<SigningKey>,
+    ids: Vec<AccountId>,
+    validators: ValidatorSet,
+    /// Honest nodes 0..BYZANTINE.
+    engines: Vec<ConsensusEngine>,
+    /// Every block proposed so far.
+    blocks: HashMap<Hash, Block>,
+    /// Every vote sent so far, by block.
+    votes: HashMap<Hash, Vec<Vote>>,
+    /// Height -> block committed by any honest node.
+    committed: HashMap<u64, Hash>,
+    /// Per honest node: last committed (height, hash).
+    tips: Vec<(u64, Hash)>,
+}
+
+impl Sim {
+    fn new() -> Self {
+        let keys: Vec<SigningKey> = (1..=N as u8).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let ids: Vec<AccountId> = keys.iter().map(|k| AccountId(k.verifying_key().to_bytes())).collect();
+        let validators = ValidatorSet::equal_weight(ids.clone());
+        let engines = (0..BYZANTINE).map(|_| ConsensusEngine::new(validators.clone())).collect();
+        Self {
+            keys,
+            ids,
+            validators,
+            engines,
+            blocks: HashMap::new(),
+            votes: HashMap::new(),
+            committed: HashMap::new(),
+            tips: vec![(0, Hash::ZERO); BYZANTINE],
+        }
+    }
+
+    fn mk_block(&self, proposer: usize, view: u64, parent: (Hash, u64), justify: Option<QuorumCertificate>, salt: u64) -> Block {
+        let mut block = Block::new(
+            BlockHeader {
+                parent_hash: parent.0,
+                height: parent.1 + 1,
+                round: view,
+                timestamp: salt,
+                proposer: self.ids[proposer],
+                tx_root: Hash::ZERO,
+                state_root: Hash::ZERO,
+                validator_set_hash: self.validators.hash(),
+                vrf: Default::default(),
+                randomness: Hash::ZERO,
+                justify_hash: Hash::ZERO,
+            },
+            vec![],
+        );
+        block.set_justify(justify);
+        block.sign(&self.keys[proposer]);
+        block
+    }
+
+    /// Blocks the adversary can build on: genesis plus every block with 2f+1 votes.
+    fn certified(&self) -> Vec<((Hash, u64), Option<QuorumCertificate>)> {
+        let mut out = vec![((Hash::ZERO, 0), None)];
+        let mut hashes: Vec<&Hash> = self.votes.keys().collect();
+        hashes.sort_by_key(|h| h.0);
+        for hash in hashes {
+            let mut voters: Vec<&Vote> = Vec::new();
+            for v in &self.votes[hash] {
+                if !voters.iter().any(|x| x.validator == v.validator) {
+                    voters.push(v);
+                }
+            }
+            if voters.len() >= 3 {
+                let block = &self.blocks[hash];
+                let qc = QuorumCertificate::from_votes(*hash, voters);
+                out.push(((*hash, block.header.height), Some(qc)));
+            }
+        }
+        out
+    }
+
+    fn run_view(&mut self, view: u64, s: &ViewSchedule) {
+        let leader = self.ids.iter().position(|id| *id == self.validators.leader(view)).expect("leader in set");
+        let proposals: Vec<Block> = if leader == BYZANTINE {
+            let certified = self.certified();
+            let (a, b) = s.byzantine_parents;
+            let (pa, qa) = certified[a as usize % certified.len()].clone();
+            let (pb, qb) = certified[b as usize % certified.len()].clone();
+            vec![self.mk_block(leader, view, pa, qa, 1), self.mk_block(leader, view, pb, qb, 2)]
+        } else if self.engines[leader].view() == view {
+            let (parent, justify) = self.engines[leader].extend_target();
+            vec![self.mk_block(leader, view, (parent.hash, parent.height), justify, 0)]
+        } else {
+            vec![] // the honest leader is behind and does not know it leads
+        };
+        for p in &proposals {
+            self.blocks.insert(p.hash(), p.clone());
+        }
+
+        // Honest nodes receive proposals and vote under the safety rules.
+        let mut sent: Vec<(usize, Vote)> = Vec::new();
+        for node in 0..BYZANTINE {
+            for (j, p) in proposals.iter().enumerate() {
+                let delivered = node == leader || s.proposals[node] & (1 << j) != 0;
+                if !delivered || self.engines[node].propose(p.clone()).is_err() {
+                    continue;
+                }
+                if self.engines[node].prepare_vote(&p.hash()).is_ok() {
+                    sent.push((node, Vote::new(p.hash(), &self.keys[node])));
+                }
+            }
+        }
+        // The Byzantine validator votes for every proposal of the view.
+        for p in &proposals {
+            sent.push((BYZANTINE, Vote::new(p.hash(), &self.keys[BYZANTINE])));
+        }
+
+        for (i, (voter, vote)) in sent.iter().enumerate() {
+            self.votes.entry(vote.block_hash).or_default().push(vote.clone());
+            for node in 0..BYZANTINE {
+                let bit = (i * BYZANTINE + node) % 32;
+                if node == *voter || s.votes & (1 << bit) != 0 {
+                    let _ = self.engines[node].vote(vote.clone());
+                }
+            }
+        }
+
+        // Nodes still in this view time out; the Byzantine validator always does.
+        let mut timeouts = vec![TimeoutVote::new(view, &self.keys[BYZANTINE])];
+        for node in 0..BYZANTINE {
+            if self.engines[node].view() == view {
+                let v = self.engines[node].prepare_timeout();
+                timeouts.push(TimeoutVote::new(v, &self.keys[node]));
+            }
+        }
+        if timeouts.len() >= 3 {
+            let tc = TimeoutCertificate::from_votes(view, &timeouts);
+            for engine in self.engines.iter_mut() {
+                let _ = engine.new_view(&tc);
+            }
+        }
+
+        self.check_commits();
+    }
+
+    fn check_commits(&mut self) {
+        for node in 0..BYZANTINE {
+            for block in self.engines[node].take_committed() {
+                let hash = block.hash();
+                let (tip_height, tip_hash) = self.tips[node];
+                assert_eq!(block.header.height, tip_height + 1, "node {node} committed out of order");
+                assert_eq!(block.header.parent_hash, tip_hash, "node {node} committed a fork of its own chain");
+                let qc = block.qc.as_ref().expect("committed block carries its QC");
+                assert!(qc.verify(&hash, &self.validators).is_ok());
+                let first = *self.committed.entry(block.header.height).or_insert(hash);
+                assert_eq!(first, hash, "conflicting commits at height {}", block.header.height);
+                self.tips[node] = (block.header.height, hash);
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    /// No two honest validators commit different blocks at the same height, whatever the
+    /// Byzantine validator proposes or votes and however messages are dropped.
+    #[test]
+    fn prop_no_conflicting_commits(schedules in proptest::collection::vec(schedule(), VIEWS as usize)) {
+        let mut sim = Sim::new();
+        for (i, s) in schedules.iter().enumerate() {
+            sim.run_view(i as u64 + 1, s);
+        }
+    }
+}
+
+/// With every message delivered, honest leaders drive commits despite the views the
+/// Byzantine validator leads.
+#[test]
+fn full_delivery_commits() {
+    let mut sim = Sim::new();
+    let everything = ViewSchedule { proposals: [0xff; 3], votes: u32::MAX, byzantine_parents: (0, 0) };
+    for view in 1..=VIEWS {
+        sim.run_view(view, &everything);
+    }
+    assert!(sim.tips.iter().all(|(h, _)| *h >= 3), "tips: {:?}", sim.tips);
+}
@@ -1,7 +1,7 @@
 //! Benchmarks: sequential vs parallel block execution.
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use boing_execution::BlockExecutor;
+use boing_execution::{BlockContext, BlockExecutor};
 use boing_primitives::{AccessList, Account, AccountId, AccountState, Transaction, TransactionPayload};
 use boing_state::StateStore;
 
@@ -20,6 +20,7 @@ fn mk_parallel_transfers(n: usize) -> (Vec<Transaction>, StateStore) {
             let from = AccountId({ let mut a = [0u8; 32]; a[0] = (i * 2) as u8; a });
             let to = AccountId({ let mut a = [0u8; 32]; a[0] = (i * 2 + 1) as u8; a });
             Transaction {
+                chain_id: 1,
                 nonce: 0,
                 sender: from,
                 payload: TransactionPayload::Transfer { to, amount: 1 },
@@ -47,6 +48,7 @@ fn mk_sequential_transfers(n: usize) -> (Vec<Transaction>, StateStore) {
             let to = AccountId({ let mut a = [0u8; 32]; a[0] = (i % 2 + 2) as u8; a });
             let nonce = (i / 2) as u64; // per-sender: 0,0,1,1,2,2,...
             Transaction {
+                chain_id: 1,
                 nonce,
                 sender: from,
                 payload: TransactionPayload::Transfer { to, amount: 1 },
@@ -65,7 +67,7 @@ fn bench_parallel(c: &mut Criterion) {
             let exec = BlockExecutor::new();
             b.iter(|| {
                 let mut s = state.snapshot();
-                exec.execute_block(black_box(&txs), &mut s).unwrap()
+                exec.execute_block(black_box(&txs), &mut s, &BlockContext::default()).unwrap()
             });
         });
     }
@@ -80,7 +82,7 @@ fn bench_sequential(c: &mut Criterion) {
             let exec = BlockExecutor::new();
             b.iter(|| {
                 let mut s = state.snapshot();
-                exec.execute_block(black_box(&txs), &mut s).unwrap()
+                exec.execute_block(black_box(&txs), &mut s, &BlockContext::default()).unwrap()
             });
         });
     }
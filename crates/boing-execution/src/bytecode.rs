@@ -14,6 +14,8 @@ pub enum Opcode {
     Sub = 0x02,
     /// Multiply (0x03)
     Mul = 0x03,
+    /// Push the block's randomness beacon, like EVM PREVRANDAO (0x44)
+    PrevRandao = 0x44,
     /// Load from memory at offset (0x51)
     MLoad = 0x51,
     /// Store to memory (0x52)
@@ -41,6 +43,7 @@ impl Opcode {
             0x01 => Some(Self::Add),
             0x02 => Some(Self::Sub),
             0x03 => Some(Self::Mul),
+            0x44 => Some(Self::PrevRandao),
             0x51 => Some(Self::MLoad),
             0x52 => Some(Self::MStore),
             0x54 => Some(Self::SLoad),
@@ -69,6 +72,7 @@ pub mod gas {
     pub const ADD: u64 = 3;
     pub const SUB: u64 = 3;
     pub const MUL: u64 = 5;
+    pub const PREVRANDAO: u64 = 2;
     pub const MLOAD: u64 = 3;
     pub const MSTORE: u64 = 3;
     pub const SLOAD: u64 = 100;
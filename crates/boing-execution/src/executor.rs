@@ -4,17 +4,21 @@
 
 use rayon::prelude::*;
 
-use boing_primitives::{Transaction, TransactionPayload};
+use boing_primitives::{AccountId, Transaction, TransactionPayload, ValidatorSet};
 use boing_state::StateStore;
 
 use super::parallel::ExecutionView;
-use super::{TransactionScheduler, Vm, VmError};
+use super::{
+    punish_downtime, settle_relays, BlockContext, LivenessParams, RelayParams, SlashingParams,
+    TransactionScheduler, Vm, VmError,
+};
 
 /// Executes a block of transactions. Batches run sequentially; within each batch,
 /// Transfer-only txs run in parallel.
 pub struct BlockExecutor {
     vm: Vm,
     scheduler: TransactionScheduler,
+    liveness: LivenessParams,
 }
 
 impl BlockExecutor {
@@ -22,16 +26,70 @@ impl BlockExecutor {
         Self {
             vm: Vm::new(),
             scheduler: TransactionScheduler::new(),
+            liveness: LivenessParams::default(),
         }
     }
 
-    /// Execute all transactions. Returns total gas used and any error.
+    /// Set the unbonding delay (blocks) applied to `Unbond` transactions.
+    pub fn with_unbonding_delay(mut self, blocks: u64) -> Self {
+        self.vm = self.vm.with_unbonding_delay(blocks);
+        self
+    }
+
+    /// Set the slashing parameters applied to equivocation reports and appeals.
+    pub fn with_slashing(mut self, params: SlashingParams) -> Self {
+        self.vm = self.vm.with_slashing(params);
+        self
+    }
+
+    /// Set the downtime window and penalty applied by [`Self::record_liveness`].
+    pub fn with_liveness(mut self, params: LivenessParams) -> Self {
+        self.liveness = params;
+        self
+    }
+
+    /// Set the relay registry parameters applied to relay transactions and to
+    /// [`Self::settle_relays`].
+    pub fn with_relays(mut self, params: RelayParams) -> Self {
+        self.vm = self.vm.with_relays(params);
+        self
+    }
+
+    /// After the block's transactions: record the proposals and votes it shows were missed
+    /// and slash validators over the downtime limit. `set` governs the block (height `ctx`,
+    /// view `round`); `signers` signed its `justify` QC.
+    pub fn record_liveness(
+        &self,
+        state: &mut StateStore,
+        ctx: &BlockContext,
+        round: u64,
+        set: &ValidatorSet,
+        signers: &[AccountId],
+    ) {
+        punish_downtime(state, ctx.height, round, set, signers, &self.liveness, self.vm.slashing());
+    }
+
+    /// After [`Self::record_liveness`]: at an epoch boundary, score, pay or drop the
+    /// registered relays from this epoch's reports.
+    pub fn settle_relays(&self, state: &mut StateStore, ctx: &BlockContext) {
+        settle_relays(state, ctx.height, self.vm.relays());
+    }
+
+    /// Execute one transaction on its own, in order, as the sequential path of
+    /// [`Self::execute_block`] would. Block producers use it to leave out transactions
+    /// that would fail the block.
+    pub fn execute_transaction(&self, tx: &Transaction, state: &mut StateStore, ctx: &BlockContext) -> Result<u64, VmError> {
+        self.vm.execute(tx, state, ctx)
+    }
+
+    /// Execute all transactions of the block in `ctx`. Returns total gas used and any error.
     /// On error, state may be partially applied (caller should revert if needed).
     /// Transfer-only batches run in parallel; other batches run sequentially.
     pub fn execute_block(
         &self,
         txs: &[Transaction],
         state: &mut StateStore,
+        ctx: &BlockContext,
     ) -> Result<u64, ExecutionError> {
         let batches = self.scheduler.schedule(txs);
         let mut total_gas = 0u64;
@@ -87,7 +145,7 @@ impl BlockExecutor {
                 // Sequential path
                 for &idx in &batch {
                     let tx = &txs[idx];
-                    let gas = self.vm.execute(tx, state).map_err(ExecutionError::Vm)?;
+                    let gas = self.vm.execute(tx, state, ctx).map_err(ExecutionError::Vm)?;
                     total_gas = total_gas.saturating_add(gas);
                 }
             }
@@ -109,6 +167,7 @@ mod tests {
 
     fn tx(sender: AccountId, to: AccountId, nonce: u64, amount: u128) -> Transaction {
         Transaction {
+            chain_id: 1,
             nonce,
             sender,
             payload: TransactionPayload::Transfer { to, amount },
@@ -131,7 +190,7 @@ mod tests {
             state: AccountState { balance: 0, nonce: 0, stake: 0 },
         });
         let txs = vec![tx(a, b, 0, 100)];
-        let gas = exec.execute_block(&txs, &mut state).unwrap();
+        let gas = exec.execute_block(&txs, &mut state, &BlockContext::default()).unwrap();
         assert_eq!(gas, super::super::vm::GAS_PER_TRANSFER);
         assert_eq!(state.get(&a).unwrap().balance, 900);
         assert_eq!(state.get(&b).unwrap().balance, 100);
@@ -154,7 +213,7 @@ mod tests {
             tx(a, b, 0, 100),
             tx(c, d, 0, 50),
         ];
-        let gas = exec.execute_block(&txs, &mut state).unwrap();
+        let gas = exec.execute_block(&txs, &mut state, &BlockContext::default()).unwrap();
         assert_eq!(state.get(&a).unwrap().balance, 900);
         assert_eq!(state.get(&b).unwrap().balance, 100);
         assert_eq!(state.get(&c).unwrap().balance, 450);
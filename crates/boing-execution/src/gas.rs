@@ -9,6 +9,19 @@ pub mod base {
     pub const CONTRACT_DEPLOY: u64 = 200_000;
     pub const BOND: u64 = 21_000;
     pub const UNBOND: u64 = 21_000;
+    pub const WITHDRAW: u64 = 21_000;
+    pub const REGISTER_VALIDATOR: u64 = 21_000;
+    pub const DELEGATE: u64 = 21_000;
+    pub const UNDELEGATE: u64 = 21_000;
+    pub const REPORT_EQUIVOCATION: u64 = 50_000;
+    pub const APPEAL_SLASH: u64 = 21_000;
+    pub const RESOLVE_APPEAL: u64 = 21_000;
+    pub const UNJAIL: u64 = 21_000;
+    pub const REGISTER_RELAY: u64 = 21_000;
+    pub const REPORT_RELAY_METRICS: u64 = 21_000;
+    pub const DEREGISTER_RELAY: u64 = 21_000;
+    pub const FULFILL_INTENT: u64 = 50_000;
+    pub const CANCEL_INTENT: u64 = 21_000;
 }
 
 /// Maximum gas multiplier (e.g. 2x under heavy load).
@@ -6,7 +6,7 @@ use boing_primitives::AccountId;
 use boing_state::StateStore;
 
 use super::bytecode::{gas, Opcode};
-use super::vm::VmError;
+use super::vm::{BlockContext, VmError};
 
 /// Stack machine interpreter.
 pub struct Interpreter {
@@ -17,6 +17,8 @@ pub struct Interpreter {
     pub gas_used: u64,
     pub gas_limit: u64,
     pub return_data: Option<Vec<u8>>,
+    /// Block the code runs in (for `PREVRANDAO`).
+    pub context: BlockContext,
 }
 
 /// Storage interface for SLOAD/SSTORE.
@@ -35,9 +37,15 @@ impl Interpreter {
             gas_used: 0,
             gas_limit,
             return_data: None,
+            context: BlockContext::default(),
         }
     }
 
+    pub fn with_context(mut self, context: BlockContext) -> Self {
+        self.context = context;
+        self
+    }
+
     fn spend_gas(&mut self, amount: u64) -> Result<(), VmError> {
         self.gas_used = self.gas_used.saturating_add(amount);
         if self.gas_used > self.gas_limit {
@@ -164,6 +172,10 @@ impl Interpreter {
                     let a = self.pop()?;
                     self.push(Self::mul_u256(&a, &b));
                 }
+                Opcode::PrevRandao => {
+                    self.spend_gas(gas::PREVRANDAO)?;
+                    self.push(self.context.randomness.0);
+                }
                 Opcode::MLoad => {
                     self.spend_gas(gas::MLOAD)?;
                     let offset = Self::u256_to_usize(&self.pop()?);
@@ -264,4 +276,16 @@ mod tests {
         assert_eq!(interpreter.stack.len(), 1);
         assert_eq!(interpreter.stack[0][31], 5); // low byte = 5
     }
+
+    #[test]
+    fn test_prevrandao_pushes_block_randomness() {
+        let mut state = StateStore::new();
+        let contract = AccountId([1u8; 32]);
+        let randomness = boing_primitives::Hash([7u8; 32]);
+        let ctx = BlockContext::at_height(3).with_randomness(randomness);
+        let mut interpreter = Interpreter::new(vec![0x44, 0x00], 1000).with_context(ctx); // PREVRANDAO STOP
+        interpreter.run(contract, &[], &mut state).unwrap();
+        assert_eq!(interpreter.stack, vec![randomness.0]);
+        assert_eq!(interpreter.gas_used, gas::PREVRANDAO);
+    }
 }
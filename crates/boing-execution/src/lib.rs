@@ -7,7 +7,9 @@ mod executor;
 mod gas;
 mod interpreter;
 mod parallel;
+mod relays;
 mod scheduler;
+mod slashing;
 mod vm;
 
 pub use bytecode::{gas as bytecode_gas, Opcode};
@@ -15,6 +17,15 @@ pub use executor::{BlockExecutor, ExecutionError};
 pub use gas::GasConfig;
 pub use interpreter::{Interpreter, StorageAccess};
 pub use parallel::ExecutionView;
+pub use relays::{
+    settle_relays, RelayParams, DEFAULT_RELAY_DEREGISTER_SLASH_BPS, DEFAULT_RELAY_EPOCH_REWARD,
+    DEFAULT_RELAY_MIN_REPUTATION, DEFAULT_RELAY_MIN_STAKE,
+};
 pub use scheduler::TransactionScheduler;
-pub use vm::{TransferState, Vm, VmError};
+pub use slashing::{
+    punish_downtime, slash_validator, LivenessParams, SlashingParams, DEFAULT_APPEAL_WINDOW,
+    DEFAULT_EQUIVOCATION_SLASH_BPS, DEFAULT_LIVENESS_JAIL_BLOCKS, DEFAULT_LIVENESS_MAX_MISSED,
+    DEFAULT_LIVENESS_SLASH_BPS, DEFAULT_LIVENESS_WINDOW,
+};
+pub use vm::{BlockContext, TransferState, Vm, VmError, DEFAULT_UNBONDING_DELAY};
 pub use boing_primitives::{Transaction, AccessList};
@@ -0,0 +1,87 @@
+//! Relay settlement — per-epoch reputation, rewards and deregistration of STUN/TURN relays.
+//!
+//! At every epoch boundary each relay's reports are reduced to their field-wise median and
+//! scored; the score is blended into the relay's reputation. Relays that were reported and
+//! stay at or above the minimum reputation earn the epoch reward scaled by reputation.
+//! Relays that fall below it are dropped from the registry and lose a share of their stake
+//! (burned); the rest is returned. Relays without reports keep their reputation and earn
+//! nothing. Relays that asked to leave get their whole stake back.
+
+use boing_primitives::{StunTurnMetrics, StunTurnReputation};
+use boing_state::StateStore;
+
+use crate::vm::credit;
+
+/// Minimum stake to register a relay, unless configured otherwise.
+pub const DEFAULT_RELAY_MIN_STAKE: u128 = 1_000;
+
+/// Reputation (0–10000) a relay must keep to stay registered, unless configured otherwise.
+pub const DEFAULT_RELAY_MIN_REPUTATION: u16 = 6_000;
+
+/// Reward for a relay at full reputation per epoch, unless configured otherwise.
+pub const DEFAULT_RELAY_EPOCH_REWARD: u128 = 10;
+
+/// Share of stake (basis points) burned when a relay is dropped for low reputation, unless
+/// configured otherwise.
+pub const DEFAULT_RELAY_DEREGISTER_SLASH_BPS: u16 = 1_000;
+
+/// How relays are admitted, scored and paid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelayParams {
+    /// Blocks per epoch; relays are settled at every multiple of it.
+    pub epoch_length: u64,
+    /// Minimum total stake of a registered relay.
+    pub min_stake: u128,
+    /// A relay whose reputation falls below this is deregistered.
+    pub min_reputation: u16,
+    /// Paid to a reported relay per epoch, scaled by its reputation.
+    pub epoch_reward: u128,
+    pub deregister_slash_bps: u16,
+}
+
+impl Default for RelayParams {
+    fn default() -> Self {
+        Self {
+            epoch_length: 1_000,
+            min_stake: DEFAULT_RELAY_MIN_STAKE,
+            min_reputation: DEFAULT_RELAY_MIN_REPUTATION,
+            epoch_reward: DEFAULT_RELAY_EPOCH_REWARD,
+            deregister_slash_bps: DEFAULT_RELAY_DEREGISTER_SLASH_BPS,
+        }
+    }
+}
+
+/// Settle the epoch ending at `height` for every registered relay. Does nothing at other
+/// heights.
+pub fn settle_relays(state: &mut StateStore, height: u64, params: &RelayParams) {
+    if height == 0 || params.epoch_length == 0 || !height.is_multiple_of(params.epoch_length) {
+        return;
+    }
+    for operator in state.staking().relays().operators() {
+        let registry = state.staking_mut().relays_mut();
+        let reports = registry.take_reports(&operator);
+        let Some(entry) = registry.get(&operator).cloned() else {
+            continue;
+        };
+        if entry.leaving {
+            registry.remove(&operator);
+            credit(state, operator, entry.stake);
+            continue;
+        }
+        let Some(median) = StunTurnMetrics::median(&reports) else {
+            continue;
+        };
+        let reputation = entry.reputation.update(StunTurnReputation::from_metrics(&median));
+        if reputation.score < params.min_reputation {
+            registry.remove(&operator);
+            let penalty = entry.stake * params.deregister_slash_bps.min(10_000) as u128 / 10_000;
+            credit(state, operator, entry.stake - penalty);
+            continue;
+        }
+        registry.set_reputation(&operator, reputation);
+        let reward = params.epoch_reward * reputation.score as u128 / StunTurnReputation::MAX as u128;
+        if reward > 0 {
+            credit(state, operator, reward);
+        }
+    }
+}
@@ -55,6 +55,7 @@ mod tests {
 
     fn tx_with_access(accounts: Vec<AccountId>) -> Transaction {
         Transaction {
+            chain_id: 1,
             nonce: 0,
             sender: accounts[0],
             payload: TransactionPayload::Transfer {
@@ -0,0 +1,132 @@
+//! Slashing — stake deductions for equivocation and downtime.
+//!
+//! A slash takes a share of the validator's own bonded stake, of every delegation to it and
+//! of unbonding entries still bonded to it, and burns it. The slash is recorded in the
+//! staking state's registry with what was taken from whom (refunded if an appeal is
+//! approved), and the validator is jailed: validator sets derived while it is jailed skip it.
+
+use boing_governance::SlashReason;
+use boing_primitives::{AccountId, ValidatorSet};
+use boing_state::StateStore;
+
+/// Share of stake (basis points) taken for equivocation, unless configured otherwise.
+pub const DEFAULT_EQUIVOCATION_SLASH_BPS: u16 = 500;
+
+/// Blocks after a slash during which the validator may appeal, unless configured otherwise.
+pub const DEFAULT_APPEAL_WINDOW: u64 = 2_000;
+
+/// How equivocation is punished and who decides appeals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlashingParams {
+    /// Share of the validator's own, delegated and unbonding stake taken (and burned).
+    pub equivocation_slash_bps: u16,
+    /// Blocks after the slash during which an appeal can be submitted.
+    pub appeal_window: u64,
+    /// Account allowed to resolve appeals. None: appeals can never be resolved.
+    pub governance: Option<AccountId>,
+}
+
+impl Default for SlashingParams {
+    fn default() -> Self {
+        Self {
+            equivocation_slash_bps: DEFAULT_EQUIVOCATION_SLASH_BPS,
+            appeal_window: DEFAULT_APPEAL_WINDOW,
+            governance: None,
+        }
+    }
+}
+
+/// Blocks over which missed proposals and votes are counted, unless configured otherwise.
+pub const DEFAULT_LIVENESS_WINDOW: u64 = 1_000;
+
+/// Misses tolerated within the window, unless configured otherwise.
+pub const DEFAULT_LIVENESS_MAX_MISSED: u64 = 500;
+
+/// Share of stake (basis points) taken for downtime, unless configured otherwise.
+pub const DEFAULT_LIVENESS_SLASH_BPS: u16 = 10;
+
+/// Blocks a validator jailed for downtime waits before it can `Unjail`, unless configured
+/// otherwise.
+pub const DEFAULT_LIVENESS_JAIL_BLOCKS: u64 = 1_000;
+
+/// How downtime is detected and punished.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LivenessParams {
+    /// Sliding window, in blocks.
+    pub window: u64,
+    /// A validator with more missed proposals and votes than this in the window is slashed.
+    pub max_missed: u64,
+    pub slash_bps: u16,
+    /// Blocks from the slash until the validator may send `Unjail`.
+    pub jail_blocks: u64,
+}
+
+impl Default for LivenessParams {
+    fn default() -> Self {
+        Self {
+            window: DEFAULT_LIVENESS_WINDOW,
+            max_missed: DEFAULT_LIVENESS_MAX_MISSED,
+            slash_bps: DEFAULT_LIVENESS_SLASH_BPS,
+            jail_blocks: DEFAULT_LIVENESS_JAIL_BLOCKS,
+        }
+    }
+}
+
+/// Slash `bps` of the stake behind `validator` for `reason` at `height`, and jail it until
+/// `release_height` (`u64::MAX`: until an approved appeal). Returns the slash id.
+pub fn slash_validator(
+    state: &mut StateStore,
+    validator: AccountId,
+    bps: u16,
+    reason: SlashReason,
+    height: u64,
+    appeal_window: u64,
+    release_height: u64,
+) -> u64 {
+    let bps = bps.min(10_000);
+    let mut taken = state.staking_mut().slash_bonded_to(&validator, bps);
+    if let Some(account) = state.get_mut(&validator) {
+        let own = account.stake * bps as u128 / 10_000;
+        account.stake -= own;
+        if own > 0 {
+            taken.push((validator, own));
+        }
+    }
+    let total = taken.iter().map(|(_, amount)| amount).sum();
+    let staking = state.staking_mut();
+    let slash_id = staking.slashes_mut().record_slash(validator.0, total, reason, height, appeal_window);
+    staking.record_deductions(slash_id, taken);
+    staking.jail(validator, release_height);
+    slash_id
+}
+
+/// Record the missed duties revealed by the block at `height` in view `round` under `set`,
+/// whose `justify` QC was signed by `signers`, then slash and jail every validator over the
+/// limit. Validators already jailed are not slashed again; their misses are dropped.
+pub fn punish_downtime(
+    state: &mut StateStore,
+    height: u64,
+    round: u64,
+    set: &ValidatorSet,
+    signers: &[AccountId],
+    liveness: &LivenessParams,
+    slashing: &SlashingParams,
+) {
+    let tracker = state.staking_mut().liveness_mut();
+    tracker.record_block(height, round, set, signers, liveness.window);
+    for validator in tracker.over_limit(liveness.max_missed) {
+        state.staking_mut().liveness_mut().clear(&validator);
+        if state.staking().is_jailed(&validator) {
+            continue;
+        }
+        slash_validator(
+            state,
+            validator,
+            liveness.slash_bps,
+            SlashReason::Liveness,
+            height,
+            slashing.appeal_window,
+            height.saturating_add(liveness.jail_blocks),
+        );
+    }
+}
@@ -1,9 +1,17 @@
 //! Boing VM — deterministic execution engine.
 
-use boing_primitives::{hasher, AccountId, AccountState, Transaction, TransactionPayload};
+use boing_governance::{SlashReason, SlashingError};
+use boing_primitives::{
+    hasher, AccountId, AccountState, EquivocationEvidence, EvidenceError, Hash, IntentKind,
+    SignedIntent, Transaction, TransactionPayload, NATIVE_ASSET,
+};
+use boing_primitives::relay::MAX_RELAY_MULTIADDR_LEN;
 use boing_state::StateStore;
+use boing_tokenomics::{VALIDATOR_COMMISSION_MAX_BPS, VALIDATOR_COMMISSION_MIN_BPS};
 
 use crate::gas::base;
+use crate::relays::RelayParams;
+use crate::slashing::{slash_validator, SlashingParams};
 use super::interpreter::Interpreter;
 
 /// Gas used by a transaction.
@@ -11,6 +19,28 @@ pub const GAS_PER_TRANSFER: u64 = 21_000;
 pub const GAS_PER_CONTRACT_CALL: u64 = 100_000;
 pub const GAS_PER_CONTRACT_DEPLOY: u64 = 200_000;
 
+/// Blocks unbonded stake waits before it can be withdrawn, unless configured otherwise.
+pub const DEFAULT_UNBONDING_DELAY: u64 = 3_000;
+
+/// Block a transaction executes in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockContext {
+    pub height: u64,
+    /// The block's randomness beacon, readable by contracts through `PREVRANDAO`.
+    pub randomness: Hash,
+}
+
+impl BlockContext {
+    pub fn at_height(height: u64) -> Self {
+        Self { height, randomness: Hash::ZERO }
+    }
+
+    pub fn with_randomness(mut self, randomness: Hash) -> Self {
+        self.randomness = randomness;
+        self
+    }
+}
+
 /// Minimal state for Transfer-only execution (used by parallel path).
 pub trait TransferState {
     fn get(&self, id: &AccountId) -> Option<AccountState>;
@@ -43,11 +73,50 @@ impl TransferState for super::parallel::ExecutionView {
 }
 
 /// Virtual machine for executing transactions.
-pub struct Vm;
+#[derive(Clone, Debug)]
+pub struct Vm {
+    unbonding_delay: u64,
+    slashing: SlashingParams,
+    relays: RelayParams,
+}
 
 impl Vm {
     pub fn new() -> Self {
-        Self
+        Self {
+            unbonding_delay: DEFAULT_UNBONDING_DELAY,
+            slashing: SlashingParams::default(),
+            relays: RelayParams::default(),
+        }
+    }
+
+    /// Set how many blocks unbonded stake stays queued before `Withdraw` can claim it.
+    pub fn with_unbonding_delay(mut self, blocks: u64) -> Self {
+        self.unbonding_delay = blocks;
+        self
+    }
+
+    pub fn unbonding_delay(&self) -> u64 {
+        self.unbonding_delay
+    }
+
+    /// Set the equivocation penalty, appeal window and appeal authority.
+    pub fn with_slashing(mut self, params: SlashingParams) -> Self {
+        self.slashing = params;
+        self
+    }
+
+    pub fn slashing(&self) -> &SlashingParams {
+        &self.slashing
+    }
+
+    /// Set the relay registry's stake, reputation and reward parameters.
+    pub fn with_relays(mut self, params: RelayParams) -> Self {
+        self.relays = params;
+        self
+    }
+
+    pub fn relays(&self) -> &RelayParams {
+        &self.relays
     }
 
     /// Execute Transfer tx against any TransferState (for parallel path).
@@ -92,8 +161,13 @@ impl Vm {
         Ok(GAS_PER_TRANSFER)
     }
 
-    /// Execute a single transaction against the state.
-    pub fn execute(&self, tx: &Transaction, state: &mut StateStore) -> Result<u64, VmError> {
+    /// Execute a single transaction against the state, as part of the block in `ctx`.
+    pub fn execute(
+        &self,
+        tx: &Transaction,
+        state: &mut StateStore,
+        ctx: &BlockContext,
+    ) -> Result<u64, VmError> {
         // Nonce validation
         let sender_state = state.get(&tx.sender).ok_or(VmError::AccountNotFound)?;
         if sender_state.nonce != tx.nonce {
@@ -123,13 +197,170 @@ impl Vm {
                     .stake
                     .checked_sub(*amount)
                     .ok_or(VmError::InsufficientBalance)?;
-                sender_state.balance = sender_state.balance.saturating_add(*amount);
                 sender_state.nonce = sender_state
                     .nonce
                     .checked_add(1)
                     .ok_or(VmError::NonceOverflow)?;
+                let release_height = ctx.height.saturating_add(self.unbonding_delay);
+                state.staking_mut().queue_unbond(tx.sender, tx.sender, *amount, release_height);
                 base::UNBOND
             }
+            TransactionPayload::Withdraw => {
+                let released = state.staking_mut().withdraw_matured(&tx.sender, ctx.height);
+                if released == 0 {
+                    return Err(VmError::NothingToWithdraw);
+                }
+                let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
+                sender_state.balance = sender_state.balance.saturating_add(released);
+                sender_state.nonce = sender_state
+                    .nonce
+                    .checked_add(1)
+                    .ok_or(VmError::NonceOverflow)?;
+                base::WITHDRAW
+            }
+            TransactionPayload::RegisterValidator { commission_bps } => {
+                if !(VALIDATOR_COMMISSION_MIN_BPS..=VALIDATOR_COMMISSION_MAX_BPS).contains(commission_bps) {
+                    return Err(VmError::InvalidCommission(*commission_bps));
+                }
+                let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
+                sender_state.nonce = sender_state
+                    .nonce
+                    .checked_add(1)
+                    .ok_or(VmError::NonceOverflow)?;
+                state.staking_mut().register_validator(tx.sender, *commission_bps);
+                base::REGISTER_VALIDATOR
+            }
+            TransactionPayload::Delegate { validator, amount } => {
+                if state.staking().validator(validator).is_none() {
+                    return Err(VmError::NotAValidator);
+                }
+                let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
+                sender_state.balance = sender_state
+                    .balance
+                    .checked_sub(*amount)
+                    .ok_or(VmError::InsufficientBalance)?;
+                sender_state.nonce = sender_state
+                    .nonce
+                    .checked_add(1)
+                    .ok_or(VmError::NonceOverflow)?;
+                state.staking_mut().delegate(*validator, tx.sender, *amount);
+                base::DELEGATE
+            }
+            TransactionPayload::Undelegate { validator, amount } => {
+                if !state.staking_mut().undelegate(validator, &tx.sender, *amount) {
+                    return Err(VmError::InsufficientDelegation);
+                }
+                let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
+                sender_state.nonce = sender_state
+                    .nonce
+                    .checked_add(1)
+                    .ok_or(VmError::NonceOverflow)?;
+                let release_height = ctx.height.saturating_add(self.unbonding_delay);
+                state.staking_mut().queue_unbond(tx.sender, *validator, *amount, release_height);
+                base::UNDELEGATE
+            }
+            TransactionPayload::ReportEquivocation { evidence } => {
+                self.punish_equivocation(state, evidence, ctx)?;
+                bump_nonce(state, &tx.sender)?;
+                base::REPORT_EQUIVOCATION
+            }
+            TransactionPayload::AppealSlash { slash_id, evidence } => {
+                let slash = state.staking().slashes().get_slash(*slash_id).ok_or(SlashingError::SlashNotFound)?;
+                if slash.validator != tx.sender.0 {
+                    return Err(VmError::NotSlashedValidator);
+                }
+                state.staking_mut().slashes_mut().submit_appeal(*slash_id, evidence.clone(), ctx.height)?;
+                bump_nonce(state, &tx.sender)?;
+                base::APPEAL_SLASH
+            }
+            TransactionPayload::ResolveAppeal { appeal_id, approved } => {
+                if self.slashing.governance != Some(tx.sender) {
+                    return Err(VmError::NotGovernance);
+                }
+                let staking = state.staking_mut();
+                staking.slashes_mut().resolve_appeal(*appeal_id, *approved)?;
+                if *approved {
+                    let slash_id = staking.slashes().get_appeal(*appeal_id).map(|a| a.slash_id).unwrap_or(0);
+                    let validator = staking.slashes().get_slash(slash_id).map(|s| AccountId(s.validator));
+                    let refunds = staking.take_deductions(slash_id);
+                    if let Some(validator) = validator {
+                        staking.unjail(&validator);
+                    }
+                    for (owner, amount) in refunds {
+                        credit(state, owner, amount);
+                    }
+                }
+                bump_nonce(state, &tx.sender)?;
+                base::RESOLVE_APPEAL
+            }
+            TransactionPayload::Unjail => {
+                match state.staking().jailed_until(&tx.sender) {
+                    None => return Err(VmError::NotJailed),
+                    Some(release) if release > ctx.height => return Err(VmError::StillJailed(release)),
+                    Some(_) => state.staking_mut().unjail(&tx.sender),
+                }
+                bump_nonce(state, &tx.sender)?;
+                base::UNJAIL
+            }
+            TransactionPayload::RegisterRelay { multiaddr, stake } => {
+                if !multiaddr.starts_with('/') || multiaddr.len() > MAX_RELAY_MULTIADDR_LEN {
+                    return Err(VmError::InvalidRelayAddress);
+                }
+                let staked = state.staking().relays().get(&tx.sender).map(|r| r.stake).unwrap_or(0);
+                let total = staked.saturating_add(*stake);
+                if total < self.relays.min_stake {
+                    return Err(VmError::RelayStakeTooLow { min: self.relays.min_stake, got: total });
+                }
+                let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
+                sender_state.balance = sender_state
+                    .balance
+                    .checked_sub(*stake)
+                    .ok_or(VmError::InsufficientBalance)?;
+                sender_state.nonce = sender_state
+                    .nonce
+                    .checked_add(1)
+                    .ok_or(VmError::NonceOverflow)?;
+                state.staking_mut().relays_mut().register(tx.sender, multiaddr.clone(), *stake, ctx.height);
+                base::REGISTER_RELAY
+            }
+            TransactionPayload::ReportRelayMetrics { relay, metrics } => {
+                if *relay == tx.sender {
+                    return Err(VmError::SelfReport);
+                }
+                if state.staking().relays().get(relay).is_none() {
+                    return Err(VmError::NotARelay);
+                }
+                if state.voting_power(&tx.sender) == 0 {
+                    return Err(VmError::NotAStaker);
+                }
+                if !metrics.is_valid() {
+                    return Err(VmError::InvalidRelayMetrics);
+                }
+                state.staking_mut().relays_mut().report(*relay, tx.sender, metrics.clone());
+                bump_nonce(state, &tx.sender)?;
+                base::REPORT_RELAY_METRICS
+            }
+            TransactionPayload::DeregisterRelay => {
+                if !state.staking_mut().relays_mut().mark_leaving(&tx.sender) {
+                    return Err(VmError::NotARelay);
+                }
+                bump_nonce(state, &tx.sender)?;
+                base::DEREGISTER_RELAY
+            }
+            TransactionPayload::FulfillIntent { intent, amount_out } => {
+                self.fulfill_intent(state, tx, intent, *amount_out, ctx)?;
+                bump_nonce(state, &tx.sender)?;
+                base::FULFILL_INTENT
+            }
+            TransactionPayload::CancelIntent { nonce } => {
+                let next = state.intents().next_nonce(&tx.sender);
+                if *nonce < next {
+                    return Err(VmError::StaleIntentNonce { next, got: *nonce });
+                }
+                state.intents_mut().consume_nonce(tx.sender, *nonce);
+                bump_nonce(state, &tx.sender)?;
+                base::CANCEL_INTENT
+            }
             TransactionPayload::Transfer { to, amount } => {
                 let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
                 sender_state.balance = sender_state
@@ -160,7 +391,7 @@ impl Vm {
                 GAS_PER_TRANSFER
             }
             TransactionPayload::ContractCall { contract, calldata } => {
-                self.execute_contract_call(state, tx, contract, calldata)?
+                self.execute_contract_call(state, tx, contract, calldata, ctx)?
             }
             TransactionPayload::ContractDeploy { bytecode } => {
                 self.execute_contract_deploy(state, tx, bytecode)?
@@ -169,6 +400,95 @@ impl Vm {
         Ok(gas_used)
     }
 
+    /// Slash and jail the validator convicted by `evidence`. Evidence for an offence
+    /// already punished is accepted and ignored, so the reports of several honest nodes do
+    /// not fail each other's blocks.
+    fn punish_equivocation(
+        &self,
+        state: &mut StateStore,
+        evidence: &EquivocationEvidence,
+        ctx: &BlockContext,
+    ) -> Result<(), VmError> {
+        evidence.verify()?;
+        if ctx.height > evidence.height().saturating_add(self.unbonding_delay) {
+            return Err(VmError::StaleEvidence);
+        }
+        let validator = evidence.validator;
+        if !state.staking_mut().mark_punished(validator, evidence.view()) {
+            return Ok(());
+        }
+        slash_validator(
+            state,
+            validator,
+            self.slashing.equivocation_slash_bps,
+            SlashReason::Equivocation,
+            ctx.height,
+            self.slashing.appeal_window,
+            u64::MAX,
+        );
+        Ok(())
+    }
+
+    /// Settle `signed` for the solver sending `tx`. Every check runs before any balance
+    /// moves, so a rejected settlement changes nothing.
+    fn fulfill_intent(
+        &self,
+        state: &mut StateStore,
+        tx: &Transaction,
+        signed: &SignedIntent,
+        amount_out: u128,
+        ctx: &BlockContext,
+    ) -> Result<(), VmError> {
+        let intent = &signed.intent;
+        if intent.chain_id != tx.chain_id {
+            return Err(VmError::IntentWrongChain);
+        }
+        signed.verify().map_err(|_| VmError::InvalidIntentSignature)?;
+        if ctx.height > intent.deadline_block {
+            return Err(VmError::IntentExpired(intent.deadline_block));
+        }
+        let id = intent.id();
+        if state.intents().is_fulfilled(&id) {
+            return Err(VmError::IntentAlreadyFulfilled);
+        }
+        let next = state.intents().next_nonce(&intent.signer);
+        if intent.nonce < next {
+            return Err(VmError::StaleIntentNonce { next, got: intent.nonce });
+        }
+        let balance = |id: &AccountId| state.get(id).map_or(0, |s| s.balance);
+        let signer = intent.signer;
+        match &intent.kind {
+            IntentKind::Transfer { to, amount } => {
+                if balance(&signer) < *amount {
+                    return Err(VmError::InsufficientBalance);
+                }
+                debit(state, &signer, *amount)?;
+                credit(state, *to, *amount);
+            }
+            IntentKind::Swap { from_asset, to_asset, amount, min_out } => {
+                if *from_asset != NATIVE_ASSET || *to_asset != NATIVE_ASSET {
+                    return Err(VmError::UnsupportedAsset);
+                }
+                let min = min_out.unwrap_or(0);
+                if amount_out < min {
+                    return Err(VmError::InsufficientOutput { min, got: amount_out });
+                }
+                let solver = tx.sender;
+                if balance(&signer) < *amount || (solver != signer && balance(&solver) < amount_out) {
+                    return Err(VmError::InsufficientBalance);
+                }
+                debit(state, &signer, *amount)?;
+                credit(state, solver, *amount);
+                debit(state, &solver, amount_out)?;
+                credit(state, signer, amount_out);
+            }
+            IntentKind::Custom(_) => return Err(VmError::UnsupportedIntent),
+        }
+        state.intents_mut().mark_fulfilled(id, intent.deadline_block, ctx.height);
+        state.intents_mut().consume_nonce(signer, intent.nonce);
+        Ok(())
+    }
+
     fn execute_contract_deploy(&self, state: &mut StateStore, tx: &Transaction, bytecode: &[u8]) -> Result<u64, VmError> {
         let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
         sender_state.nonce = sender_state
@@ -191,6 +511,7 @@ impl Vm {
         tx: &Transaction,
         contract: &AccountId,
         calldata: &[u8],
+        ctx: &BlockContext,
     ) -> Result<u64, VmError> {
         let sender_state = state.get_mut(&tx.sender).ok_or(VmError::AccountNotFound)?;
         sender_state.nonce = sender_state
@@ -199,7 +520,7 @@ impl Vm {
             .ok_or(VmError::NonceOverflow)?;
 
         let code = state.get_contract_code(contract).ok_or(VmError::AccountNotFound)?.clone();
-        let mut interpreter = Interpreter::new(code, GAS_PER_CONTRACT_CALL);
+        let mut interpreter = Interpreter::new(code, GAS_PER_CONTRACT_CALL).with_context(*ctx);
         let gas_used = interpreter.run(*contract, calldata, state)?;
         Ok(gas_used)
     }
@@ -211,6 +532,28 @@ impl Default for Vm {
     }
 }
 
+fn bump_nonce(state: &mut StateStore, id: &AccountId) -> Result<(), VmError> {
+    let account = state.get_mut(id).ok_or(VmError::AccountNotFound)?;
+    account.nonce = account.nonce.checked_add(1).ok_or(VmError::NonceOverflow)?;
+    Ok(())
+}
+
+fn debit(state: &mut StateStore, id: &AccountId, amount: u128) -> Result<(), VmError> {
+    let account = state.get_mut(id).ok_or(VmError::AccountNotFound)?;
+    account.balance = account.balance.checked_sub(amount).ok_or(VmError::InsufficientBalance)?;
+    Ok(())
+}
+
+pub(crate) fn credit(state: &mut StateStore, id: AccountId, amount: u128) {
+    match state.get_mut(&id) {
+        Some(s) => s.balance = s.balance.saturating_add(amount),
+        None => state.insert(boing_primitives::Account {
+            id,
+            state: AccountState { balance: amount, nonce: 0, stake: 0 },
+        }),
+    }
+}
+
 fn derive_contract_address(sender: &AccountId, nonce: u64) -> AccountId {
     let mut h = hasher();
     h.update(&sender.0);
@@ -240,4 +583,54 @@ pub enum VmError {
     InvalidBytecode,
     #[error("Invalid jump destination")]
     InvalidJump,
+    #[error("No matured unbonding entries to withdraw")]
+    NothingToWithdraw,
+    #[error("Commission {0} bps outside the allowed range")]
+    InvalidCommission(u16),
+    #[error("Not a registered validator")]
+    NotAValidator,
+    #[error("Delegation smaller than the amount")]
+    InsufficientDelegation,
+    #[error("Invalid equivocation evidence: {0}")]
+    InvalidEvidence(#[from] EvidenceError),
+    #[error("Evidence is older than the unbonding delay")]
+    StaleEvidence,
+    #[error("Only the slashed validator may appeal")]
+    NotSlashedValidator,
+    #[error("Only the governance account may resolve appeals")]
+    NotGovernance,
+    #[error("Validator is not jailed")]
+    NotJailed,
+    #[error("Validator is jailed until height {0}")]
+    StillJailed(u64),
+    #[error("Appeal rejected: {0}")]
+    Appeal(#[from] SlashingError),
+    #[error("Relay address must be a multiaddr of at most {MAX_RELAY_MULTIADDR_LEN} bytes")]
+    InvalidRelayAddress,
+    #[error("Relay stake {got} below the minimum {min}")]
+    RelayStakeTooLow { min: u128, got: u128 },
+    #[error("Not a registered relay")]
+    NotARelay,
+    #[error("A relay operator may not report its own relay")]
+    SelfReport,
+    #[error("Only accounts with stake may report relay metrics")]
+    NotAStaker,
+    #[error("Relay metrics rates exceed 10000 bps")]
+    InvalidRelayMetrics,
+    #[error("Intent is signed for another chain")]
+    IntentWrongChain,
+    #[error("Invalid intent signature")]
+    InvalidIntentSignature,
+    #[error("Intent expired at block {0}")]
+    IntentExpired(u64),
+    #[error("Intent already fulfilled")]
+    IntentAlreadyFulfilled,
+    #[error("Intent nonce {got} is stale; the signer's next intent nonce is {next}")]
+    StaleIntentNonce { next: u64, got: u64 },
+    #[error("Intent kind cannot be settled on-chain")]
+    UnsupportedIntent,
+    #[error("Only the native asset settles on-chain")]
+    UnsupportedAsset,
+    #[error("Swap output {got} below the intent's minimum {min}")]
+    InsufficientOutput { min: u128, got: u128 },
 }
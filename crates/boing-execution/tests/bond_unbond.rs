@@ -1,37 +1,110 @@
-//! Tests for Bond and Unbond staking transactions.
+//! Tests for Bond, Unbond and Withdraw staking transactions.
 
-use boing_execution::Vm;
-use boing_primitives::{AccessList, Account, AccountId, AccountState, Transaction, TransactionPayload};
+use boing_execution::{BlockContext, Vm, VmError};
+use boing_primitives::{
+    AccessList, Account, AccountId, AccountState, Transaction, TransactionPayload, UnbondingEntry,
+};
 use boing_state::StateStore;
 
-#[test]
-fn test_bond_and_unbond() {
-    let vm = Vm::new();
-    let a = AccountId::from_bytes([1u8; 32]);
+fn staking_tx(sender: AccountId, nonce: u64, payload: TransactionPayload) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    }
+}
+
+fn funded(a: AccountId) -> StateStore {
     let mut state = StateStore::new();
     state.insert(Account {
         id: a,
         state: AccountState { balance: 1000, nonce: 0, stake: 0 },
     });
+    state
+}
+
+#[test]
+fn test_bond_and_unbond() {
+    let vm = Vm::new().with_unbonding_delay(10);
+    let a = AccountId::from_bytes([1u8; 32]);
+    let mut state = funded(a);
 
-    let bond_tx = Transaction {
-        nonce: 0,
-        sender: a,
-        payload: TransactionPayload::Bond { amount: 300 },
-        access_list: AccessList::new(vec![a], vec![a]),
-    };
-    vm.execute(&bond_tx, &mut state).unwrap();
+    let bond_tx = staking_tx(a, 0, TransactionPayload::Bond { amount: 300 });
+    vm.execute(&bond_tx, &mut state, &BlockContext::at_height(1)).unwrap();
     assert_eq!(state.get(&a).unwrap().balance, 700);
     assert_eq!(state.get(&a).unwrap().stake, 300);
     assert_eq!(state.get(&a).unwrap().nonce, 1);
 
-    let unbond_tx = Transaction {
-        nonce: 1,
-        sender: a,
-        payload: TransactionPayload::Unbond { amount: 100 },
-        access_list: AccessList::new(vec![a], vec![a]),
-    };
-    vm.execute(&unbond_tx, &mut state).unwrap();
-    assert_eq!(state.get(&a).unwrap().balance, 800);
+    // Unbonded stake leaves the stake at once but is not yet liquid.
+    let unbond_tx = staking_tx(a, 1, TransactionPayload::Unbond { amount: 100 });
+    vm.execute(&unbond_tx, &mut state, &BlockContext::at_height(5)).unwrap();
+    assert_eq!(state.get(&a).unwrap().balance, 700);
     assert_eq!(state.get(&a).unwrap().stake, 200);
+    assert_eq!(
+        state.staking().unbonding(&a),
+        &[UnbondingEntry { validator: a, amount: 100, release_height: 15 }]
+    );
+}
+
+#[test]
+fn test_withdraw_before_release_fails() {
+    let vm = Vm::new().with_unbonding_delay(10);
+    let a = AccountId::from_bytes([1u8; 32]);
+    let mut state = funded(a);
+    vm.execute(&staking_tx(a, 0, TransactionPayload::Bond { amount: 300 }), &mut state, &BlockContext::at_height(1))
+        .unwrap();
+    vm.execute(&staking_tx(a, 1, TransactionPayload::Unbond { amount: 100 }), &mut state, &BlockContext::at_height(5))
+        .unwrap();
+
+    let withdraw = staking_tx(a, 2, TransactionPayload::Withdraw);
+    let result = vm.execute(&withdraw, &mut state, &BlockContext::at_height(14));
+    assert!(matches!(result, Err(VmError::NothingToWithdraw)));
+    assert_eq!(state.get(&a).unwrap().balance, 700);
+    assert_eq!(state.get(&a).unwrap().nonce, 2);
+    assert_eq!(state.staking().unbonding_total(&a), 100);
+}
+
+#[test]
+fn test_withdraw_claims_only_matured_entries() {
+    let vm = Vm::new().with_unbonding_delay(10);
+    let a = AccountId::from_bytes([1u8; 32]);
+    let mut state = funded(a);
+    vm.execute(&staking_tx(a, 0, TransactionPayload::Bond { amount: 300 }), &mut state, &BlockContext::at_height(1))
+        .unwrap();
+    vm.execute(&staking_tx(a, 1, TransactionPayload::Unbond { amount: 100 }), &mut state, &BlockContext::at_height(5))
+        .unwrap();
+    vm.execute(&staking_tx(a, 2, TransactionPayload::Unbond { amount: 50 }), &mut state, &BlockContext::at_height(8))
+        .unwrap();
+
+    vm.execute(&staking_tx(a, 3, TransactionPayload::Withdraw), &mut state, &BlockContext::at_height(15))
+        .unwrap();
+    assert_eq!(state.get(&a).unwrap().balance, 800);
+    assert_eq!(state.get(&a).unwrap().stake, 150);
+    assert_eq!(
+        state.staking().unbonding(&a),
+        &[UnbondingEntry { validator: a, amount: 50, release_height: 18 }]
+    );
+
+    vm.execute(&staking_tx(a, 4, TransactionPayload::Withdraw), &mut state, &BlockContext::at_height(18))
+        .unwrap();
+    assert_eq!(state.get(&a).unwrap().balance, 850);
+    assert!(state.staking().unbonding(&a).is_empty());
+}
+
+#[test]
+fn test_unbond_more_than_staked_fails() {
+    let vm = Vm::new();
+    let a = AccountId::from_bytes([1u8; 32]);
+    let mut state = funded(a);
+    vm.execute(&staking_tx(a, 0, TransactionPayload::Bond { amount: 300 }), &mut state, &BlockContext::at_height(1))
+        .unwrap();
+    let result = vm.execute(
+        &staking_tx(a, 1, TransactionPayload::Unbond { amount: 301 }),
+        &mut state,
+        &BlockContext::at_height(2),
+    );
+    assert!(matches!(result, Err(VmError::InsufficientBalance)));
+    assert!(state.staking().unbonding(&a).is_empty());
 }
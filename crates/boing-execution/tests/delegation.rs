@@ -0,0 +1,100 @@
+//! Tests for validator registration, Delegate and Undelegate transactions.
+
+use boing_execution::{BlockContext, Vm, VmError};
+use boing_primitives::{
+    AccessList, Account, AccountId, AccountState, Transaction, TransactionPayload, UnbondingEntry,
+};
+use boing_state::StateStore;
+use boing_tokenomics::{VALIDATOR_COMMISSION_MAX_BPS, VALIDATOR_COMMISSION_MIN_BPS};
+
+fn staking_tx(sender: AccountId, nonce: u64, payload: TransactionPayload) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    }
+}
+
+fn state_with(accounts: &[AccountId]) -> StateStore {
+    let mut state = StateStore::new();
+    for id in accounts {
+        state.insert(Account {
+            id: *id,
+            state: AccountState { balance: 1000, nonce: 0, stake: 0 },
+        });
+    }
+    state
+}
+
+#[test]
+fn test_register_validator_checks_commission_bounds() {
+    let vm = Vm::new();
+    let v = AccountId::from_bytes([1u8; 32]);
+    let mut state = state_with(&[v]);
+    let ctx = BlockContext::at_height(1);
+
+    for bps in [VALIDATOR_COMMISSION_MIN_BPS - 1, VALIDATOR_COMMISSION_MAX_BPS + 1] {
+        let tx = staking_tx(v, 0, TransactionPayload::RegisterValidator { commission_bps: bps });
+        assert!(matches!(vm.execute(&tx, &mut state, &ctx), Err(VmError::InvalidCommission(b)) if b == bps));
+    }
+    assert!(state.staking().validator(&v).is_none());
+
+    let tx = staking_tx(v, 0, TransactionPayload::RegisterValidator { commission_bps: 700 });
+    vm.execute(&tx, &mut state, &ctx).unwrap();
+    assert_eq!(state.staking().validator(&v).unwrap().commission_bps, 700);
+    assert_eq!(state.get(&v).unwrap().nonce, 1);
+}
+
+#[test]
+fn test_delegate_adds_voting_power() {
+    let vm = Vm::new();
+    let (v, d) = (AccountId::from_bytes([1u8; 32]), AccountId::from_bytes([2u8; 32]));
+    let mut state = state_with(&[v, d]);
+    let ctx = BlockContext::at_height(1);
+
+    let delegate = staking_tx(d, 0, TransactionPayload::Delegate { validator: v, amount: 400 });
+    assert!(matches!(vm.execute(&delegate, &mut state, &ctx), Err(VmError::NotAValidator)));
+
+    vm.execute(&staking_tx(v, 0, TransactionPayload::RegisterValidator { commission_bps: 500 }), &mut state, &ctx)
+        .unwrap();
+    vm.execute(&staking_tx(v, 1, TransactionPayload::Bond { amount: 100 }), &mut state, &ctx).unwrap();
+    vm.execute(&delegate, &mut state, &ctx).unwrap();
+    assert_eq!(state.get(&d).unwrap().balance, 600);
+    assert_eq!(state.staking().delegation(&v, &d), 400);
+    assert_eq!(state.voting_power(&v), 500);
+    assert_eq!(state.top_stakers(1), vec![v]);
+}
+
+#[test]
+fn test_undelegate_goes_through_unbonding_queue() {
+    let vm = Vm::new().with_unbonding_delay(10);
+    let (v, d) = (AccountId::from_bytes([1u8; 32]), AccountId::from_bytes([2u8; 32]));
+    let mut state = state_with(&[v, d]);
+    let ctx = BlockContext::at_height(1);
+    vm.execute(&staking_tx(v, 0, TransactionPayload::RegisterValidator { commission_bps: 500 }), &mut state, &ctx)
+        .unwrap();
+    vm.execute(&staking_tx(d, 0, TransactionPayload::Delegate { validator: v, amount: 400 }), &mut state, &ctx)
+        .unwrap();
+
+    let too_much = staking_tx(d, 1, TransactionPayload::Undelegate { validator: v, amount: 401 });
+    assert!(matches!(
+        vm.execute(&too_much, &mut state, &BlockContext::at_height(2)),
+        Err(VmError::InsufficientDelegation)
+    ));
+
+    let undelegate = staking_tx(d, 1, TransactionPayload::Undelegate { validator: v, amount: 150 });
+    vm.execute(&undelegate, &mut state, &BlockContext::at_height(2)).unwrap();
+    assert_eq!(state.staking().delegation(&v, &d), 250);
+    assert_eq!(state.voting_power(&v), 250);
+    assert_eq!(state.get(&d).unwrap().balance, 600);
+    assert_eq!(
+        state.staking().unbonding(&d),
+        &[UnbondingEntry { validator: v, amount: 150, release_height: 12 }]
+    );
+
+    vm.execute(&staking_tx(d, 2, TransactionPayload::Withdraw), &mut state, &BlockContext::at_height(12))
+        .unwrap();
+    assert_eq!(state.get(&d).unwrap().balance, 750);
+}
@@ -0,0 +1,146 @@
+//! Tests for intent settlement: transfers and native swaps move balances atomically, and
+//! intents that are expired, already fulfilled, stale, foreign, forged or short of `min_out`
+//! are refused without changing state.
+
+use boing_execution::{BlockContext, Vm, VmError};
+use boing_primitives::{
+    AccessList, Account, AccountId, AccountState, Intent, IntentKind, SignedIntent, Transaction,
+    TransactionPayload, NATIVE_ASSET,
+};
+use boing_state::StateStore;
+use ed25519_dalek::SigningKey;
+
+const SOLVER: AccountId = AccountId([9u8; 32]);
+const BOB: AccountId = AccountId([8u8; 32]);
+
+fn user() -> (SigningKey, AccountId) {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let id = AccountId(key.verifying_key().to_bytes());
+    (key, id)
+}
+
+fn setup() -> StateStore {
+    let mut state = StateStore::new();
+    state.insert(Account { id: user().1, state: AccountState { balance: 1_000, nonce: 0, stake: 0 } });
+    state.insert(Account { id: SOLVER, state: AccountState { balance: 500, nonce: 0, stake: 0 } });
+    state
+}
+
+fn intent(kind: IntentKind, deadline_block: u64) -> Box<SignedIntent> {
+    let (key, signer) = user();
+    Box::new(SignedIntent::new(Intent { chain_id: 1, signer, kind, nonce: 0, deadline_block }, &key))
+}
+
+fn swap(amount: u128, min_out: Option<u128>) -> IntentKind {
+    IntentKind::Swap { from_asset: NATIVE_ASSET, to_asset: NATIVE_ASSET, amount, min_out }
+}
+
+fn fulfill(state: &mut StateStore, nonce: u64, intent: Box<SignedIntent>, amount_out: u128, height: u64) -> Result<u64, VmError> {
+    let tx = Transaction {
+        chain_id: 1,
+        nonce,
+        sender: SOLVER,
+        payload: TransactionPayload::FulfillIntent { intent, amount_out },
+        access_list: AccessList::new(vec![SOLVER], vec![SOLVER]),
+    };
+    Vm::new().execute(&tx, state, &BlockContext::at_height(height))
+}
+
+fn balance(state: &StateStore, id: &AccountId) -> u128 {
+    state.get(id).map_or(0, |s| s.balance)
+}
+
+#[test]
+fn test_transfer_intent_settles_once() {
+    let mut state = setup();
+    let transfer = intent(IntentKind::Transfer { to: BOB, amount: 300 }, 10);
+    fulfill(&mut state, 0, transfer.clone(), 0, 5).unwrap();
+    assert_eq!((balance(&state, &user().1), balance(&state, &BOB)), (700, 300));
+    assert!(state.intents().is_fulfilled(&transfer.intent.id()));
+    assert_eq!(state.get(&SOLVER).unwrap().nonce, 1);
+
+    assert!(matches!(fulfill(&mut state, 1, transfer, 0, 6), Err(VmError::IntentAlreadyFulfilled)));
+}
+
+#[test]
+fn test_swap_intent_enforces_min_out() {
+    let mut state = setup();
+    let offer = intent(swap(400, Some(350)), 10);
+    assert!(matches!(
+        fulfill(&mut state, 0, offer.clone(), 349, 5),
+        Err(VmError::InsufficientOutput { min: 350, got: 349 })
+    ));
+    // The solver cannot pay more than it holds; nothing moves.
+    let greedy = intent(swap(400, Some(600)), 10);
+    assert!(matches!(fulfill(&mut state, 0, greedy, 600, 5), Err(VmError::InsufficientBalance)));
+    assert_eq!((balance(&state, &user().1), balance(&state, &SOLVER)), (1_000, 500));
+
+    let token = AccountId([7u8; 32]);
+    let foreign_asset = intent(IntentKind::Swap { from_asset: NATIVE_ASSET, to_asset: token, amount: 1, min_out: None }, 10);
+    assert!(matches!(fulfill(&mut state, 0, foreign_asset, 1, 5), Err(VmError::UnsupportedAsset)));
+    assert!(matches!(fulfill(&mut state, 0, intent(IntentKind::Custom(vec![1]), 10), 0, 5), Err(VmError::UnsupportedIntent)));
+
+    fulfill(&mut state, 0, offer, 380, 5).unwrap();
+    assert_eq!((balance(&state, &user().1), balance(&state, &SOLVER)), (1_000 - 400 + 380, 500 + 400 - 380));
+}
+
+#[test]
+fn test_expired_foreign_and_forged_intents_rejected() {
+    let mut state = setup();
+    let transfer = IntentKind::Transfer { to: BOB, amount: 1 };
+    assert!(matches!(fulfill(&mut state, 0, intent(transfer.clone(), 4), 0, 5), Err(VmError::IntentExpired(4))));
+
+    let (key, signer) = user();
+    let foreign = SignedIntent::new(Intent { chain_id: 2, signer, kind: transfer.clone(), nonce: 0, deadline_block: 10 }, &key);
+    assert!(matches!(fulfill(&mut state, 0, Box::new(foreign), 0, 5), Err(VmError::IntentWrongChain)));
+
+    let mut forged = intent(transfer, 10);
+    forged.intent.kind = IntentKind::Transfer { to: BOB, amount: 1_000 };
+    assert!(matches!(fulfill(&mut state, 0, forged, 0, 5), Err(VmError::InvalidIntentSignature)));
+    assert_eq!(balance(&state, &BOB), 0);
+}
+
+#[test]
+fn test_fulfilled_record_forgets_past_deadlines() {
+    let mut state = setup();
+    let early = intent(IntentKind::Transfer { to: BOB, amount: 1 }, 5);
+    fulfill(&mut state, 0, early.clone(), 0, 5).unwrap();
+    let (key, signer) = user();
+    let kind = IntentKind::Transfer { to: BOB, amount: 2 };
+    let late = Box::new(SignedIntent::new(Intent { chain_id: 1, signer, kind, nonce: 1, deadline_block: 50 }, &key));
+    fulfill(&mut state, 1, late.clone(), 0, 6).unwrap();
+    assert!(!state.intents().is_fulfilled(&early.intent.id()), "deadline passed, no longer needed");
+    assert!(state.intents().is_fulfilled(&late.intent.id()));
+}
+
+#[test]
+fn test_intent_nonces_and_cancel() {
+    let mut state = setup();
+    let (key, signer) = user();
+    let sign = |nonce, amount| {
+        let kind = IntentKind::Transfer { to: BOB, amount };
+        Box::new(SignedIntent::new(Intent { chain_id: 1, signer, kind, nonce, deadline_block: 10 }, &key))
+    };
+    // Settling nonce 1 makes nonce 0 stale.
+    fulfill(&mut state, 0, sign(1, 10), 0, 5).unwrap();
+    assert_eq!(state.intents().next_nonce(&signer), 2);
+    assert!(matches!(fulfill(&mut state, 1, sign(0, 20), 0, 5), Err(VmError::StaleIntentNonce { next: 2, got: 0 })));
+
+    let cancel = |state: &mut StateStore, nonce| {
+        let tx = Transaction {
+            chain_id: 1,
+            nonce: 0,
+            sender: signer,
+            payload: TransactionPayload::CancelIntent { nonce },
+            access_list: AccessList::new(vec![signer], vec![signer]),
+        };
+        Vm::new().execute(&tx, state, &BlockContext::at_height(5))
+    };
+    assert!(matches!(cancel(&mut state, 1), Err(VmError::StaleIntentNonce { next: 2, got: 1 })));
+    cancel(&mut state, 4).unwrap();
+    assert_eq!(state.intents().next_nonce(&signer), 5);
+    assert_eq!(state.get(&signer).unwrap().nonce, 1);
+    assert!(matches!(fulfill(&mut state, 1, sign(3, 30), 0, 5), Err(VmError::StaleIntentNonce { next: 5, got: 3 })));
+    fulfill(&mut state, 1, sign(5, 30), 0, 5).unwrap();
+    assert_eq!(balance(&state, &BOB), 40);
+}
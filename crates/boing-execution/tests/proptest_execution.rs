@@ -1,7 +1,7 @@
 //! Property-based tests for execution (proptest).
 
 use proptest::prelude::*;
-use boing_execution::BlockExecutor;
+use boing_execution::{BlockContext, BlockExecutor};
 use boing_primitives::{AccessList, Account, AccountId, AccountState, Transaction, TransactionPayload};
 use boing_state::StateStore;
 
@@ -20,6 +20,7 @@ fn mk_parallel_transfers(n: usize, amt: u128) -> (Vec<Transaction>, StateStore)
             let from = AccountId({ let mut a = [0u8; 32]; a[0] = (i * 2) as u8; a });
             let to = AccountId({ let mut a = [0u8; 32]; a[0] = (i * 2 + 1) as u8; a });
             Transaction {
+                chain_id: 1,
                 nonce: 0,
                 sender: from,
                 payload: TransactionPayload::Transfer { to, amount: amt },
@@ -48,7 +49,7 @@ proptest! {
         let max_id = (count * 2) as u8;
         let total_before = total_balance(&state, max_id);
         let exec = BlockExecutor::new();
-        exec.execute_block(&txs, &mut state).unwrap();
+        exec.execute_block(&txs, &mut state, &BlockContext::default()).unwrap();
         let total_after = total_balance(&state, max_id);
         prop_assert_eq!(total_before, total_after, "total balance must be preserved");
     }
@@ -61,10 +62,10 @@ proptest! {
         let max_id = (count * 2) as u8;
 
         let mut s1 = state.snapshot();
-        exec.execute_block(&txs, &mut s1).unwrap();
+        exec.execute_block(&txs, &mut s1, &BlockContext::default()).unwrap();
 
         let mut s2 = state.snapshot();
-        exec.execute_block(&txs, &mut s2).unwrap();
+        exec.execute_block(&txs, &mut s2, &BlockContext::default()).unwrap();
 
         for i in 0..=max_id {
             let id = AccountId({ let mut a = [0u8; 32]; a[0] = i; a });
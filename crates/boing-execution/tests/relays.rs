@@ -0,0 +1,143 @@
+//! Tests for the STUN/TURN relay registry: staking to register, metrics reports from
+//! staked accounts, and per-epoch reputation, rewards and deregistration.
+
+use boing_execution::{settle_relays, BlockContext, RelayParams, Vm, VmError};
+use boing_primitives::{
+    AccessList, Account, AccountId, AccountState, StunTurnMetrics, Transaction, TransactionPayload,
+};
+use boing_state::StateStore;
+
+const OPERATOR: AccountId = AccountId([1u8; 32]);
+const STAKER: AccountId = AccountId([2u8; 32]);
+const STAKER_2: AccountId = AccountId([3u8; 32]);
+const NOBODY: AccountId = AccountId([4u8; 32]);
+
+fn tx(sender: AccountId, nonce: u64, payload: TransactionPayload) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    }
+}
+
+fn params() -> RelayParams {
+    RelayParams {
+        epoch_length: 10,
+        min_stake: 500,
+        min_reputation: 7_000,
+        epoch_reward: 100,
+        deregister_slash_bps: 2_000,
+    }
+}
+
+fn setup() -> (Vm, StateStore) {
+    let mut state = StateStore::new();
+    state.insert(Account { id: OPERATOR, state: AccountState { balance: 10_000, nonce: 0, stake: 0 } });
+    for id in [STAKER, STAKER_2] {
+        state.insert(Account { id, state: AccountState { balance: 0, nonce: 0, stake: 1_000 } });
+    }
+    state.insert(Account { id: NOBODY, state: AccountState { balance: 100, nonce: 0, stake: 0 } });
+    (Vm::new().with_relays(params()), state)
+}
+
+fn register(vm: &Vm, state: &mut StateStore, nonce: u64, stake: u128) -> Result<u64, VmError> {
+    let payload = TransactionPayload::RegisterRelay { multiaddr: "/ip4/1.2.3.4/udp/3478".into(), stake };
+    vm.execute(&tx(OPERATOR, nonce, payload), state, &BlockContext::at_height(1))
+}
+
+fn report(vm: &Vm, state: &mut StateStore, reporter: AccountId, nonce: u64, metrics: StunTurnMetrics) -> Result<u64, VmError> {
+    let payload = TransactionPayload::ReportRelayMetrics { relay: OPERATOR, metrics };
+    vm.execute(&tx(reporter, nonce, payload), state, &BlockContext::at_height(2))
+}
+
+fn metrics(uptime_bps: u16, success_rate_bps: u16) -> StunTurnMetrics {
+    StunTurnMetrics { uptime_bps, avg_latency_ms: 50, success_rate_bps, throughput_mb: 10 }
+}
+
+#[test]
+fn test_register_requires_stake_and_multiaddr() {
+    let (vm, mut state) = setup();
+    assert!(matches!(register(&vm, &mut state, 0, 499), Err(VmError::RelayStakeTooLow { min: 500, got: 499 })));
+    let bad = TransactionPayload::RegisterRelay { multiaddr: "1.2.3.4:3478".into(), stake: 500 };
+    assert!(matches!(
+        vm.execute(&tx(OPERATOR, 0, bad), &mut state, &BlockContext::at_height(1)),
+        Err(VmError::InvalidRelayAddress)
+    ));
+
+    register(&vm, &mut state, 0, 600).unwrap();
+    // A top-up may be below the minimum as long as the total is not.
+    register(&vm, &mut state, 1, 100).unwrap();
+    let entry = state.staking().relays().get(&OPERATOR).unwrap().clone();
+    assert_eq!((entry.stake, entry.reputation.score, entry.registered_at), (700, 10_000, 1));
+    assert_eq!(state.get(&OPERATOR).unwrap().balance, 9_300);
+    assert_eq!(state.get(&OPERATOR).unwrap().stake, 0, "relay stake is not validator stake");
+}
+
+#[test]
+fn test_reports_need_stake_and_a_registered_relay() {
+    let (vm, mut state) = setup();
+    assert!(matches!(report(&vm, &mut state, STAKER, 0, metrics(9_000, 9_000)), Err(VmError::NotARelay)));
+    register(&vm, &mut state, 0, 500).unwrap();
+    assert!(matches!(report(&vm, &mut state, NOBODY, 0, metrics(9_000, 9_000)), Err(VmError::NotAStaker)));
+    assert!(matches!(report(&vm, &mut state, STAKER, 0, metrics(10_001, 9_000)), Err(VmError::InvalidRelayMetrics)));
+    let own = TransactionPayload::ReportRelayMetrics { relay: OPERATOR, metrics: metrics(10_000, 10_000) };
+    assert!(matches!(
+        vm.execute(&tx(OPERATOR, 1, own), &mut state, &BlockContext::at_height(2)),
+        Err(VmError::SelfReport)
+    ));
+
+    report(&vm, &mut state, STAKER, 0, metrics(1_000, 1_000)).unwrap();
+    report(&vm, &mut state, STAKER, 1, metrics(9_000, 9_000)).unwrap();
+    assert_eq!(state.staking().relays().reports(&OPERATOR), vec![metrics(9_000, 9_000)], "latest report counts");
+}
+
+#[test]
+fn test_epoch_settlement_rewards_and_deregisters() {
+    let (vm, mut state) = setup();
+    register(&vm, &mut state, 0, 1_000).unwrap();
+    report(&vm, &mut state, STAKER, 0, metrics(4_000, 4_000)).unwrap();
+    report(&vm, &mut state, STAKER_2, 0, metrics(3_000, 3_000)).unwrap();
+
+    // Only epoch boundaries settle.
+    settle_relays(&mut state, 9, &params());
+    assert_eq!(state.staking().relays().reports(&OPERATOR).len(), 2);
+
+    // Median of (3000, 4000) is 3000 -> score 8000; reputation (10000 + 8000) / 2.
+    settle_relays(&mut state, 10, &params());
+    let entry = state.staking().relays().get(&OPERATOR).unwrap().clone();
+    assert_eq!(entry.reputation.score, 9_000);
+    assert_eq!(state.get(&OPERATOR).unwrap().balance, 9_000 + 90);
+    assert!(state.staking().relays().reports(&OPERATOR).is_empty());
+
+    // No reports: reputation kept, nothing paid.
+    settle_relays(&mut state, 20, &params());
+    assert_eq!(state.staking().relays().get(&OPERATOR).unwrap().reputation.score, 9_000);
+    assert_eq!(state.get(&OPERATOR).unwrap().balance, 9_090);
+
+    // A poor epoch (score 4950) drops it below the minimum: deregistered, 20% of the
+    // stake burned.
+    let poor = StunTurnMetrics { uptime_bps: 0, avg_latency_ms: 600, success_rate_bps: 0, throughput_mb: 0 };
+    report(&vm, &mut state, STAKER, 1, poor).unwrap();
+    settle_relays(&mut state, 30, &params());
+    assert!(state.staking().relays().get(&OPERATOR).is_none());
+    assert_eq!(state.get(&OPERATOR).unwrap().balance, 9_090 + 800);
+}
+
+#[test]
+fn test_deregister_refunds_at_boundary() {
+    let (vm, mut state) = setup();
+    let deregister = |state: &mut StateStore, nonce| {
+        vm.execute(&tx(OPERATOR, nonce, TransactionPayload::DeregisterRelay), state, &BlockContext::at_height(3))
+    };
+    assert!(matches!(deregister(&mut state, 0), Err(VmError::NotARelay)));
+    register(&vm, &mut state, 0, 1_000).unwrap();
+    deregister(&mut state, 1).unwrap();
+    assert!(state.staking().relays().get(&OPERATOR).unwrap().leaving);
+    report(&vm, &mut state, STAKER, 0, metrics(10_000, 10_000)).unwrap();
+
+    settle_relays(&mut state, 10, &params());
+    assert!(state.staking().relays().get(&OPERATOR).is_none());
+    assert_eq!(state.get(&OPERATOR).unwrap().balance, 10_000, "full stake back, no reward");
+}
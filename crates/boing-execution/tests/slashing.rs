@@ -0,0 +1,172 @@
+//! Tests for equivocation reports, downtime slashing, slash appeals, refunds and unjailing.
+
+use boing_execution::{punish_downtime, BlockContext, LivenessParams, SlashingParams, Vm, VmError};
+use boing_governance::SlashReason;
+use boing_primitives::{
+    AccessList, Account, AccountId, AccountState, BlockHeader, EquivocationEvidence, Hash,
+    Transaction, TransactionPayload, ValidatorSet, Vote,
+};
+use boing_state::StateStore;
+use ed25519_dalek::SigningKey;
+
+fn tx(sender: AccountId, nonce: u64, payload: TransactionPayload) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    }
+}
+
+fn header(height: u64, round: u64, timestamp: u64) -> BlockHeader {
+    BlockHeader {
+        parent_hash: Hash::ZERO,
+        height,
+        round,
+        timestamp,
+        proposer: AccountId([9u8; 32]),
+        tx_root: Hash::ZERO,
+        state_root: Hash::ZERO,
+        validator_set_hash: Hash::ZERO,
+        vrf: Default::default(),
+        randomness: Hash::ZERO,
+        justify_hash: Hash::ZERO,
+    }
+}
+
+fn evidence(key: &SigningKey, height: u64, round: u64) -> Box<EquivocationEvidence> {
+    let (a, b) = (header(height, round, 1), header(height, round, 2));
+    let (va, vb) = (Vote::new(a.hash(), key), Vote::new(b.hash(), key));
+    Box::new(EquivocationEvidence::new((&a, &va), (&b, &vb)))
+}
+
+/// Validator with 10_000 own stake and 2_000 delegated; reporter and governance funded.
+fn setup() -> (SigningKey, AccountId, AccountId, AccountId, AccountId, StateStore) {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let v = AccountId(key.verifying_key().to_bytes());
+    let (d, reporter, gov) = (AccountId([2u8; 32]), AccountId([3u8; 32]), AccountId([4u8; 32]));
+    let mut state = StateStore::new();
+    state.insert(Account { id: v, state: AccountState { balance: 0, nonce: 0, stake: 10_000 } });
+    for id in [d, reporter, gov] {
+        state.insert(Account { id, state: AccountState { balance: 1_000, nonce: 0, stake: 0 } });
+    }
+    state.staking_mut().register_validator(v, 500);
+    state.staking_mut().delegate(v, d, 2_000);
+    (key, v, d, reporter, gov, state)
+}
+
+fn vm(gov: AccountId) -> Vm {
+    Vm::new().with_unbonding_delay(100).with_slashing(SlashingParams {
+        equivocation_slash_bps: 1_000,
+        appeal_window: 50,
+        governance: Some(gov),
+    })
+}
+
+#[test]
+fn test_equivocation_slashes_and_jails_once() {
+    let (key, v, d, reporter, gov, mut state) = setup();
+    let vm = vm(gov);
+    let ctx = BlockContext::at_height(10);
+
+    let report = TransactionPayload::ReportEquivocation { evidence: evidence(&key, 8, 8) };
+    vm.execute(&tx(reporter, 0, report.clone()), &mut state, &ctx).unwrap();
+    assert_eq!(state.get(&v).unwrap().stake, 9_000);
+    assert_eq!(state.staking().delegation(&v, &d), 1_800);
+    assert!(state.staking().is_jailed(&v));
+    let slash = state.staking().slashes().get_slash(1).unwrap();
+    assert_eq!((slash.amount, &slash.reason, slash.appeal_deadline), (1_200, &SlashReason::Equivocation, 60));
+
+    // A second report of the same offence is a no-op.
+    vm.execute(&tx(reporter, 1, report), &mut state, &ctx).unwrap();
+    assert_eq!(state.get(&v).unwrap().stake, 9_000);
+    assert_eq!(state.staking().slashes().list_slashes().len(), 1);
+    assert_eq!(state.get(&reporter).unwrap().nonce, 2);
+}
+
+#[test]
+fn test_invalid_and_stale_evidence_rejected() {
+    let (key, _, _, reporter, gov, mut state) = setup();
+    let vm = vm(gov);
+
+    let mut forged = evidence(&key, 8, 8);
+    forged.second.header.round = 9;
+    let report = TransactionPayload::ReportEquivocation { evidence: forged };
+    assert!(matches!(
+        vm.execute(&tx(reporter, 0, report), &mut state, &BlockContext::at_height(10)),
+        Err(VmError::InvalidEvidence(_))
+    ));
+
+    let report = TransactionPayload::ReportEquivocation { evidence: evidence(&key, 8, 8) };
+    assert!(matches!(
+        vm.execute(&tx(reporter, 0, report), &mut state, &BlockContext::at_height(109)),
+        Err(VmError::StaleEvidence)
+    ));
+}
+
+#[test]
+fn test_approved_appeal_refunds_and_unjails() {
+    let (key, v, d, reporter, gov, mut state) = setup();
+    let vm = vm(gov);
+    let report = TransactionPayload::ReportEquivocation { evidence: evidence(&key, 8, 8) };
+    vm.execute(&tx(reporter, 0, report), &mut state, &BlockContext::at_height(10)).unwrap();
+
+    let appeal = TransactionPayload::AppealSlash { slash_id: 1, evidence: b"replayed key".to_vec() };
+    assert!(matches!(
+        vm.execute(&tx(reporter, 1, appeal.clone()), &mut state, &BlockContext::at_height(20)),
+        Err(VmError::NotSlashedValidator)
+    ));
+    assert!(matches!(
+        vm.execute(&tx(v, 0, appeal.clone()), &mut state, &BlockContext::at_height(61)),
+        Err(VmError::Appeal(_))
+    ));
+    vm.execute(&tx(v, 0, appeal), &mut state, &BlockContext::at_height(60)).unwrap();
+
+    let resolve = TransactionPayload::ResolveAppeal { appeal_id: 1, approved: true };
+    assert!(matches!(
+        vm.execute(&tx(reporter, 1, resolve.clone()), &mut state, &BlockContext::at_height(70)),
+        Err(VmError::NotGovernance)
+    ));
+    vm.execute(&tx(gov, 0, resolve), &mut state, &BlockContext::at_height(70)).unwrap();
+    assert!(state.staking().slashes().is_slash_reversed(1));
+    assert!(!state.staking().is_jailed(&v));
+    assert_eq!(state.get(&v).unwrap().balance, 1_000);
+    assert_eq!(state.get(&d).unwrap().balance, 1_200);
+}
+
+#[test]
+fn test_downtime_slashes_once_then_unjail_after_jail_period() {
+    let (_, v, d, _, gov, mut state) = setup();
+    let other = AccountId([7u8; 32]);
+    let set = ValidatorSet::equal_weight([other, v]);
+    let liveness = LivenessParams { window: 10, max_missed: 2, slash_bps: 1_000, jail_blocks: 5 };
+    let slashing = SlashingParams { governance: Some(gov), ..SlashingParams::default() };
+
+    // Only `other` signs; v misses a vote at every block from height 2 on.
+    for height in 1..=4 {
+        punish_downtime(&mut state, height, height, &set, &[other], &liveness, &slashing);
+    }
+    assert_eq!(state.staking().jailed_until(&v), Some(9));
+    assert_eq!(state.get(&v).unwrap().stake, 9_000);
+    assert_eq!(state.staking().delegation(&v, &d), 1_800);
+    assert_eq!(state.staking().slashes().get_slash(1).unwrap().reason, SlashReason::Liveness);
+
+    // Misses while jailed are not slashed again.
+    for height in 5..=8 {
+        punish_downtime(&mut state, height, height, &set, &[other], &liveness, &slashing);
+    }
+    assert_eq!(state.staking().slashes().list_slashes().len(), 1);
+
+    let vm = vm(gov);
+    assert!(matches!(
+        vm.execute(&tx(v, 0, TransactionPayload::Unjail), &mut state, &BlockContext::at_height(8)),
+        Err(VmError::StillJailed(9))
+    ));
+    vm.execute(&tx(v, 0, TransactionPayload::Unjail), &mut state, &BlockContext::at_height(9)).unwrap();
+    assert!(!state.staking().is_jailed(&v));
+    assert!(matches!(
+        vm.execute(&tx(v, 1, TransactionPayload::Unjail), &mut state, &BlockContext::at_height(9)),
+        Err(VmError::NotJailed)
+    ));
+}
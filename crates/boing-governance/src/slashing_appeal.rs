@@ -1,12 +1,15 @@
 //! Transparent slashing with appeal — auditable records and appeal flow.
 //!
 //! Slashed validators can submit appeals; governance decides via phased proposal.
-//! Delegators see exactly why validators were slashed.
+//! Delegators see exactly why validators were slashed. The registry is part of consensus
+//! state, so it is ordered and serializable.
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
 
 /// Reason for a slash (auditable, transparent).
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SlashReason {
     /// Equivocation in consensus (double-sign, conflicting votes).
     Equivocation,
@@ -19,7 +22,7 @@ pub enum SlashReason {
 }
 
 /// Record of a slashing event — transparent and auditable.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SlashRecord {
     pub id: u64,
     /// Validator account that was slashed.
@@ -32,7 +35,7 @@ pub struct SlashRecord {
 }
 
 /// Status of an appeal.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AppealStatus {
     Pending,
     Approved,
@@ -40,7 +43,7 @@ pub enum AppealStatus {
 }
 
 /// An appeal against a slash — validator contests with evidence.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SlashingAppeal {
     pub id: u64,
     pub slash_id: u64,
@@ -50,9 +53,10 @@ pub struct SlashingAppeal {
 }
 
 /// Registry of slashes and appeals — transparent, auditable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SlashRegistry {
-    slashes: HashMap<u64, SlashRecord>,
-    appeals: HashMap<u64, SlashingAppeal>,
+    slashes: BTreeMap<u64, SlashRecord>,
+    appeals: BTreeMap<u64, SlashingAppeal>,
     next_slash_id: u64,
     next_appeal_id: u64,
 }
@@ -60,8 +64,8 @@ pub struct SlashRegistry {
 impl SlashRegistry {
     pub fn new() -> Self {
         Self {
-            slashes: HashMap::new(),
-            appeals: HashMap::new(),
+            slashes: BTreeMap::new(),
+            appeals: BTreeMap::new(),
             next_slash_id: 1,
             next_appeal_id: 1,
         }
@@ -92,13 +96,11 @@ impl SlashRegistry {
         id
     }
 
-    /// Submit an appeal for a slash. Returns appeal ID or error.
-    pub fn submit_appeal(&mut self, slash_id: u64, evidence: Vec<u8>) -> Result<u64, SlashingError> {
+    /// Submit an appeal for a slash at block `height`. Returns appeal ID or error.
+    pub fn submit_appeal(&mut self, slash_id: u64, evidence: Vec<u8>, height: u64) -> Result<u64, SlashingError> {
         let slash = self.slashes.get(&slash_id).ok_or(SlashingError::SlashNotFound)?;
-        // Check appeal window
-        if slash.appeal_deadline > 0 {
-            // Caller must pass current height; we don't have chain access here
-            // For now we allow appeal if slash exists and no appeal yet
+        if height > slash.appeal_deadline {
+            return Err(SlashingError::AppealWindowClosed);
         }
         if self.appeals.values().any(|a| a.slash_id == slash_id) {
             return Err(SlashingError::AppealAlreadyExists);
@@ -146,10 +148,20 @@ impl SlashRegistry {
         self.appeals.get(&id)
     }
 
-    /// List all slashes (for transparency / delegator visibility).
+    /// No slash was ever recorded.
+    pub fn is_empty(&self) -> bool {
+        self.slashes.is_empty()
+    }
+
+    /// List all slashes in id order (for transparency / delegator visibility).
     pub fn list_slashes(&self) -> Vec<&SlashRecord> {
         self.slashes.values().collect()
     }
+
+    /// List all appeals in id order.
+    pub fn list_appeals(&self) -> Vec<&SlashingAppeal> {
+        self.appeals.values().collect()
+    }
 }
 
 impl Default for SlashRegistry {
@@ -176,9 +188,9 @@ mod tests {
         assert_eq!(slash_id, 1);
         assert!(!reg.is_slash_reversed(slash_id));
 
-        let appeal_id = reg.submit_appeal(slash_id, b"evidence".to_vec()).unwrap();
+        let appeal_id = reg.submit_appeal(slash_id, b"evidence".to_vec(), 150).unwrap();
         assert_eq!(appeal_id, 1);
-        assert!(reg.submit_appeal(slash_id, vec![]).is_err()); // duplicate
+        assert!(reg.submit_appeal(slash_id, vec![], 150).is_err()); // duplicate
 
         reg.resolve_appeal(appeal_id, true).unwrap();
         assert!(reg.is_slash_reversed(slash_id));
@@ -188,10 +200,18 @@ mod tests {
     fn test_appeal_rejected() {
         let mut reg = SlashRegistry::new();
         let slash_id = reg.record_slash([2u8; 32], 500, SlashReason::Liveness, 50, 100);
-        let appeal_id = reg.submit_appeal(slash_id, vec![]).unwrap();
+        let appeal_id = reg.submit_appeal(slash_id, vec![], 150).unwrap();
         reg.resolve_appeal(appeal_id, false).unwrap();
         assert!(!reg.is_slash_reversed(slash_id));
     }
+
+    #[test]
+    fn test_appeal_after_deadline_rejected() {
+        let mut reg = SlashRegistry::new();
+        let slash_id = reg.record_slash([3u8; 32], 500, SlashReason::Equivocation, 50, 100);
+        assert!(matches!(reg.submit_appeal(slash_id, vec![], 151), Err(SlashingError::AppealWindowClosed)));
+        assert!(reg.submit_appeal(slash_id, vec![], 150).is_ok());
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -204,4 +224,6 @@ pub enum SlashingError {
     AppealAlreadyExists,
     #[error("Appeal already resolved")]
     AppealAlreadyResolved,
+    #[error("Appeal window closed")]
+    AppealWindowClosed,
 }
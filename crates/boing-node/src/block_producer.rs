@@ -2,115 +2,227 @@
 
 use tracing::{info, warn};
 
-use boing_primitives::{tx_root, Account, AccountId, AccountState, Block, BlockHeader, Hash, Transaction};
+use boing_primitives::{
+    next_beacon, tx_root, AccessList, AccountId, Block, BlockHeader, Hash, SignedTransaction,
+    TimeoutVote, Transaction, TransactionPayload, Vote,
+};
 use boing_consensus::ConsensusEngine;
-use boing_execution::BlockExecutor;
+use boing_execution::{BlockContext, BlockExecutor, VmError};
 use boing_state::StateStore;
-use boing_tokenomics::block_emission_validators;
+use ed25519_dalek::SigningKey;
 
 use crate::chain::ChainState;
 use crate::mempool::Mempool;
+use crate::rewards::credit_block_reward;
 
-/// Block producer — drains mempool, executes, builds block, proposes to consensus.
+/// Block producer — drains mempool, executes, builds and signs block, proposes to consensus.
 pub struct BlockProducer {
     proposer: AccountId,
+    signing_key: SigningKey,
     max_txs_per_block: usize,
 }
 
 impl BlockProducer {
-    pub fn new(proposer: AccountId) -> Self {
+    /// Producer for the validator owning `signing_key`.
+    pub fn new(signing_key: SigningKey) -> Self {
         Self {
-            proposer,
+            proposer: AccountId(signing_key.verifying_key().to_bytes()),
+            signing_key,
             max_txs_per_block: 1000,
         }
     }
 
+    /// Validator account this producer proposes as.
+    pub fn proposer(&self) -> AccountId {
+        self.proposer
+    }
+
     pub fn with_max_txs(mut self, max: usize) -> Self {
         self.max_txs_per_block = max;
         self
     }
 
-    /// Produce and commit a block. Returns the block hash if successful.
-    /// Only the round leader produces; other validators skip.
-    pub fn produce_block(
+    /// Sign a vote for `block_hash` as this validator.
+    pub fn vote(&self, block_hash: Hash) -> Vote {
+        Vote::new(block_hash, &self.signing_key)
+    }
+
+    /// Sign a timeout vote for `view` as this validator.
+    pub fn timeout_vote(&self, view: u64) -> TimeoutVote {
+        TimeoutVote::new(view, &self.signing_key)
+    }
+
+    /// Sign a transaction from this validator's account (e.g. an equivocation report).
+    pub fn sign_transaction(&self, chain_id: u64, nonce: u64, payload: TransactionPayload) -> SignedTransaction {
+        let tx = Transaction {
+            chain_id,
+            nonce,
+            sender: self.proposer,
+            payload,
+            access_list: AccessList::new(vec![self.proposer], vec![self.proposer]),
+        };
+        SignedTransaction::new(tx, &self.signing_key)
+    }
+
+    /// Build and sign a block for the current view, extending the highest certified block
+    /// (its QC goes in `justify`) and executed on a snapshot of `parent_state`, the state
+    /// after that block. Only the view leader builds; returns None when it is not our turn,
+    /// there is nothing to include and `allow_empty` is false, or execution fails (drained
+    /// txs are re-inserted). Transactions that fail on their own are dropped from the pool,
+    /// and those waiting on an earlier nonce are put back, so neither stalls the block.
+    pub fn build_block(
         &self,
-        chain: &ChainState,
         mempool: &Mempool,
-        state: &mut StateStore,
+        parent_state: &StateStore,
         executor: &BlockExecutor,
-        consensus: &mut ConsensusEngine,
-    ) -> Option<Hash> {
-        let next_height = chain.height() + 1;
-        if consensus.leader(next_height) != self.proposer {
+        consensus: &ConsensusEngine,
+        allow_empty: bool,
+    ) -> Option<ProposedBlock> {
+        let round = consensus.view();
+        let (parent, justify) = consensus.extend_target();
+        let height = parent.height + 1;
+        if consensus.leader(height, round) != self.proposer {
             return None; // Not our turn to propose
         }
-        let signed_txs = mempool.drain_for_block(self.max_txs_per_block);
-        if signed_txs.is_empty() {
+
+        // Transactions already included by uncommitted ancestors stay pooled until they commit.
+        let (signed_txs, included): (Vec<SignedTransaction>, Vec<SignedTransaction>) = mempool
+            .drain_for_block(self.max_txs_per_block)
+            .into_iter()
+            .partition(|s| s.tx.nonce >= parent_state.get(&s.tx.sender).map(|a| a.nonce).unwrap_or(0));
+        mempool.reinsert(included);
+        if signed_txs.is_empty() && !allow_empty {
             return None;
         }
-        let txs: Vec<Transaction> = signed_txs.iter().map(|s| s.tx.clone()).collect();
 
-        let parent_hash = chain.parent_hash();
-        let height = chain.height() + 1;
-        let tx_root = tx_root(&txs);
+        let mut header = BlockHeader {
+            parent_hash: parent.hash,
+            height,
+            round,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            proposer: self.proposer,
+            tx_root: Hash::ZERO,
+            state_root: Hash::ZERO,
+            validator_set_hash: consensus.validators_at(height).hash(),
+            vrf: Default::default(),
+            randomness: Hash::ZERO,
+            justify_hash: Hash::ZERO,
+        };
+        header.prove_vrf(&self.signing_key);
+        header.randomness = next_beacon(&parent_state.beacon(), &header.vrf);
 
-        // Execute transactions; revert on failure and re-insert txs so they can be retried
-        let checkpoint = state.checkpoint();
-        if let Err(e) = executor.execute_block(&txs, state) {
+        let mut post_state = parent_state.snapshot();
+        post_state.set_beacon(header.randomness);
+        let ctx = BlockContext::at_height(height).with_randomness(header.randomness);
+        let signed_txs = select_executable(signed_txs, mempool, &post_state, executor, &ctx);
+        if signed_txs.is_empty() && !allow_empty {
+            return None;
+        }
+        let txs: Vec<Transaction> = signed_txs.iter().map(|s| s.tx.clone()).collect();
+        header.tx_root = tx_root(&txs);
+
+        // Execute transactions on a snapshot; re-insert txs on failure so they can be retried
+        if let Err(e) = executor.execute_block(&txs, &mut post_state, &ctx) {
             warn!("Block execution failed: {}", e);
-            state.revert(checkpoint);
             mempool.reinsert(signed_txs);
             return None;
         }
+        let signers = justify.as_ref().map(|qc| qc.signers()).unwrap_or_default();
+        executor.record_liveness(&mut post_state, &ctx, round, consensus.validators_at(height), &signers);
+        executor.settle_relays(&mut post_state, &ctx);
 
-        // Credit block reward to proposer
-        let reward = block_emission_validators(height);
-        if reward > 0 {
-            match state.get_mut(&self.proposer) {
-                Some(s) => s.balance = s.balance.saturating_add(reward),
-                None => {
-                    state.insert(Account {
-                        id: self.proposer,
-                        state: AccountState { balance: reward, nonce: 0, stake: 0 },
-                    });
-                }
-            }
-        }
+        // Credit block reward to proposer and its delegators
+        credit_block_reward(&mut post_state, self.proposer, height);
 
-        let state_root = state.state_root();
-
-        let block = Block {
-            header: BlockHeader {
-                parent_hash,
-                height,
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
-                proposer: self.proposer,
-                tx_root,
-                state_root,
-            },
-            transactions: txs,
-        };
+        header.state_root = post_state.state_root();
+
+        let mut block = Block::new(header, txs);
+        block.set_justify(justify);
+        block.sign(&self.signing_key);
+
+        Some(ProposedBlock {
+            block,
+            state: post_state,
+            txs: signed_txs,
+        })
+    }
+
+    /// Produce and commit a block, voting only with our own key (single-validator chains and
+    /// single-process tests). Returns the block hash if successful.
+    pub fn produce_block(
+        &self,
+        chain: &ChainState,
+        mempool: &Mempool,
+        state: &mut StateStore,
+        executor: &BlockExecutor,
+        consensus: &mut ConsensusEngine,
+    ) -> Option<Hash> {
+        let ProposedBlock { block, state: post_state, txs } =
+            self.build_block(mempool, state, executor, consensus, false)?;
+        let height = block.header.height;
 
-        match consensus.propose_and_commit(block.clone()) {
-            Ok(hash) => {
-                if let Err(e) = chain.append(block) {
-                    warn!("Failed to append block to chain: {}", e);
-                    state.revert(checkpoint);
-                    mempool.reinsert(signed_txs);
-                    return None;
+        match consensus.propose_and_commit(block, &[&self.signing_key]) {
+            Ok(qc) => {
+                let hash = qc.block_hash;
+                for block in consensus.take_committed() {
+                    if let Err(e) = chain.append(block) {
+                        warn!("Failed to append block to chain: {}", e);
+                        mempool.reinsert(txs);
+                        return None;
+                    }
                 }
+                *state = post_state;
                 info!("Block committed: height={} hash={:?}", height, hash);
                 Some(hash)
             }
             Err(e) => {
                 warn!("Consensus failed: {}", e);
-                state.revert(checkpoint);
-                mempool.reinsert(signed_txs);
+                mempool.reinsert(txs);
                 None
             }
         }
     }
 }
+
+/// Keep the transactions that execute in order on `state`. A transaction whose nonce is
+/// ahead of its sender's goes back to the pool for a later block; any other failure (a
+/// settlement whose intent expired, an unjail before the jail ends, ...) would fail every
+/// block it is in, so it is dropped.
+fn select_executable(
+    signed_txs: Vec<SignedTransaction>,
+    mempool: &Mempool,
+    state: &StateStore,
+    executor: &BlockExecutor,
+    ctx: &BlockContext,
+) -> Vec<SignedTransaction> {
+    let mut trial = state.snapshot();
+    let mut selected: Vec<SignedTransaction> = Vec::with_capacity(signed_txs.len());
+    let mut waiting = Vec::new();
+    for signed in signed_txs {
+        let Err(e) = executor.execute_transaction(&signed.tx, &mut trial, ctx) else {
+            selected.push(signed);
+            continue;
+        };
+        match e {
+            VmError::InvalidNonce { expected, got } if got > expected => waiting.push(signed),
+            e => warn!("Dropping transaction {:?} that fails execution: {}", signed.tx.id(), e),
+        }
+        // The failed transaction may have been partly applied; replay the selected ones.
+        trial = state.snapshot();
+        for s in &selected {
+            let _ = executor.execute_transaction(&s.tx, &mut trial, ctx);
+        }
+    }
+    mempool.reinsert(waiting);
+    selected
+}
+
+/// Signed block built by the leader, with its post-execution state and source transactions.
+pub struct ProposedBlock {
+    pub block: Block,
+    pub state: StateStore,
+    pub txs: Vec<SignedTransaction>,
+}
@@ -1,51 +1,95 @@
 //! Block import and validation — validate blocks from peers.
 
-use boing_primitives::{tx_root, Account, AccountId, AccountState, Block, Hash};
+use boing_primitives::{next_beacon, tx_root, Block, CertificateError, Hash, ValidatorSet, VrfError};
 use boing_consensus::ConsensusEngine;
-use boing_execution::BlockExecutor;
+use boing_execution::{BlockContext, BlockExecutor};
 use boing_state::StateStore;
-use boing_tokenomics::block_emission_validators;
 
-/// Validate and execute a block. Returns updated state on success.
-/// Caller must ensure block chains to parent (parent_hash, height).
+use crate::chain::ChainError;
+use crate::rewards::credit_block_reward;
+
+/// Validate and execute a committed block (must carry a quorum certificate) against the
+/// validator set of its epoch. Returns updated state on success. Caller must ensure block
+/// chains to parent (parent_hash, height).
 pub fn validate_and_execute_block(
     block: &Block,
     parent_state: &StateStore,
-    validator_set: &[AccountId],
+    validator_set: &ValidatorSet,
+    chain_id: u64,
+    executor: &BlockExecutor,
+) -> Result<StateStore, BlockValidationError> {
+    let qc = block.qc.as_ref().ok_or(BlockValidationError::MissingQuorumCertificate)?;
+    qc.verify(&block.hash(), validator_set)?;
+    validate_proposal(block, parent_state, validator_set, chain_id, executor)
+}
+
+/// Validate and execute a proposed block before voting on it: everything except the
+/// quorum certificate. Returns the post-execution state on success.
+pub fn validate_proposal(
+    block: &Block,
+    parent_state: &StateStore,
+    validator_set: &ValidatorSet,
+    chain_id: u64,
     executor: &BlockExecutor,
 ) -> Result<StateStore, BlockValidationError> {
+    // 0. Every tx must be signed for this chain
+    if let Some(tx) = block.transactions.iter().find(|tx| tx.chain_id != chain_id) {
+        return Err(BlockValidationError::WrongChainId {
+            expected: chain_id,
+            got: tx.chain_id,
+        });
+    }
+
     // 1. Tx root
     let expected_tx_root = tx_root(&block.transactions);
     if block.header.tx_root != expected_tx_root {
         return Err(BlockValidationError::InvalidTxRoot);
     }
 
-    // 2. Proposer in validator set
-    if !validator_set.contains(&block.header.proposer) {
+    // 2. Header commits to the epoch's validator set, and the proposer leads the block's view
+    if block.header.validator_set_hash != validator_set.hash() {
+        return Err(BlockValidationError::WrongValidatorSet {
+            expected: validator_set.hash(),
+            got: block.header.validator_set_hash,
+        });
+    }
+    if validator_set.leader(block.header.round) != block.header.proposer {
         return Err(BlockValidationError::InvalidProposer);
     }
 
-    // 3. Execute on snapshot
+    // 3. Proposer signature over the block hash, the justify QC it commits to, its VRF
+    //    proof, and the beacon it yields
+    if block.verify_signature().is_err() {
+        return Err(BlockValidationError::InvalidSignature);
+    }
+    if !block.justify_matches() {
+        return Err(BlockValidationError::JustifyMismatch);
+    }
+    block.header.verify_vrf()?;
+    let randomness = next_beacon(&parent_state.beacon(), &block.header.vrf);
+    if block.header.randomness != randomness {
+        return Err(BlockValidationError::InvalidRandomness);
+    }
+
+    // 4. Execute on snapshot
     let mut state = parent_state.snapshot();
-    if let Err(e) = executor.execute_block(&block.transactions, &mut state) {
+    state.set_beacon(randomness);
+    let ctx = BlockContext::at_height(block.header.height).with_randomness(randomness);
+    if let Err(e) = executor.execute_block(&block.transactions, &mut state, &ctx) {
         return Err(BlockValidationError::ExecutionFailed(e.to_string()));
     }
 
-    // 4. Block reward
-    let reward = block_emission_validators(block.header.height);
-    if reward > 0 {
-        match state.get_mut(&block.header.proposer) {
-            Some(s) => s.balance = s.balance.saturating_add(reward),
-            None => {
-                state.insert(Account {
-                    id: block.header.proposer,
-                    state: AccountState { balance: reward, nonce: 0, stake: 0 },
-                });
-            }
-        }
-    }
+    // 5. Missed proposals and votes, downtime slashing
+    let signers = block.justify.as_ref().map(|qc| qc.signers()).unwrap_or_default();
+    executor.record_liveness(&mut state, &ctx, block.header.round, validator_set, &signers);
+
+    // 6. Relay reputation, rewards and deregistration at epoch boundaries
+    executor.settle_relays(&mut state, &ctx);
 
-    // 5. State root
+    // 7. Block reward
+    credit_block_reward(&mut state, block.header.proposer, block.header.height);
+
+    // 8. State root
     let computed_root = state.state_root();
     if block.header.state_root != computed_root {
         return Err(BlockValidationError::InvalidStateRoot {
@@ -69,30 +113,44 @@ pub fn import_block(
     our_height: u64,
     parent_state: &StateStore,
     consensus: &ConsensusEngine,
+    chain_id: u64,
     executor: &BlockExecutor,
 ) -> Result<StateStore, BlockValidationError> {
     if !chains_to(block, our_latest_hash, our_height) {
         return Err(BlockValidationError::DoesNotChain);
     }
-    let validator_set = consensus.validators();
-    if validator_set.is_empty() {
-        return Err(BlockValidationError::NoValidators);
-    }
-    validate_and_execute_block(block, parent_state, validator_set, executor)
+    let validator_set = consensus.validators_at(block.header.height);
+    validate_and_execute_block(block, parent_state, validator_set, chain_id, executor)
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum BlockValidationError {
     #[error("Block does not chain to our tip")]
     DoesNotChain,
+    #[error("{0}")]
+    Chain(#[from] ChainError),
     #[error("Invalid tx root")]
     InvalidTxRoot,
+    #[error("Transaction for wrong chain: expected chain id {expected}, got {got}")]
+    WrongChainId { expected: u64, got: u64 },
+    #[error("Header validator set {got:?} is not the epoch's set {expected:?}")]
+    WrongValidatorSet { expected: Hash, got: Hash },
     #[error("Invalid proposer")]
     InvalidProposer,
+    #[error("Missing or invalid proposer signature")]
+    InvalidSignature,
+    #[error("Invalid VRF proof: {0}")]
+    InvalidVrf(#[from] VrfError),
+    #[error("Header randomness does not follow from the parent beacon and VRF output")]
+    InvalidRandomness,
+    #[error("Justify QC does not match the one the header commits to")]
+    JustifyMismatch,
+    #[error("Missing quorum certificate")]
+    MissingQuorumCertificate,
+    #[error("Invalid quorum certificate: {0}")]
+    InvalidQuorumCertificate(#[from] CertificateError),
     #[error("Execution failed: {0}")]
     ExecutionFailed(String),
     #[error("Invalid state root: expected {expected:?}, computed {computed:?}")]
     InvalidStateRoot { expected: Hash, computed: Hash },
-    #[error("No validators configured")]
-    NoValidators,
 }
@@ -1,6 +1,11 @@
-//! Chain state — tracks latest block and height.
+//! Chain state — the canonical chain and a tree of unfinalized blocks.
+//!
+//! Blocks above the finalized block may fork. Every known block descending from the
+//! finalized one is kept, and fork choice picks the head: the branch through the finalized
+//! block (nothing below it is ever reverted), then the one with the highest QC (its latest
+//! certified block has the highest view), then the longer one.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
 use boing_primitives::{AccountId, Block, Hash};
@@ -16,24 +21,68 @@ struct ChainStateInner {
     pub height: u64,
     pub latest_hash: Hash,
     pub latest_block: Option<Block>,
-    /// Block index: height -> block, hash -> block (for RPC queries).
+    /// Block index: height -> canonical block, hash -> any known block (for RPC queries).
     blocks_by_height: HashMap<u64, Block>,
     blocks_by_hash: HashMap<Hash, Block>,
+    /// Last finalized block (height, hash); the canonical chain up to it is fixed.
+    finalized: (u64, Hash),
+    /// Children of the finalized block and of every block above it.
+    children: HashMap<Hash, Vec<Hash>>,
+}
+
+/// Named chain position accepted in place of a height by block and account RPCs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockTag {
+    /// The head.
+    Latest,
+    /// The newest certified canonical block; only a higher QC on another branch reverts it.
+    Safe,
+    /// The last finalized block; never reverted.
+    Finalized,
+}
+
+impl std::str::FromStr for BlockTag {
+    type Err = ChainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" => Ok(Self::Latest),
+            "safe" => Ok(Self::Safe),
+            "finalized" => Ok(Self::Finalized),
+            _ => Err(ChainError::UnknownTag(s.to_string())),
+        }
+    }
+}
+
+/// Blocks to undo and apply to move the head to another branch.
+#[derive(Clone, Debug)]
+pub struct Reorg {
+    /// Last block shared by both branches.
+    pub ancestor: Block,
+    /// Canonical blocks above the ancestor, newest first.
+    pub retracted: Vec<Block>,
+    /// Blocks of the new branch above the ancestor, oldest first.
+    pub enacted: Vec<Block>,
 }
 
 impl ChainState {
     pub fn genesis(proposer: AccountId) -> Block {
-        Block {
-            header: boing_primitives::BlockHeader {
+        Block::new(
+            boing_primitives::BlockHeader {
                 parent_hash: Hash::ZERO,
                 height: 0,
+                round: 0,
                 timestamp: 0,
                 proposer,
                 tx_root: Hash::ZERO,
                 state_root: Hash::ZERO,
+                validator_set_hash: Hash::ZERO,
+                vrf: Default::default(),
+                randomness: Hash::ZERO,
+                justify_hash: Hash::ZERO,
             },
-            transactions: vec![],
-        }
+            vec![],
+        )
     }
 
     pub fn new(genesis_proposer: AccountId) -> Self {
@@ -54,6 +103,8 @@ impl ChainState {
                 latest_block: Some(genesis),
                 blocks_by_height,
                 blocks_by_hash,
+                finalized: (0, hash),
+                children: HashMap::new(),
             })),
         }
     }
@@ -70,7 +121,39 @@ impl ChainState {
         self.inner.read().unwrap().latest_hash
     }
 
-    /// Append a committed block. Returns error if block doesn't chain.
+    /// Height and hash of the last finalized block.
+    pub fn finalized(&self) -> (u64, Hash) {
+        self.inner.read().unwrap().finalized
+    }
+
+    /// Height and hash of the newest canonical block with a QC: its own, or the `justify`
+    /// of its canonical child. Falls back to the finalized block.
+    pub fn safe(&self) -> (u64, Hash) {
+        let inner = self.inner.read().unwrap();
+        let mut child: Option<&Block> = None;
+        for height in ((inner.finalized.0 + 1)..=inner.height).rev() {
+            let Some(block) = inner.blocks_by_height.get(&height) else {
+                break;
+            };
+            if block.qc.is_some() || child.map(|c| c.justify.is_some()).unwrap_or(false) {
+                return (height, block.hash());
+            }
+            child = Some(block);
+        }
+        inner.finalized
+    }
+
+    /// Canonical block at `tag`.
+    pub fn get_block_by_tag(&self, tag: BlockTag) -> Option<Block> {
+        let hash = match tag {
+            BlockTag::Latest => self.latest_hash(),
+            BlockTag::Safe => self.safe().1,
+            BlockTag::Finalized => self.finalized().1,
+        };
+        self.get_block_by_hash(&hash)
+    }
+
+    /// Append a block extending the head. Returns error if block doesn't chain.
     pub fn append(&self, block: Block) -> Result<(), ChainError> {
         let mut inner = self.inner.write().unwrap();
         if block.header.parent_hash != inner.latest_hash {
@@ -80,11 +163,143 @@ impl ChainState {
             return Err(ChainError::InvalidHeight);
         }
         let hash = block.hash();
-        inner.blocks_by_height.insert(block.header.height, block.clone());
-        inner.blocks_by_hash.insert(hash, block.clone());
-        inner.height = block.header.height;
-        inner.latest_hash = hash;
-        inner.latest_block = Some(block);
+        inner.add_to_tree(hash, &block);
+        inner.set_head(hash);
+        Ok(())
+    }
+
+    /// Add a block to the tree without moving the head. Its parent must be known and not
+    /// below the finalized block.
+    pub fn insert(&self, block: Block) -> Result<(), ChainError> {
+        let mut inner = self.inner.write().unwrap();
+        let hash = block.hash();
+        if inner.blocks_by_hash.contains_key(&hash) {
+            return Err(ChainError::AlreadyKnown);
+        }
+        let parent = inner
+            .blocks_by_hash
+            .get(&block.header.parent_hash)
+            .ok_or(ChainError::BlockNotChained)?;
+        if block.header.height != parent.header.height + 1 {
+            return Err(ChainError::InvalidHeight);
+        }
+        if block.header.height <= inner.finalized.0 || !inner.in_tree(&block.header.parent_hash) {
+            return Err(ChainError::BelowFinalized);
+        }
+        inner.add_to_tree(hash, &block);
+        Ok(())
+    }
+
+    /// Fork choice: the best known block descending from the finalized one. Ranks by the
+    /// view of the highest certified block on its branch, then height; the head wins ties.
+    pub fn fork_choice(&self) -> Hash {
+        let inner = self.inner.read().unwrap();
+        let head = inner.latest_hash;
+        let mut best = (0, 0, head);
+        // (block, highest certified view on the way to it)
+        let mut stack = vec![(inner.finalized.1, 0)];
+        while let Some((hash, view)) = stack.pop() {
+            let block = &inner.blocks_by_hash[&hash];
+            let view = if block.qc.is_some() { view.max(block.header.round) } else { view };
+            let rank = |(view, height, hash): (u64, u64, Hash)| (view, height, hash == head, std::cmp::Reverse(hash.0));
+            if rank((view, block.header.height, hash)) > rank(best) {
+                best = (view, block.header.height, hash);
+            }
+            for child in inner.children.get(&hash).into_iter().flatten() {
+                // A child's justify certifies this block.
+                let justified = inner.blocks_by_hash[child].justify.is_some();
+                stack.push((*child, if justified { view.max(block.header.round) } else { view }));
+            }
+        }
+        best.2
+    }
+
+    /// Path from the head to `tip`, a known block descending from the finalized one.
+    pub fn reorg_to(&self, tip: &Hash) -> Option<Reorg> {
+        let inner = self.inner.read().unwrap();
+        if !inner.in_tree(tip) {
+            return None;
+        }
+        let mut enacted = Vec::new();
+        let mut cur = inner.blocks_by_hash.get(tip)?.clone();
+        while inner.blocks_by_height.get(&cur.header.height).map(|b| b.hash()) != Some(cur.hash()) {
+            let parent = inner.blocks_by_hash.get(&cur.header.parent_hash)?.clone();
+            enacted.push(cur);
+            cur = parent;
+        }
+        enacted.reverse();
+        let retracted = ((cur.header.height + 1)..=inner.height)
+            .rev()
+            .filter_map(|h| inner.blocks_by_height.get(&h).cloned())
+            .collect();
+        Some(Reorg { ancestor: cur, retracted, enacted })
+    }
+
+    /// Make `hash` the head: the canonical chain becomes its ancestry. It must be a known
+    /// block descending from the finalized one.
+    pub fn set_head(&self, hash: &Hash) -> Result<(), ChainError> {
+        let mut inner = self.inner.write().unwrap();
+        if !inner.in_tree(hash) {
+            return Err(ChainError::BelowFinalized);
+        }
+        inner.set_head(*hash);
+        Ok(())
+    }
+
+    /// Drop `hash` and its descendants from the tree (e.g. a block that failed execution).
+    /// Canonical blocks are not dropped.
+    pub fn discard(&self, hash: &Hash) {
+        let mut inner = self.inner.write().unwrap();
+        let Some(block) = inner.blocks_by_hash.get(hash) else {
+            return;
+        };
+        if inner.blocks_by_height.get(&block.header.height).map(|b| b.hash()) == Some(*hash) {
+            return;
+        }
+        let parent = block.header.parent_hash;
+        if let Some(siblings) = inner.children.get_mut(&parent) {
+            siblings.retain(|h| h != hash);
+        }
+        let mut stack = vec![*hash];
+        while let Some(h) = stack.pop() {
+            inner.blocks_by_hash.remove(&h);
+            stack.extend(inner.children.remove(&h).unwrap_or_default());
+        }
+    }
+
+    /// Finalize the canonical block `hash`: it can no longer be reverted, and branches that
+    /// do not descend from it are dropped.
+    pub fn finalize(&self, hash: &Hash) -> Result<(), ChainError> {
+        let mut inner = self.inner.write().unwrap();
+        let height = inner.blocks_by_hash.get(hash).ok_or(ChainError::BlockNotChained)?.header.height;
+        if inner.blocks_by_height.get(&height).map(|b| b.hash()) != Some(*hash) {
+            return Err(ChainError::NotCanonical);
+        }
+        if height <= inner.finalized.0 {
+            return Ok(());
+        }
+        let mut keep = HashSet::new();
+        let mut stack = vec![*hash];
+        while let Some(h) = stack.pop() {
+            keep.insert(h);
+            stack.extend(inner.children.get(&h).into_iter().flatten().copied());
+        }
+        let dropped: Vec<Hash> = inner
+            .children
+            .values()
+            .flatten()
+            .filter(|h| !keep.contains(*h))
+            .filter(|h| {
+                let block = &inner.blocks_by_hash[*h];
+                inner.blocks_by_height.get(&block.header.height).map(|b| b.hash()) != Some(**h)
+            })
+            .copied()
+            .collect();
+        for h in dropped {
+            inner.blocks_by_hash.remove(&h);
+        }
+        inner.children.retain(|h, _| keep.contains(h));
+        inner.finalized = (height, *hash);
         Ok(())
     }
 
@@ -99,10 +314,49 @@ impl ChainState {
     }
 }
 
+impl ChainStateInner {
+    fn add_to_tree(&mut self, hash: Hash, block: &Block) {
+        self.blocks_by_hash.insert(hash, block.clone());
+        self.children.entry(block.header.parent_hash).or_default().push(hash);
+    }
+
+    /// Whether `hash` is the finalized block or a known block above it.
+    fn in_tree(&self, hash: &Hash) -> bool {
+        *hash == self.finalized.1
+            || self.blocks_by_hash.get(hash).map(|b| b.header.height > self.finalized.0).unwrap_or(false)
+    }
+
+    fn set_head(&mut self, hash: Hash) {
+        let block = self.blocks_by_hash[&hash].clone();
+        let height = block.header.height;
+        self.blocks_by_height.retain(|h, _| *h <= height);
+        let mut cur = block.clone();
+        while self.blocks_by_height.get(&cur.header.height).map(|b| b.hash()) != Some(cur.hash()) {
+            let parent = cur.header.parent_hash;
+            self.blocks_by_height.insert(cur.header.height, cur);
+            match self.blocks_by_hash.get(&parent) {
+                Some(p) => cur = p.clone(),
+                None => break,
+            }
+        }
+        self.height = height;
+        self.latest_hash = hash;
+        self.latest_block = Some(block);
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ChainError {
     #[error("Block does not chain to latest")]
     BlockNotChained,
     #[error("Invalid block height")]
     InvalidHeight,
+    #[error("Block already known")]
+    AlreadyKnown,
+    #[error("Block does not descend from the finalized block")]
+    BelowFinalized,
+    #[error("Block is not on the canonical chain")]
+    NotCanonical,
+    #[error("Unknown block tag {0:?}: expected latest, safe or finalized")]
+    UnknownTag(String),
 }
@@ -0,0 +1,51 @@
+//! Epoch transitions — validator sets derived from on-chain stake.
+//!
+//! Every `epoch_length` blocks the chain reaches an epoch boundary. Of the unjailed accounts
+//! whose voting power (own stake plus delegations) is at least `min_validator_stake` after
+//! the boundary block, the top `max_validators` form the validator set one full epoch later.
+//! The lag means the set governing a height is always fixed by a committed block before any
+//! block at that height is proposed. The boundary block's VRF output seeds the new set's
+//! leader selection, so leaders are unknown until the boundary commits. If no account
+//! qualifies, the current set carries over.
+
+use boing_primitives::{Hash, Validator, ValidatorSet};
+use boing_state::StateStore;
+
+use crate::genesis::ProtocolParams;
+
+/// Whether `height` ends an epoch.
+pub fn is_boundary(height: u64, params: &ProtocolParams) -> bool {
+    height > 0 && height.is_multiple_of(params.epoch_length)
+}
+
+/// First height governed by the set derived at `boundary`.
+pub fn activation_height(boundary: u64, params: &ProtocolParams) -> u64 {
+    boundary + params.epoch_length + 1
+}
+
+/// Whether the validator set for `height` is known with `committed_height` committed:
+/// the boundary it depends on must be committed.
+pub fn validator_set_known(height: u64, committed_height: u64, params: &ProtocolParams) -> bool {
+    height <= committed_height + params.epoch_length + 1
+}
+
+/// Validator set from bonded and delegated stake in `state`, drawing leaders from `seed`, or
+/// None if no account meets the minimum. Jailed accounts and those below the minimum are
+/// dropped before the `max_validators` largest by voting power are picked, largest first.
+pub fn validator_set_from_stake(
+    state: &StateStore,
+    params: &ProtocolParams,
+    seed: Hash,
+) -> Option<ValidatorSet> {
+    let validators: Vec<Validator> = state
+        .top_stakers(usize::MAX)
+        .into_iter()
+        .filter(|id| !state.staking().is_jailed(id))
+        .filter_map(|id| {
+            let stake = state.voting_power(&id);
+            (stake > 0 && stake >= params.min_validator_stake).then_some(Validator { id, stake })
+        })
+        .take(params.max_validators)
+        .collect();
+    (!validators.is_empty()).then(|| ValidatorSet::new(validators).with_seed(seed))
+}
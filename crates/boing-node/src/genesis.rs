@@ -0,0 +1,465 @@
+//! Genesis configuration — chain id, initial allocations, stakes, validator set,
+//! contract code, and protocol parameters.
+//!
+//! Loaded from `genesis.json` via `boing-node --genesis`. Every node on a network
+//! must start from the same file; peers compare genesis hashes on connect.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use boing_primitives::{
+    hasher, Account, AccountId, AccountState, Block, BlockHeader, Hash, Validator, ValidatorSet,
+};
+use boing_execution::{LivenessParams, RelayParams, SlashingParams};
+use boing_state::StateStore;
+use boing_tokenomics::BLOCK_TIME_SECS;
+use ed25519_dalek::SigningKey;
+
+/// Chain id used by local dev chains (`boing dev`, tests).
+pub const DEV_CHAIN_ID: u64 = 1;
+
+/// Deterministic dev validator key (from seed). Validator of the default dev genesis.
+/// **Dev chains only** — anyone can derive this key.
+pub fn dev_validator_key() -> SigningKey {
+    let mut h = hasher();
+    h.update(b"boing-dev-validator-v1");
+    SigningKey::from_bytes(h.finalize().as_bytes())
+}
+
+/// Initial liquid balance for an account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisAllocation {
+    #[serde(with = "hex_account")]
+    pub account: AccountId,
+    #[serde(with = "u128_string")]
+    pub balance: u128,
+}
+
+/// Initial bonded stake for an account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisStake {
+    #[serde(with = "hex_account")]
+    pub account: AccountId,
+    #[serde(with = "u128_string")]
+    pub amount: u128,
+}
+
+/// Contract deployed at genesis (address + bytecode).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisContract {
+    #[serde(with = "hex_account")]
+    pub address: AccountId,
+    #[serde(with = "hex_bytes")]
+    pub code: Vec<u8>,
+}
+
+/// Protocol parameters fixed at genesis.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtocolParams {
+    /// Target block time in seconds.
+    pub block_time_secs: u64,
+    /// Max transactions per block.
+    pub max_txs_per_block: usize,
+    /// Base view timeout in milliseconds; doubles for each consecutive view that times out.
+    pub round_timeout_ms: u64,
+    /// Blocks per epoch. The validator set is re-derived from stake at every epoch boundary.
+    pub epoch_length: u64,
+    /// Max validators in a set derived from stake (the top stakers).
+    pub max_validators: usize,
+    /// Minimum bonded stake to join a validator set derived from stake.
+    #[serde(with = "u128_string")]
+    pub min_validator_stake: u128,
+    /// Blocks unbonded stake stays queued (and slashable) before it can be withdrawn. Must
+    /// exceed two epochs, the longest a stake can keep counting toward a validator set.
+    pub unbonding_delay: u64,
+    /// Share of stake (basis points) slashed for equivocation.
+    pub equivocation_slash_bps: u16,
+    /// Blocks after a slash during which the validator may appeal.
+    pub appeal_window: u64,
+    /// Account allowed to resolve slash appeals. Without one, appeals stay pending.
+    #[serde(with = "hex_account_opt")]
+    pub governance_account: Option<AccountId>,
+    /// Blocks over which missed proposals and votes are counted.
+    pub liveness_window: u64,
+    /// Missed proposals and votes tolerated within the window before a downtime slash.
+    pub liveness_max_missed: u64,
+    /// Share of stake (basis points) slashed for downtime.
+    pub liveness_slash_bps: u16,
+    /// Blocks a validator jailed for downtime waits before it can send `Unjail`.
+    pub liveness_jail_blocks: u64,
+    /// Minimum stake of a registered STUN/TURN relay.
+    #[serde(with = "u128_string")]
+    pub relay_min_stake: u128,
+    /// Reputation (0–10000) a relay must keep at epoch settlement to stay registered.
+    pub relay_min_reputation: u16,
+    /// Paid per epoch to a reported relay at full reputation (scaled by reputation).
+    #[serde(with = "u128_string")]
+    pub relay_epoch_reward: u128,
+    /// Share of stake (basis points) burned when a relay is dropped for low reputation.
+    pub relay_deregister_slash_bps: u16,
+}
+
+impl Default for ProtocolParams {
+    fn default() -> Self {
+        Self {
+            block_time_secs: BLOCK_TIME_SECS,
+            max_txs_per_block: 100,
+            round_timeout_ms: 10_000,
+            epoch_length: 1_000,
+            max_validators: 100,
+            min_validator_stake: 1_000,
+            unbonding_delay: boing_execution::DEFAULT_UNBONDING_DELAY,
+            equivocation_slash_bps: boing_execution::DEFAULT_EQUIVOCATION_SLASH_BPS,
+            appeal_window: boing_execution::DEFAULT_APPEAL_WINDOW,
+            governance_account: None,
+            liveness_window: boing_execution::DEFAULT_LIVENESS_WINDOW,
+            liveness_max_missed: boing_execution::DEFAULT_LIVENESS_MAX_MISSED,
+            liveness_slash_bps: boing_execution::DEFAULT_LIVENESS_SLASH_BPS,
+            liveness_jail_blocks: boing_execution::DEFAULT_LIVENESS_JAIL_BLOCKS,
+            relay_min_stake: boing_execution::DEFAULT_RELAY_MIN_STAKE,
+            relay_min_reputation: boing_execution::DEFAULT_RELAY_MIN_REPUTATION,
+            relay_epoch_reward: boing_execution::DEFAULT_RELAY_EPOCH_REWARD,
+            relay_deregister_slash_bps: boing_execution::DEFAULT_RELAY_DEREGISTER_SLASH_BPS,
+        }
+    }
+}
+
+impl ProtocolParams {
+    /// Slashing parameters for the VM.
+    pub fn slashing(&self) -> SlashingParams {
+        SlashingParams {
+            equivocation_slash_bps: self.equivocation_slash_bps,
+            appeal_window: self.appeal_window,
+            governance: self.governance_account,
+        }
+    }
+
+    /// Downtime parameters for the executor.
+    pub fn liveness(&self) -> LivenessParams {
+        LivenessParams {
+            window: self.liveness_window,
+            max_missed: self.liveness_max_missed,
+            slash_bps: self.liveness_slash_bps,
+            jail_blocks: self.liveness_jail_blocks,
+        }
+    }
+
+    /// Relay registry parameters for the VM and executor.
+    pub fn relays(&self) -> RelayParams {
+        RelayParams {
+            epoch_length: self.epoch_length,
+            min_stake: self.relay_min_stake,
+            min_reputation: self.relay_min_reputation,
+            epoch_reward: self.relay_epoch_reward,
+            deregister_slash_bps: self.relay_deregister_slash_bps,
+        }
+    }
+}
+
+/// Genesis file contents.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    pub chain_id: u64,
+    /// Genesis block timestamp (unix seconds).
+    #[serde(default)]
+    pub timestamp: u64,
+    /// Initial validator set (AccountIds / Ed25519 public keys), in set order.
+    /// Must be non-empty. Weighted by their genesis stakes, or equally if none is staked.
+    #[serde(with = "hex_accounts")]
+    pub validators: Vec<AccountId>,
+    #[serde(default)]
+    pub allocations: Vec<GenesisAllocation>,
+    #[serde(default)]
+    pub stakes: Vec<GenesisStake>,
+    #[serde(default)]
+    pub contracts: Vec<GenesisContract>,
+    #[serde(default)]
+    pub params: ProtocolParams,
+}
+
+impl GenesisConfig {
+    /// Single-validator dev genesis: `validator` is funded with `balance`.
+    pub fn single_validator(validator: AccountId, balance: u128) -> Self {
+        Self {
+            chain_id: DEV_CHAIN_ID,
+            timestamp: 0,
+            validators: vec![validator],
+            allocations: vec![GenesisAllocation { account: validator, balance }],
+            stakes: vec![],
+            contracts: vec![],
+            params: ProtocolParams::default(),
+        }
+    }
+
+    /// Default dev genesis ([`dev_validator_key`] with 1,000,000 BOING).
+    pub fn dev() -> Self {
+        let validator = AccountId(dev_validator_key().verifying_key().to_bytes());
+        Self::single_validator(validator, 1_000_000)
+    }
+
+    /// Load and validate a genesis file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GenesisError> {
+        let bytes = std::fs::read(path)?;
+        let config: Self =
+            serde_json::from_slice(&bytes).map_err(|e| GenesisError::Parse(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check structural invariants (non-empty validator set, no duplicate entries).
+    pub fn validate(&self) -> Result<(), GenesisError> {
+        if self.validators.is_empty() {
+            return Err(GenesisError::NoValidators);
+        }
+        let mut seen = HashSet::new();
+        if let Some(v) = self.validators.iter().find(|v| !seen.insert(**v)) {
+            return Err(GenesisError::Duplicate(format!("validator {:?}", v)));
+        }
+        let mut seen = HashSet::new();
+        if let Some(a) = self.allocations.iter().find(|a| !seen.insert(a.account)) {
+            return Err(GenesisError::Duplicate(format!("allocation {:?}", a.account)));
+        }
+        let mut seen = HashSet::new();
+        if let Some(s) = self.stakes.iter().find(|s| !seen.insert(s.account)) {
+            return Err(GenesisError::Duplicate(format!("stake {:?}", s.account)));
+        }
+        let mut seen = HashSet::new();
+        if let Some(c) = self.contracts.iter().find(|c| !seen.insert(c.address)) {
+            return Err(GenesisError::Duplicate(format!("contract {:?}", c.address)));
+        }
+        if self.params.max_txs_per_block == 0 {
+            return Err(GenesisError::InvalidParam("max_txs_per_block must be > 0".into()));
+        }
+        if self.params.epoch_length == 0 {
+            return Err(GenesisError::InvalidParam("epoch_length must be > 0".into()));
+        }
+        if self.params.max_validators == 0 {
+            return Err(GenesisError::InvalidParam("max_validators must be > 0".into()));
+        }
+        if self.params.unbonding_delay <= self.params.epoch_length.saturating_mul(2) {
+            return Err(GenesisError::InvalidParam(
+                "unbonding_delay must be > 2 * epoch_length".into(),
+            ));
+        }
+        if self.params.equivocation_slash_bps > 10_000 {
+            return Err(GenesisError::InvalidParam("equivocation_slash_bps must be <= 10000".into()));
+        }
+        if self.params.liveness_window == 0 {
+            return Err(GenesisError::InvalidParam("liveness_window must be > 0".into()));
+        }
+        if self.params.liveness_slash_bps > 10_000 {
+            return Err(GenesisError::InvalidParam("liveness_slash_bps must be <= 10000".into()));
+        }
+        if self.params.relay_min_reputation > 10_000 {
+            return Err(GenesisError::InvalidParam("relay_min_reputation must be <= 10000".into()));
+        }
+        if self.params.relay_deregister_slash_bps > 10_000 {
+            return Err(GenesisError::InvalidParam("relay_deregister_slash_bps must be <= 10000".into()));
+        }
+        let staked: Vec<bool> = self.validators.iter().map(|v| self.stake_of(v) > 0).collect();
+        if staked.contains(&true) {
+            if let Some(v) = self.validators.iter().zip(&staked).find(|(_, s)| !**s).map(|(v, _)| v) {
+                return Err(GenesisError::UnstakedValidator(*v));
+            }
+        }
+        self.config_hash()?;
+        Ok(())
+    }
+
+    fn stake_of(&self, account: &AccountId) -> u128 {
+        self.stakes.iter().find(|s| s.account == *account).map(|s| s.amount).unwrap_or(0)
+    }
+
+    /// Validator set of the first epochs: `validators` weighted by genesis stake, or equally
+    /// weighted when no validator has stake (dev chains).
+    pub fn validator_set(&self) -> ValidatorSet {
+        if self.validators.iter().all(|v| self.stake_of(v) == 0) {
+            return ValidatorSet::equal_weight(self.validators.iter().copied());
+        }
+        ValidatorSet::new(
+            self.validators
+                .iter()
+                .map(|v| Validator { id: *v, stake: self.stake_of(v) })
+                .collect(),
+        )
+    }
+
+    /// Build the initial state from allocations, stakes, and contracts.
+    pub fn build_state(&self) -> StateStore {
+        let mut state = StateStore::new();
+        for alloc in &self.allocations {
+            state.insert(Account {
+                id: alloc.account,
+                state: AccountState { balance: alloc.balance, nonce: 0, stake: 0 },
+            });
+        }
+        for stake in &self.stakes {
+            match state.get_mut(&stake.account) {
+                Some(s) => s.stake = stake.amount,
+                None => state.insert(Account {
+                    id: stake.account,
+                    state: AccountState { balance: 0, nonce: 0, stake: stake.amount },
+                }),
+            }
+        }
+        for contract in &self.contracts {
+            if state.get(&contract.address).is_none() {
+                state.insert(Account {
+                    id: contract.address,
+                    state: AccountState::default(),
+                });
+            }
+            state.set_contract_code(contract.address, contract.code.clone());
+        }
+        state
+    }
+
+    /// Hash of the full genesis configuration (chain id, params, allocations, ...).
+    pub fn config_hash(&self) -> Result<Hash, GenesisError> {
+        let bytes = bincode::serialize(self).map_err(|e| GenesisError::Encode(e.to_string()))?;
+        let mut h = hasher();
+        h.update(&bytes);
+        Ok(Hash(*h.finalize().as_bytes()))
+    }
+
+    /// Build the genesis block for the given initial state.
+    /// The parent hash commits to the full config, so genesis files that differ only in
+    /// chain id or protocol params still produce different genesis hashes.
+    pub fn build_block(&self, state: &mut StateStore) -> Result<Block, GenesisError> {
+        Ok(Block::new(
+            BlockHeader {
+                parent_hash: self.config_hash()?,
+                height: 0,
+                round: 0,
+                timestamp: self.timestamp,
+                proposer: self.validators[0],
+                tx_root: Hash::ZERO,
+                state_root: state.state_root(),
+                validator_set_hash: self.validator_set().hash(),
+                vrf: Default::default(),
+                randomness: Hash::ZERO,
+                justify_hash: Hash::ZERO,
+            },
+            vec![],
+        ))
+    }
+
+    /// Build initial state and genesis block together.
+    pub fn build(&self) -> Result<(StateStore, Block), GenesisError> {
+        let mut state = self.build_state();
+        let block = self.build_block(&mut state)?;
+        Ok((state, block))
+    }
+
+    /// Genesis block hash — identifies the network.
+    pub fn hash(&self) -> Result<Hash, GenesisError> {
+        Ok(self.build()?.1.hash())
+    }
+}
+
+impl Default for GenesisConfig {
+    fn default() -> Self {
+        Self::dev()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GenesisError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid genesis file: {0}")]
+    Parse(String),
+    #[error("Genesis has no validators")]
+    NoValidators,
+    #[error("Duplicate genesis entry: {0}")]
+    Duplicate(String),
+    #[error("Invalid protocol parameter: {0}")]
+    InvalidParam(String),
+    #[error("Validator {0:?} has no genesis stake, but other validators do")]
+    UnstakedValidator(AccountId),
+    #[error("Cannot encode genesis config: {0}")]
+    Encode(String),
+}
+
+fn parse_hex_account(s: &str) -> Result<AccountId, String> {
+    let bytes = hex::decode(s.trim_start_matches("0x")).map_err(|e| format!("Invalid hex: {}", e))?;
+    AccountId::from_slice(&bytes).ok_or_else(|| "Account ID must be 32 bytes".to_string())
+}
+
+mod hex_account {
+    use boing_primitives::AccountId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &AccountId, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("0x{}", hex::encode(id.0)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<AccountId, D::Error> {
+        let s = String::deserialize(d)?;
+        super::parse_hex_account(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+mod hex_account_opt {
+    use boing_primitives::AccountId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(id: &Option<AccountId>, s: S) -> Result<S::Ok, S::Error> {
+        match id {
+            Some(id) => s.serialize_some(&format!("0x{}", hex::encode(id.0))),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<AccountId>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|s| super::parse_hex_account(&s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+mod hex_accounts {
+    use boing_primitives::AccountId;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ids: &[AccountId], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(ids.iter().map(|id| format!("0x{}", hex::encode(id.0))))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<AccountId>, D::Error> {
+        let v = Vec::<String>::deserialize(d)?;
+        v.iter()
+            .map(|s| super::parse_hex_account(s).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(d)?;
+        hex::decode(s.trim_start_matches("0x")).map_err(serde::de::Error::custom)
+    }
+}
+
+/// u128 as decimal string (avoids JSON number precision loss, matches RPC).
+mod u128_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &u128, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&v.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u128, D::Error> {
+        let s = String::deserialize(d)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
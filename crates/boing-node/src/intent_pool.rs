@@ -1,40 +1,219 @@
-//! Intent pool — stores signed intents for solver/executor fulfillment.
-//! Stub implementation; production would persist and integrate with solver network.
+//! Intent pool — signed intents waiting for a solver.
+//!
+//! Solvers list the pool (`boing_getIntents`) and settle an intent by submitting a
+//! `FulfillIntent` transaction (`boing_submitSolution`). The pool is pruned after every
+//! committed block: intents fulfilled on-chain, cancelled (their nonce was used up) or past
+//! their deadline are dropped and remembered with that status for a while, so
+//! `boing_getIntent` can still report what became of them. Every newly pooled intent,
+//! submitted locally or gossiped, is also published to subscribers (the solver stream).
+//!
+//! Pooling is free, so the pool is bounded: it holds at most [`MAX_POOLED_INTENTS`], and
+//! [`MAX_INTENTS_PER_SIGNER`] per signer, whose committed balance must cover what its pooled
+//! intents move (at least one unit each). Deadlines may be at most [`MAX_INTENT_HORIZON`]
+//! blocks ahead.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, RwLock};
 
-use boing_primitives::{Hash, SignedIntent};
+use boing_primitives::{AccountId, Hash, IntentKind, SignedIntent, NATIVE_ASSET};
+use boing_state::{IntentRegistry, StateStore};
+use tokio::sync::broadcast;
+
+/// How many dropped intents are remembered with their final status.
+pub const MAX_INTENT_HISTORY: usize = 10_000;
+
+/// Most intents the pool holds.
+pub const MAX_POOLED_INTENTS: usize = 10_000;
+
+/// Most pooled intents per signer.
+pub const MAX_INTENTS_PER_SIGNER: usize = 16;
+
+/// Furthest an intent's deadline may be past the next block.
+pub const MAX_INTENT_HORIZON: u64 = 10_000;
+
+/// Newly pooled intents buffered per subscriber; a subscriber further behind skips ahead.
+pub const INTENT_FEED_CAPACITY: usize = 256;
+
+/// What became of an intent the pool has seen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntentStatus {
+    /// In the pool, waiting for a solver.
+    Pending,
+    /// Settled on-chain.
+    Fulfilled,
+    /// Its nonce was used up by a `CancelIntent` or by another of the signer's intents.
+    Cancelled,
+    /// Its deadline passed before it was settled.
+    Expired,
+}
+
+impl IntentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntentStatus::Pending => "pending",
+            IntentStatus::Fulfilled => "fulfilled",
+            IntentStatus::Cancelled => "cancelled",
+            IntentStatus::Expired => "expired",
+        }
+    }
+}
 
 /// In-memory intent pool.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct IntentPool {
+    /// Only intents signed for this chain are accepted.
+    chain_id: u64,
     inner: Arc<RwLock<IntentPoolInner>>,
+    feed: broadcast::Sender<(Hash, SignedIntent)>,
 }
 
 #[derive(Debug, Default)]
 struct IntentPoolInner {
     intents: HashMap<[u8; 32], SignedIntent>,
+    /// Signers with a solution in the mempool, and the intent it settles.
+    claims: HashMap<AccountId, Hash>,
+    /// Dropped intents with their final status, oldest first in `history_order`.
+    history: HashMap<[u8; 32], (SignedIntent, IntentStatus)>,
+    history_order: VecDeque<[u8; 32]>,
 }
 
 impl IntentPool {
-    pub fn new() -> Self {
+    pub fn new(chain_id: u64) -> Self {
         Self {
+            chain_id,
             inner: Arc::new(RwLock::new(IntentPoolInner::default())),
+            feed: broadcast::channel(INTENT_FEED_CAPACITY).0,
         }
     }
 
-    /// Submit a signed intent. Returns intent ID (hash) if accepted.
-    pub fn submit(&self, signed: SignedIntent) -> Result<Hash, IntentPoolError> {
+    /// Submit a signed intent that the block at `next_height` could still settle, given the
+    /// committed `state`. Returns intent ID (hash) if accepted.
+    pub fn submit(&self, signed: SignedIntent, next_height: u64, state: &StateStore) -> Result<Hash, IntentPoolError> {
+        let intent = &signed.intent;
+        if intent.chain_id != self.chain_id {
+            return Err(IntentPoolError::WrongChainId {
+                expected: self.chain_id,
+                got: intent.chain_id,
+            });
+        }
         if signed.verify().is_err() {
             return Err(IntentPoolError::InvalidSignature);
         }
-        let id = signed.intent.id();
+        if intent.deadline_block < next_height {
+            return Err(IntentPoolError::Expired(intent.deadline_block));
+        }
+        if intent.deadline_block - next_height > MAX_INTENT_HORIZON {
+            return Err(IntentPoolError::DeadlineTooFar { max: next_height + MAX_INTENT_HORIZON, got: intent.deadline_block });
+        }
+        let next = state.intents().next_nonce(&intent.signer);
+        if intent.nonce < next {
+            return Err(IntentPoolError::StaleNonce { next, got: intent.nonce });
+        }
+        let id = intent.id();
         let mut inner = self.inner.write().unwrap();
-        inner.intents.insert(id.0, signed);
+        if inner.intents.contains_key(&id.0) {
+            return Err(IntentPoolError::Duplicate);
+        }
+        if inner.intents.values().any(|s| s.intent.signer == intent.signer && s.intent.nonce == intent.nonce) {
+            return Err(IntentPoolError::NonceInUse(intent.nonce));
+        }
+        let pooled: Vec<_> = inner.intents.values().filter(|s| s.intent.signer == intent.signer).collect();
+        if pooled.len() >= MAX_INTENTS_PER_SIGNER {
+            return Err(IntentPoolError::SignerLimit(MAX_INTENTS_PER_SIGNER));
+        }
+        let needed = pooled
+            .iter()
+            .fold(intent_cost(&intent.kind), |sum, s| sum.saturating_add(intent_cost(&s.intent.kind)));
+        let balance = state.get(&intent.signer).map_or(0, |a| a.balance);
+        if needed > balance {
+            return Err(IntentPoolError::InsufficientBalance { needed, balance });
+        }
+        if inner.intents.len() >= MAX_POOLED_INTENTS {
+            return Err(IntentPoolError::PoolFull);
+        }
+        inner.intents.insert(id.0, signed.clone());
+        // No subscribers is not an error.
+        let _ = self.feed.send((id, signed));
         Ok(id)
     }
 
+    /// Receive every intent pooled from now on, with its id.
+    pub fn subscribe(&self) -> broadcast::Receiver<(Hash, SignedIntent)> {
+        self.feed.subscribe()
+    }
+
+    pub fn get(&self, id: &Hash) -> Option<SignedIntent> {
+        self.inner.read().unwrap().intents.get(&id.0).cloned()
+    }
+
+    /// A pooled or recently dropped intent with its status.
+    pub fn lookup(&self, id: &Hash) -> Option<(SignedIntent, IntentStatus)> {
+        let inner = self.inner.read().unwrap();
+        match inner.intents.get(&id.0) {
+            Some(signed) => Some((signed.clone(), IntentStatus::Pending)),
+            None => inner.history.get(&id.0).cloned(),
+        }
+    }
+
+    /// Pooled intents with their ids, soonest deadline first (then by id).
+    pub fn pending(&self) -> Vec<(Hash, SignedIntent)> {
+        let inner = self.inner.read().unwrap();
+        let mut out: Vec<_> = inner.intents.iter().map(|(id, s)| (Hash(*id), s.clone())).collect();
+        out.sort_by_key(|(id, s)| (s.intent.deadline_block, id.0));
+        out
+    }
+
+    /// Reserve the pooled intent `id` for a solution. A signer's intents are settled one
+    /// block at a time: settling one uses up the nonces of the others below it, so a second
+    /// solution in the same block could fail it. Claims are released on every prune.
+    pub fn claim(&self, id: &Hash) -> Result<(), IntentPoolError> {
+        let mut inner = self.inner.write().unwrap();
+        let signer = inner.intents.get(&id.0).ok_or(IntentPoolError::Unknown)?.intent.signer;
+        if inner.claims.contains_key(&signer) {
+            return Err(IntentPoolError::SolutionPending);
+        }
+        inner.claims.insert(signer, *id);
+        Ok(())
+    }
+
+    /// Release a claim taken by [`claim`](Self::claim) whose solution was not submitted.
+    pub fn release(&self, id: &Hash) {
+        self.inner.write().unwrap().claims.retain(|_, claimed| claimed != id);
+    }
+
+    /// Drop intents the block at `next_height` can no longer settle: fulfilled or cancelled
+    /// according to `registry`, or past their deadline. Returns how many were dropped.
+    pub fn prune(&self, next_height: u64, registry: &IntentRegistry) -> usize {
+        let mut inner = self.inner.write().unwrap();
+        inner.claims.clear();
+        let mut dropped = Vec::new();
+        inner.intents.retain(|id, s| {
+            let status = if registry.is_fulfilled(&Hash(*id)) {
+                IntentStatus::Fulfilled
+            } else if s.intent.nonce < registry.next_nonce(&s.intent.signer) {
+                IntentStatus::Cancelled
+            } else if s.intent.deadline_block < next_height {
+                IntentStatus::Expired
+            } else {
+                return true;
+            };
+            dropped.push((*id, s.clone(), status));
+            false
+        });
+        let count = dropped.len();
+        dropped.sort_by_key(|(id, _, _)| *id);
+        for (id, signed, status) in dropped {
+            inner.history.insert(id, (signed, status));
+            inner.history_order.push_back(id);
+        }
+        while inner.history_order.len() > MAX_INTENT_HISTORY {
+            if let Some(oldest) = inner.history_order.pop_front() {
+                inner.history.remove(&oldest);
+            }
+        }
+        count
+    }
+
     pub fn len(&self) -> usize {
         self.inner.read().unwrap().intents.len()
     }
@@ -44,8 +223,57 @@ impl IntentPool {
     }
 }
 
+/// Native amount an intent moves out of its signer's balance, at least one unit so every
+/// pooled intent needs a funded signer.
+fn intent_cost(kind: &IntentKind) -> u128 {
+    let amount = match kind {
+        IntentKind::Transfer { amount, .. } => *amount,
+        IntentKind::Swap { from_asset, amount, .. } if *from_asset == NATIVE_ASSET => *amount,
+        _ => 0,
+    };
+    amount.max(1)
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum IntentPoolError {
     #[error("Invalid intent signature")]
     InvalidSignature,
+    #[error("Wrong chain id: expected {expected}, got {got}")]
+    WrongChainId { expected: u64, got: u64 },
+    #[error("Intent expired at block {0}")]
+    Expired(u64),
+    #[error("Intent nonce {got} is stale; the signer's next intent nonce is {next}")]
+    StaleNonce { next: u64, got: u64 },
+    #[error("Intent already in the pool")]
+    Duplicate,
+    #[error("Another pooled intent from this signer uses nonce {0}")]
+    NonceInUse(u64),
+    #[error("Intent deadline {got} is past the latest accepted deadline {max}")]
+    DeadlineTooFar { max: u64, got: u64 },
+    #[error("Signer already has {0} intents in the pool")]
+    SignerLimit(usize),
+    #[error("Signer balance {balance} does not cover the {needed} its pooled intents would move")]
+    InsufficientBalance { needed: u128, balance: u128 },
+    #[error("Intent pool is full")]
+    PoolFull,
+    #[error("Intent is not in the pool (unknown, expired, cancelled or already fulfilled)")]
+    Unknown,
+    #[error("A solution for this signer's intents is already pending; retry after the next block")]
+    SolutionPending,
+}
+
+impl IntentPoolError {
+    /// Whether a gossiped intent rejected with this error is the relaying peer's fault:
+    /// one no honest node would have relayed. Stale nonces, expiry and duplicates can be
+    /// overtaken by a block or another peer in transit, and a full pool is ours to bear.
+    pub fn is_invalid_gossip(&self) -> bool {
+        matches!(
+            self,
+            IntentPoolError::InvalidSignature
+                | IntentPoolError::WrongChainId { .. }
+                | IntentPoolError::DeadlineTooFar { .. }
+                | IntentPoolError::SignerLimit(_)
+                | IntentPoolError::InsufficientBalance { .. }
+        )
+    }
 }
@@ -9,8 +9,13 @@ pub mod block_producer;
 pub mod chain;
 pub use node::ChainBlockProvider;
 pub mod dapp_registry;
+pub mod epoch;
+pub mod genesis;
 pub mod intent_pool;
 pub mod mempool;
 pub mod node;
+pub mod rewards;
 pub mod rpc;
 pub mod security;
+pub mod signaling;
+pub mod sync;
@@ -5,17 +5,17 @@
 use std::sync::Arc;
 use std::time::Duration;
 
-use boing_p2p::BlockRequest;
+use boing_p2p::{BootnodeEntry, P2pConfig, PeerDiscoveryConfig};
 use clap::Parser;
 use rand::seq::SliceRandom;
 use tokio::sync::RwLock;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use boing_primitives::{Account, AccountState};
+use boing_node::genesis::{self, GenesisConfig};
 use boing_node::{faucet, node, rpc, security};
-use boing_tokenomics::BLOCK_TIME_SECS;
 
-const SYNC_INTERVAL_SECS: u64 = 2;
+const SYNC_INTERVAL_MS: u64 = 500;
 
 #[derive(Parser)]
 #[command(name = "boing-node")]
@@ -32,10 +32,24 @@ struct Args {
     #[arg(long)]
     p2p_listen: Option<String>,
 
-    /// Comma-separated bootnode multiaddrs to dial on startup (e.g. /ip4/1.2.3.4/tcp/4001). Requires --p2p_listen.
+    /// Comma-separated bootnode multiaddrs to dial on startup (e.g. /ip4/1.2.3.4/tcp/4001/p2p/<peer_id>).
+    /// They also seed the Kademlia DHT when they include a peer id. Requires --p2p_listen.
     #[arg(long)]
     bootnodes: Option<String>,
 
+    /// Most peers to stay connected to (default 50).
+    #[arg(long)]
+    max_peers: Option<u32>,
+
+    /// Path to the libp2p node key (32 bytes, hex). Generated if missing. Defaults to
+    /// <data_dir>/node_key, so the PeerId survives restarts.
+    #[arg(long)]
+    node_key: Option<String>,
+
+    /// Derive the libp2p node key from the validator key instead of a key file.
+    #[arg(long, conflicts_with = "node_key")]
+    node_key_from_validator: bool,
+
     /// Enable testnet faucet (boing_faucetRequest). Do not use on mainnet.
     #[arg(long)]
     faucet_enable: bool,
@@ -43,6 +57,25 @@ struct Args {
     /// Data directory for chain and state persistence
     #[arg(long, default_value = "./data")]
     data_dir: String,
+
+    /// Path to genesis.json (chain id, allocations, validator set). Omit for a single-validator dev chain.
+    #[arg(long)]
+    genesis: Option<String>,
+
+    /// Path to the validator's Ed25519 secret key (32 bytes, hex). Required with --validator
+    /// and --genesis; the dev chain uses a built-in dev key.
+    #[arg(long)]
+    validator_key: Option<String>,
+}
+
+/// Read a hex-encoded 32-byte Ed25519 secret key.
+fn load_signing_key(path: &str) -> anyhow::Result<ed25519_dalek::SigningKey> {
+    let text = std::fs::read_to_string(path)?;
+    let bytes = hex::decode(text.trim().trim_start_matches("0x"))?;
+    let seed: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("validator key must be 32 bytes"))?;
+    Ok(ed25519_dalek::SigningKey::from_bytes(&seed))
 }
 
 #[tokio::main]
@@ -55,80 +88,93 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     let args = Args::parse();
+    let genesis = match &args.genesis {
+        Some(path) => GenesisConfig::load(path)
+            .map_err(|e| anyhow::anyhow!("Genesis {}: {}", path, e))?,
+        None => GenesisConfig::dev(),
+    };
+    let block_time_secs = genesis.params.block_time_secs;
+    let validator_key = match (&args.validator_key, args.validator, &args.genesis) {
+        (Some(path), _, _) => Some(
+            load_signing_key(path).map_err(|e| anyhow::anyhow!("Validator key {}: {}", path, e))?,
+        ),
+        (None, true, None) => Some(genesis::dev_validator_key()),
+        (None, true, Some(_)) => anyhow::bail!("--validator with --genesis requires --validator-key"),
+        (None, false, _) => None,
+    };
     let node = match &args.p2p_listen {
         Some(addr) => {
-            let (n, mut p2p_rx) = node::BoingNode::with_p2p(addr, Some(&args.data_dir))
+            // The P2P node dials the bootnodes, seeds its DHT with them, and queries the
+            // DHT for more peers while it has fewer than the minimum.
+            let mut discovery = PeerDiscoveryConfig::default_mainnet();
+            discovery.bootnodes = args
+                .bootnodes
+                .iter()
+                .flat_map(|s| s.split(','))
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|addr| BootnodeEntry { multiaddr: addr.to_string(), peer_id: None })
+                .collect();
+            if let Some(max) = args.max_peers {
+                discovery.max_peers = max;
+            }
+            let node_key = match (&validator_key, args.node_key_from_validator) {
+                (Some(key), true) => boing_p2p::node_key_from_validator_key(&key.to_bytes()),
+                (None, true) => anyhow::bail!("--node-key-from-validator requires a validator key"),
+                (_, false) => {
+                    let path = args.node_key.clone().unwrap_or_else(|| {
+                        std::path::Path::new(&args.data_dir).join(boing_p2p::NODE_KEY_FILE).display().to_string()
+                    });
+                    boing_p2p::load_or_generate_node_key(&path)
+                        .map_err(|e| anyhow::anyhow!("Node key {}: {}", path, e))?
+                }
+            };
+            let config = P2pConfig::new(addr, genesis.hash()?).with_discovery(discovery).with_keypair(node_key);
+            let (mut n, mut p2p_rx) = node::BoingNode::with_p2p(config, genesis, Some(&args.data_dir))
                 .map_err(|e| anyhow::anyhow!("P2P init: {}", e))?;
+            if let Some(key) = validator_key {
+                n = n.with_validator_key(key);
+            }
             let p2p = n.p2p.clone();
             let node = Arc::new(RwLock::new(n));
             let node_clone = node.clone();
             let p2p_clone = p2p.clone();
 
-            // Dial bootnodes after a short delay so our listener is up
-            if let Some(ref bootnodes_str) = args.bootnodes {
-                let p2p_boot = p2p.clone();
-                let addrs: Vec<String> = bootnodes_str
-                    .split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-                if !addrs.is_empty() {
-                    tokio::spawn(async move {
-                        tokio::time::sleep(Duration::from_secs(1)).await;
-                        for addr in &addrs {
-                            if let Err(e) = p2p_boot.dial(addr) {
-                                tracing::warn!("Bootnode dial send failed for {}: {}", addr, e);
-                            } else {
-                                tracing::info!("Bootnode: dialing {}", addr);
-                            }
-                        }
-                    });
-                }
-            }
-
             tokio::spawn(async move {
                 while let Some(ev) = p2p_rx.recv().await {
-                    match ev {
-                        boing_p2p::P2pEvent::BlockReceived(block)
-                        | boing_p2p::P2pEvent::BlockFetched(block) => {
-                            let mut n = node_clone.write().await;
-                            if let Err(e) = n.import_network_block(&block) {
-                                tracing::debug!("P2P: block import failed: {}", e);
-                            } else {
-                                tracing::info!("P2P: imported block height={}", block.header.height);
-                            }
-                        }
-                        boing_p2p::P2pEvent::TransactionReceived(_tx) => {
-                            // Could insert into mempool
-                        }
-                    }
+                    node_clone.write().await.handle_p2p_event(ev);
                 }
             });
 
             let node_sync = node.clone();
             tokio::spawn(async move {
-                let mut interval =
-                    tokio::time::interval(Duration::from_secs(SYNC_INTERVAL_SECS));
+                let mut interval = tokio::time::interval(Duration::from_millis(SYNC_INTERVAL_MS));
                 loop {
                     interval.tick().await;
-                    let peers = p2p_clone.connected_peers().await;
-                    let peer = match peers.choose(&mut rand::rngs::OsRng) {
-                        Some(p) => *p,
-                        None => continue,
-                    };
-                    let height = node_sync.read().await.chain.height();
-                    let next_height = height + 1;
-                    if let Err(e) = p2p_clone.request_block(peer, BlockRequest::ByHeight(next_height))
-                    {
-                        tracing::debug!("P2P: sync request failed: {}", e);
+                    let mut peers = p2p_clone.connected_peers().await;
+                    peers.shuffle(&mut rand::rngs::OsRng);
+                    let requests = node_sync
+                        .write()
+                        .await
+                        .sync_requests(peers.len(), std::time::Instant::now());
+                    // Spread ranges over peers so they download in parallel.
+                    for (peer, request) in peers.iter().cycle().zip(requests) {
+                        if let Err(e) = p2p_clone.request_block(*peer, request) {
+                            tracing::debug!("P2P: sync request failed: {}", e);
+                        }
                     }
                 }
             });
             node
         }
-        None => Arc::new(RwLock::new(
-            node::BoingNode::with_data_dir(Some(&args.data_dir)).expect("node init"),
-        )),
+        None => {
+            let mut n = node::BoingNode::with_data_dir(genesis, Some(&args.data_dir))
+                .map_err(|e| anyhow::anyhow!("Node init: {}", e))?;
+            if let Some(key) = validator_key {
+                n = n.with_validator_key(key);
+            }
+            Arc::new(RwLock::new(n))
+        }
     };
 
     // Testnet faucet: ensure faucet account exists and pass signer to RPC
@@ -153,13 +199,30 @@ async fn main() -> anyhow::Result<()> {
         None
     };
 
+    {
+        let n = node.read().await;
+        tracing::info!(
+            "Genesis: chain_id={} hash={} validators={}",
+            n.genesis.chain_id,
+            n.genesis_hash,
+            n.genesis.validators.len()
+        );
+        if let Some(producer) = &n.producer {
+            if !n.genesis.validators.contains(&producer.proposer()) {
+                tracing::warn!("Validator key {:?} is not in the genesis validator set", producer.proposer());
+            }
+        }
+    }
+
     let rpc_addr = format!("0.0.0.0:{}", args.rpc_port);
+    let peer_id = node.read().await.p2p.local_peer_id();
     tracing::info!(
-        "Boing node initialized. validator={} rpc={} data_dir={} faucet={}",
+        "Boing node initialized. validator={} rpc={} data_dir={} faucet={} peer_id={}",
         args.validator,
         rpc_addr,
         args.data_dir,
-        args.faucet_enable
+        args.faucet_enable,
+        peer_id.map_or("none".to_string(), |p| p.to_string())
     );
 
     let rate_limit = security::RateLimitConfig::default_mainnet();
@@ -170,12 +233,13 @@ async fn main() -> anyhow::Result<()> {
     if args.validator {
         let node_clone = node.clone();
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(BLOCK_TIME_SECS));
+            let mut interval = tokio::time::interval(Duration::from_secs(block_time_secs));
             loop {
                 interval.tick().await;
                 let mut n = node_clone.write().await;
-                if let Some(h) = n.produce_block_if_ready() {
-                    tracing::info!("Produced block: {:?}", h);
+                n.on_tick(std::time::Instant::now());
+                if let Some(h) = n.propose_if_leader() {
+                    tracing::info!("Proposed block: {:?}", h);
                 }
             }
         });
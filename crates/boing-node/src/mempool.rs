@@ -1,15 +1,19 @@
 //! Transaction mempool — pending transactions awaiting inclusion in a block.
 //! Runs protocol QA on ContractDeploy before accepting; rejects with structured reason when QA fails.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::sync::Mutex;
 
-use boing_primitives::{AccountId, SignedTransaction, TransactionPayload};
+use boing_primitives::{AccountId, Hash, SignedTransaction, Transaction, TransactionPayload};
 use boing_qa::{check_contract_deploy, RuleRegistry, QaReject, QaResult};
 
+/// Transaction ids remembered by the seen-cache.
+pub const SEEN_CACHE_SIZE: usize = 16_384;
+
 /// In-memory mempool. Tracks pending transactions by sender nonce.
-#[derive(Default)]
 pub struct Mempool {
+    /// Only transactions signed for this chain are accepted.
+    chain_id: u64,
     inner: Mutex<MempoolInner>,
 }
 
@@ -26,15 +30,57 @@ struct MempoolInner {
     by_id: HashMap<boing_primitives::Hash, ()>,
     /// Count of pending txs.
     len: usize,
+    /// Ids of transactions recently submitted or gossiped to us, pending or not.
+    seen: SeenCache,
+}
+
+/// Bounded set of transaction ids; the oldest is forgotten first.
+#[derive(Default)]
+struct SeenCache {
+    ids: HashSet<Hash>,
+    order: VecDeque<Hash>,
+}
+
+impl SeenCache {
+    fn contains(&self, id: &Hash) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// Remember `id`; false if it was already remembered.
+    fn insert(&mut self, id: Hash) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > SEEN_CACHE_SIZE {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
 }
 
 impl Mempool {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            inner: Mutex::new(MempoolInner::default()),
+        }
     }
 
-    /// Insert a signed transaction. Rejects duplicates, invalid nonces, and ContractDeploy that fail QA.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Insert a signed transaction. Rejects other-chain txs, duplicates, invalid nonces, and ContractDeploy that fail QA.
     pub fn insert(&self, signed: SignedTransaction) -> Result<(), MempoolError> {
+        if signed.tx.chain_id != self.chain_id {
+            return Err(MempoolError::WrongChainId {
+                expected: self.chain_id,
+                got: signed.tx.chain_id,
+            });
+        }
         signed.verify().map_err(|_| MempoolError::InvalidSignature)?;
         if let TransactionPayload::ContractDeploy { bytecode } = &signed.tx.payload {
             let registry = default_qa_registry();
@@ -94,6 +140,46 @@ impl Mempool {
         }
     }
 
+    /// Drop pending transactions made obsolete by a committed block: for each committed tx,
+    /// every pending tx from the same sender with a nonce at or below it.
+    pub fn prune_committed(&self, committed: &[Transaction]) {
+        let mut inner = self.inner.lock().unwrap();
+        for tx in committed {
+            let Some(by_nonce) = inner.by_sender.get_mut(&tx.sender) else {
+                continue;
+            };
+            let keep = by_nonce.split_off(&(tx.nonce + 1));
+            let stale = std::mem::replace(by_nonce, keep);
+            if by_nonce.is_empty() {
+                inner.by_sender.remove(&tx.sender);
+            }
+            for signed in stale.into_values() {
+                inner.by_id.remove(&signed.tx.id());
+                inner.len = inner.len.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Nonce for the next transaction from `sender`, whose committed nonce is
+    /// `state_nonce`: one past its highest pending transaction, if any.
+    pub fn next_nonce(&self, sender: &AccountId, state_nonce: u64) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        let pending = inner.by_sender.get(sender).and_then(|by_nonce| by_nonce.keys().next_back());
+        pending.map_or(state_nonce, |highest| state_nonce.max(highest + 1))
+    }
+
+    /// Record a transaction id in the seen-cache. Returns false if it was seen before, so a
+    /// gossiped transaction is only validated and relayed once, even after it has left the
+    /// pool.
+    pub fn mark_seen(&self, id: Hash) -> bool {
+        self.inner.lock().unwrap().seen.insert(id)
+    }
+
+    /// Whether a transaction id is in the seen-cache.
+    pub fn is_seen(&self, id: &Hash) -> bool {
+        self.inner.lock().unwrap().seen.contains(id)
+    }
+
     /// Number of pending transactions.
     pub fn len(&self) -> usize {
         self.inner.lock().unwrap().len
@@ -110,6 +196,12 @@ pub enum MempoolError {
     Duplicate,
     #[error("Invalid signature")]
     InvalidSignature,
+    #[error("Nonce too low: account nonce is {expected}, got {got}")]
+    NonceTooLow { expected: u64, got: u64 },
+    #[error("Wrong chain id: expected {expected}, got {got}")]
+    WrongChainId { expected: u64, got: u64 },
+    #[error("Balance is reserved: {locked} is locked in open signaling offers")]
+    SignalingDepositLocked { locked: u128 },
     /// Protocol QA rejected this deployment; rule_id and message give user feedback.
     #[error("QA rejected: {0}")]
     QaRejected(QaReject),
@@ -1,19 +1,30 @@
 //! Boing node — wires consensus, execution, state, and P2P together.
 
-use boing_primitives::{Account, AccountId, AccountState, Block, Hash, SignedTransaction};
-use boing_consensus::ConsensusEngine;
-use boing_execution::{BlockExecutor, TransactionScheduler, Vm};
-use boing_p2p::{P2pEvent, P2pNode};
-use boing_state::StateStore;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+use boing_primitives::{
+    Block, Hash, QuorumCertificate, SignedTransaction, TimeoutCertificate, TimeoutVote,
+    TransactionPayload, Vote,
+};
+use boing_consensus::{ConsensusEngine, ConsensusError, Pacemaker};
+use boing_execution::{BlockContext, BlockExecutor, TransactionScheduler, Vm, VmError};
+use boing_p2p::{BlockRequest, MessageAcceptance, P2pConfig, P2pEvent, P2pNode};
+use boing_state::{StateCheckpoint, StateStore};
+use ed25519_dalek::SigningKey;
 use tokio::sync::mpsc;
 
 use crate::block_producer::BlockProducer;
-use crate::block_validation::import_block;
-use crate::chain::ChainState;
+use crate::block_validation::{validate_and_execute_block, validate_proposal, BlockValidationError};
+use crate::chain::{ChainError, ChainState};
 use crate::dapp_registry::DappRegistry;
-use crate::intent_pool::IntentPool;
+use crate::epoch;
+use crate::genesis::{dev_validator_key, GenesisConfig, GenesisError};
+use crate::intent_pool::{IntentPool, IntentPoolError};
 use crate::mempool::{Mempool, MempoolError};
 use crate::persistence::{Persistence, PersistenceError};
+use crate::signaling::{SignalingError, SignalingStore};
+use crate::sync::{BlockSync, SyncError, SyncStatus};
 
 /// Wraps ChainState to implement BlockProvider for P2P block requests.
 pub struct ChainBlockProvider(pub ChainState);
@@ -27,65 +38,129 @@ impl boing_p2p::BlockProvider for ChainBlockProvider {
     }
 }
 
+/// Max votes buffered for proposals we have not received yet.
+const MAX_EARLY_VOTES: usize = 256;
+
+/// Proposed block in the consensus pipeline, not yet committed.
+struct PendingProposal {
+    block: Block,
+    /// State after executing the block; children execute on it, and it becomes our
+    /// state on commit.
+    state: StateStore,
+    /// Mempool txs drained by us as leader; re-inserted if the proposal is abandoned.
+    txs: Vec<SignedTransaction>,
+}
+
 /// Full Boing node.
 #[allow(dead_code)]
 pub struct BoingNode {
     pub chain: ChainState,
     pub consensus: ConsensusEngine,
+    /// View timer driving timeouts and view changes.
+    pub pacemaker: Pacemaker,
     pub state: StateStore,
     pub executor: BlockExecutor,
-    pub producer: BlockProducer,
+    /// Block producer; None unless the node runs with a validator key.
+    pub producer: Option<BlockProducer>,
     pub vm: Vm,
     pub scheduler: TransactionScheduler,
     pub mempool: Mempool,
     pub p2p: P2pNode,
     pub dapp_registry: DappRegistry,
     pub intent_pool: IntentPool,
+    /// WebRTC offers, answers and ICE candidates awaiting their recipients.
+    pub signaling: SignalingStore,
+    /// Header-first catch-up sync, driven by the node's sync loop.
+    pub sync: BlockSync,
     /// Persistence backend; None for in-memory only (e.g. tests).
     pub persistence: Option<Persistence>,
+    /// Genesis this node was started from.
+    pub genesis: GenesisConfig,
+    /// Hash of the genesis block (network identity).
+    pub genesis_hash: Hash,
+    /// Uncommitted proposals by hash (networked consensus).
+    pipeline: HashMap<Hash, PendingProposal>,
+    /// Proposal received before its parent, or before the timeout certificate that
+    /// opened its view.
+    early_proposal: Option<Block>,
+    /// Votes received before the proposal they refer to, by block hash, with the view we
+    /// were in when the first of them arrived.
+    early_votes: HashMap<Hash, (u64, Vec<Vote>)>,
+    /// State after each canonical block from the finalized one up to the head (exclusive),
+    /// by height; a reorg rolls back to the common ancestor's.
+    checkpoints: BTreeMap<u64, StateCheckpoint>,
 }
 
 impl BoingNode {
-    /// Create a node with inert P2P (for tests).
+    /// Create a node from the default dev genesis with inert P2P (for tests).
+    /// Produces blocks with the dev validator key.
     pub fn new() -> Self {
-        let proposer = AccountId([1u8; 32]);
-        let genesis = ChainState::genesis(proposer);
-        let chain = ChainState::from_genesis(genesis.clone());
-        let mut consensus = ConsensusEngine::single_validator(proposer);
-        let _ = consensus.propose_and_commit(genesis);
+        Self::from_genesis(GenesisConfig::dev())
+            .expect("dev genesis is valid")
+            .with_validator_key(dev_validator_key())
+    }
 
-        let mut state = StateStore::new();
-        state.insert(Account {
-            id: proposer,
-            state: AccountState {
-                balance: 1_000_000,
-                nonce: 0,
-                stake: 0,
-            },
-        });
+    /// Produce blocks as the validator owning `signing_key`.
+    pub fn with_validator_key(mut self, signing_key: SigningKey) -> Self {
+        self.producer = Some(
+            BlockProducer::new(signing_key).with_max_txs(self.genesis.params.max_txs_per_block),
+        );
+        self
+    }
 
-        Self {
+    /// Create an in-memory node with inert P2P from a genesis configuration.
+    pub fn from_genesis(genesis: GenesisConfig) -> Result<Self, GenesisError> {
+        genesis.validate()?;
+        let (state, genesis_block) = genesis.build()?;
+        let genesis_hash = genesis_block.hash();
+        let mut consensus = ConsensusEngine::new(genesis.validator_set());
+        consensus.sync_to(&genesis_block);
+        let chain = ChainState::from_genesis(genesis_block);
+        let pacemaker = Pacemaker::new(
+            Duration::from_millis(genesis.params.round_timeout_ms),
+            Instant::now(),
+        );
+
+        Ok(Self {
             chain,
             consensus,
+            pacemaker,
             state,
-            executor: BlockExecutor::new(),
-            producer: BlockProducer::new(proposer).with_max_txs(100),
-            vm: Vm::new(),
+            executor: BlockExecutor::new()
+                .with_unbonding_delay(genesis.params.unbonding_delay)
+                .with_slashing(genesis.params.slashing())
+                .with_liveness(genesis.params.liveness())
+                .with_relays(genesis.params.relays()),
+            producer: None,
+            vm: Vm::new()
+                .with_unbonding_delay(genesis.params.unbonding_delay)
+                .with_slashing(genesis.params.slashing())
+                .with_relays(genesis.params.relays()),
             scheduler: TransactionScheduler::new(),
-            mempool: Mempool::new(),
+            mempool: Mempool::new(genesis.chain_id),
             p2p: P2pNode::default(),
             dapp_registry: DappRegistry::new(),
-            intent_pool: IntentPool::new(),
+            intent_pool: IntentPool::new(genesis.chain_id),
+            signaling: SignalingStore::new(genesis.chain_id),
+            sync: BlockSync::new(),
             persistence: None,
-        }
+            genesis,
+            genesis_hash,
+            pipeline: HashMap::new(),
+            early_proposal: None,
+            early_votes: HashMap::new(),
+            checkpoints: BTreeMap::new(),
+        })
     }
 
     /// Create a node with optional data directory for persistence.
-    /// If data_dir is Some and contains persisted data, loads from disk. Otherwise starts fresh.
+    /// If data_dir is Some and contains persisted data, loads from disk (the persisted
+    /// genesis block must match `genesis`). Otherwise starts fresh and writes genesis to disk.
     pub fn with_data_dir(
+        genesis: GenesisConfig,
         data_dir: Option<impl AsRef<std::path::Path>>,
-    ) -> Result<Self, PersistenceError> {
-        let mut node = Self::new();
+    ) -> Result<Self, NodeError> {
+        let mut node = Self::from_genesis(genesis)?;
 
         if let Some(ref path) = data_dir {
             let path = path.as_ref();
@@ -93,13 +168,34 @@ impl BoingNode {
 
             if persistence.has_persisted_data() {
                 if let Some(chain) = persistence.load_chain()? {
+                    let found = chain
+                        .get_block_by_height(0)
+                        .map(|b| b.hash())
+                        .unwrap_or(Hash::ZERO);
+                    if found != node.genesis_hash {
+                        return Err(NodeError::GenesisMismatch {
+                            expected: node.genesis_hash,
+                            found,
+                        });
+                    }
                     node.chain = chain;
                 }
                 if let Some(state) = persistence.load_state()? {
                     node.state = state;
                 }
-                let height = node.chain.height();
-                node.consensus.sync_round(height);
+                if let Some(tip) = node.chain.get_block_by_hash(&node.chain.latest_hash()) {
+                    node.consensus.sync_to(&tip);
+                }
+                if let Some(epochs) = persistence.load_epochs()? {
+                    node.consensus.restore_epochs(epochs);
+                }
+                if let Some(safety) = persistence.load_safety_state()? {
+                    node.consensus.restore_safety_state(safety);
+                }
+            } else if let Some(genesis_block) = node.chain.get_block_by_height(0) {
+                persistence.save_block(&genesis_block)?;
+                persistence.save_chain_meta(0, node.genesis_hash, (0, node.genesis_hash))?;
+                persistence.save_state(&node.state)?;
             }
 
             node.persistence = Some(persistence);
@@ -109,25 +205,49 @@ impl BoingNode {
     }
 
     /// Create a node with live P2P. Returns the node and a receiver for incoming blocks/txs.
-    /// Enables block request/response so peers can fetch blocks from us.
-    /// When data_dir is Some, enables disk persistence.
+    /// Enables block request/response so peers can fetch blocks from us; `config` sets the
+    /// listen address, bootnodes, DHT, peer limits and identity key (its genesis hash is
+    /// replaced by ours). When data_dir is Some, enables disk persistence.
     pub fn with_p2p(
-        p2p_listen: &str,
+        config: P2pConfig,
+        genesis: GenesisConfig,
         data_dir: Option<impl AsRef<std::path::Path>>,
     ) -> Result<(Self, mpsc::Receiver<P2pEvent>), boing_p2p::P2pError> {
-        let mut node = Self::with_data_dir(data_dir).map_err(|e| boing_p2p::P2pError::Network(e.to_string()))?;
+        let mut node = Self::with_data_dir(genesis, data_dir).map_err(|e| boing_p2p::P2pError::Network(e.to_string()))?;
         let chain = node.chain.clone();
-        let (p2p, event_rx) = P2pNode::new(p2p_listen, Some(std::sync::Arc::new(ChainBlockProvider(chain))))?;
+        let (p2p, event_rx) = P2pNode::new(
+            P2pConfig { genesis_hash: node.genesis_hash, ..config },
+            Some(std::sync::Arc::new(ChainBlockProvider(chain))),
+        )?;
         node.p2p = p2p;
         Ok((node, event_rx))
     }
 
+    /// After committing an epoch boundary block, schedule the validator set derived from
+    /// stake in the new state for the epoch after next, seeded with the block's VRF output.
+    fn advance_epoch(&mut self, header: &boing_primitives::BlockHeader) {
+        let (params, height) = (&self.genesis.params, header.height);
+        if !epoch::is_boundary(height, params) {
+            return;
+        }
+        let Some(set) = epoch::validator_set_from_stake(&self.state, params, header.vrf.seed()) else {
+            tracing::info!("Epoch: no account meets the minimum stake at height {}; validator set unchanged", height);
+            return;
+        };
+        self.consensus.schedule_epoch(epoch::activation_height(height, params), set);
+        if let Some(ref p) = self.persistence {
+            if let Err(e) = p.save_epochs(self.consensus.epochs()) {
+                tracing::warn!("Persistence: failed to save validator sets: {}", e);
+            }
+        }
+    }
+
     fn persist_block_and_state(&self, block: &boing_primitives::Block) {
         if let Some(ref p) = self.persistence {
             if let Err(e) = p.save_block(block) {
                 tracing::warn!("Persistence: failed to save block: {}", e);
             }
-            if let Err(e) = p.save_chain_meta(block.header.height, block.hash()) {
+            if let Err(e) = p.save_chain_meta(self.chain.height(), self.chain.latest_hash(), self.chain.finalized()) {
                 tracing::warn!("Persistence: failed to save chain meta: {}", e);
             }
             if let Err(e) = p.save_state(&self.state) {
@@ -136,38 +256,844 @@ impl BoingNode {
         }
     }
 
-    /// Import a block from the network if it chains to our tip.
-    pub fn import_network_block(&mut self, block: &boing_primitives::Block) -> Result<(), crate::block_validation::BlockValidationError> {
-        let (latest_hash, height) = (self.chain.latest_hash(), self.chain.height());
-        let new_state = import_block(
+    /// Persist the consensus safety state. Returns false if it could not be written; the
+    /// caller must then not send the vote it was about to sign.
+    fn persist_safety(&self) -> bool {
+        match &self.persistence {
+            Some(p) => match p.save_safety_state(self.consensus.safety_state()) {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!("Persistence: failed to save safety state: {}", e);
+                    false
+                }
+            },
+            None => true,
+        }
+    }
+
+    /// Import a certified block from the network. It may extend any known block above the
+    /// finalized one; the node then follows fork choice, rolling state back to the fork
+    /// point and executing the winning branch if the head moves to another branch.
+    /// Transactions of reverted blocks are not returned to the mempool (blocks do not carry
+    /// their signatures); their senders resubmit.
+    pub fn import_network_block(&mut self, block: &boing_primitives::Block) -> Result<(), BlockValidationError> {
+        let hash = block.hash();
+        if self.chain.get_block_by_hash(&hash).is_some() {
+            return Err(ChainError::AlreadyKnown.into());
+        }
+        // Check the certificates before keeping a block we may not execute yet; finality is
+        // derived from them.
+        if !block.justify_matches() {
+            return Err(BlockValidationError::JustifyMismatch);
+        }
+        let qc = block.qc.as_ref().ok_or(BlockValidationError::MissingQuorumCertificate)?;
+        qc.verify(&hash, self.consensus.validators_at(block.header.height))?;
+        if let Some(justify) = &block.justify {
+            let parent_height = block.header.height.saturating_sub(1);
+            justify.verify(&block.header.parent_hash, self.consensus.validators_at(parent_height))?;
+        }
+        self.chain.insert(block.clone())?;
+
+        let head_before = self.chain.latest_hash();
+        let result = self.follow_fork_choice();
+        if self.chain.latest_hash() != head_before {
+            self.pacemaker.on_progress(Instant::now());
+            self.finalize_by_certificates();
+        }
+        self.pipeline.remove(&hash);
+        self.prune_pipeline();
+        self.replay_early_proposal();
+        result
+    }
+
+    /// Move the head to the fork choice, one block at a time. A block that fails execution
+    /// is dropped with its descendants and fork choice runs again (possibly back to the old
+    /// branch); its error is returned.
+    fn follow_fork_choice(&mut self) -> Result<(), BlockValidationError> {
+        let mut result = Ok(());
+        loop {
+            let best = self.chain.fork_choice();
+            if best == self.chain.latest_hash() {
+                return result;
+            }
+            let Some(reorg) = self.chain.reorg_to(&best) else {
+                return result;
+            };
+            if !reorg.retracted.is_empty() && !self.rewind_to(&reorg.ancestor, &reorg.retracted) {
+                return result;
+            }
+            for block in &reorg.enacted {
+                if let Err(e) = self.apply_block(block) {
+                    tracing::warn!("Fork choice: block {} at height {} is invalid: {}", block.hash(), block.header.height, e);
+                    self.chain.discard(&block.hash());
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Roll state back to `ancestor` from its checkpoint, undoing `retracted` (newest first).
+    /// Returns false if no checkpoint is held for it.
+    fn rewind_to(&mut self, ancestor: &Block, retracted: &[Block]) -> bool {
+        let height = ancestor.header.height;
+        let Some(checkpoint) = self.checkpoints.remove(&height) else {
+            tracing::warn!("Fork choice: no state checkpoint at height {}; not reorganizing", height);
+            return false;
+        };
+        tracing::info!("Fork choice: reorg reverts {} blocks to height {}", retracted.len(), height);
+        self.checkpoints.split_off(&height);
+        self.state.revert(checkpoint);
+        self.chain.set_head(&ancestor.hash()).expect("ancestor is canonical");
+        for block in retracted.iter().filter(|b| epoch::is_boundary(b.header.height, &self.genesis.params)) {
+            self.consensus.unschedule_epoch(epoch::activation_height(block.header.height, &self.genesis.params));
+        }
+        self.consensus.sync_to(ancestor);
+        if let Some(ref p) = self.persistence {
+            if let Err(e) = p.save_epochs(self.consensus.epochs()) {
+                tracing::warn!("Persistence: failed to save validator sets: {}", e);
+            }
+        }
+        self.persist_block_and_state(ancestor);
+        true
+    }
+
+    /// Validate and execute a certified block on the head's state and make it the head,
+    /// keeping a checkpoint of the state it replaces.
+    fn apply_block(&mut self, block: &Block) -> Result<(), BlockValidationError> {
+        let new_state = validate_and_execute_block(
             block,
-            latest_hash,
-            height,
             &self.state,
-            &self.consensus,
+            self.consensus.validators_at(block.header.height),
+            self.genesis.chain_id,
             &self.executor,
         )?;
+        self.checkpoints.insert(self.chain.height(), self.state.checkpoint());
         self.state = new_state;
-        self.chain.append(block.clone()).expect("block chains (validated by import_block)");
-        self.consensus.sync_round(block.header.height);
+        self.chain.set_head(&block.hash())?;
+        self.consensus.sync_to(block);
+        self.mempool.prune_committed(&block.transactions);
+        self.prune_intents();
+        self.prune_signaling();
+        self.advance_epoch(&block.header);
         self.persist_block_and_state(block);
         Ok(())
     }
 
-    /// Submit a signed intent for solver fulfillment.
-    pub fn submit_intent(&self, signed: boing_primitives::SignedIntent) -> Result<boing_primitives::Hash, crate::intent_pool::IntentPoolError> {
-        self.intent_pool.submit(signed)
+    /// Finalize from the commit certificates of imported blocks. By the three-chain rule,
+    /// a block is committed once it, its child, and its grandchild are certified in
+    /// consecutive views; the newest such canonical block becomes final.
+    fn finalize_by_certificates(&mut self) {
+        let (finalized, head) = (self.chain.finalized().0, self.chain.height());
+        for height in ((finalized + 1)..=head.saturating_sub(2)).rev() {
+            let chain = &self.chain;
+            let (Some(g), Some(p), Some(c)) = (
+                chain.get_block_by_height(height),
+                chain.get_block_by_height(height + 1),
+                chain.get_block_by_height(height + 2),
+            ) else {
+                continue;
+            };
+            let c_certified = c.qc.is_some()
+                || chain.get_block_by_height(height + 3).map(|b| b.justify.is_some()).unwrap_or(false);
+            let consecutive = p.header.round == g.header.round + 1 && c.header.round == p.header.round + 1;
+            if consecutive && p.justify.is_some() && c.justify.is_some() && c_certified {
+                self.finalize(height);
+                return;
+            }
+        }
+    }
+
+    /// Finalize the canonical block at `height` (if above the finalized one) and drop state
+    /// checkpoints below it.
+    fn finalize(&mut self, height: u64) {
+        if height <= self.chain.finalized().0 {
+            return;
+        }
+        let Some(block) = self.chain.get_block_by_height(height) else {
+            return;
+        };
+        if let Err(e) = self.chain.finalize(&block.hash()) {
+            tracing::warn!("Finality: cannot finalize height {}: {}", height, e);
+            return;
+        }
+        tracing::debug!("Finality: finalized block height={} hash={}", height, block.hash());
+        self.checkpoints = self.checkpoints.split_off(&height);
+        if let Some(ref p) = self.persistence {
+            if let Err(e) = p.save_chain_meta(self.chain.height(), self.chain.latest_hash(), self.chain.finalized()) {
+                tracing::warn!("Persistence: failed to save chain meta: {}", e);
+            }
+        }
+    }
+
+    /// State after the canonical block at `height`: the head's state, or the checkpoint of
+    /// a block between the finalized one and the head. None if no state is held for it
+    /// (e.g. below the head right after a restart).
+    pub fn state_at(&self, height: u64) -> Option<StateStore> {
+        if height == self.chain.height() {
+            return Some(self.state.snapshot());
+        }
+        let checkpoint = self.checkpoints.get(&height)?.clone();
+        let mut state = StateStore::new();
+        state.revert(checkpoint);
+        Some(state)
+    }
+
+    /// Apply an incoming P2P event (blocks and consensus messages). Failures are logged.
+    pub fn handle_p2p_event(&mut self, event: P2pEvent) {
+        match event {
+            P2pEvent::BlockReceived(block) | P2pEvent::BlockFetched(block) => {
+                match self.import_network_block(&block) {
+                    Ok(()) => tracing::info!("P2P: imported block height={}", block.header.height),
+                    Err(e) => tracing::debug!("P2P: block import failed: {}", e),
+                }
+            }
+            P2pEvent::HeadersFetched { peer, start, headers } => {
+                let head = self.chain_head();
+                let (consensus, params) = (&self.consensus, &self.genesis.params);
+                let validators =
+                    |height| epoch::validator_set_known(height, head.0, params).then(|| consensus.validators_at(height));
+                if let Err(e) = self.sync.on_headers(head, peer, start, headers, validators) {
+                    self.report_sync_error(e);
+                }
+            }
+            P2pEvent::BlocksFetched { peer, start, blocks } => {
+                if let Err(e) = self.sync.on_blocks(peer, start, blocks) {
+                    self.report_sync_error(e);
+                }
+                self.import_synced_blocks();
+            }
+            P2pEvent::ProposalReceived(block) => {
+                if let Err(e) = self.handle_proposal(block) {
+                    tracing::debug!("Consensus: proposal rejected: {}", e);
+                }
+            }
+            P2pEvent::VoteReceived(vote) => {
+                if let Err(e) = self.handle_vote(vote) {
+                    tracing::debug!("Consensus: vote rejected: {}", e);
+                }
+            }
+            P2pEvent::TimeoutReceived(vote) => {
+                if let Err(e) = self.handle_timeout_vote(vote) {
+                    tracing::debug!("Consensus: timeout vote rejected: {}", e);
+                }
+            }
+            P2pEvent::NewViewReceived(tc) => {
+                if let Err(e) = self.handle_new_view(&tc) {
+                    tracing::debug!("Consensus: new view rejected: {}", e);
+                }
+            }
+            P2pEvent::TransactionReceived(signed) => match self.receive_transaction(signed) {
+                Ok(()) => tracing::debug!("P2P: added gossiped tx to mempool"),
+                Err(e) => tracing::debug!("P2P: gossiped tx dropped: {}", e),
+            },
+            P2pEvent::IntentReceived(signed, message) => {
+                let acceptance = match self.receive_intent(signed) {
+                    Ok(id) => {
+                        tracing::debug!("P2P: added gossiped intent {} to the pool", id);
+                        MessageAcceptance::Accept
+                    }
+                    Err(e) if e.is_invalid_gossip() => {
+                        tracing::debug!("P2P: invalid gossiped intent from {}: {}", message.source, e);
+                        MessageAcceptance::Reject
+                    }
+                    Err(e) => {
+                        tracing::debug!("P2P: gossiped intent dropped: {}", e);
+                        MessageAcceptance::Ignore
+                    }
+                };
+                if let Err(e) = self.p2p.report_validation(message, acceptance) {
+                    tracing::debug!("P2P: intent validation report failed: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Block sync requests to send this tick, spread by the caller over `peers` peers.
+    pub fn sync_requests(&mut self, peers: usize, now: Instant) -> Vec<BlockRequest> {
+        let head = self.chain_head();
+        self.sync.next_requests(head, peers, now)
+    }
+
+    /// Sync progress; None when caught up.
+    pub fn sync_status(&self) -> Option<SyncStatus> {
+        self.sync.status(self.chain.height())
+    }
+
+    /// Import downloaded blocks that extend our head, in height order. A block that fails
+    /// validation drops the headers from its run on, and its peers are penalized.
+    fn import_synced_blocks(&mut self) {
+        let mut imported = 0;
+        while let Some((block, peer)) = self.sync.next_block(self.chain.height()) {
+            match self.import_network_block(&block) {
+                Ok(()) => imported += 1,
+                Err(BlockValidationError::Chain(ChainError::AlreadyKnown)) => {}
+                Err(e) => {
+                    tracing::warn!("Sync: block {} rejected: {}", block.header.height, e);
+                    let error = self.sync.reject_block(block.header.height, peer);
+                    self.report_sync_error(error);
+                    break;
+                }
+            }
+        }
+        if imported > 0 {
+            tracing::info!("Sync: imported {} blocks, height={}", imported, self.chain.height());
+        }
+        let head = self.chain_head();
+        self.sync.advance(head);
+    }
+
+    /// Penalize the peers behind invalid sync data.
+    fn report_sync_error(&self, error: SyncError) {
+        tracing::warn!("Sync: {} (from {:?})", error, error.peers());
+        for peer in error.peers() {
+            if let Err(e) = self.p2p.report_peer(*peer) {
+                tracing::debug!("P2P: peer report failed: {}", e);
+            }
+        }
+    }
+
+    fn chain_head(&self) -> (u64, Hash) {
+        (self.chain.height(), self.chain.latest_hash())
+    }
+
+    /// State after executing `hash`: our committed state for the tip, or a pending block's.
+    fn state_after(&self, hash: &Hash) -> Option<&StateStore> {
+        if *hash == self.chain.latest_hash() {
+            return Some(&self.state);
+        }
+        self.pipeline.get(hash).map(|p| &p.state)
+    }
+
+    /// Whether uncommitted blocks with transactions are waiting on later views to commit.
+    fn pipeline_has_txs(&self) -> bool {
+        self.pipeline.values().any(|p| !p.block.transactions.is_empty())
+    }
+
+    /// Networked consensus: if we are the view leader, build, sign, and gossip a proposal
+    /// extending the highest certified block, then vote for it. Proposes when transactions
+    /// are pending, or with an empty block while earlier transactions still need the two
+    /// following views to commit. Returns the proposed block hash.
+    pub fn propose_if_leader(&mut self) -> Option<Hash> {
+        let view = self.consensus.view();
+        if self.pipeline.values().any(|p| p.block.header.round == view) {
+            return None;
+        }
+        let (parent, _) = self.consensus.extend_target();
+        if !epoch::validator_set_known(parent.height + 1, self.chain.height(), &self.genesis.params) {
+            return None;
+        }
+        let proposed = self.producer.as_ref()?.build_block(
+            &self.mempool,
+            self.state_after(&parent.hash)?,
+            &self.executor,
+            &self.consensus,
+            self.pipeline_has_txs(),
+        )?;
+        let view_before = self.consensus.view();
+        if let Err(e) = self.consensus.propose(proposed.block.clone()) {
+            tracing::warn!("Consensus: own proposal rejected: {}", e);
+            self.mempool.reinsert(proposed.txs);
+            return None;
+        }
+        let hash = proposed.block.hash();
+        let _ = self.p2p.broadcast_proposal(&proposed.block);
+        tracing::info!("Consensus: proposed block height={} hash={}", proposed.block.header.height, hash);
+        self.pipeline.insert(
+            hash,
+            PendingProposal { block: proposed.block, state: proposed.state, txs: proposed.txs },
+        );
+        self.apply_progress(view_before);
+        self.cast_vote(hash);
+        Some(hash)
+    }
+
+    /// Handle a block proposal from the network: validate and execute it on its parent's
+    /// state, add it to the pipeline, then vote for it if the safety rules allow.
+    pub fn handle_proposal(&mut self, block: Block) -> Result<(), ConsensusMessageError> {
+        let hash = block.hash();
+        if self.pipeline.contains_key(&hash) || self.chain.get_block_by_hash(&hash).is_some() {
+            return Ok(()); // duplicate gossip
+        }
+        let Some(parent_state) = self.state_after(&block.header.parent_hash) else {
+            if block.header.height > self.chain.height() {
+                // Parent not seen yet; retry once it arrives.
+                return self.stash_early_proposal(block);
+            }
+            return Err(ConsensusMessageError::DoesNotChain);
+        };
+        if !epoch::validator_set_known(block.header.height, self.chain.height(), &self.genesis.params) {
+            return Err(ConsensusMessageError::EpochNotCommitted);
+        }
+        let state = validate_proposal(
+            &block,
+            parent_state,
+            self.consensus.validators_at(block.header.height),
+            self.genesis.chain_id,
+            &self.executor,
+        )?;
+        let view_before = self.consensus.view();
+        match self.consensus.propose(block.clone()) {
+            Ok(()) => {}
+            Err(ConsensusError::WrongView { view }) if view > self.consensus.view() => {
+                // Its view opened by a timeout certificate we have not seen yet.
+                self.apply_progress(view_before);
+                return self.stash_early_proposal(block);
+            }
+            Err(e) => {
+                self.apply_progress(view_before);
+                return Err(e.into());
+            }
+        }
+        self.pipeline.insert(hash, PendingProposal { block, state, txs: vec![] });
+        self.apply_progress(view_before);
+        self.cast_vote(hash);
+
+        // Votes that raced ahead of the proposal. One that is now stale must not cost us
+        // the others, nor the proposal waiting on this one.
+        if let Some((_, votes)) = self.early_votes.remove(&hash) {
+            for vote in votes {
+                if let Err(e) = self.handle_vote(vote) {
+                    tracing::debug!("Consensus: early vote rejected: {}", e);
+                }
+            }
+        }
+        self.replay_early_proposal();
+        Ok(())
+    }
+
+    /// Handle a signed vote. Returns the quorum certificate once the block gathers 2f+1
+    /// votes; blocks that complete a three-chain are committed.
+    pub fn handle_vote(&mut self, vote: Vote) -> Result<Option<QuorumCertificate>, ConsensusMessageError> {
+        if !self.pipeline.contains_key(&vote.block_hash) {
+            if self.chain.get_block_by_hash(&vote.block_hash).is_none() {
+                self.buffer_early_vote(vote)?;
+            }
+            return Ok(None);
+        }
+        let view_before = self.consensus.view();
+        let result = self.consensus.vote(vote);
+        self.report_evidence();
+        let qc = result?;
+        self.apply_progress(view_before);
+        Ok(qc)
+    }
+
+    /// Keep a vote for a block we have not received yet. Only signed votes from a validator
+    /// of the next block (or of the block after our pending ones) are kept, one per
+    /// validator and block, up to [`MAX_EARLY_VOTES`] in total.
+    fn buffer_early_vote(&mut self, vote: Vote) -> Result<(), ConsensusMessageError> {
+        vote.verify().map_err(|_| ConsensusError::InvalidSignature(vote.validator))?;
+        let next = self.chain.height() + 1;
+        let pending_next = self.pipeline.values().map(|p| p.block.header.height + 1).max().unwrap_or(next);
+        if !self.consensus.validators_at(next).contains(&vote.validator)
+            && !self.consensus.validators_at(pending_next).contains(&vote.validator)
+        {
+            return Err(ConsensusError::InvalidBlock("Voter not in validator set".into()).into());
+        }
+        let buffered: usize = self.early_votes.values().map(|(_, votes)| votes.len()).sum();
+        if buffered >= MAX_EARLY_VOTES {
+            return Ok(());
+        }
+        let view = self.consensus.view();
+        let (_, votes) = self.early_votes.entry(vote.block_hash).or_insert_with(|| (view, Vec::new()));
+        if !votes.iter().any(|v| v.validator == vote.validator) {
+            votes.push(vote);
+        }
+        Ok(())
+    }
+
+    /// Drop early votes for committed blocks and votes buffered before the previous view:
+    /// a proposal that has not shown up by then has been abandoned.
+    fn prune_early_votes(&mut self) {
+        let view = self.consensus.view();
+        let chain = &self.chain;
+        self.early_votes
+            .retain(|hash, (buffered_at, _)| *buffered_at + 1 >= view && chain.get_block_by_hash(hash).is_none());
     }
 
-    /// Submit a signed transaction to the mempool.
+    /// Submit equivocation evidence detected by consensus as transactions from our
+    /// validator account, so the next block slashes the offender.
+    fn report_evidence(&mut self) {
+        let evidence = self.consensus.take_evidence();
+        let Some(producer) = &self.producer else {
+            return;
+        };
+        for evidence in evidence {
+            let reporter = producer.proposer();
+            let state_nonce = self.state.get(&reporter).map(|s| s.nonce).unwrap_or(0);
+            let nonce = self.mempool.next_nonce(&reporter, state_nonce);
+            tracing::warn!(
+                "Equivocation by {:?} in view {}; reporting with nonce {}",
+                evidence.validator,
+                evidence.view(),
+                nonce
+            );
+            let payload = TransactionPayload::ReportEquivocation { evidence: Box::new(evidence) };
+            let signed = producer.sign_transaction(self.genesis.chain_id, nonce, payload);
+            if let Err(e) = self.mempool.insert(signed) {
+                tracing::warn!("Could not queue equivocation report: {}", e);
+            }
+        }
+    }
+
+    /// Drive the pacemaker. When the current view times out while work is waiting (pending
+    /// transactions, or uncommitted blocks that carry some), sign and gossip a timeout vote;
+    /// the vote is repeated with a doubled timeout until a timeout certificate or a new
+    /// certified block moves the view on. Returns true if a timeout vote was sent.
+    pub fn on_tick(&mut self, now: Instant) -> bool {
+        if !self.pacemaker.is_expired(now) {
+            return false;
+        }
+        if self.mempool.is_empty() && !self.pipeline_has_txs() {
+            self.pacemaker.restart(now);
+            return false;
+        }
+        self.pacemaker.on_local_timeout(now);
+        self.send_timeout_vote()
+    }
+
+    /// Handle a timeout vote. Once 2f+1 validators timed out the view, gossip the timeout
+    /// certificate (new-view message) and move to the next view. Once more than f timed out,
+    /// at least one honest validator gave up on the view, so join without waiting for our timer.
+    pub fn handle_timeout_vote(&mut self, vote: TimeoutVote) -> Result<(), ConsensusMessageError> {
+        if vote.view < self.consensus.view() {
+            return Ok(()); // stale
+        }
+        if let Some(tc) = self.consensus.timeout_vote(vote)? {
+            let _ = self.p2p.broadcast_new_view(&tc);
+            self.enter_new_view(&tc);
+            return Ok(());
+        }
+        let joined = self
+            .producer
+            .as_ref()
+            .map(|p| self.consensus.has_timed_out(&p.proposer()))
+            .unwrap_or(true);
+        if !joined && self.consensus.timeout_stake() > self.consensus.max_faulty() {
+            self.send_timeout_vote();
+        }
+        Ok(())
+    }
+
+    /// Handle a timeout certificate from the network: move to the view after it.
+    pub fn handle_new_view(&mut self, tc: &TimeoutCertificate) -> Result<(), ConsensusMessageError> {
+        if self.consensus.new_view(tc)? {
+            self.enter_new_view(tc);
+        }
+        Ok(())
+    }
+
+    /// Sign, gossip, and count our timeout vote for the current view (validators only).
+    /// We stop voting in the view first, and persist that before the timeout goes out.
+    fn send_timeout_vote(&mut self) -> bool {
+        if self.producer.is_none() {
+            return false;
+        }
+        let view = self.consensus.prepare_timeout();
+        if !self.persist_safety() {
+            return false;
+        }
+        let Some(producer) = &self.producer else {
+            return false;
+        };
+        let vote = producer.timeout_vote(view);
+        tracing::info!("Consensus: view {} timed out", view);
+        let _ = self.p2p.broadcast_timeout(&vote);
+        if let Err(e) = self.handle_timeout_vote(vote) {
+            tracing::warn!("Consensus: own timeout vote rejected: {}", e);
+        }
+        true
+    }
+
+    /// The engine moved past `tc.view`: give the new leader a fresh timer.
+    fn enter_new_view(&mut self, tc: &TimeoutCertificate) {
+        tracing::info!("Consensus: entering view {}", tc.view + 1);
+        self.pacemaker.restart(Instant::now());
+        self.prune_early_votes();
+        self.replay_early_proposal();
+    }
+
+    /// Sign and gossip our vote for `block_hash` (validators only), and count it locally.
+    /// The engine's voting rules are checked and the safety state persisted first.
+    fn cast_vote(&mut self, block_hash: Hash) {
+        if self.producer.is_none() {
+            return;
+        }
+        if let Err(e) = self.consensus.prepare_vote(&block_hash) {
+            tracing::debug!("Consensus: not voting for {}: {}", block_hash, e);
+            return;
+        }
+        if !self.persist_safety() {
+            return;
+        }
+        let Some(producer) = &self.producer else {
+            return;
+        };
+        let vote = producer.vote(block_hash);
+        let _ = self.p2p.broadcast_vote(&vote);
+        if let Err(e) = self.handle_vote(vote) {
+            tracing::warn!("Consensus: own vote rejected: {}", e);
+        }
+    }
+
+    /// After a consensus step: restart the view timer if a QC moved the view, and append
+    /// blocks the engine committed.
+    fn apply_progress(&mut self, view_before: u64) {
+        if self.consensus.view() > view_before {
+            self.pacemaker.on_progress(Instant::now());
+        }
+        let committed = self.consensus.take_committed();
+        if committed.is_empty() {
+            if self.consensus.view() > view_before {
+                self.prune_early_votes();
+            }
+            return;
+        }
+        // Blocks from the first one we cannot apply from the pipeline on.
+        let mut unapplied = Vec::new();
+        for block in committed {
+            if !unapplied.is_empty() {
+                unapplied.push(block);
+                continue;
+            }
+            let hash = block.hash();
+            let Some(PendingProposal { state, .. }) = self.pipeline.remove(&hash) else {
+                tracing::warn!("Consensus: committed block {} missing from pipeline; resyncing", hash);
+                unapplied.push(block);
+                continue;
+            };
+            if let Err(e) = self.chain.append(block.clone()) {
+                tracing::warn!("Consensus: committed block {} does not extend our chain: {}; resyncing", hash, e);
+                unapplied.push(block);
+                continue;
+            }
+            self.state = state;
+            self.finalize(block.header.height);
+            self.mempool.prune_committed(&block.transactions);
+            self.prune_intents();
+            self.prune_signaling();
+            self.advance_epoch(&block.header);
+            self.persist_block_and_state(&block);
+            tracing::info!("Consensus: committed block height={} hash={}", block.header.height, hash);
+            // The proposer also gossips the committed block for nodes that missed the votes.
+            if self.producer.as_ref().map(|p| p.proposer()) == Some(block.header.proposer) {
+                let _ = self.p2p.broadcast_block(&block);
+            }
+        }
+        self.prune_pipeline();
+        self.prune_early_votes();
+        if !unapplied.is_empty() {
+            self.resync(unapplied);
+        }
+    }
+
+    /// Catch the chain up with blocks consensus committed but we could not apply from the
+    /// pipeline (never executed, or not extending our head). They carry their QCs, so they
+    /// are imported as certified blocks; if one cannot be, block sync restarts from our head
+    /// to fetch the missing blocks from peers.
+    fn resync(&mut self, blocks: Vec<Block>) {
+        for block in blocks {
+            match self.import_network_block(&block) {
+                Ok(()) | Err(BlockValidationError::Chain(ChainError::AlreadyKnown)) => {}
+                Err(e) => {
+                    tracing::warn!(
+                        "Consensus: cannot import committed block {}: {}; restarting block sync",
+                        block.header.height,
+                        e
+                    );
+                    self.sync.reset();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Drop pipeline blocks at or below the committed height (forks that lost), returning
+    /// transactions we drained as leader to the mempool.
+    fn prune_pipeline(&mut self) {
+        let height = self.chain.height();
+        let stale: Vec<Hash> = self
+            .pipeline
+            .iter()
+            .filter(|(_, p)| p.block.header.height <= height)
+            .map(|(h, _)| *h)
+            .collect();
+        for hash in stale {
+            if let Some(abandoned) = self.pipeline.remove(&hash) {
+                self.mempool.reinsert(abandoned.txs);
+            }
+        }
+    }
+
+    /// Keep a proposal we cannot process yet for [`replay_early_proposal`](Self::replay_early_proposal).
+    /// It must be signed by the leader of its view, and that view must be the current or the
+    /// next one, so a forged or far-future block cannot evict the real pending proposal.
+    fn stash_early_proposal(&mut self, block: Block) -> Result<(), ConsensusMessageError> {
+        let view = self.consensus.view();
+        let round = block.header.round;
+        if round < view || round > view + 1 {
+            return Err(ConsensusError::WrongView { view: round }.into());
+        }
+        let leader = self.consensus.leader(block.header.height, round);
+        if block.header.proposer != leader {
+            return Err(ConsensusError::InvalidBlock(format!(
+                "Proposer {:?} is not the view leader {:?}",
+                block.header.proposer, leader
+            ))
+            .into());
+        }
+        if block.verify_signature().is_err() {
+            return Err(ConsensusError::InvalidSignature(block.header.proposer).into());
+        }
+        self.early_proposal = Some(block);
+        Ok(())
+    }
+
+    fn replay_early_proposal(&mut self) {
+        if let Some(block) = self.early_proposal.take() {
+            if let Err(e) = self.handle_proposal(block) {
+                tracing::debug!("Consensus: early proposal rejected: {}", e);
+            }
+        }
+    }
+
+    /// Submit a signed intent for solver fulfillment and gossip it to peers. It must still
+    /// be settleable in the next block.
+    pub fn submit_intent(&self, signed: boing_primitives::SignedIntent) -> Result<boing_primitives::Hash, IntentPoolError> {
+        let id = self.intent_pool.submit(signed.clone(), self.chain.height() + 1, &self.state)?;
+        if let Err(e) = self.p2p.broadcast_intent(&signed) {
+            tracing::debug!("P2P: intent broadcast failed: {}", e);
+        }
+        Ok(id)
+    }
+
+    /// Add an intent gossiped by a peer to the pool. Gossipsub relays the message onward;
+    /// we do not rebroadcast it.
+    pub fn receive_intent(&self, signed: boing_primitives::SignedIntent) -> Result<boing_primitives::Hash, IntentPoolError> {
+        self.intent_pool.submit(signed, self.chain.height() + 1, &self.state)
+    }
+
+    /// Submit a solver's `FulfillIntent` transaction for a pooled intent. The settlement is
+    /// simulated on the head state first (its nonce is left to the mempool), so a solution
+    /// that would not settle is refused here instead of failing in a block. Only one
+    /// solution per intent signer is admitted per block. Returns the intent id.
+    pub fn submit_solution(&self, signed: SignedTransaction) -> Result<Hash, SolutionError> {
+        let TransactionPayload::FulfillIntent { intent, .. } = &signed.tx.payload else {
+            return Err(SolutionError::NotAFulfillment);
+        };
+        let intent_id = intent.intent.id();
+        if self.intent_pool.get(&intent_id).is_none() {
+            return Err(SolutionError::UnknownIntent);
+        }
+        let mut state = self.state.snapshot();
+        if let Some(solver) = state.get_mut(&signed.tx.sender) {
+            solver.nonce = signed.tx.nonce;
+        }
+        let ctx = BlockContext::at_height(self.chain.height() + 1).with_randomness(self.state.beacon());
+        self.vm.execute(&signed.tx, &mut state, &ctx)?;
+        self.intent_pool.claim(&intent_id).map_err(|e| match e {
+            IntentPoolError::SolutionPending => SolutionError::SolutionPending,
+            _ => SolutionError::UnknownIntent,
+        })?;
+        if let Err(e) = self.submit_transaction(signed) {
+            self.intent_pool.release(&intent_id);
+            return Err(e.into());
+        }
+        Ok(intent_id)
+    }
+
+    /// Drop pooled intents the next block can no longer settle, and move gossip validation
+    /// to the new head so it stops relaying expired intents.
+    fn prune_intents(&self) {
+        self.intent_pool.prune(self.chain.height() + 1, self.state.intents());
+        self.p2p.set_head_height(self.chain.height());
+    }
+
+    /// Post a signed WebRTC signaling message. An offer's deposit is reserved against the
+    /// sender's committed balance.
+    pub fn post_signaling(
+        &self,
+        signed: boing_p2p::SignedSignalingMessage,
+        deposit: u128,
+    ) -> Result<Hash, SignalingError> {
+        let balance = self.state.get(&boing_primitives::AccountId(signed.message.sender)).map_or(0, |a| a.balance);
+        self.signaling.post(signed, deposit, balance, crate::signaling::unix_now())
+    }
+
+    /// Submit a signed transaction to the mempool and gossip it to peers, so it reaches
+    /// the leader whichever node it was submitted to.
     pub fn submit_transaction(&self, signed: SignedTransaction) -> Result<(), MempoolError> {
-        self.mempool.insert(signed)
+        self.check_signaling_deposits(&signed)?;
+        let id = signed.tx.id();
+        self.mempool.insert(signed.clone())?;
+        self.mempool.mark_seen(id);
+        if let Err(e) = self.p2p.broadcast_transaction(&signed) {
+            tracing::debug!("P2P: tx broadcast failed: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Add a transaction gossiped by a peer to the mempool. Transactions pooled before, even
+    /// if since committed, are dropped as duplicates, as are those whose nonce is below the
+    /// sender's committed one.
+    /// Gossipsub relays the message onward; we do not rebroadcast it.
+    pub fn receive_transaction(&self, signed: SignedTransaction) -> Result<(), MempoolError> {
+        // Check the signature first: the id does not cover it, so a forged copy must not
+        // shadow the real transaction in the seen-cache.
+        signed.verify().map_err(|_| MempoolError::InvalidSignature)?;
+        let id = signed.tx.id();
+        if self.mempool.is_seen(&id) {
+            return Err(MempoolError::Duplicate);
+        }
+        let expected = self.state.get(&signed.tx.sender).map_or(0, |s| s.nonce);
+        if signed.tx.nonce < expected {
+            return Err(MempoolError::NonceTooLow { expected, got: signed.tx.nonce });
+        }
+        self.check_signaling_deposits(&signed)?;
+        // Only remembered once pooled: a copy refused for now (say, while its sender's
+        // deposit is locked) must not shadow a later copy that would be accepted.
+        self.mempool.insert(signed)?;
+        self.mempool.mark_seen(id);
+        Ok(())
+    }
+
+    /// Refuse a transaction that would spend deposits its sender has reserved in open
+    /// signaling offers on this node. It is simulated on the head state with the reserved
+    /// amount taken out of the sender's balance.
+    fn check_signaling_deposits(&self, signed: &SignedTransaction) -> Result<(), MempoolError> {
+        let locked = self.signaling.locked_deposit(&signed.tx.sender.0, crate::signaling::unix_now());
+        if locked == 0 {
+            return Ok(());
+        }
+        let mut state = self.state.snapshot();
+        if let Some(sender) = state.get_mut(&signed.tx.sender) {
+            sender.balance = sender.balance.saturating_sub(locked);
+            sender.nonce = signed.tx.nonce;
+        }
+        let ctx = BlockContext::at_height(self.chain.height() + 1).with_randomness(self.state.beacon());
+        match self.vm.execute(&signed.tx, &mut state, &ctx) {
+            Err(VmError::InsufficientBalance) => Err(MempoolError::SignalingDepositLocked { locked }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Withdraw signaling offers whose senders can no longer cover their deposits after a
+    /// commit moved funds out from under them.
+    fn prune_signaling(&self) {
+        let withdrawn = self.signaling.withdraw_uncovered(
+            |sender| self.state.get(&boing_primitives::AccountId(*sender)).map_or(0, |a| a.balance),
+            crate::signaling::unix_now(),
+        );
+        if withdrawn > 0 {
+            tracing::debug!("Signaling: withdrew {} offers no longer covered by their deposits", withdrawn);
+        }
     }
 
     /// Produce one block from mempool if there are pending txs.
     /// Broadcasts the block via P2P on success.
     pub fn produce_block_if_ready(&mut self) -> Option<boing_primitives::Hash> {
-        let hash = self.producer.produce_block(
+        let hash = self.producer.as_ref()?.produce_block(
             &self.chain,
             &self.mempool,
             &mut self.state,
@@ -175,6 +1101,10 @@ impl BoingNode {
             &mut self.consensus,
         )?;
         if let Some(block) = self.chain.get_block_by_hash(&hash) {
+            self.finalize(block.header.height);
+            self.prune_intents();
+            self.prune_signaling();
+            self.advance_epoch(&block.header);
             self.persist_block_and_state(&block);
             let _ = self.p2p.broadcast_block(&block);
         }
@@ -187,3 +1117,39 @@ impl Default for BoingNode {
         Self::new()
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum NodeError {
+    #[error("Genesis error: {0}")]
+    Genesis(#[from] GenesisError),
+    #[error("Persistence error: {0}")]
+    Persistence(#[from] PersistenceError),
+    #[error("Data directory belongs to genesis {found}, but this node was started with genesis {expected}")]
+    GenesisMismatch { expected: Hash, found: Hash },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConsensusMessageError {
+    #[error("Proposal does not extend our chain")]
+    DoesNotChain,
+    #[error("Proposal is past an epoch whose validator set is not committed yet")]
+    EpochNotCommitted,
+    #[error("Invalid proposal: {0}")]
+    InvalidProposal(#[from] BlockValidationError),
+    #[error("Consensus error: {0}")]
+    Consensus(#[from] ConsensusError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SolutionError {
+    #[error("Transaction is not a FulfillIntent")]
+    NotAFulfillment,
+    #[error("Intent is not in the pool (unknown, expired, cancelled or already fulfilled)")]
+    UnknownIntent,
+    #[error("A solution for this signer's intents is already pending; retry after the next block")]
+    SolutionPending,
+    #[error("Solution does not settle: {0}")]
+    Rejected(#[from] VmError),
+    #[error("Mempool rejected the solution: {0}")]
+    Mempool(#[from] MempoolError),
+}
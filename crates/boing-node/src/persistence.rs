@@ -3,10 +3,12 @@
 //! Persists blocks and state to the data directory so the node can resume
 //! after restart without losing data.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-use boing_primitives::{AccountId, AccountState, Block, Hash};
-use boing_state::{ContractStorageEntry, StateStore};
+use boing_consensus::SafetyState;
+use boing_primitives::{AccountId, AccountState, Block, Hash, ValidatorSet};
+use boing_state::{ContractStorageEntry, IntentRegistry, StakingState, StateStore};
 
 use crate::chain::ChainState;
 
@@ -15,12 +17,27 @@ const BLOCKS_DIR: &str = "blocks";
 const CHAIN_META_FILE: &str = "meta.bin";
 const STATE_DIR: &str = "state";
 const STATE_FILE: &str = "accounts.bin";
+const STAKING_FILE: &str = "staking.bin";
+const BEACON_FILE: &str = "beacon.bin";
+const INTENTS_FILE: &str = "intents.bin";
+const CONSENSUS_DIR: &str = "consensus";
+const SAFETY_FILE: &str = "safety.bin";
+const EPOCHS_FILE: &str = "epochs.bin";
 
 /// Chain metadata stored on disk.
 #[derive(serde::Serialize, serde::Deserialize)]
 struct ChainMeta {
     height: u64,
     latest_hash: Hash,
+    /// Last finalized block (height, hash).
+    finalized: (u64, Hash),
+}
+
+/// Chain metadata written before finality tracking; its head counts as finalized.
+#[derive(serde::Deserialize)]
+struct LegacyChainMeta {
+    height: u64,
+    latest_hash: Hash,
 }
 
 /// Persisted state: accounts and contract data.
@@ -63,10 +80,15 @@ impl Persistence {
         self.base.join(STATE_DIR)
     }
 
+    fn consensus_dir(&self) -> PathBuf {
+        self.base.join(CONSENSUS_DIR)
+    }
+
     /// Ensure required directories exist.
     pub fn ensure_dirs(&self) -> Result<(), PersistenceError> {
         std::fs::create_dir_all(self.blocks_dir())?;
         std::fs::create_dir_all(self.state_dir())?;
+        std::fs::create_dir_all(self.consensus_dir())?;
         Ok(())
     }
 
@@ -79,10 +101,10 @@ impl Persistence {
         Ok(())
     }
 
-    /// Save chain metadata (height, latest_hash).
-    pub fn save_chain_meta(&self, height: u64, latest_hash: Hash) -> Result<(), PersistenceError> {
+    /// Save chain metadata (height, latest_hash, finalized block).
+    pub fn save_chain_meta(&self, height: u64, latest_hash: Hash, finalized: (u64, Hash)) -> Result<(), PersistenceError> {
         self.ensure_dirs()?;
-        let meta = ChainMeta { height, latest_hash };
+        let meta = ChainMeta { height, latest_hash, finalized };
         let path = self.chain_dir().join(CHAIN_META_FILE);
         let bytes = bincode::serialize(&meta).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
         std::fs::write(path, bytes)?;
@@ -101,9 +123,59 @@ impl Persistence {
         let path = self.state_dir().join(STATE_FILE);
         let bytes = bincode::serialize(&persisted).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
         std::fs::write(path, bytes)?;
+        let bytes = bincode::serialize(state.staking()).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        std::fs::write(self.state_dir().join(STAKING_FILE), bytes)?;
+        let bytes = bincode::serialize(state.intents()).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        std::fs::write(self.state_dir().join(INTENTS_FILE), bytes)?;
+        std::fs::write(self.state_dir().join(BEACON_FILE), state.beacon().0)?;
+        Ok(())
+    }
+
+    /// Save the consensus safety state (last voted view, locked QC). Written to a temporary
+    /// file and renamed so a crash never leaves a truncated file behind; a validator that
+    /// lost this file could vote twice in a view after restart.
+    pub fn save_safety_state(&self, safety: &SafetyState) -> Result<(), PersistenceError> {
+        self.ensure_dirs()?;
+        let path = self.consensus_dir().join(SAFETY_FILE);
+        let tmp = path.with_extension("tmp");
+        let bytes = bincode::serialize(safety).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Load the consensus safety state, if one was saved.
+    pub fn load_safety_state(&self) -> Result<Option<SafetyState>, PersistenceError> {
+        let path = self.consensus_dir().join(SAFETY_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(&path)?;
+        let safety = bincode::deserialize(&bytes).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        Ok(Some(safety))
+    }
+
+    /// Save the validator set schedule (first height -> set). Sets derived from stake at
+    /// past epoch boundaries cannot be recomputed without the state at that boundary.
+    pub fn save_epochs(&self, epochs: &BTreeMap<u64, ValidatorSet>) -> Result<(), PersistenceError> {
+        self.ensure_dirs()?;
+        let path = self.consensus_dir().join(EPOCHS_FILE);
+        let bytes = bincode::serialize(epochs).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        std::fs::write(path, bytes)?;
         Ok(())
     }
 
+    /// Load the validator set schedule, if one was saved.
+    pub fn load_epochs(&self) -> Result<Option<BTreeMap<u64, ValidatorSet>>, PersistenceError> {
+        let path = self.consensus_dir().join(EPOCHS_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(&path)?;
+        let epochs = bincode::deserialize(&bytes).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        Ok(Some(epochs))
+    }
+
     /// Load all blocks from disk and build ChainState.
     pub fn load_chain(&self) -> Result<Option<ChainState>, PersistenceError> {
         let meta_path = self.chain_dir().join(CHAIN_META_FILE);
@@ -111,7 +183,14 @@ impl Persistence {
             return Ok(None);
         }
         let meta_bytes = std::fs::read(&meta_path)?;
-        let meta: ChainMeta = bincode::deserialize(&meta_bytes).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        let meta: ChainMeta = match bincode::deserialize(&meta_bytes) {
+            Ok(meta) => meta,
+            Err(_) => {
+                let legacy: LegacyChainMeta =
+                    bincode::deserialize(&meta_bytes).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+                ChainMeta { height: legacy.height, latest_hash: legacy.latest_hash, finalized: (legacy.height, legacy.latest_hash) }
+            }
+        };
 
         let blocks_dir = self.blocks_dir();
         if !blocks_dir.exists() {
@@ -137,6 +216,7 @@ impl Persistence {
             let block: Block = bincode::deserialize(&block_bytes).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
             chain.append(block).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
         }
+        chain.finalize(&meta.finalized.1).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
 
         Ok(Some(chain))
     }
@@ -150,11 +230,31 @@ impl Persistence {
         let bytes = std::fs::read(&path)?;
         let persisted: PersistedState = bincode::deserialize(&bytes).map_err(|e| PersistenceError::Serialization(e.to_string()))?;
 
-        let state = StateStore::load_from_persistence(
+        let mut state = StateStore::load_from_persistence(
             persisted.accounts,
             persisted.contract_code,
             persisted.contract_storage,
         );
+        // Data directories from before unbonding queues have no staking file.
+        let staking_path = self.state_dir().join(STAKING_FILE);
+        if staking_path.exists() {
+            let bytes = std::fs::read(&staking_path)?;
+            *state.staking_mut() = bincode::deserialize::<StakingState>(&bytes)
+                .map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        }
+        let intents_path = self.state_dir().join(INTENTS_FILE);
+        if intents_path.exists() {
+            let bytes = std::fs::read(&intents_path)?;
+            *state.intents_mut() = bincode::deserialize::<IntentRegistry>(&bytes)
+                .map_err(|e| PersistenceError::Serialization(e.to_string()))?;
+        }
+        let beacon_path = self.state_dir().join(BEACON_FILE);
+        if beacon_path.exists() {
+            let bytes = std::fs::read(&beacon_path)?;
+            let beacon = Hash::from_slice(&bytes)
+                .ok_or_else(|| PersistenceError::Serialization("beacon is not 32 bytes".into()))?;
+            state.set_beacon(beacon);
+        }
         Ok(Some(state))
     }
 
@@ -0,0 +1,83 @@
+//! Block rewards — the proposer's emission, shared with its delegators.
+//!
+//! A registered validator keeps its commission, then the rest is split pro rata between its
+//! own bonded stake and each delegation; rounding dust stays with the validator. Proposers
+//! without delegations keep the whole reward.
+
+use boing_primitives::{Account, AccountId, AccountState};
+use boing_state::StateStore;
+use boing_tokenomics::block_emission_validators;
+
+/// Split `reward` for a validator with `commission_bps`, `own_stake` and `delegations`.
+/// Returns the validator's share and each delegator's share.
+pub fn split_reward(
+    reward: u128,
+    commission_bps: u16,
+    own_stake: u128,
+    delegations: &[(AccountId, u128)],
+) -> (u128, Vec<(AccountId, u128)>) {
+    let delegated: u128 = delegations.iter().map(|(_, amount)| amount).sum();
+    let total = own_stake.saturating_add(delegated);
+    if delegated == 0 || total == 0 {
+        return (reward, Vec::new());
+    }
+    let commission = reward * commission_bps as u128 / 10_000;
+    let shared = reward - commission;
+    let shares: Vec<(AccountId, u128)> = delegations
+        .iter()
+        .map(|(delegator, amount)| (*delegator, shared * amount / total))
+        .collect();
+    let paid: u128 = shares.iter().map(|(_, share)| share).sum();
+    (reward - paid, shares)
+}
+
+/// Credit the emission for the block at `height` to `proposer` and its delegators.
+pub fn credit_block_reward(state: &mut StateStore, proposer: AccountId, height: u64) {
+    let reward = block_emission_validators(height);
+    if reward == 0 {
+        return;
+    }
+    let (validator_share, delegator_shares) = match state.staking().validator(&proposer) {
+        Some(record) => split_reward(
+            reward,
+            record.commission_bps,
+            state.get(&proposer).map(|s| s.stake).unwrap_or(0),
+            &state.staking().delegators(&proposer),
+        ),
+        None => (reward, Vec::new()),
+    };
+    credit(state, proposer, validator_share);
+    for (delegator, share) in delegator_shares {
+        credit(state, delegator, share);
+    }
+}
+
+fn credit(state: &mut StateStore, id: AccountId, amount: u128) {
+    if amount == 0 {
+        return;
+    }
+    match state.get_mut(&id) {
+        Some(s) => s.balance = s.balance.saturating_add(amount),
+        None => state.insert(Account {
+            id,
+            state: AccountState { balance: amount, nonce: 0, stake: 0 },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_reward_pro_rata_after_commission() {
+        let (d1, d2) = (AccountId([2u8; 32]), AccountId([3u8; 32]));
+        // 10% commission on 1000 leaves 900 shared over 100 own + 200 + 300 delegated.
+        let (validator, shares) = split_reward(1_000, 1_000, 100, &[(d1, 200), (d2, 300)]);
+        assert_eq!(shares, vec![(d1, 300), (d2, 450)]);
+        assert_eq!(validator, 250);
+
+        // No delegations: the validator keeps everything, commission or not.
+        assert_eq!(split_reward(1_000, 1_000, 100, &[]), (1_000, vec![]));
+    }
+}
@@ -1,33 +1,45 @@
 //! Simple JSON-RPC over HTTP for Boing node.
 //!
 //! Supports boing_submitTransaction for submitting signed transactions.
+//! Solvers can follow new intents as Server-Sent Events at `GET /intents/subscribe`.
 //! Optional global rate limiting when RateLimitConfig.requests_per_sec > 0.
 //! Optional testnet faucet (boing_faucetRequest) when --faucet-enable.
 
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use axum::{
     extract::State,
     http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
     response::IntoResponse,
-    routing::post,
+    routing::{get, post},
     Json, Router,
 };
+use futures_util::stream::{self, Stream, StreamExt};
 use governor::{Quota, RateLimiter};
 use std::num::NonZeroU32;
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{debug, info};
 
+use crate::chain::BlockTag;
 use crate::faucet::{self, testnet_faucet_account_id};
+use crate::intent_pool::IntentStatus;
 use crate::mempool::MempoolError;
 use crate::node::BoingNode;
 use crate::security::RateLimitConfig;
+use crate::signaling::{unix_now, StoredSignal};
+use boing_p2p::{SignalingMessageKind, SignalingQuery, SignedSignalingMessage};
 use boing_primitives::{
-    AccessList, AccountId, SignedIntent, SignedTransaction, Transaction, TransactionPayload,
+    AccessList, AccountId, Block, Hash, IntentKind, SignedIntent, SignedTransaction, Transaction,
+    TransactionPayload,
 };
+use boing_state::StateStore;
+use boing_governance::SlashReason;
 use boing_qa::{check_contract_deploy, QaResult, RuleRegistry};
 
 /// Shared node state for RPC and validator loop.
@@ -113,6 +125,135 @@ fn rpc_ok(id: Option<serde_json::Value>, result: serde_json::Value) -> JsonRpcRe
     }
 }
 
+/// Parse a 32-byte account id from hex (with or without 0x).
+fn parse_account_id(hex_account: &str) -> Option<AccountId> {
+    let bytes = hex::decode(hex_account.trim_start_matches("0x")).ok()?;
+    AccountId::from_slice(&bytes)
+}
+
+/// Block as JSON, with its hash added.
+fn block_json(block: &Block) -> serde_json::Value {
+    let block_json = serde_json::to_value(block).unwrap_or(serde_json::Value::Null);
+    let mut obj = block_json.as_object().cloned().unwrap_or_default();
+    obj.insert("hash".to_string(), serde_json::json!(hex::encode(block.hash().0)));
+    serde_json::Value::Object(obj)
+}
+
+/// Optional block tag parameter of account RPCs; `latest` when omitted.
+fn parse_block_tag(param: Option<&String>) -> Result<BlockTag, String> {
+    param.map_or(Ok(BlockTag::Latest), |t| t.parse::<BlockTag>().map_err(|e| e.to_string()))
+}
+
+/// State at `tag` when it is not the head's (None: use `node.state`).
+fn tagged_state(node: &BoingNode, tag: BlockTag) -> Result<Option<StateStore>, String> {
+    let height = match tag {
+        BlockTag::Latest => return Ok(None),
+        BlockTag::Safe => node.chain.safe().0,
+        BlockTag::Finalized => node.chain.finalized().0,
+    };
+    if height == node.chain.height() {
+        return Ok(None);
+    }
+    node.state_at(height)
+        .map(Some)
+        .ok_or_else(|| format!("State at height {} is not available on this node", height))
+}
+
+/// Signaling message as JSON for dApps polling their messages.
+fn signal_json(signal: &StoredSignal) -> serde_json::Value {
+    let m = &signal.message;
+    let kind = match m.kind {
+        SignalingMessageKind::Offer => "offer",
+        SignalingMessageKind::Answer => "answer",
+        SignalingMessageKind::IceCandidate => "ice_candidate",
+    };
+    serde_json::json!({
+        "message_id": hex::encode(signal.id.0),
+        "kind": kind,
+        "sender": hex::encode(m.sender),
+        "recipient": hex::encode(m.recipient),
+        "payload": m.payload.as_ref().map(hex::encode),
+        "content_pointer": m.content_pointer.as_ref().map(|p| serde_json::json!({
+            "cid": p.cid,
+            "hash": p.hash.map(hex::encode),
+        })),
+        "posted_at": signal.posted_at,
+        "expires_at": signal.expires_at,
+        "answered": signal.answered,
+    })
+}
+
+fn intent_json(intent_id: &Hash, signed: &SignedIntent, status: IntentStatus) -> serde_json::Value {
+    let intent = &signed.intent;
+    let kind = match &intent.kind {
+        IntentKind::Transfer { to, amount } => serde_json::json!({
+            "type": "transfer",
+            "to": hex::encode(to.0),
+            "amount": amount.to_string(),
+        }),
+        IntentKind::Swap { from_asset, to_asset, amount, min_out } => serde_json::json!({
+            "type": "swap",
+            "from_asset": hex::encode(from_asset.0),
+            "to_asset": hex::encode(to_asset.0),
+            "amount": amount.to_string(),
+            "min_out": min_out.map(|m| m.to_string()),
+        }),
+        IntentKind::Custom(data) => serde_json::json!({ "type": "custom", "data": hex::encode(data) }),
+    };
+    serde_json::json!({
+        "intent_id": hex::encode(intent_id.0),
+        "signer": hex::encode(intent.signer.0),
+        "nonce": intent.nonce,
+        "deadline_block": intent.deadline_block,
+        "status": status.as_str(),
+        "kind": kind,
+        "signed_intent": hex::encode(bincode::serialize(signed).unwrap_or_default()),
+    })
+}
+
+fn slash_reason_name(reason: &SlashReason) -> String {
+    match reason {
+        SlashReason::Equivocation => "equivocation".into(),
+        SlashReason::Liveness => "liveness".into(),
+        SlashReason::Fraud => "fraud".into(),
+        SlashReason::Other(details) => format!("other: {}", details),
+    }
+}
+
+/// Stream intents to a solver as Server-Sent Events: the pooled intents first, then every
+/// intent pooled afterwards, local or gossiped. Each event is named `intent` and carries
+/// the intent id as its id and the `boing_getIntents` JSON as its data. A solver that falls
+/// more than the feed capacity behind skips the missed intents; the same intent may be
+/// sent twice around the switch from backlog to live, so solvers dedupe by id.
+async fn subscribe_intents(
+    State(state): State<RpcState>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    if let Some(ref limiter) = state.rate_limiter {
+        if limiter.check().is_err() {
+            return Err(StatusCode::TOO_MANY_REQUESTS);
+        }
+    }
+    let (feed, backlog) = {
+        let n = state.node.read().await;
+        // Subscribe before listing so nothing pooled in between is missed.
+        (n.intent_pool.subscribe(), n.intent_pool.pending())
+    };
+    let live = stream::unfold(feed, |mut feed| async move {
+        loop {
+            match feed.recv().await {
+                Ok(item) => return Some((item, feed)),
+                Err(RecvError::Lagged(missed)) => debug!("RPC: intent subscriber skipped {} intents", missed),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    let events = stream::iter(backlog).chain(live).map(|(intent_id, signed)| {
+        let data = intent_json(&intent_id, &signed, IntentStatus::Pending);
+        Ok(Event::default().event("intent").id(hex::encode(intent_id.0)).data(data.to_string()))
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
 async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcRequest>) -> impl IntoResponse {
     if let Some(ref limiter) = state.rate_limiter {
         if limiter.check().is_err() {
@@ -165,16 +306,67 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
                 Err(e) => rpc_error(id, -32602, format!("Invalid hex: {}", e)),
             }
         }
+        "boing_chainId" => {
+            let n = node.read().await;
+            rpc_ok(id, serde_json::json!(n.genesis.chain_id))
+        }
         "boing_chainHeight" => {
             let n = node.read().await;
             let height = n.chain.height();
             rpc_ok(id, serde_json::json!(height))
         }
+        "boing_syncing" => {
+            let n = node.read().await;
+            match n.sync_status() {
+                Some(status) => rpc_ok(
+                    id,
+                    serde_json::json!({
+                        "phase": status.phase.as_str(),
+                        "starting_block": status.starting_block,
+                        "current_block": status.current_block,
+                        "highest_block": status.highest_block,
+                    }),
+                ),
+                None => rpc_ok(id, serde_json::json!(false)),
+            }
+        }
+        "boing_nodeInfo" => {
+            let n = node.read().await;
+            rpc_ok(
+                id,
+                serde_json::json!({
+                    "peer_id": n.p2p.local_peer_id().map(|p| p.to_string()),
+                    "chain_id": n.genesis.chain_id,
+                    "genesis_hash": hex::encode(n.genesis_hash.0),
+                }),
+            )
+        }
+        "boing_peers" => {
+            let p2p = node.read().await.p2p.clone();
+            let peers: Vec<_> = p2p
+                .peer_info()
+                .await
+                .into_iter()
+                .map(|p| {
+                    serde_json::json!({
+                        "peer_id": p.peer_id.to_string(),
+                        "addresses": p.addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+                        "score": p.score.rank(),
+                        "reliability": p.score.reliability,
+                    })
+                })
+                .collect();
+            rpc_ok(id, serde_json::json!(peers))
+        }
         "boing_getBalance" => {
             let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
-            let hex_account = match params {
-                Some(v) if !v.is_empty() => v[0].clone(),
-                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_account_id]".into()))),
+            let (hex_account, params_tag) = match params {
+                Some(v) if !v.is_empty() => (v[0].clone(), v.get(1).cloned()),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_account_id, block_tag?]".into()))),
+            };
+            let tag = match parse_block_tag(params_tag.as_ref()) {
+                Ok(t) => t,
+                Err(e) => return (StatusCode::OK, Json(rpc_error(id, -32602, e))),
             };
             match hex::decode(hex_account.trim_start_matches("0x")) {
                 Ok(bytes) if bytes.len() == 32 => {
@@ -182,7 +374,12 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
                     arr.copy_from_slice(&bytes);
                     let account_id = boing_primitives::AccountId(arr);
                     let n = node.read().await;
-                    let balance = n.state.get(&account_id).map(|s| s.balance).unwrap_or(0);
+                    let tagged = match tagged_state(&n, tag) {
+                        Ok(t) => t,
+                        Err(e) => return (StatusCode::OK, Json(rpc_error(id, -32000, e))),
+                    };
+                    let state = tagged.as_ref().unwrap_or(&n.state);
+                    let balance = state.get(&account_id).map(|s| s.balance).unwrap_or(0);
                     rpc_ok(id, serde_json::json!({ "balance": balance.to_string() }))
                 }
                 _ => rpc_error(id, -32602, "Invalid account id: expected 32 bytes hex".into()),
@@ -190,9 +387,13 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
         }
         "boing_getAccount" => {
             let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
-            let hex_account = match params {
-                Some(v) if !v.is_empty() => v[0].clone(),
-                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_account_id]".into()))),
+            let (hex_account, params_tag) = match params {
+                Some(v) if !v.is_empty() => (v[0].clone(), v.get(1).cloned()),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_account_id, block_tag?]".into()))),
+            };
+            let tag = match parse_block_tag(params_tag.as_ref()) {
+                Ok(t) => t,
+                Err(e) => return (StatusCode::OK, Json(rpc_error(id, -32602, e))),
             };
             match hex::decode(hex_account.trim_start_matches("0x")) {
                 Ok(bytes) if bytes.len() == 32 => {
@@ -200,27 +401,186 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
                     arr.copy_from_slice(&bytes);
                     let account_id = boing_primitives::AccountId(arr);
                     let n = node.read().await;
-                    match n.state.get(&account_id) {
+                    let tagged = match tagged_state(&n, tag) {
+                        Ok(t) => t,
+                        Err(e) => return (StatusCode::OK, Json(rpc_error(id, -32000, e))),
+                    };
+                    let state = tagged.as_ref().unwrap_or(&n.state);
+                    let unbonding: Vec<_> = state
+                        .staking()
+                        .unbonding(&account_id)
+                        .iter()
+                        .map(|e| serde_json::json!({
+                            "validator": hex::encode(e.validator.0),
+                            "amount": e.amount.to_string(),
+                            "release_height": e.release_height
+                        }))
+                        .collect();
+                    match state.get(&account_id) {
                         Some(s) => rpc_ok(id, serde_json::json!({
                             "balance": s.balance.to_string(),
                             "nonce": s.nonce,
-                            "stake": s.stake.to_string()
+                            "stake": s.stake.to_string(),
+                            "unbonding": unbonding
                         })),
                         None => rpc_ok(id, serde_json::json!({
                             "balance": "0",
                             "nonce": 0,
-                            "stake": "0"
+                            "stake": "0",
+                            "unbonding": unbonding
                         })),
                     }
                 }
                 _ => rpc_error(id, -32602, "Invalid account id: expected 32 bytes hex".into()),
             }
         }
-        "boing_getAccountProof" => {
+        "boing_getDelegators" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let hex_account = match params {
+                Some(v) if !v.is_empty() => v[0].clone(),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_validator_id]".into()))),
+            };
+            match parse_account_id(&hex_account) {
+                Some(validator) => {
+                    let n = node.read().await;
+                    let staking = n.state.staking();
+                    let delegators: Vec<_> = staking
+                        .delegators(&validator)
+                        .into_iter()
+                        .map(|(delegator, amount)| serde_json::json!({
+                            "delegator": hex::encode(delegator.0),
+                            "amount": amount.to_string()
+                        }))
+                        .collect();
+                    rpc_ok(id, serde_json::json!({
+                        "registered": staking.validator(&validator).is_some(),
+                        "commission_bps": staking.validator(&validator).map(|r| r.commission_bps),
+                        "stake": n.state.get(&validator).map(|s| s.stake).unwrap_or(0).to_string(),
+                        "delegated": staking.delegated_to(&validator).to_string(),
+                        "voting_power": n.state.voting_power(&validator).to_string(),
+                        "delegators": delegators
+                    }))
+                }
+                None => rpc_error(id, -32602, "Invalid account id: expected 32 bytes hex".into()),
+            }
+        }
+        "boing_getValidatorStats" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let hex_account = match params {
+                Some(v) if !v.is_empty() => v[0].clone(),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_validator_id]".into()))),
+            };
+            match parse_account_id(&hex_account) {
+                Some(validator) => {
+                    let n = node.read().await;
+                    let staking = n.state.staking();
+                    let missed = staking.liveness().missed(&validator);
+                    let params = &n.genesis.params;
+                    rpc_ok(id, serde_json::json!({
+                        "window": params.liveness_window,
+                        "max_missed": params.liveness_max_missed,
+                        "missed_proposals": missed.proposals.len(),
+                        "missed_votes": missed.votes.len(),
+                        "jailed": staking.is_jailed(&validator),
+                        "jailed_until": staking.jailed_until(&validator).filter(|h| *h != u64::MAX)
+                    }))
+                }
+                None => rpc_error(id, -32602, "Invalid account id: expected 32 bytes hex".into()),
+            }
+        }
+        "boing_listRelays" => {
+            let n = node.read().await;
+            let registry = n.state.staking().relays();
+            let mut relays: Vec<_> = registry.iter().collect();
+            relays.sort_by(|a, b| {
+                (a.leaving, std::cmp::Reverse(a.reputation), std::cmp::Reverse(a.stake))
+                    .cmp(&(b.leaving, std::cmp::Reverse(b.reputation), std::cmp::Reverse(b.stake)))
+            });
+            let relays: Vec<_> = relays
+                .into_iter()
+                .map(|r| serde_json::json!({
+                    "operator": hex::encode(r.server_id),
+                    "multiaddr": r.multiaddr,
+                    "stake": r.stake.to_string(),
+                    "reputation": r.reputation.score,
+                    "registered_at": r.registered_at,
+                    "leaving": r.leaving,
+                    "reports": registry.reports(&AccountId(r.server_id)).len()
+                }))
+                .collect();
+            rpc_ok(id, serde_json::json!(relays))
+        }
+        "boing_getSlashes" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let hex_account = match params {
+                Some(v) if !v.is_empty() => v[0].clone(),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_validator_id]".into()))),
+            };
+            match parse_account_id(&hex_account) {
+                Some(validator) => {
+                    let n = node.read().await;
+                    let staking = n.state.staking();
+                    let registry = staking.slashes();
+                    let slashes: Vec<_> = registry
+                        .list_slashes()
+                        .into_iter()
+                        .filter(|s| s.validator == validator.0)
+                        .map(|s| {
+                            let appeal = registry.list_appeals().into_iter().find(|a| a.slash_id == s.id);
+                            serde_json::json!({
+                                "id": s.id,
+                                "amount": s.amount.to_string(),
+                                "reason": slash_reason_name(&s.reason),
+                                "block_height": s.block_height,
+                                "appeal_deadline": s.appeal_deadline,
+                                "appeal": appeal.map(|a| serde_json::json!({
+                                    "id": a.id,
+                                    "status": format!("{:?}", a.status)
+                                }))
+                            })
+                        })
+                        .collect();
+                    rpc_ok(id, serde_json::json!({
+                        "jailed": staking.is_jailed(&validator),
+                        "slashes": slashes
+                    }))
+                }
+                None => rpc_error(id, -32602, "Invalid account id: expected 32 bytes hex".into()),
+            }
+        }
+        "boing_getDelegations" => {
             let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
             let hex_account = match params {
                 Some(v) if !v.is_empty() => v[0].clone(),
-                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_account_id]".into()))),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_delegator_id]".into()))),
+            };
+            match parse_account_id(&hex_account) {
+                Some(delegator) => {
+                    let n = node.read().await;
+                    let delegations: Vec<_> = n
+                        .state
+                        .staking()
+                        .delegations_of(&delegator)
+                        .into_iter()
+                        .map(|(validator, amount)| serde_json::json!({
+                            "validator": hex::encode(validator.0),
+                            "amount": amount.to_string()
+                        }))
+                        .collect();
+                    rpc_ok(id, serde_json::json!({ "delegations": delegations }))
+                }
+                None => rpc_error(id, -32602, "Invalid account id: expected 32 bytes hex".into()),
+            }
+        }
+        "boing_getAccountProof" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let (hex_account, params_tag) = match params {
+                Some(v) if !v.is_empty() => (v[0].clone(), v.get(1).cloned()),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_account_id, block_tag?]".into()))),
+            };
+            let tag = match parse_block_tag(params_tag.as_ref()) {
+                Ok(t) => t,
+                Err(e) => return (StatusCode::OK, Json(rpc_error(id, -32602, e))),
             };
             match hex::decode(hex_account.trim_start_matches("0x")) {
                 Ok(bytes) if bytes.len() == 32 => {
@@ -228,7 +588,15 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
                     arr.copy_from_slice(&bytes);
                     let account_id = boing_primitives::AccountId(arr);
                     let mut n = node.write().await;
-                    if let Some(proof) = n.state.prove_account(&account_id) {
+                    let mut tagged = match tagged_state(&n, tag) {
+                        Ok(t) => t,
+                        Err(e) => return (StatusCode::OK, Json(rpc_error(id, -32000, e))),
+                    };
+                    let state = match tagged.as_mut() {
+                        Some(state) => state,
+                        None => &mut n.state,
+                    };
+                    if let Some(proof) = state.prove_account(&account_id) {
                         match bincode::serialize(&proof) {
                             Ok(ser) => rpc_ok(id, serde_json::json!({
                                 "proof": hex::encode(ser),
@@ -276,12 +644,14 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
             match hex::decode(hex_tx.trim_start_matches("0x")) {
                 Ok(bytes) => match bincode::deserialize::<SignedTransaction>(&bytes) {
                     Ok(signed) => {
-                        let mut state_copy = {
+                        let (mut state_copy, vm, ctx) = {
                             let n = node.read().await;
-                            n.state.snapshot()
+                            // The next block's beacon is unknown; simulate with the latest one.
+                            let ctx = boing_execution::BlockContext::at_height(n.chain.height() + 1)
+                                .with_randomness(n.state.beacon());
+                            (n.state.snapshot(), n.vm.clone(), ctx)
                         };
-                        let vm = boing_execution::Vm::new();
-                        match vm.execute(&signed.tx, &mut state_copy) {
+                        match vm.execute(&signed.tx, &mut state_copy, &ctx) {
                             Ok(gas) => rpc_ok(id, serde_json::json!({"gas_used": gas, "success": true})),
                             Err(e) => rpc_ok(id, serde_json::json!({"gas_used": 0, "success": false, "error": format!("{}", e)})),
                         }
@@ -293,23 +663,26 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
         }
         "boing_getBlockByHeight" => {
             let params = req.params.and_then(|p| serde_json::from_value::<Vec<serde_json::Value>>(p).ok());
-            let height = match params {
-                Some(v) if !v.is_empty() => v[0].as_u64(),
-                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [height: u64]".into()))),
+            let param = match params {
+                Some(v) if !v.is_empty() => v[0].clone(),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [height: u64 | block_tag]".into()))),
             };
-            let height = match height {
-                Some(h) => h,
-                None => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid height: expected u64".into()))),
+            let n = node.read().await;
+            let block = match (param.as_u64(), param.as_str().map(str::parse::<BlockTag>)) {
+                (Some(h), _) => n.chain.get_block_by_height(h),
+                (None, Some(Ok(tag))) => n.chain.get_block_by_tag(tag),
+                (None, Some(Err(e))) => return (StatusCode::OK, Json(rpc_error(id, -32602, e.to_string()))),
+                (None, None) => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid height: expected u64 or latest, safe, finalized".into()))),
             };
+            match block {
+                Some(block) => rpc_ok(id, block_json(&block)),
+                None => rpc_ok(id, serde_json::Value::Null),
+            }
+        }
+        "boing_getFinalizedBlock" => {
             let n = node.read().await;
-            match n.chain.get_block_by_height(height) {
-                Some(block) => {
-                    let hash = block.hash();
-                    let block_json = serde_json::to_value(&block).unwrap_or(serde_json::Value::Null);
-                    let mut obj = block_json.as_object().cloned().unwrap_or_default();
-                    obj.insert("hash".to_string(), serde_json::json!(hex::encode(hash.0)));
-                    rpc_ok(id, serde_json::Value::Object(obj))
-                }
+            match n.chain.get_block_by_tag(BlockTag::Finalized) {
+                Some(block) => rpc_ok(id, block_json(&block)),
                 None => rpc_ok(id, serde_json::Value::Null),
             }
         }
@@ -329,12 +702,7 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
             let hash = boing_primitives::Hash(arr);
             let n = node.read().await;
             match n.chain.get_block_by_hash(&hash) {
-                Some(block) => {
-                    let block_json = serde_json::to_value(&block).unwrap_or(serde_json::Value::Null);
-                    let mut obj = block_json.as_object().cloned().unwrap_or_default();
-                    obj.insert("hash".to_string(), serde_json::json!(hex::encode(block.hash().0)));
-                    rpc_ok(id, serde_json::Value::Object(obj))
-                }
+                Some(block) => rpc_ok(id, block_json(&block)),
                 None => rpc_ok(id, serde_json::Value::Null),
             }
         }
@@ -418,7 +786,10 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
                         match n.submit_intent(signed) {
                             Ok(intent_id) => {
                                 info!("RPC: intent submitted");
-                                rpc_ok(id, serde_json::json!({"intent_id": hex::encode(intent_id.0)}))
+                                rpc_ok(id, serde_json::json!({
+                                    "intent_id": hex::encode(intent_id.0),
+                                    "status": IntentStatus::Pending.as_str()
+                                }))
                             }
                             Err(e) => rpc_error(id, -32000, format!("{}", e)),
                         }
@@ -428,6 +799,108 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
                 Err(e) => rpc_error(id, -32602, format!("Invalid hex: {}", e)),
             }
         }
+        "boing_getIntents" => {
+            let n = node.read().await;
+            let intents: Vec<_> = n
+                .intent_pool
+                .pending()
+                .iter()
+                .map(|(intent_id, signed)| intent_json(intent_id, signed, IntentStatus::Pending))
+                .collect();
+            rpc_ok(id, serde_json::json!(intents))
+        }
+        "boing_getIntent" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let hex_id = match params {
+                Some(v) if !v.is_empty() => v[0].clone(),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_intent_id]".into()))),
+            };
+            let bytes = match hex::decode(hex_id.trim_start_matches("0x")) {
+                Ok(b) if b.len() == 32 => b,
+                Ok(_) => return (StatusCode::OK, Json(rpc_error(id, -32602, "Intent id must be 32 bytes".into()))),
+                Err(e) => return (StatusCode::OK, Json(rpc_error(id, -32602, format!("Invalid hex: {}", e)))),
+            };
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&bytes);
+            let intent_id = Hash(arr);
+            let n = node.read().await;
+            match n.intent_pool.lookup(&intent_id) {
+                Some((signed, status)) => rpc_ok(id, intent_json(&intent_id, &signed, status)),
+                None => rpc_ok(id, serde_json::Value::Null),
+            }
+        }
+        "boing_submitSolution" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let hex_tx = match params {
+                Some(v) if !v.is_empty() => v[0].clone(),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_signed_tx]".into()))),
+            };
+            match hex::decode(hex_tx.trim_start_matches("0x")) {
+                Ok(bytes) => match bincode::deserialize::<SignedTransaction>(&bytes) {
+                    Ok(signed) => {
+                        let tx_hash = signed.tx.id();
+                        let n = node.read().await;
+                        match n.submit_solution(signed) {
+                            Ok(intent_id) => {
+                                info!("RPC: solution submitted for intent {}", hex::encode(intent_id.0));
+                                rpc_ok(id, serde_json::json!({
+                                    "intent_id": hex::encode(intent_id.0),
+                                    "tx_hash": hex::encode(tx_hash.0)
+                                }))
+                            }
+                            Err(e) => rpc_error(id, -32000, format!("{}", e)),
+                        }
+                    }
+                    Err(e) => rpc_error(id, -32602, format!("Invalid transaction: {}", e)),
+                },
+                Err(e) => rpc_error(id, -32602, format!("Invalid hex: {}", e)),
+            }
+        }
+        "boing_postSignalingMessage" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let (hex_message, deposit) = match params {
+                Some(v) if !v.is_empty() => (v[0].clone(), v.get(1).cloned()),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_signed_message, deposit?]".into()))),
+            };
+            let deposit = match deposit.map(|d| d.parse::<u128>()).transpose() {
+                Ok(d) => d.unwrap_or(0),
+                Err(_) => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid deposit: expected decimal string".into()))),
+            };
+            match hex::decode(hex_message.trim_start_matches("0x")) {
+                Ok(bytes) => match bincode::deserialize::<SignedSignalingMessage>(&bytes) {
+                    Ok(signed) => {
+                        let n = node.read().await;
+                        match n.post_signaling(signed, deposit) {
+                            Ok(message_id) => rpc_ok(id, serde_json::json!({"message_id": hex::encode(message_id.0)})),
+                            Err(e) => rpc_error(id, -32000, format!("{}", e)),
+                        }
+                    }
+                    Err(e) => rpc_error(id, -32602, format!("Invalid signaling message: {}", e)),
+                },
+                Err(e) => rpc_error(id, -32602, format!("Invalid hex: {}", e)),
+            }
+        }
+        "boing_getSignalingMessages" | "boing_getPendingOffers" => {
+            let params = req.params.and_then(|p| serde_json::from_value::<Vec<String>>(p).ok());
+            let hex_query = match params {
+                Some(v) if !v.is_empty() => v[0].clone(),
+                _ => return (StatusCode::OK, Json(rpc_error(id, -32602, "Invalid params: expected [hex_signed_query]".into()))),
+            };
+            match hex::decode(hex_query.trim_start_matches("0x")) {
+                Ok(bytes) => match bincode::deserialize::<SignalingQuery>(&bytes) {
+                    Ok(query) => {
+                        let n = node.read().await;
+                        let pending_only = req.method == "boing_getPendingOffers";
+                        match n.signaling.read(&query, pending_only, unix_now()) {
+                            Ok(signals) => rpc_ok(id, serde_json::json!(signals.iter().map(signal_json).collect::<Vec<_>>())),
+                            Err(e) => rpc_error(id, -32000, format!("{}", e)),
+                        }
+                    }
+                    Err(e) => rpc_error(id, -32602, format!("Invalid signaling query: {}", e)),
+                },
+                Err(e) => rpc_error(id, -32602, format!("Invalid hex: {}", e)),
+            }
+        }
         "boing_faucetRequest" => {
             let Some(ref faucet_signer) = state.faucet_signer else {
                 return (StatusCode::OK, Json(rpc_error(id, -32601, "Faucet not enabled on this node.".into())));
@@ -479,6 +952,7 @@ async fn handle_rpc(State(state): State<RpcState>, Json(req): Json<JsonRpcReques
                 return (StatusCode::OK, Json(rpc_error(id, -32000, "Faucet balance too low.".into())));
             }
             let tx = Transaction {
+                chain_id: n.genesis.chain_id,
                 nonce,
                 sender: faucet_id,
                 payload: TransactionPayload::Transfer {
@@ -538,6 +1012,7 @@ pub fn rpc_router(
 
     Router::new()
         .route("/", post(handle_rpc))
+        .route("/intents/subscribe", get(subscribe_intents))
         .with_state(RpcState {
             node,
             rate_limiter,
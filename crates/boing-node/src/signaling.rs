@@ -0,0 +1,403 @@
+//! WebRTC signaling — the native implementation of [`SignalingContract`].
+//!
+//! dApps exchange WebRTC offers, answers and ICE candidates through the node instead of a
+//! central signaling server. Posting is gated the way the contract design describes:
+//! - An offer reserves a deposit (at least `min_deposit`) against the sender's balance.
+//!   The reservation is released when the recipient answers or the offer expires; nothing
+//!   is transferred, so a sender cannot have more offers open than its balance covers.
+//! - An answer must reply to an open offer, and ICE candidates are only accepted between
+//!   peers with an offer open in either direction.
+//! - Each sender may post at most `max_per_window` messages per rate-limit window.
+//! - Every message expires `expiration_secs` after it is posted.
+//!
+//! Reading a recipient's messages takes a [`SignalingQuery`] signed by the recipient, so
+//! SDP and ICE payloads are only handed to the peer they are addressed to.
+//!
+//! Signaling is node-local: messages are held in memory by the node they were posted to and
+//! are not gossiped, so both peers must use the same node. Deposits are reserved by that node
+//! only, not in chain state. It enforces them on what passes through it: transactions it
+//! accepts may not spend a sender's reserved deposits, and after each commit it withdraws the
+//! newest offers of any sender whose balance no longer covers them.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+use boing_p2p::{
+    ContentPointer, SignalingContract, SignalingDepositConfig, SignalingMessage, SignalingMessageKind,
+    SignalingPostResult, SignalingQuery, SignalingRateLimit, SignedSignalingMessage, MAX_SIGNALING_PAYLOAD,
+};
+use boing_primitives::{hasher, AccountId, Hash};
+
+use crate::node::BoingNode;
+
+/// How far ahead of our clock a signed timestamp may be.
+pub const MAX_CLOCK_SKEW_SECS: u64 = 30;
+
+/// How long a signed [`SignalingQuery`] is accepted either side of its timestamp.
+pub const MAX_QUERY_AGE_SECS: u64 = 60;
+
+/// A signaling message accepted by the node.
+#[derive(Clone, Debug)]
+pub struct StoredSignal {
+    pub id: Hash,
+    pub message: SignalingMessage,
+    /// Unix seconds when the node accepted the message.
+    pub posted_at: u64,
+    pub expires_at: u64,
+    /// Deposit still reserved by an offer; zero once answered and for other kinds.
+    pub deposit: u128,
+    /// Whether an offer has been answered.
+    pub answered: bool,
+}
+
+/// In-memory signaling store.
+#[derive(Clone, Debug)]
+pub struct SignalingStore {
+    /// Only messages signed for this chain are accepted.
+    chain_id: u64,
+    deposit: SignalingDepositConfig,
+    rate_limit: SignalingRateLimit,
+    inner: Arc<RwLock<SignalingInner>>,
+}
+
+#[derive(Debug, Default)]
+struct SignalingInner {
+    messages: HashMap<Hash, StoredSignal>,
+    /// Post times per sender inside the current rate-limit window, oldest first.
+    sent: HashMap<[u8; 32], VecDeque<u64>>,
+}
+
+impl SignalingStore {
+    pub fn new(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            deposit: SignalingDepositConfig::default(),
+            rate_limit: SignalingRateLimit::default(),
+            inner: Arc::new(RwLock::new(SignalingInner::default())),
+        }
+    }
+
+    pub fn with_deposit(mut self, deposit: SignalingDepositConfig) -> Self {
+        self.deposit = deposit;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, rate_limit: SignalingRateLimit) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    pub fn deposit_config(&self) -> &SignalingDepositConfig {
+        &self.deposit
+    }
+
+    /// Post a signed message. `balance` is the sender's spendable balance, which must
+    /// cover an offer's deposit on top of the deposits it already has reserved.
+    pub fn post(
+        &self,
+        signed: SignedSignalingMessage,
+        deposit: u128,
+        balance: u128,
+        now: u64,
+    ) -> Result<Hash, SignalingError> {
+        if signed.chain_id != self.chain_id {
+            return Err(SignalingError::WrongChainId { expected: self.chain_id, got: signed.chain_id });
+        }
+        signed.verify().map_err(|_| SignalingError::InvalidSignature)?;
+        if signed.timestamp_secs + self.deposit.expiration_secs <= now
+            || signed.timestamp_secs > now + MAX_CLOCK_SKEW_SECS
+        {
+            return Err(SignalingError::StaleTimestamp);
+        }
+        let id = signed.id();
+        self.accept(id, signed.message, deposit, balance, now)
+    }
+
+    /// Apply the posting rules to a message whose sender is already authenticated.
+    pub fn accept(
+        &self,
+        id: Hash,
+        message: SignalingMessage,
+        deposit: u128,
+        balance: u128,
+        now: u64,
+    ) -> Result<Hash, SignalingError> {
+        check_content(&message)?;
+        let mut inner = self.inner.write().unwrap();
+        inner.prune(now, self.rate_limit.window_secs);
+        if inner.messages.contains_key(&id) {
+            return Err(SignalingError::Duplicate);
+        }
+        if inner.sent.get(&message.sender).map_or(0, |s| s.len()) >= self.rate_limit.max_per_window as usize {
+            return Err(SignalingError::RateLimited);
+        }
+
+        let (sender, recipient) = (message.sender, message.recipient);
+        let reserved = match message.kind {
+            SignalingMessageKind::Offer => {
+                if deposit < self.deposit.min_deposit {
+                    return Err(SignalingError::DepositTooLow { min: self.deposit.min_deposit, got: deposit });
+                }
+                let locked = inner.locked(&sender);
+                if locked.saturating_add(deposit) > balance {
+                    return Err(SignalingError::InsufficientBalance { locked, deposit, balance });
+                }
+                deposit
+            }
+            SignalingMessageKind::Answer => {
+                // Answer the oldest open offer from the recipient; its deposit is released.
+                let offer = inner
+                    .messages
+                    .values_mut()
+                    .filter(|m| is_open_offer(m, recipient, sender))
+                    .min_by_key(|m| (m.posted_at, m.id.0))
+                    .ok_or(SignalingError::NoPendingOffer)?;
+                offer.answered = true;
+                offer.deposit = 0;
+                0
+            }
+            SignalingMessageKind::IceCandidate => {
+                let session = inner.messages.values().any(|m| {
+                    m.message.kind == SignalingMessageKind::Offer
+                        && ((m.message.sender, m.message.recipient) == (sender, recipient)
+                            || (m.message.sender, m.message.recipient) == (recipient, sender))
+                });
+                if !session {
+                    return Err(SignalingError::NoSession);
+                }
+                0
+            }
+        };
+
+        inner.sent.entry(sender).or_default().push_back(now);
+        inner.messages.insert(
+            id,
+            StoredSignal {
+                id,
+                message,
+                posted_at: now,
+                expires_at: now + self.deposit.expiration_secs,
+                deposit: reserved,
+                answered: false,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Messages for the query's recipient, oldest first: only unanswered offers if
+    /// `pending_only`, otherwise every unexpired message.
+    pub fn read(&self, query: &SignalingQuery, pending_only: bool, now: u64) -> Result<Vec<StoredSignal>, SignalingError> {
+        if query.chain_id != self.chain_id {
+            return Err(SignalingError::WrongChainId { expected: self.chain_id, got: query.chain_id });
+        }
+        if query.timestamp_secs.abs_diff(now) > MAX_QUERY_AGE_SECS {
+            return Err(SignalingError::StaleTimestamp);
+        }
+        query.verify().map_err(|_| SignalingError::InvalidSignature)?;
+        Ok(if pending_only {
+            self.pending_offers(&query.recipient, now)
+        } else {
+            self.messages_for(&query.recipient, now)
+        })
+    }
+
+    /// Unanswered, unexpired offers for `recipient`, oldest first.
+    pub fn pending_offers(&self, recipient: &[u8; 32], now: u64) -> Vec<StoredSignal> {
+        self.collect(now, |m| {
+            m.message.recipient == *recipient && m.message.kind == SignalingMessageKind::Offer && !m.answered
+        })
+    }
+
+    /// Every unexpired message addressed to `recipient`, oldest first.
+    pub fn messages_for(&self, recipient: &[u8; 32], now: u64) -> Vec<StoredSignal> {
+        self.collect(now, |m| m.message.recipient == *recipient)
+    }
+
+    /// Deposits `sender` has reserved in unanswered, unexpired offers.
+    pub fn locked_deposit(&self, sender: &[u8; 32], now: u64) -> u128 {
+        self.collect(now, |m| m.message.sender == *sender).iter().map(|m| m.deposit).sum()
+    }
+
+    /// Withdraw offers whose sender's balance no longer covers its reserved deposits,
+    /// newest first until the rest are covered. Returns how many offers were withdrawn.
+    pub fn withdraw_uncovered(&self, balance_of: impl Fn(&[u8; 32]) -> u128, now: u64) -> usize {
+        let mut inner = self.inner.write().unwrap();
+        inner.prune(now, self.rate_limit.window_secs);
+        let mut locked: HashMap<[u8; 32], u128> = HashMap::new();
+        for m in inner.messages.values().filter(|m| m.deposit > 0) {
+            *locked.entry(m.message.sender).or_default() += m.deposit;
+        }
+        let mut withdrawn = 0;
+        for (sender, mut total) in locked {
+            let balance = balance_of(&sender);
+            if total <= balance {
+                continue;
+            }
+            let mut offers: Vec<_> = inner
+                .messages
+                .values()
+                .filter(|m| m.message.sender == sender && m.deposit > 0)
+                .map(|m| (m.posted_at, m.id.0, m.deposit))
+                .collect();
+            offers.sort_unstable();
+            while total > balance {
+                let Some((_, id, deposit)) = offers.pop() else { break };
+                inner.messages.remove(&Hash(id));
+                total -= deposit;
+                withdrawn += 1;
+            }
+        }
+        withdrawn
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap().messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn collect(&self, now: u64, filter: impl Fn(&StoredSignal) -> bool) -> Vec<StoredSignal> {
+        let inner = self.inner.read().unwrap();
+        let mut out: Vec<_> = inner
+            .messages
+            .values()
+            .filter(|m| m.expires_at > now && filter(m))
+            .cloned()
+            .collect();
+        out.sort_by_key(|m| (m.posted_at, m.id.0));
+        out
+    }
+}
+
+impl SignalingInner {
+    /// Drop expired messages (releasing their deposits) and posts outside the rate window.
+    fn prune(&mut self, now: u64, window_secs: u64) {
+        self.messages.retain(|_, m| m.expires_at > now);
+        self.sent.retain(|_, times| {
+            while times.front().is_some_and(|t| t + window_secs <= now) {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+    }
+
+    fn locked(&self, sender: &[u8; 32]) -> u128 {
+        self.messages
+            .values()
+            .filter(|m| m.message.sender == *sender)
+            .map(|m| m.deposit)
+            .sum()
+    }
+}
+
+fn is_open_offer(m: &StoredSignal, sender: [u8; 32], recipient: [u8; 32]) -> bool {
+    m.message.kind == SignalingMessageKind::Offer
+        && !m.answered
+        && m.message.sender == sender
+        && m.message.recipient == recipient
+}
+
+/// Exactly one of an inline payload (within the size limit) or a content pointer.
+fn check_content(message: &SignalingMessage) -> Result<(), SignalingError> {
+    if message.sender == message.recipient {
+        return Err(SignalingError::SelfAddressed);
+    }
+    match (&message.payload, &message.content_pointer) {
+        (Some(payload), None) if payload.len() <= MAX_SIGNALING_PAYLOAD => Ok(()),
+        (Some(payload), None) => Err(SignalingError::PayloadTooLarge { size: payload.len(), max: MAX_SIGNALING_PAYLOAD }),
+        (None, Some(pointer)) if !pointer.cid.is_empty() => Ok(()),
+        _ => Err(SignalingError::InvalidContent),
+    }
+}
+
+/// Current Unix time in seconds.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Post through the contract interface, for in-process callers that authenticated the
+/// sender themselves.
+fn post_unsigned(node: &BoingNode, message: SignalingMessage, deposit: u128) -> SignalingPostResult {
+    let now = unix_now();
+    let mut h = hasher();
+    h.update(&bincode::serialize(&(now, &message)).unwrap_or_default());
+    let id = Hash(*h.finalize().as_bytes());
+    let balance = node.state.get(&AccountId(message.sender)).map_or(0, |a| a.balance);
+    match node.signaling.accept(id, message, deposit, balance, now) {
+        Ok(id) => SignalingPostResult { ok: true, message_id: Some(id.0) },
+        Err(e) => {
+            tracing::debug!("Signaling: message rejected: {}", e);
+            SignalingPostResult { ok: false, message_id: None }
+        }
+    }
+}
+
+impl SignalingContract for BoingNode {
+    fn post_offer(
+        &self,
+        sender: [u8; 32],
+        recipient: [u8; 32],
+        payload: Option<Vec<u8>>,
+        content_pointer: Option<ContentPointer>,
+        deposit: u128,
+    ) -> SignalingPostResult {
+        let message = SignalingMessage { kind: SignalingMessageKind::Offer, sender, recipient, payload, content_pointer };
+        post_unsigned(self, message, deposit)
+    }
+
+    fn post_answer(
+        &self,
+        sender: [u8; 32],
+        recipient: [u8; 32],
+        payload: Option<Vec<u8>>,
+        content_pointer: Option<ContentPointer>,
+    ) -> SignalingPostResult {
+        let message = SignalingMessage { kind: SignalingMessageKind::Answer, sender, recipient, payload, content_pointer };
+        post_unsigned(self, message, 0)
+    }
+
+    fn post_ice_candidate(&self, sender: [u8; 32], recipient: [u8; 32], payload: Vec<u8>) -> SignalingPostResult {
+        post_unsigned(self, SignalingMessage::ice_candidate(sender, recipient, payload), 0)
+    }
+
+    fn get_pending_offers(&self, recipient: [u8; 32]) -> Vec<SignalingMessage> {
+        self.signaling
+            .pending_offers(&recipient, unix_now())
+            .into_iter()
+            .map(|m| m.message)
+            .collect()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignalingError {
+    #[error("Wrong chain id: expected {expected}, got {got}")]
+    WrongChainId { expected: u64, got: u64 },
+    #[error("Invalid signature")]
+    InvalidSignature,
+    #[error("Timestamp is expired or too far in the future")]
+    StaleTimestamp,
+    #[error("Message addressed to its sender")]
+    SelfAddressed,
+    #[error("Message needs either an inline payload or a content pointer")]
+    InvalidContent,
+    #[error("Payload of {size} bytes exceeds {max}; use a content pointer")]
+    PayloadTooLarge { size: usize, max: usize },
+    #[error("Duplicate message")]
+    Duplicate,
+    #[error("Rate limit exceeded")]
+    RateLimited,
+    #[error("Deposit {got} below minimum {min}")]
+    DepositTooLow { min: u128, got: u128 },
+    #[error("Insufficient balance: {locked} reserved + {deposit} deposit exceeds balance {balance}")]
+    InsufficientBalance { locked: u128, deposit: u128, balance: u128 },
+    #[error("No pending offer from the recipient to answer")]
+    NoPendingOffer,
+    #[error("No open offer between sender and recipient")]
+    NoSession,
+}
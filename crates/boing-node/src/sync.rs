@@ -0,0 +1,392 @@
+//! Block sync — catches a node up to its peers with header-first range downloads.
+//!
+//! A node behind its peers first downloads headers from its head onward and checks that
+//! they link by parent hash and were proposed by the view leader of the validator set we
+//! know for their height (signature and VRF). Headers past the last height whose set is
+//! known wait until our head gets closer. It then fetches the bodies for those headers in
+//! ranges, from several peers in parallel, and imports them in height order. A body must
+//! hash to the header already downloaded for its height, so once the header chain is fixed
+//! no peer can substitute blocks; the blocks themselves are validated as usual on import.
+//!
+//! Each downloaded run of headers remembers the peer that served it. A body that does not
+//! match, or a block that fails import, drops that run (and everything above it) and names
+//! the peers to penalize; the rest of the sync carries on.
+//!
+//! The state machine is driven by the node's sync loop, which asks for the next requests
+//! on every tick and routes the responses back here.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use boing_p2p::{BlockRequest, PeerId, SealedHeader, MAX_RANGE_BLOCKS, MAX_RANGE_HEADERS};
+use boing_primitives::{Block, BlockHeader, Hash, ValidatorSet};
+
+/// Most headers held above the head before pausing header download to fetch bodies.
+pub const MAX_HEADERS_AHEAD: usize = 8 * MAX_RANGE_HEADERS as usize;
+/// Body ranges in flight per connected peer.
+const REQUESTS_PER_PEER: usize = 2;
+/// A request unanswered for this long is dropped and sent again on a later tick.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sync phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncPhase {
+    /// Caught up; polls a peer for headers past our head.
+    Idle,
+    /// Downloading headers.
+    Headers,
+    /// Downloading bodies for the downloaded headers and importing them.
+    Bodies,
+}
+
+impl SyncPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncPhase::Idle => "idle",
+            SyncPhase::Headers => "headers",
+            SyncPhase::Bodies => "bodies",
+        }
+    }
+}
+
+/// Sync progress (boing_syncing).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyncStatus {
+    pub phase: SyncPhase,
+    /// Our head height when this sync started.
+    pub starting_block: u64,
+    /// Our head height.
+    pub current_block: u64,
+    /// Highest header downloaded.
+    pub highest_block: u64,
+}
+
+/// Header-first block sync state machine.
+#[derive(Debug)]
+pub struct BlockSync {
+    phase: SyncPhase,
+    starting_block: u64,
+    /// Downloaded headers above our head, by height; each links to the one below.
+    headers: BTreeMap<u64, BlockHeader>,
+    /// Peer that served the headers from each height up to the next entry.
+    header_sources: BTreeMap<u64, PeerId>,
+    /// Downloaded bodies not yet imported, by height, with the peer that served them.
+    bodies: BTreeMap<u64, (Block, PeerId)>,
+    /// Outstanding header request: start height and when it was sent.
+    header_request: Option<(u64, Instant)>,
+    /// Outstanding body ranges: start height -> (count, when sent).
+    body_requests: BTreeMap<u64, (u64, Instant)>,
+    /// Whether the last header batch was full, so peers likely have more.
+    more_headers: bool,
+}
+
+impl BlockSync {
+    pub fn new() -> Self {
+        Self {
+            phase: SyncPhase::Idle,
+            starting_block: 0,
+            headers: BTreeMap::new(),
+            header_sources: BTreeMap::new(),
+            bodies: BTreeMap::new(),
+            header_request: None,
+            body_requests: BTreeMap::new(),
+            more_headers: false,
+        }
+    }
+
+    pub fn phase(&self) -> SyncPhase {
+        self.phase
+    }
+
+    /// Progress while syncing; None when idle.
+    pub fn status(&self, head_height: u64) -> Option<SyncStatus> {
+        if self.phase == SyncPhase::Idle {
+            return None;
+        }
+        let highest = self.headers.keys().next_back().copied().unwrap_or(head_height);
+        Some(SyncStatus {
+            phase: self.phase,
+            starting_block: self.starting_block,
+            current_block: head_height,
+            highest_block: highest.max(head_height),
+        })
+    }
+
+    /// Drop everything downloaded and go back to idle.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Requests to send this tick, given our head and the number of connected peers.
+    /// The caller spreads them across distinct peers.
+    pub fn next_requests(&mut self, head: (u64, Hash), peers: usize, now: Instant) -> Vec<BlockRequest> {
+        self.advance(head);
+        if self.header_request.is_some_and(|(_, sent)| now.duration_since(sent) >= REQUEST_TIMEOUT) {
+            self.header_request = None;
+        }
+        self.body_requests
+            .retain(|_, (_, sent)| now.duration_since(*sent) < REQUEST_TIMEOUT);
+        if peers == 0 {
+            return vec![];
+        }
+
+        match self.phase {
+            SyncPhase::Idle | SyncPhase::Headers => {
+                if self.header_request.is_some() {
+                    return vec![];
+                }
+                let start = self.next_header_height(head.0);
+                self.header_request = Some((start, now));
+                vec![BlockRequest::Headers { start, count: MAX_RANGE_HEADERS }]
+            }
+            SyncPhase::Bodies => self.body_ranges(head.0, peers * REQUESTS_PER_PEER, now),
+        }
+    }
+
+    /// Headers received from `peer` for a request starting at `start`. Stale or unlinked
+    /// batches are ignored; the request is retried on a later tick. `validators` gives the
+    /// validator set for a height, or None if it is not known yet; headers from there on are
+    /// left for a later request. A header its set's leader did not propose is an error
+    /// naming `peer`, and the batch is dropped.
+    pub fn on_headers<'a>(
+        &mut self,
+        head: (u64, Hash),
+        peer: PeerId,
+        start: u64,
+        headers: Vec<SealedHeader>,
+        validators: impl Fn(u64) -> Option<&'a ValidatorSet>,
+    ) -> Result<(), SyncError> {
+        if self.header_request.map(|(s, _)| s) != Some(start) {
+            return Ok(());
+        }
+        self.header_request = None;
+        if headers.is_empty() {
+            self.more_headers = false;
+            if self.phase == SyncPhase::Headers {
+                self.phase = SyncPhase::Bodies;
+            }
+            self.advance(head);
+            return Ok(());
+        }
+
+        let mut prev = match self.headers.iter().next_back() {
+            Some((&height, header)) => (height, header.hash()),
+            None => head,
+        };
+        if start != prev.0 + 1 {
+            return Ok(());
+        }
+        let received = headers.len();
+        let mut accepted = Vec::with_capacity(headers.len());
+        for sealed in headers {
+            let header = &sealed.header;
+            if header.height != prev.0 + 1 || header.parent_hash != prev.1 {
+                tracing::debug!("Sync: header {} does not link to {}; dropping batch", header.height, prev.0);
+                return Ok(());
+            }
+            let Some(set) = validators(header.height) else {
+                break;
+            };
+            verify_header(&sealed, set).map_err(|reason| SyncError::InvalidHeader {
+                height: header.height,
+                reason,
+                peers: vec![peer],
+            })?;
+            prev = (header.height, header.hash());
+            accepted.push(sealed.header);
+        }
+        // Headers past a validator set we do not know yet wait until our head has imported
+        // the bodies below them.
+        let cut = accepted.len() < received;
+        if accepted.is_empty() {
+            if cut && !self.headers.is_empty() {
+                self.more_headers = true;
+                self.phase = SyncPhase::Bodies;
+            }
+            return Ok(());
+        }
+
+        if self.phase == SyncPhase::Idle {
+            self.starting_block = head.0;
+        }
+        self.more_headers = cut || received >= MAX_RANGE_HEADERS as usize;
+        self.header_sources.insert(start, peer);
+        self.headers.extend(accepted.into_iter().map(|h| (h.height, h)));
+        self.phase = if self.more_headers && !cut && self.headers.len() < MAX_HEADERS_AHEAD {
+            SyncPhase::Headers
+        } else {
+            SyncPhase::Bodies
+        };
+        Ok(())
+    }
+
+    /// Blocks received from `peer` for a range starting at `start`. A block that does not
+    /// match its downloaded header drops the headers from that run of headers on, and names
+    /// both the body's peer and the headers' peer: one of them lied.
+    pub fn on_blocks(&mut self, peer: PeerId, start: u64, blocks: Vec<Block>) -> Result<(), SyncError> {
+        if self.body_requests.remove(&start).is_none() {
+            return Ok(());
+        }
+        for block in blocks {
+            let height = block.header.height;
+            match self.headers.get(&height) {
+                Some(header) if header.hash() == block.hash() => {
+                    self.bodies.insert(height, (block, peer));
+                }
+                Some(_) => {
+                    let header_peer = self.drop_segment(height);
+                    tracing::warn!("Sync: block {} from {} does not match its header from {:?}", height, peer, header_peer);
+                    return Err(SyncError::BodyMismatch { height, peers: blame(peer, header_peer) });
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The downloaded block at `height` failed import: drop the headers from its run on.
+    /// Returns the peers that served its header and its body.
+    pub fn reject_block(&mut self, height: u64, body_peer: PeerId) -> SyncError {
+        let header_peer = self.drop_segment(height);
+        SyncError::InvalidBlock { height, peers: blame(body_peer, header_peer) }
+    }
+
+    /// Next downloaded block to import on top of `head_height`, if it has arrived, with the
+    /// peer that served it.
+    pub fn next_block(&mut self, head_height: u64) -> Option<(Block, PeerId)> {
+        self.bodies.remove(&(head_height + 1))
+    }
+
+    /// Forget what our head has passed and move between phases. Headers that no longer
+    /// extend our head (it moved to another branch) are dropped.
+    pub fn advance(&mut self, head: (u64, Hash)) {
+        self.headers = self.headers.split_off(&(head.0 + 1));
+        self.bodies = self.bodies.split_off(&(head.0 + 1));
+        match self.headers.keys().next().copied() {
+            Some(first) => {
+                // The run the first remaining header is in keeps its source.
+                let source = self.header_sources.range(..=first).next_back().map(|(_, p)| *p);
+                self.header_sources = self.header_sources.split_off(&first);
+                if let Some(peer) = source {
+                    self.header_sources.entry(first).or_insert(peer);
+                }
+            }
+            None => self.header_sources.clear(),
+        }
+        self.body_requests.retain(|start, (count, _)| start + *count > head.0 + 1);
+
+        let linked = match self.headers.iter().next() {
+            Some((&height, header)) => height == head.0 + 1 && header.parent_hash == head.1,
+            None => true,
+        };
+        if !linked {
+            tracing::debug!("Sync: head {} left the downloaded headers; restarting sync", head.0);
+            self.reset();
+            return;
+        }
+        if self.phase == SyncPhase::Bodies && self.headers.is_empty() {
+            self.phase = if self.more_headers { SyncPhase::Headers } else { SyncPhase::Idle };
+        }
+    }
+
+    /// Drop the run of headers containing `height` and everything above it, with their
+    /// bodies and requests, and fetch headers again from there. Returns the run's source.
+    fn drop_segment(&mut self, height: u64) -> Option<PeerId> {
+        let (from, peer) = self.header_sources.range(..=height).next_back().map(|(h, p)| (*h, *p))?;
+        self.headers.split_off(&from);
+        self.header_sources.split_off(&from);
+        self.bodies.split_off(&from);
+        self.body_requests.retain(|start, _| *start < from);
+        self.header_request = None;
+        self.more_headers = true;
+        if self.headers.is_empty() {
+            self.phase = SyncPhase::Headers;
+        }
+        Some(peer)
+    }
+
+    /// Height of the next header to download.
+    fn next_header_height(&self, head_height: u64) -> u64 {
+        self.headers.keys().next_back().map_or(head_height, |h| *h) + 1
+    }
+
+    /// Ranges covering downloaded headers that have neither a body nor a request, up to
+    /// `limit` requests in flight.
+    fn body_ranges(&mut self, head_height: u64, limit: usize, now: Instant) -> Vec<BlockRequest> {
+        let Some(&last) = self.headers.keys().next_back() else {
+            return vec![];
+        };
+        let mut out = Vec::new();
+        let mut height = head_height + 1;
+        while self.body_requests.len() < limit && height <= last {
+            if let Some((&start, &(count, _))) = self.body_requests.range(..=height).next_back() {
+                if height < start + count {
+                    height = start + count;
+                    continue;
+                }
+            }
+            if self.bodies.contains_key(&height) {
+                height += 1;
+                continue;
+            }
+            let next_request = self.body_requests.range(height..).next().map_or(u64::MAX, |(s, _)| *s);
+            let end = (last + 1).min(next_request).min(height + MAX_RANGE_BLOCKS as u64);
+            let count = end - height;
+            self.body_requests.insert(height, (count, now));
+            out.push(BlockRequest::Range { start: height, count: count as u32 });
+            height = end;
+        }
+        out
+    }
+}
+
+impl Default for BlockSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check that `header` was proposed by the leader of its view in `set`: it commits to the
+/// set, and carries the leader's signature and VRF proof.
+fn verify_header(sealed: &SealedHeader, set: &ValidatorSet) -> Result<(), String> {
+    let header = &sealed.header;
+    if header.validator_set_hash != set.hash() {
+        return Err("wrong validator set".into());
+    }
+    if set.leader(header.round) != header.proposer {
+        return Err("proposer is not the view leader".into());
+    }
+    if sealed.verify_signature().is_err() {
+        return Err("invalid proposer signature".into());
+    }
+    header.verify_vrf().map_err(|e| e.to_string())
+}
+
+/// Sync data a peer should not have sent, with the peers that served it (to penalize).
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    #[error("Header {height} is invalid: {reason}")]
+    InvalidHeader { height: u64, reason: String, peers: Vec<PeerId> },
+    #[error("Block {height} does not match its header")]
+    BodyMismatch { height: u64, peers: Vec<PeerId> },
+    #[error("Block {height} failed import")]
+    InvalidBlock { height: u64, peers: Vec<PeerId> },
+}
+
+impl SyncError {
+    /// Peers that served the bad data: the body's peer first, then the headers' peer.
+    pub fn peers(&self) -> &[PeerId] {
+        match self {
+            SyncError::InvalidHeader { peers, .. }
+            | SyncError::BodyMismatch { peers, .. }
+            | SyncError::InvalidBlock { peers, .. } => peers,
+        }
+    }
+}
+
+/// The peer that served a body, and the one that served its header if different.
+fn blame(body_peer: PeerId, header_peer: Option<PeerId>) -> Vec<PeerId> {
+    let mut peers = vec![body_peer];
+    peers.extend(header_peer.filter(|p| *p != body_peer));
+    peers
+}
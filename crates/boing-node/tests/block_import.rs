@@ -1,17 +1,35 @@
 //! Test block import and validation.
 
-use boing_node::block_validation::{chains_to, import_block, validate_and_execute_block};
+use boing_node::block_validation::{
+    chains_to, import_block, validate_and_execute_block, BlockValidationError,
+};
 use boing_node::chain::ChainState;
+use boing_node::genesis::GenesisConfig;
+use boing_node::node::BoingNode;
+use boing_p2p::{validate_block, GossipError};
 use boing_primitives::{
-    tx_root, AccessList, Account, AccountId, AccountState, Block, BlockHeader, Transaction,
-    TransactionPayload,
+    next_beacon, tx_root, AccessList, Account, AccountId, AccountState, Block, BlockHeader, CertificateError,
+    Hash, QuorumCertificate, Transaction, TransactionPayload, Validator, ValidatorSet, Vote,
+    VrfError,
 };
 use boing_consensus::ConsensusEngine;
-use boing_execution::BlockExecutor;
+use boing_execution::{BlockContext, BlockExecutor};
 use boing_state::StateStore;
+use ed25519_dalek::SigningKey;
+
+const CHAIN_ID: u64 = 1;
+
+fn key(i: u8) -> SigningKey {
+    SigningKey::from_bytes(&[i; 32])
+}
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
 
 fn mk_transfer(from: AccountId, to: AccountId, nonce: u64, amount: u128) -> Transaction {
     Transaction {
+        chain_id: CHAIN_ID,
         nonce,
         sender: from,
         payload: TransactionPayload::Transfer { to, amount },
@@ -19,28 +37,24 @@ fn mk_transfer(from: AccountId, to: AccountId, nonce: u64, amount: u128) -> Tran
     }
 }
 
-#[test]
-fn test_chains_to() {
-    let genesis = ChainState::genesis(AccountId([1u8; 32]));
-    let chain = ChainState::from_genesis(genesis.clone());
-    let block = Block {
-        header: BlockHeader {
-            parent_hash: genesis.hash(),
-            height: 1,
-            timestamp: 1,
-            proposer: AccountId([1u8; 32]),
-            tx_root: boing_primitives::Hash::ZERO,
-            state_root: boing_primitives::Hash::ZERO,
-        },
-        transactions: vec![],
-    };
-    assert!(chains_to(&block, chain.latest_hash(), chain.height()));
-    assert!(!chains_to(&block, chain.latest_hash(), chain.height() + 1));
+/// Key of the leader of `view` under `validators`.
+fn leader<'a>(keys: &'a [SigningKey], validators: &ValidatorSet, view: u64) -> &'a SigningKey {
+    keys.iter().find(|k| id(k) == validators.leader(view)).expect("leader is in the set")
 }
 
-#[test]
-fn test_validate_and_execute_block() {
-    let proposer = AccountId([1u8; 32]);
+/// Sign `block` as `proposer` and attach a QC with votes from `voters`.
+fn sign_and_certify(mut block: Block, proposer: &SigningKey, voters: &[&SigningKey]) -> Block {
+    block.sign(proposer);
+    let hash = block.hash();
+    let votes: Vec<Vote> = voters.iter().map(|k| Vote::new(hash, k)).collect();
+    block.qc = Some(QuorumCertificate::from_votes(hash, &votes));
+    block
+}
+
+/// Parent state funding `key`'s account, and a valid transfer block (height 1, view 0)
+/// proposed by it on that state for `validators`.
+fn transfer_block(key: &SigningKey, parent_hash: Hash, validators: &ValidatorSet) -> (StateStore, Block) {
+    let proposer = id(key);
     let to = AccountId([2u8; 32]);
     let mut parent = StateStore::new();
     parent.insert(Account {
@@ -52,82 +66,292 @@ fn test_validate_and_execute_block() {
         state: AccountState { balance: 0, nonce: 0, stake: 0 },
     });
 
-    let tx = mk_transfer(proposer, to, 0, 100);
-    let txs = vec![tx.clone()];
-    let exec = BlockExecutor::new();
+    let txs = vec![mk_transfer(proposer, to, 0, 100)];
+    let mut header = BlockHeader {
+        parent_hash,
+        height: 1,
+        round: 0,
+        timestamp: 1,
+        proposer,
+        tx_root: tx_root(&txs),
+        state_root: Hash::ZERO,
+        validator_set_hash: validators.hash(),
+        vrf: Default::default(),
+        randomness: Hash::ZERO,
+        justify_hash: Hash::ZERO,
+    };
+    header.prove_vrf(key);
+    header.randomness = next_beacon(&parent.beacon(), &header.vrf);
+
     let mut state = parent.snapshot();
-    exec.execute_block(&txs, &mut state).unwrap();
+    state.set_beacon(header.randomness);
+    let executor = BlockExecutor::new();
+    let ctx = BlockContext::at_height(1).with_randomness(header.randomness);
+    executor.execute_block(&txs, &mut state, &ctx).unwrap();
+    executor.record_liveness(&mut state, &ctx, 0, validators, &[]);
     let reward = boing_tokenomics::block_emission_validators(1);
     state.get_mut(&proposer).unwrap().balance = state.get(&proposer).unwrap().balance.saturating_add(reward);
-    let state_root = state.state_root();
+    header.state_root = state.state_root();
+    (parent, Block::new(header, txs))
+}
 
-    let block = Block {
-        header: BlockHeader {
-            parent_hash: boing_primitives::Hash::ZERO,
+#[test]
+fn test_chains_to() {
+    let genesis = ChainState::genesis(AccountId([1u8; 32]));
+    let chain = ChainState::from_genesis(genesis.clone());
+    let block = Block::new(
+        BlockHeader {
+            parent_hash: genesis.hash(),
             height: 1,
+            round: 0,
             timestamp: 1,
-            proposer,
-            tx_root: tx_root(&txs),
-            state_root,
+            proposer: AccountId([1u8; 32]),
+            tx_root: Hash::ZERO,
+            state_root: Hash::ZERO,
+            validator_set_hash: Hash::ZERO,
+            vrf: Default::default(),
+            randomness: Hash::ZERO,
+            justify_hash: Hash::ZERO,
         },
-        transactions: txs,
-    };
+        vec![],
+    );
+    assert!(chains_to(&block, chain.latest_hash(), chain.height()));
+    assert!(!chains_to(&block, chain.latest_hash(), chain.height() + 1));
+}
 
-    let validators = vec![proposer];
-    let result = validate_and_execute_block(&block, &parent, &validators, &exec);
-    assert!(result.is_ok());
+#[test]
+fn test_validate_and_execute_block() {
+    let k = key(1);
+    let proposer = id(&k);
+    let to = AccountId([2u8; 32]);
+    let validators = ValidatorSet::equal_weight([proposer]);
+    let (parent, block) = transfer_block(&k, Hash::ZERO, &validators);
+    let block = sign_and_certify(block, &k, &[&k]);
+
+    let result = validate_and_execute_block(&block, &parent, &validators, CHAIN_ID, &BlockExecutor::new());
     let new_state = result.unwrap();
+    let reward = boing_tokenomics::block_emission_validators(1);
     assert_eq!(new_state.get(&proposer).unwrap().balance, 1000 - 100 + reward);
     assert_eq!(new_state.get(&to).unwrap().balance, 100);
 }
 
 #[test]
 fn test_import_block() {
-    let proposer = AccountId([1u8; 32]);
+    let k = key(1);
+    let proposer = id(&k);
     let genesis = ChainState::genesis(proposer);
     let chain = ChainState::from_genesis(genesis.clone());
-    let mut consensus = ConsensusEngine::single_validator(proposer);
-    let _ = consensus.propose_and_commit(genesis.clone());
+    let consensus = ConsensusEngine::single_validator(proposer);
+
+    let (parent, block) = transfer_block(&k, genesis.hash(), consensus.validators());
+    let block = sign_and_certify(block, &k, &[&k]);
+
+    let result = import_block(
+        &block,
+        chain.latest_hash(),
+        chain.height(),
+        &parent,
+        &consensus,
+        CHAIN_ID,
+        &BlockExecutor::new(),
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_unsigned_or_forged_block_rejected() {
+    let (k1, k2) = (key(1), key(2));
+    let validators = ValidatorSet::equal_weight([id(&k1), id(&k2)]);
+    let exec = BlockExecutor::new();
+    let (parent, block) = transfer_block(&k1, Hash::ZERO, &validators);
+
+    let mut unsigned = sign_and_certify(block.clone(), &k1, &[&k1, &k2]);
+    unsigned.signature = None;
+    let result = validate_and_execute_block(&unsigned, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(result, Err(BlockValidationError::InvalidSignature)));
+
+    // Signed by another validator while claiming k1 as proposer.
+    let forged = sign_and_certify(block, &k2, &[&k1, &k2]);
+    let result = validate_and_execute_block(&forged, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(result, Err(BlockValidationError::InvalidSignature)));
+}
+
+#[test]
+fn test_missing_or_wrong_vrf_rejected() {
+    let k = key(1);
+    let validators = ValidatorSet::equal_weight([id(&k)]);
+    let exec = BlockExecutor::new();
+    let (parent, block) = transfer_block(&k, Hash::ZERO, &validators);
+
+    let mut unproven = block.clone();
+    unproven.header.vrf = Default::default();
+    let unproven = sign_and_certify(unproven, &k, &[&k]);
+    let result = validate_and_execute_block(&unproven, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(result, Err(BlockValidationError::InvalidVrf(VrfError::MalformedProof))));
 
+    // A proof for another view does not verify for this header.
+    let mut other_view = block.clone();
+    other_view.header.round = 1;
+    other_view.header.prove_vrf(&k);
+    let mut replayed = block;
+    replayed.header.vrf = other_view.header.vrf;
+    let replayed = sign_and_certify(replayed, &k, &[&k]);
+    let result = validate_and_execute_block(&replayed, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(result, Err(BlockValidationError::InvalidVrf(VrfError::InvalidProof))));
+}
+
+#[test]
+fn test_wrong_randomness_rejected() {
+    let k = key(1);
+    let validators = ValidatorSet::equal_weight([id(&k)]);
+    let (parent, mut block) = transfer_block(&k, Hash::ZERO, &validators);
+    block.header.randomness = Hash([1u8; 32]);
+    let block = sign_and_certify(block, &k, &[&k]);
+    let result = validate_and_execute_block(&block, &parent, &validators, CHAIN_ID, &BlockExecutor::new());
+    assert!(matches!(result, Err(BlockValidationError::InvalidRandomness)));
+}
+
+#[test]
+fn test_block_without_quorum_rejected() {
+    let keys: Vec<SigningKey> = (1..=4).map(key).collect();
+    let validators = ValidatorSet::equal_weight(keys.iter().map(id)); // n=4, quorum=3
+    let exec = BlockExecutor::new();
+    let proposer = leader(&keys, &validators, 0);
+    let (parent, block) = transfer_block(proposer, Hash::ZERO, &validators);
+
+    let mut no_qc = sign_and_certify(block.clone(), proposer, &[]);
+    no_qc.qc = None;
+    let result = validate_and_execute_block(&no_qc, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(result, Err(BlockValidationError::MissingQuorumCertificate)));
+
+    let two_votes = sign_and_certify(block.clone(), proposer, &[&keys[0], &keys[1]]);
+    let result = validate_and_execute_block(&two_votes, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(
+        result,
+        Err(BlockValidationError::InvalidQuorumCertificate(CertificateError::InsufficientStake { have: 2, need: 3 }))
+    ));
+
+    let outsider = key(9);
+    let with_outsider = sign_and_certify(block.clone(), proposer, &[&keys[0], &keys[1], &outsider]);
+    let result = validate_and_execute_block(&with_outsider, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(
+        result,
+        Err(BlockValidationError::InvalidQuorumCertificate(CertificateError::UnknownValidator(_)))
+    ));
+
+    let quorum = sign_and_certify(block, proposer, &[&keys[0], &keys[1], &keys[2]]);
+    assert!(validate_and_execute_block(&quorum, &parent, &validators, CHAIN_ID, &exec).is_ok());
+}
+
+#[test]
+fn test_block_with_wrong_chain_id_rejected() {
+    let k = key(1);
+    let proposer = id(&k);
     let mut parent = StateStore::new();
     parent.insert(Account {
         id: proposer,
-        state: AccountState { balance: 1_000_000, nonce: 0, stake: 0 },
-    });
-    let to = AccountId([2u8; 32]);
-    parent.insert(Account {
-        id: to,
-        state: AccountState { balance: 0, nonce: 0, stake: 0 },
+        state: AccountState { balance: 1000, nonce: 0, stake: 0 },
     });
 
-    let tx = mk_transfer(proposer, to, 0, 50);
+    let mut tx = mk_transfer(proposer, AccountId([2u8; 32]), 0, 100);
+    tx.chain_id = CHAIN_ID + 1;
     let txs = vec![tx];
-    let exec = BlockExecutor::new();
-    let mut state = parent.snapshot();
-    exec.execute_block(&txs, &mut state).unwrap();
-    let reward = boing_tokenomics::block_emission_validators(1);
-    state.get_mut(&proposer).unwrap().balance = state.get(&proposer).unwrap().balance.saturating_add(reward);
-    let state_root = state.state_root();
-
-    let block = Block {
-        header: BlockHeader {
-            parent_hash: genesis.hash(),
+    let block = Block::new(
+        BlockHeader {
+            parent_hash: Hash::ZERO,
             height: 1,
+            round: 0,
             timestamp: 1,
             proposer,
             tx_root: tx_root(&txs),
-            state_root,
+            state_root: Hash::ZERO,
+            validator_set_hash: ValidatorSet::equal_weight([proposer]).hash(),
+            vrf: Default::default(),
+            randomness: Hash::ZERO,
+            justify_hash: Hash::ZERO,
         },
-        transactions: txs,
-    };
+        txs,
+    );
+    let block = sign_and_certify(block, &k, &[&k]);
 
-    let result = import_block(
-        &block,
-        chain.latest_hash(),
-        chain.height(),
-        &parent,
-        &consensus,
-        &exec,
+    let validators = ValidatorSet::equal_weight([proposer]);
+    let result = validate_and_execute_block(&block, &parent, &validators, CHAIN_ID, &BlockExecutor::new());
+    assert!(matches!(
+        result,
+        Err(BlockValidationError::WrongChainId { expected: CHAIN_ID, got }) if got == CHAIN_ID + 1
+    ));
+}
+
+/// Votes count by stake: a single validator with most of the stake certifies a block that
+/// three small validators cannot.
+#[test]
+fn test_quorum_weighted_by_stake() {
+    let keys: Vec<SigningKey> = (1..=4).map(key).collect();
+    let validators = ValidatorSet::new(
+        keys.iter()
+            .zip([100, 100, 100, 700])
+            .map(|(k, stake)| Validator { id: id(k), stake })
+            .collect(),
     );
-    assert!(result.is_ok());
+    let exec = BlockExecutor::new();
+    let proposer = leader(&keys, &validators, 0);
+    let (parent, block) = transfer_block(proposer, Hash::ZERO, &validators);
+
+    let small = sign_and_certify(block.clone(), proposer, &[&keys[0], &keys[1], &keys[2]]);
+    let result = validate_and_execute_block(&small, &parent, &validators, CHAIN_ID, &exec);
+    assert!(matches!(
+        result,
+        Err(BlockValidationError::InvalidQuorumCertificate(CertificateError::InsufficientStake { have: 300, need: 667 }))
+    ));
+
+    let whale = sign_and_certify(block, proposer, &[&keys[3]]);
+    assert!(validate_and_execute_block(&whale, &parent, &validators, CHAIN_ID, &exec).is_ok());
+}
+
+/// A block whose header commits to another validator set is rejected even with a valid QC.
+#[test]
+fn test_block_for_other_validator_set_rejected() {
+    let (k1, k2) = (key(1), key(2));
+    let validators = ValidatorSet::equal_weight([id(&k1)]);
+    let other = ValidatorSet::equal_weight([id(&k1), id(&k2)]);
+    let (parent, block) = transfer_block(&k1, Hash::ZERO, &other);
+    let block = sign_and_certify(block, &k1, &[&k1]);
+
+    let result = validate_and_execute_block(&block, &parent, &validators, CHAIN_ID, &BlockExecutor::new());
+    assert!(matches!(
+        result,
+        Err(BlockValidationError::WrongValidatorSet { expected, got }) if expected == validators.hash() && got == other.hash()
+    ));
+}
+
+#[test]
+fn test_swapped_justify_signers_rejected() {
+    let keys: Vec<SigningKey> = (1..=4).map(key).collect();
+    let voters: Vec<&SigningKey> = keys.iter().collect();
+    let validators = ValidatorSet::equal_weight(keys.iter().map(id));
+    let k = leader(&keys, &validators, 0);
+    let (parent, mut block) = transfer_block(k, Hash([7u8; 32]), &validators);
+    let parent_hash = block.header.parent_hash;
+    let votes: Vec<Vote> = keys.iter().map(|k| Vote::new(parent_hash, k)).collect();
+    block.set_justify(Some(QuorumCertificate::from_votes(parent_hash, &votes)));
+    let block = sign_and_certify(block, k, &voters);
+    validate_and_execute_block(&block, &parent, &validators, CHAIN_ID, &BlockExecutor::new()).unwrap();
+
+    // Dropping a signer keeps a quorum and leaves the block hash and signature intact, but
+    // would change who is recorded as missing the vote.
+    let mut swapped = block.clone();
+    swapped.justify.as_mut().unwrap().signatures.pop();
+    assert_eq!(swapped.hash(), block.hash());
+    swapped.verify_signature().unwrap();
+
+    assert!(matches!(
+        validate_and_execute_block(&swapped, &parent, &validators, CHAIN_ID, &BlockExecutor::new()),
+        Err(BlockValidationError::JustifyMismatch)
+    ));
+    assert!(matches!(
+        validate_block(&bincode::serialize(&swapped).unwrap()),
+        Err(GossipError::JustifyMismatch)
+    ));
+    let mut node = BoingNode::from_genesis(GenesisConfig::single_validator(id(k), 1_000)).unwrap();
+    assert!(matches!(node.import_network_block(&swapped), Err(BlockValidationError::JustifyMismatch)));
 }
@@ -0,0 +1,204 @@
+//! Test header-first range sync: a fresh node downloads the headers of a longer chain,
+//! fetches the bodies in parallel ranges (answered out of order), and imports them. Forged
+//! headers and mismatching bodies are blamed on the peers that served them, and only their
+//! run of headers is dropped.
+
+use std::time::Instant;
+
+use boing_node::genesis::GenesisConfig;
+use boing_node::node::{BoingNode, ChainBlockProvider};
+use boing_node::sync::{BlockSync, SyncError, SyncPhase, REQUEST_TIMEOUT};
+use boing_p2p::{serve_block_request, BlockRequest, BlockResponse, P2pEvent, PeerId, SealedHeader};
+use boing_primitives::{AccessList, AccountId, Block, SignedTransaction, Transaction, TransactionPayload};
+use ed25519_dalek::SigningKey;
+
+const BLOCKS: u64 = 200;
+
+/// Answer `request` from `source` and hand the response to `node`.
+fn deliver(node: &mut BoingNode, source: &BoingNode, request: &BlockRequest) {
+    let provider = ChainBlockProvider(source.chain.clone());
+    let peer = PeerId::random();
+    let event = match serve_block_request(&provider, request) {
+        BlockResponse::Block(block) => P2pEvent::BlockFetched(*block.unwrap()),
+        BlockResponse::Blocks { start, blocks } => P2pEvent::BlocksFetched { peer, start, blocks },
+        BlockResponse::Headers { start, headers } => P2pEvent::HeadersFetched { peer, start, headers },
+    };
+    node.handle_p2p_event(event);
+}
+
+/// A single-validator node that committed `blocks` transfers of `amount`.
+fn source_chain(genesis: &GenesisConfig, key: &SigningKey, blocks: u64, amount: u128) -> BoingNode {
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let mut source = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(key.clone());
+    let to = AccountId([2u8; 32]);
+    for nonce in 0..blocks {
+        let tx = Transaction {
+            chain_id: source.genesis.chain_id,
+            nonce,
+            sender,
+            payload: TransactionPayload::Transfer { to, amount },
+            access_list: AccessList::new(vec![sender, to], vec![sender, to]),
+        };
+        source.submit_transaction(SignedTransaction::new(tx, key)).unwrap();
+        source.produce_block_if_ready().unwrap();
+    }
+    source
+}
+
+fn blocks(node: &BoingNode, start: u64, end: u64) -> Vec<Block> {
+    (start..=end).map(|h| node.chain.get_block_by_height(h).unwrap()).collect()
+}
+
+#[test]
+fn test_header_first_range_sync() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let genesis = GenesisConfig::single_validator(sender, 1_000_000);
+    let mut source = source_chain(&genesis, &key, BLOCKS, 1);
+    assert_eq!(source.chain.height(), BLOCKS);
+
+    let mut node = BoingNode::from_genesis(genesis).unwrap();
+    assert_eq!(node.sync_status(), None);
+    let now = Instant::now();
+    assert!(node.sync_requests(0, now).is_empty(), "no peers, no requests");
+
+    // Headers first.
+    let requests = node.sync_requests(2, now);
+    assert!(matches!(requests[..], [BlockRequest::Headers { start: 1, .. }]));
+    assert!(node.sync_requests(2, now).is_empty(), "header request outstanding");
+    deliver(&mut node, &source, &requests[0]);
+    let status = node.sync_status().unwrap();
+    assert_eq!(status.phase, SyncPhase::Bodies);
+    assert_eq!((status.starting_block, status.current_block, status.highest_block), (0, 0, BLOCKS));
+    assert_eq!(node.chain.height(), 0, "headers alone import nothing");
+
+    // Bodies in ranges, one per peer; the later range arrives first.
+    let ranges = node.sync_requests(2, now);
+    assert_eq!(
+        ranges,
+        vec![BlockRequest::Range { start: 1, count: 128 }, BlockRequest::Range { start: 129, count: 72 }]
+    );
+    deliver(&mut node, &source, &ranges[1]);
+    assert_eq!(node.chain.height(), 0);
+
+    // An unanswered range is sent again after the timeout.
+    let retried = node.sync_requests(2, now + REQUEST_TIMEOUT);
+    assert_eq!(retried, vec![BlockRequest::Range { start: 1, count: 128 }]);
+    deliver(&mut node, &source, &retried[0]);
+
+    assert_eq!(node.chain.height(), BLOCKS);
+    assert_eq!(node.chain.latest_hash(), source.chain.latest_hash());
+    assert_eq!(node.state.state_root(), source.state.state_root());
+    assert_eq!(node.sync_status(), None, "caught up");
+}
+
+#[test]
+fn test_unlinked_headers_ignored() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let mut other = BoingNode::from_genesis(GenesisConfig::single_validator(sender, 5))
+        .unwrap()
+        .with_validator_key(key.clone());
+    let tx = Transaction {
+        chain_id: other.genesis.chain_id,
+        nonce: 0,
+        sender,
+        payload: TransactionPayload::Transfer { to: AccountId([2u8; 32]), amount: 1 },
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    other.submit_transaction(SignedTransaction::new(tx, &key)).unwrap();
+    other.produce_block_if_ready().unwrap();
+
+    // A header built on another genesis does not link to our head.
+    let mut node = BoingNode::from_genesis(GenesisConfig::single_validator(sender, 1_000_000)).unwrap();
+    let now = Instant::now();
+    let requests = node.sync_requests(1, now);
+    deliver(&mut node, &other, &requests[0]);
+    assert_eq!(node.sync_status(), None);
+    assert_eq!(node.sync_requests(1, now), requests, "header request retried");
+}
+
+#[test]
+fn test_forged_headers_blamed_on_their_peer() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let genesis = GenesisConfig::single_validator(sender, 1_000_000);
+    let source = source_chain(&genesis, &key, 20, 1);
+    let node = BoingNode::from_genesis(genesis).unwrap();
+    let head = (0, node.chain.latest_hash());
+    let set = node.consensus.validators_at(1).clone();
+    let known = |_| Some(&set);
+    let headers: Vec<SealedHeader> = blocks(&source, 1, 20).iter().map(SealedHeader::of).collect();
+    let (liar, honest) = (PeerId::random(), PeerId::random());
+    let mut sync = BlockSync::new();
+    let now = Instant::now();
+
+    // Re-signed by a key outside the validator set: links, but the leader did not propose it.
+    let outsider = SigningKey::from_bytes(&[7u8; 32]);
+    let mut forged = headers.clone();
+    forged[5].header.proposer = AccountId(outsider.verifying_key().to_bytes());
+    let mut block = Block::new(forged[5].header.clone(), vec![]);
+    block.sign(&outsider);
+    forged[5].signature = block.signature;
+    let mut forged_signature = headers.clone();
+    forged_signature[3].signature = forged[5].signature;
+
+    for batch in [forged, forged_signature] {
+        sync.next_requests(head, 1, now);
+        let err = sync.on_headers(head, liar, 1, batch, known).unwrap_err();
+        assert!(matches!(err, SyncError::InvalidHeader { .. }));
+        assert_eq!(err.peers(), [liar]);
+        assert_eq!(sync.status(0), None, "forged batch dropped");
+    }
+
+    sync.next_requests(head, 1, now);
+    sync.on_headers(head, honest, 1, headers, known).unwrap();
+    assert_eq!(sync.status(0).unwrap().highest_block, 20);
+}
+
+#[test]
+fn test_body_mismatch_blames_peers_and_keeps_syncing() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let genesis = GenesisConfig::single_validator(sender, 1_000_000);
+    let source = source_chain(&genesis, &key, 40, 1);
+    let other = source_chain(&genesis, &key, 40, 2);
+    let node = BoingNode::from_genesis(genesis).unwrap();
+    let head = (0, node.chain.latest_hash());
+    let set = node.consensus.validators_at(1).clone();
+    let headers: Vec<SealedHeader> = blocks(&source, 1, 40).iter().map(SealedHeader::of).collect();
+    let (first, second, bodies) = (PeerId::random(), PeerId::random(), PeerId::random());
+    let mut sync = BlockSync::new();
+    let now = Instant::now();
+
+    // The first peer's headers stop where the validator set is not known yet; their bodies
+    // are fetched and imported before the rest of the headers.
+    sync.next_requests(head, 1, now);
+    sync.on_headers(head, first, 1, headers.clone(), |h| (h <= 20).then_some(&set)).unwrap();
+    assert_eq!(sync.status(0).unwrap().highest_block, 20);
+    assert_eq!(sync.next_requests(head, 1, now), vec![BlockRequest::Range { start: 1, count: 20 }]);
+    sync.on_blocks(bodies, 1, blocks(&source, 1, 20)).unwrap();
+    for height in 1..=20 {
+        let (block, peer) = sync.next_block(height - 1).unwrap();
+        assert_eq!((block.hash(), peer), (headers[height as usize - 1].header.hash(), bodies));
+    }
+    let head = (20, headers[19].header.hash());
+    let requests = sync.next_requests(head, 1, now);
+    assert!(matches!(requests[..], [BlockRequest::Headers { start: 21, .. }]));
+    sync.on_headers(head, second, 21, headers[20..].to_vec(), |_| Some(&set)).unwrap();
+    assert_eq!(sync.status(20).unwrap().highest_block, 40);
+
+    // A body from another chain does not match the second run's header.
+    let ranges = sync.next_requests(head, 1, now);
+    assert_eq!(ranges, vec![BlockRequest::Range { start: 21, count: 20 }]);
+    let mut served = blocks(&source, 21, 40);
+    served[9] = other.chain.get_block_by_height(30).unwrap();
+    let err = sync.on_blocks(bodies, 21, served).unwrap_err();
+    assert!(matches!(err, SyncError::BodyMismatch { height: 30, .. }));
+    assert_eq!(err.peers(), [bodies, second]);
+
+    // Only that run is dropped: the sync carries on from our head instead of restarting.
+    let status = sync.status(20).unwrap();
+    assert_eq!((status.starting_block, status.highest_block), (0, 20));
+    assert!(matches!(sync.next_requests(head, 1, now)[..], [BlockRequest::Headers { start: 21, .. }]));
+}
@@ -0,0 +1,79 @@
+//! Test chain id replay protection for transactions and intents.
+
+use boing_node::genesis::GenesisConfig;
+use boing_node::intent_pool::IntentPoolError;
+use boing_node::mempool::MempoolError;
+use boing_node::node::BoingNode;
+use boing_primitives::{
+    AccessList, AccountId, Intent, IntentKind, SignedIntent, SignedTransaction, Transaction,
+    TransactionPayload,
+};
+use ed25519_dalek::SigningKey;
+use rand::rngs::OsRng;
+
+fn transfer(chain_id: u64, sender: AccountId, to: AccountId) -> Transaction {
+    Transaction {
+        chain_id,
+        nonce: 0,
+        sender,
+        payload: TransactionPayload::Transfer { to, amount: 10 },
+        access_list: AccessList::new(vec![sender, to], vec![sender, to]),
+    }
+}
+
+#[test]
+fn test_chain_id_is_signed() {
+    let key = SigningKey::generate(&mut OsRng);
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let mut signed = SignedTransaction::new(transfer(1, sender, AccountId([2u8; 32])), &key);
+    assert!(signed.verify().is_ok());
+
+    // Replaying the same signature on another chain must fail.
+    signed.tx.chain_id = 2;
+    assert!(signed.verify().is_err());
+}
+
+#[test]
+fn test_mempool_rejects_other_chain() {
+    let key = SigningKey::generate(&mut OsRng);
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let mut genesis = GenesisConfig::single_validator(sender, 1_000);
+    genesis.chain_id = 42;
+    let node = BoingNode::from_genesis(genesis).unwrap();
+
+    let foreign = SignedTransaction::new(transfer(7, sender, AccountId([2u8; 32])), &key);
+    assert!(matches!(
+        node.submit_transaction(foreign),
+        Err(MempoolError::WrongChainId { expected: 42, got: 7 })
+    ));
+    assert!(node.mempool.is_empty());
+
+    let local = SignedTransaction::new(transfer(42, sender, AccountId([2u8; 32])), &key);
+    node.submit_transaction(local).unwrap();
+    assert_eq!(node.mempool.len(), 1);
+}
+
+#[test]
+fn test_intent_pool_rejects_other_chain() {
+    let key = SigningKey::generate(&mut OsRng);
+    let signer = AccountId(key.verifying_key().to_bytes());
+    let node = BoingNode::from_genesis(GenesisConfig::single_validator(signer, 1_000)).unwrap();
+    let intent = |chain_id| Intent {
+        chain_id,
+        signer,
+        kind: IntentKind::Transfer { to: AccountId([2u8; 32]), amount: 5 },
+        nonce: 0,
+        deadline_block: 100,
+    };
+
+    let foreign = SignedIntent::new(intent(node.genesis.chain_id + 1), &key);
+    assert!(foreign.verify().is_ok());
+    assert!(matches!(
+        node.submit_intent(foreign),
+        Err(IntentPoolError::WrongChainId { .. })
+    ));
+
+    let local = SignedIntent::new(intent(node.genesis.chain_id), &key);
+    assert!(node.submit_intent(local).is_ok());
+    assert_eq!(node.intent_pool.len(), 1);
+}
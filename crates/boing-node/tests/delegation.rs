@@ -0,0 +1,65 @@
+//! Test delegated staking through block production: delegations add to the validator's
+//! voting power at the next epoch and earn a pro-rata share of block rewards.
+
+use boing_node::genesis::{GenesisAllocation, GenesisConfig, GenesisStake};
+use boing_node::node::BoingNode;
+use boing_node::rewards::split_reward;
+use boing_primitives::{
+    AccessList, AccountId, SignedTransaction, Transaction, TransactionPayload, Validator,
+    ValidatorSet,
+};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+fn tx(node: &BoingNode, key: &SigningKey, nonce: u64, payload: TransactionPayload) -> SignedTransaction {
+    let sender = id(key);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+#[test]
+fn test_delegation_counts_toward_set_and_earns_rewards() {
+    let (vk, dk) = (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]));
+    let (v, d) = (id(&vk), id(&dk));
+    let mut genesis = GenesisConfig::single_validator(v, 1_000_000);
+    genesis.allocations.push(GenesisAllocation { account: d, balance: 10_000 });
+    genesis.stakes.push(GenesisStake { account: v, amount: 1_000 });
+    genesis.params.epoch_length = 2;
+    genesis.params.unbonding_delay = 5;
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(vk.clone());
+
+    node.submit_transaction(tx(&node, &vk, 0, TransactionPayload::RegisterValidator { commission_bps: 1_000 }))
+        .unwrap();
+    node.produce_block_if_ready().unwrap();
+    node.submit_transaction(tx(&node, &dk, 0, TransactionPayload::Delegate { validator: v, amount: 3_000 }))
+        .unwrap();
+    node.produce_block_if_ready().unwrap();
+    assert_eq!(node.chain.height(), 2);
+    assert_eq!(node.state.voting_power(&v), 4_000);
+    assert_eq!(node.state.staking().delegations_of(&d), vec![(v, 3_000)]);
+
+    // The boundary at height 2 schedules the delegated power for heights from 5 on, with
+    // leaders drawn from the boundary block's VRF output.
+    let seed = node.chain.get_block_by_height(2).unwrap().header.vrf.seed();
+    let expected = ValidatorSet::new(vec![Validator { id: v, stake: 4_000 }]).with_seed(seed);
+    assert_eq!(node.consensus.validators_at(5), &expected);
+
+    // Block 3 pays the delegator its share of the reward after commission.
+    let before = node.state.get(&d).unwrap().balance;
+    let to = AccountId([9u8; 32]);
+    node.submit_transaction(tx(&node, &vk, 1, TransactionPayload::Transfer { to, amount: 1 })).unwrap();
+    node.produce_block_if_ready().unwrap();
+    let reward = boing_tokenomics::block_emission_validators(3);
+    let (_, shares) = split_reward(reward, 1_000, 1_000, &[(d, 3_000)]);
+    assert_eq!(node.state.get(&d).unwrap().balance, before + shares[0].1);
+    assert!(shares[0].1 > 0);
+}
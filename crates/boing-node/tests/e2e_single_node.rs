@@ -1,39 +1,18 @@
 //! End-to-end test: single node produces blocks with transactions.
 
+use boing_node::genesis::GenesisConfig;
 use boing_node::node::BoingNode;
 use boing_primitives::{
-    AccessList, Account, AccountId, AccountState, SignedTransaction, Transaction, TransactionPayload,
+    AccessList, AccountId, SignedTransaction, Transaction, TransactionPayload,
 };
 use ed25519_dalek::SigningKey;
 use rand::rngs::OsRng;
 
 fn node_with_proposer_key(signing_key: &SigningKey, balance: u128) -> BoingNode {
     let proposer = AccountId(signing_key.verifying_key().to_bytes());
-    let genesis = boing_node::chain::ChainState::genesis(proposer);
-    let chain = boing_node::chain::ChainState::from_genesis(genesis.clone());
-    let mut consensus = boing_consensus::ConsensusEngine::single_validator(proposer);
-    let _ = consensus.propose_and_commit(genesis);
-
-    let mut state = boing_state::StateStore::new();
-    state.insert(Account {
-        id: proposer,
-        state: AccountState { balance, nonce: 0, stake: 0 },
-    });
-
-    BoingNode {
-        chain,
-        consensus,
-        state,
-        executor: boing_execution::BlockExecutor::new(),
-        producer: boing_node::block_producer::BlockProducer::new(proposer).with_max_txs(100),
-        vm: boing_execution::Vm::new(),
-        scheduler: boing_execution::TransactionScheduler::new(),
-        mempool: boing_node::mempool::Mempool::new(),
-        p2p: boing_p2p::P2pNode::default(),
-        dapp_registry: boing_node::dapp_registry::DappRegistry::new(),
-        intent_pool: boing_node::intent_pool::IntentPool::new(),
-        persistence: None,
-    }
+    BoingNode::from_genesis(GenesisConfig::single_validator(proposer, balance))
+        .expect("genesis")
+        .with_validator_key(signing_key.clone())
 }
 
 #[test]
@@ -45,6 +24,7 @@ fn test_single_node_produces_block_with_transfer() {
     let mut node = node_with_proposer_key(&signing_key, 1_000_000);
 
     let tx = Transaction {
+        chain_id: node.genesis.chain_id,
         nonce: 0,
         sender: proposer,
         payload: TransactionPayload::Transfer { to, amount: 100 },
@@ -63,3 +43,39 @@ fn test_single_node_produces_block_with_transfer() {
     assert!(node.state.get(&proposer).unwrap().balance >= 999_900); // 999_900 after transfer + block reward
     assert_eq!(node.state.get(&to).unwrap().balance, 100);
 }
+
+#[test]
+fn test_failing_transaction_does_not_stall_blocks() {
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let proposer = AccountId(signing_key.verifying_key().to_bytes());
+    let to = AccountId([2u8; 32]);
+    let mut node = node_with_proposer_key(&signing_key, 1_000_000);
+    let chain_id = node.genesis.chain_id;
+    let sign = |nonce, payload| {
+        let tx = Transaction {
+            chain_id,
+            nonce,
+            sender: proposer,
+            payload,
+            access_list: AccessList::new(vec![proposer, to], vec![proposer, to]),
+        };
+        SignedTransaction::new(tx, &signing_key)
+    };
+    let transfer = |amount| TransactionPayload::Transfer { to, amount };
+
+    // The pool accepts an unjail from a validator that is not jailed; it fails execution.
+    let unjail = sign(0, TransactionPayload::Unjail);
+    let next = sign(1, transfer(100));
+    node.submit_transaction(unjail).unwrap();
+    node.submit_transaction(next).unwrap();
+
+    // The unjail is dropped rather than failing the block; the transfer behind it waits.
+    assert!(node.produce_block_if_ready().is_none());
+    assert_eq!(node.mempool.len(), 1);
+
+    node.submit_transaction(sign(0, transfer(50))).unwrap();
+    node.produce_block_if_ready().expect("block with both transfers");
+    assert_eq!(node.chain.height(), 1);
+    assert_eq!(node.mempool.len(), 0);
+    assert_eq!(node.state.get(&to).unwrap().balance, 150);
+}
@@ -0,0 +1,95 @@
+//! Test consensus messages that arrive before the proposal they depend on: votes and
+//! proposals are checked before they are kept, so junk cannot crowd out the real ones.
+
+use boing_consensus::ConsensusError;
+use boing_execution::BlockExecutor;
+use boing_node::block_producer::BlockProducer;
+use boing_node::genesis::GenesisConfig;
+use boing_node::mempool::Mempool;
+use boing_node::node::{BoingNode, ConsensusMessageError};
+use boing_primitives::{AccountId, Block, BlockHeader, Hash, Vote};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+fn four_validators() -> (Vec<SigningKey>, BoingNode) {
+    let keys: Vec<SigningKey> = (1..=4).map(|i| SigningKey::from_bytes(&[i as u8; 32])).collect();
+    let mut genesis = GenesisConfig::single_validator(id(&keys[0]), 1_000_000);
+    genesis.validators = keys.iter().map(id).collect();
+    let node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(keys[0].clone());
+    (keys, node)
+}
+
+fn first_proposal(node: &BoingNode, keys: &[SigningKey]) -> Block {
+    let leader = node.consensus.leader(1, 1);
+    let leader_key = keys.iter().find(|k| id(k) == leader).unwrap();
+    let producer = BlockProducer::new(leader_key.clone());
+    let mempool = Mempool::new(node.genesis.chain_id);
+    producer.build_block(&mempool, &node.state, &BlockExecutor::new(), &node.consensus, true).unwrap().block
+}
+
+#[test]
+fn test_junk_early_votes_do_not_crowd_out_real_ones() {
+    let (keys, mut node) = four_validators();
+    let block = first_proposal(&node, &keys);
+
+    // Far more junk than the buffer holds: outsiders' votes and votes with a forged signer.
+    for i in 0..300u32 {
+        let mut seed = [0xaa; 32];
+        seed[..4].copy_from_slice(&i.to_le_bytes());
+        let outsider = SigningKey::from_bytes(&seed);
+        assert!(node.handle_vote(Vote::new(Hash(seed), &outsider)).is_err());
+        let mut forged = Vote::new(block.hash(), &outsider);
+        forged.validator = id(&keys[1]);
+        assert!(matches!(
+            node.handle_vote(forged),
+            Err(ConsensusMessageError::Consensus(ConsensusError::InvalidSignature(_)))
+        ));
+    }
+
+    // The real votes still arrive before the proposal, and complete a quorum with ours.
+    for key in keys.iter().skip(1).take(2) {
+        assert!(node.handle_vote(Vote::new(block.hash(), key)).unwrap().is_none());
+    }
+    node.handle_proposal(block.clone()).unwrap();
+    assert_eq!(node.consensus.high_qc().unwrap().block.hash, block.hash());
+}
+
+#[test]
+fn test_forged_future_proposal_not_kept() {
+    let (keys, mut node) = four_validators();
+    let mut block = first_proposal(&node, &keys);
+    // A child of a block we have not seen: kept only if its leader signed it for this view or the next.
+    block.header.parent_hash = Hash([0xee; 32]);
+    block.header.height = 2;
+
+    let view = node.consensus.view();
+    let far = Block { header: BlockHeader { round: view + 50, ..block.header.clone() }, ..block.clone() };
+    assert!(matches!(
+        node.handle_proposal(far),
+        Err(ConsensusMessageError::Consensus(ConsensusError::WrongView { .. }))
+    ));
+
+    let mut next = block.clone();
+    next.header.round = view + 1;
+    let leader = node.consensus.leader(2, view + 1);
+    let impostor = keys.iter().find(|k| id(k) != leader).unwrap();
+    next.header.proposer = id(impostor);
+    next.sign(impostor);
+    assert!(matches!(
+        node.handle_proposal(next.clone()),
+        Err(ConsensusMessageError::Consensus(ConsensusError::InvalidBlock(_)))
+    ));
+
+    next.header.proposer = leader;
+    next.sign(impostor);
+    assert!(matches!(
+        node.handle_proposal(next.clone()),
+        Err(ConsensusMessageError::Consensus(ConsensusError::InvalidSignature(_)))
+    ));
+
+    next.sign(keys.iter().find(|k| id(k) == leader).unwrap());
+    node.handle_proposal(next).unwrap();
+}
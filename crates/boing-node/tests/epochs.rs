@@ -0,0 +1,80 @@
+//! Test epoch transitions: validator sets derived from bonded stake at epoch boundaries.
+
+use boing_node::genesis::{GenesisAllocation, GenesisConfig, GenesisStake};
+use boing_node::node::BoingNode;
+use boing_primitives::{
+    AccessList, AccountId, SignedTransaction, Transaction, TransactionPayload, Validator,
+    ValidatorSet,
+};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+fn tx(node: &BoingNode, key: &SigningKey, nonce: u64, payload: TransactionPayload) -> SignedTransaction {
+    let sender = id(key);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+/// A staker bonding at epoch 0 joins the set derived at the first boundary (height 2),
+/// which governs heights from 5 on. The genesis validator then holds a quarter of the
+/// stake and can no longer certify blocks alone. The schedule survives a restart.
+#[test]
+fn test_bonded_staker_joins_validator_set_after_epoch() {
+    let dir = std::env::temp_dir().join("boing-epochs-test");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let (k1, k2) = (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]));
+    let (v1, v2) = (id(&k1), id(&k2));
+    let mut genesis = GenesisConfig::single_validator(v1, 1_000_000);
+    genesis.allocations.push(GenesisAllocation { account: v2, balance: 10_000 });
+    genesis.stakes.push(GenesisStake { account: v1, amount: 1_000 });
+    genesis.params.epoch_length = 2;
+    genesis.params.min_validator_stake = 500;
+
+    let mut node = BoingNode::with_data_dir(genesis.clone(), Some(&dir))
+        .unwrap()
+        .with_validator_key(k1.clone());
+    let genesis_set = genesis.validator_set();
+
+    node.submit_transaction(tx(&node, &k2, 0, TransactionPayload::Bond { amount: 3_000 })).unwrap();
+    let to = AccountId([9u8; 32]);
+    for nonce in 0..3 {
+        node.submit_transaction(tx(&node, &k1, nonce, TransactionPayload::Transfer { to, amount: 1 }))
+            .unwrap();
+        node.produce_block_if_ready().expect("genesis validator certifies alone");
+    }
+    assert_eq!(node.chain.height(), 3);
+    assert_eq!(node.state.get(&v2).unwrap().stake, 3_000);
+
+    let seed = node.chain.get_block_by_height(2).unwrap().header.vrf.seed();
+    let derived = ValidatorSet::new(vec![
+        Validator { id: v2, stake: 3_000 },
+        Validator { id: v1, stake: 1_000 },
+    ])
+    .with_seed(seed);
+    assert_eq!(node.consensus.validators_at(4), &genesis_set);
+    assert_eq!(node.consensus.validators_at(5), &derived);
+
+    node.submit_transaction(tx(&node, &k1, 3, TransactionPayload::Transfer { to, amount: 1 })).unwrap();
+    node.produce_block_if_ready().expect("height 4 is still in the genesis epoch");
+    let block4 = node.chain.get_block_by_height(4).unwrap();
+    assert_eq!(block4.header.validator_set_hash, genesis_set.hash());
+
+    node.submit_transaction(tx(&node, &k1, 4, TransactionPayload::Transfer { to, amount: 1 })).unwrap();
+    assert!(node.produce_block_if_ready().is_none(), "1000 of 4000 stake is not a quorum");
+    assert_eq!(node.chain.height(), 4);
+    drop(node);
+
+    let restarted = BoingNode::with_data_dir(genesis, Some(&dir)).unwrap();
+    assert_eq!(restarted.chain.height(), 4);
+    assert_eq!(restarted.consensus.validators_at(5), &derived);
+}
@@ -0,0 +1,65 @@
+//! Test finality tracking: blocks imported from the network become final by the three-chain
+//! rule, competing blocks below the finalized one are refused, and finality survives a restart.
+
+use boing_node::block_validation::BlockValidationError;
+use boing_node::chain::{BlockTag, ChainError};
+use boing_node::genesis::GenesisConfig;
+use boing_node::node::BoingNode;
+use boing_primitives::{AccessList, AccountId, Block, SignedTransaction, Transaction, TransactionPayload};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+/// Commit a block transferring 10 from `key` to `to` on a single-validator node.
+fn commit_transfer(node: &mut BoingNode, key: &SigningKey, nonce: u64, to: AccountId) -> Block {
+    let sender = id(key);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload: TransactionPayload::Transfer { to, amount: 10 },
+        access_list: AccessList::new(vec![sender, to], vec![sender, to]),
+    };
+    node.submit_transaction(SignedTransaction::new(tx, key)).unwrap();
+    let hash = node.produce_block_if_ready().unwrap();
+    node.chain.get_block_by_hash(&hash).unwrap()
+}
+
+#[test]
+fn test_three_chain_finalizes_imported_blocks() {
+    let dir = std::env::temp_dir().join("boing-finality-test");
+    let _ = std::fs::remove_dir_all(&dir);
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(id(&key), 1_000_000);
+    let alice = AccountId([0xa; 32]);
+
+    let mut producer = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(key.clone());
+    let blocks: Vec<Block> = (0..3).map(|nonce| commit_transfer(&mut producer, &key, nonce, alice)).collect();
+    let mut rival = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(key.clone());
+    let rival1 = commit_transfer(&mut rival, &key, 0, AccountId([0xb; 32]));
+
+    let mut node = BoingNode::with_data_dir(genesis.clone(), Some(&dir)).unwrap();
+    node.import_network_block(&blocks[0]).unwrap();
+    node.import_network_block(&blocks[1]).unwrap();
+    assert_eq!(node.chain.finalized().0, 0);
+    assert_eq!(node.chain.get_block_by_tag(BlockTag::Safe).unwrap().hash(), blocks[1].hash());
+
+    // Views 1, 2, 3 in a row: block 1 is committed.
+    node.import_network_block(&blocks[2]).unwrap();
+    assert_eq!(node.chain.finalized(), (1, blocks[0].hash()));
+    assert_eq!(node.chain.get_block_by_tag(BlockTag::Latest).unwrap().hash(), blocks[2].hash());
+    assert_eq!(node.state_at(1).unwrap().get(&alice).unwrap().balance, 10);
+    assert_eq!(node.state.get(&alice).unwrap().balance, 30);
+    assert!(matches!(
+        node.import_network_block(&rival1),
+        Err(BlockValidationError::Chain(ChainError::BelowFinalized))
+    ));
+    drop(node);
+
+    let restarted = BoingNode::with_data_dir(genesis, Some(&dir)).unwrap();
+    assert_eq!(restarted.chain.finalized(), (1, blocks[0].hash()));
+    assert_eq!(restarted.chain.height(), 3);
+    assert!("pending".parse::<BlockTag>().is_err());
+}
@@ -0,0 +1,108 @@
+//! Test fork choice: a node that imported one branch switches to a competing branch with a
+//! higher QC, rolling its state back, and switches back once the first branch overtakes it.
+
+use std::time::{Duration, Instant};
+
+use boing_node::chain::{ChainError, ChainState};
+use boing_node::genesis::GenesisConfig;
+use boing_node::node::BoingNode;
+use boing_primitives::{AccessList, AccountId, Block, SignedTransaction, Transaction, TransactionPayload};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+/// Queue a transfer of `amount` from `key` to `to`.
+fn submit_transfer(node: &BoingNode, key: &SigningKey, nonce: u64, to: AccountId, amount: u128) {
+    let sender = id(key);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload: TransactionPayload::Transfer { to, amount },
+        access_list: AccessList::new(vec![sender, to], vec![sender, to]),
+    };
+    node.submit_transaction(SignedTransaction::new(tx, key)).unwrap();
+}
+
+/// Commit a block with the queued transactions on a single-validator node.
+fn commit(node: &mut BoingNode) -> Block {
+    let hash = node.produce_block_if_ready().unwrap();
+    node.chain.get_block_by_hash(&hash).unwrap()
+}
+
+fn commit_transfer(node: &mut BoingNode, key: &SigningKey, nonce: u64, to: AccountId, amount: u128) -> Block {
+    submit_transfer(node, key, nonce, to, amount);
+    commit(node)
+}
+
+#[test]
+fn test_reorg_to_higher_qc_and_back() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(id(&key), 1_000_000);
+    let (alice, bob) = (AccountId([0xa; 32]), AccountId([0xb; 32]));
+
+    // Branch a: views 1 and 2. Branch b: one block in view 3, after two timed-out views.
+    let mut a = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(key.clone());
+    let a1 = commit_transfer(&mut a, &key, 0, alice, 10);
+    let a2 = commit_transfer(&mut a, &key, 1, alice, 10);
+    let mut b = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(key.clone());
+    submit_transfer(&b, &key, 0, bob, 5);
+    for _ in 0..2 {
+        assert!(b.on_tick(Instant::now() + Duration::from_secs(3_600)));
+    }
+    let b1 = commit(&mut b);
+    assert_eq!(b1.header.round, 3);
+
+    let mut node = BoingNode::from_genesis(genesis).unwrap();
+    node.import_network_block(&a1).unwrap();
+    node.import_network_block(&a2).unwrap();
+    assert_eq!(node.chain.latest_hash(), a2.hash());
+
+    // b1 is certified in a later view than anything on branch a.
+    node.import_network_block(&b1).unwrap();
+    assert_eq!((node.chain.height(), node.chain.latest_hash()), (1, b1.hash()));
+    assert!(node.chain.get_block_by_height(2).is_none());
+    assert!(node.state.get(&alice).is_none());
+    assert_eq!(node.state.get(&bob).unwrap().balance, 5);
+    assert_eq!(node.state.state_root(), b.state.state_root());
+
+    // a3 (view 3) ties on QC view and makes branch a longer.
+    let a3 = commit_transfer(&mut a, &key, 2, alice, 10);
+    node.import_network_block(&a3).unwrap();
+    assert_eq!((node.chain.height(), node.chain.latest_hash()), (3, a3.hash()));
+    assert_eq!(node.chain.get_block_by_height(1).unwrap().hash(), a1.hash());
+    assert_eq!(node.state.get(&alice).unwrap().balance, 30);
+    assert!(node.state.get(&bob).is_none());
+    assert_eq!(node.state.state_root(), a.state.state_root());
+    assert!(matches!(
+        node.import_network_block(&a3),
+        Err(boing_node::block_validation::BlockValidationError::Chain(ChainError::AlreadyKnown))
+    ));
+}
+
+#[test]
+fn test_finalized_block_not_reverted() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(id(&key), 1_000_000);
+    let mut a = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(key.clone());
+    let a1 = commit_transfer(&mut a, &key, 0, AccountId([0xa; 32]), 10);
+    let mut b = BoingNode::from_genesis(genesis).unwrap().with_validator_key(key.clone());
+    let b1 = commit_transfer(&mut b, &key, 0, AccountId([0xb; 32]), 10);
+
+    let chain = ChainState::from_genesis(a.chain.get_block_by_height(0).unwrap());
+    chain.append(a1.clone()).unwrap();
+    chain.finalize(&a1.hash()).unwrap();
+    assert_eq!(chain.finalized(), (1, a1.hash()));
+    assert!(matches!(chain.insert(b1.clone()), Err(ChainError::BelowFinalized)));
+
+    // Before finalization the competing block is kept, and is reachable by hash.
+    let chain = ChainState::from_genesis(a.chain.get_block_by_height(0).unwrap());
+    chain.append(a1.clone()).unwrap();
+    chain.insert(b1.clone()).unwrap();
+    assert!(chain.get_block_by_hash(&b1.hash()).is_some());
+    chain.finalize(&a1.hash()).unwrap();
+    assert!(chain.get_block_by_hash(&b1.hash()).is_none(), "losing branch pruned");
+    assert_eq!(chain.fork_choice(), a1.hash());
+}
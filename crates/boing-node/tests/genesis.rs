@@ -0,0 +1,150 @@
+//! Test genesis.json loading, genesis state, data-dir genesis check, and P2P genesis handshake.
+
+use std::time::Duration;
+
+use boing_node::genesis::{GenesisConfig, GenesisError, GenesisStake};
+use boing_node::node::{BoingNode, NodeError};
+use boing_p2p::{P2pConfig, P2pNode};
+use boing_primitives::AccountId;
+
+const GENESIS_JSON: &str = r#"{
+  "chain_id": 42,
+  "timestamp": 1760000000,
+  "validators": [
+    "0x0101010101010101010101010101010101010101010101010101010101010101",
+    "0x0202020202020202020202020202020202020202020202020202020202020202"
+  ],
+  "allocations": [
+    { "account": "0x0101010101010101010101010101010101010101010101010101010101010101", "balance": "5000000" },
+    { "account": "0x0303030303030303030303030303030303030303030303030303030303030303", "balance": "250" }
+  ],
+  "stakes": [
+    { "account": "0x0101010101010101010101010101010101010101010101010101010101010101", "amount": "1000" },
+    { "account": "0x0202020202020202020202020202020202020202020202020202020202020202", "amount": "2000" }
+  ],
+  "contracts": [
+    { "address": "0x0909090909090909090909090909090909090909090909090909090909090909", "code": "0x6002600301" }
+  ],
+  "params": {
+    "max_txs_per_block": 10,
+    "governance_account": "0x0303030303030303030303030303030303030303030303030303030303030303"
+  }
+}"#;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_load_genesis_file() {
+    let dir = temp_dir("boing-genesis-load-test");
+    let path = dir.join("genesis.json");
+    std::fs::write(&path, GENESIS_JSON).unwrap();
+
+    let genesis = GenesisConfig::load(&path).unwrap();
+    assert_eq!(genesis.chain_id, 42);
+    assert_eq!(genesis.validators.len(), 2);
+    assert_eq!(genesis.params.max_txs_per_block, 10);
+    assert_eq!(genesis.params.block_time_secs, boing_tokenomics::BLOCK_TIME_SECS);
+    assert_eq!(genesis.params.governance_account, Some(AccountId([3u8; 32])));
+
+    let mut node = BoingNode::from_genesis(genesis.clone()).unwrap();
+    let v1 = AccountId([1u8; 32]);
+    let v2 = AccountId([2u8; 32]);
+    assert_eq!(node.state.get(&v1).unwrap().balance, 5_000_000);
+    assert_eq!(node.state.get(&v1).unwrap().stake, 1000);
+    assert_eq!(node.state.get(&v2).unwrap().balance, 0);
+    assert_eq!(node.state.get(&v2).unwrap().stake, 2000);
+    assert_eq!(node.state.get(&AccountId([3u8; 32])).unwrap().balance, 250);
+    assert_eq!(
+        node.state.get_contract_code(&AccountId([9u8; 32])).unwrap(),
+        &vec![0x60, 0x02, 0x60, 0x03, 0x01]
+    );
+    // Weighted by genesis stake, in the listed order.
+    let set = node.consensus.validators();
+    assert_eq!(set.accounts(), vec![v1, v2]);
+    assert_eq!((set.stake_of(&v1), set.stake_of(&v2)), (1000, 2000));
+
+    let block = node.chain.get_block_by_height(0).unwrap();
+    assert_eq!(block.header.timestamp, 1_760_000_000);
+    assert_eq!(block.header.state_root, node.state.state_root());
+    assert_eq!(block.header.validator_set_hash, set.hash());
+    assert_eq!(block.hash(), node.genesis_hash);
+    assert_eq!(genesis.hash().unwrap(), node.genesis_hash);
+}
+
+#[test]
+fn test_invalid_genesis_rejected() {
+    let mut genesis = GenesisConfig::dev();
+    genesis.validators.clear();
+    assert!(matches!(genesis.validate(), Err(GenesisError::NoValidators)));
+
+    let mut genesis = GenesisConfig::dev();
+    genesis.validators.push(genesis.validators[0]);
+    assert!(matches!(genesis.validate(), Err(GenesisError::Duplicate(_))));
+
+    // Either every validator is staked at genesis or none is.
+    let mut genesis = GenesisConfig::dev();
+    genesis.validators.push(AccountId([2u8; 32]));
+    genesis.stakes.push(GenesisStake { account: genesis.validators[0], amount: 10 });
+    assert!(matches!(genesis.validate(), Err(GenesisError::UnstakedValidator(v)) if v == AccountId([2u8; 32])));
+
+    // Unbonded stake must stay slashable for as long as it can count toward a set.
+    let mut genesis = GenesisConfig::dev();
+    genesis.params.unbonding_delay = 2 * genesis.params.epoch_length;
+    assert!(matches!(genesis.validate(), Err(GenesisError::InvalidParam(_))));
+
+    let mut genesis = GenesisConfig::dev();
+    genesis.params.equivocation_slash_bps = 10_001;
+    assert!(matches!(genesis.validate(), Err(GenesisError::InvalidParam(_))));
+
+    let dir = temp_dir("boing-genesis-invalid-test");
+    let path = dir.join("genesis.json");
+    std::fs::write(&path, r#"{ "chain_id": 1, "validators": ["0x01"] }"#).unwrap();
+    assert!(matches!(GenesisConfig::load(&path), Err(GenesisError::Parse(_))));
+}
+
+#[test]
+fn test_data_dir_genesis_mismatch() {
+    let dir = temp_dir("boing-genesis-datadir-test");
+    let genesis_a = GenesisConfig::single_validator(AccountId([1u8; 32]), 100);
+    let mut genesis_b = genesis_a.clone();
+    genesis_b.chain_id = 99;
+
+    let node = BoingNode::with_data_dir(genesis_a.clone(), Some(&dir)).unwrap();
+    let hash_a = node.genesis_hash;
+    drop(node);
+
+    let reopened = BoingNode::with_data_dir(genesis_a, Some(&dir)).unwrap();
+    assert_eq!(reopened.chain.get_block_by_height(0).unwrap().hash(), hash_a);
+    assert_eq!(reopened.state.get(&AccountId([1u8; 32])).unwrap().balance, 100);
+
+    let result = BoingNode::with_data_dir(genesis_b, Some(&dir));
+    assert!(matches!(result, Err(NodeError::GenesisMismatch { .. })));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_peers_with_different_genesis_disconnect() {
+    let genesis_a = GenesisConfig::single_validator(AccountId([1u8; 32]), 100);
+    let mut genesis_b = genesis_a.clone();
+    genesis_b.chain_id = 7;
+
+    let addr_a = "/ip4/127.0.0.1/tcp/34101";
+    let (node_a, _rx_a) = P2pNode::new(P2pConfig::new(addr_a, genesis_a.hash().unwrap()), None).unwrap();
+    let (node_same, _rx_same) =
+        P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/0", genesis_a.hash().unwrap()), None).unwrap();
+    let (node_other, _rx_other) =
+        P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/0", genesis_b.hash().unwrap()), None).unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    node_same.dial(addr_a).unwrap();
+    node_other.dial(addr_a).unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    assert_eq!(node_same.connected_peers().await.len(), 1);
+    assert!(node_other.connected_peers().await.is_empty());
+    assert_eq!(node_a.connected_peers().await.len(), 1);
+}
@@ -0,0 +1,149 @@
+//! Test intent gossip: intents received from peers enter the pool and reach subscribed
+//! solvers, an intent submitted to one node reaches another over libp2p, and the pool's
+//! bounds reject floods, costing the relaying peer reputation.
+
+use std::time::Duration;
+
+use boing_node::genesis::GenesisConfig;
+use boing_node::intent_pool::{IntentPoolError, MAX_INTENTS_PER_SIGNER, MAX_INTENT_HORIZON};
+use boing_node::node::BoingNode;
+use boing_p2p::{GossipMessage, MessageId, P2pConfig, P2pEvent, P2pNode, PeerId};
+use boing_primitives::{AccountId, Intent, IntentKind, SignedIntent};
+use ed25519_dalek::SigningKey;
+
+fn intent_until(node: &BoingNode, key: &SigningKey, nonce: u64, deadline_block: u64) -> SignedIntent {
+    let signer = AccountId(key.verifying_key().to_bytes());
+    let kind = IntentKind::Transfer { to: AccountId([2u8; 32]), amount: 1 };
+    let intent = Intent { chain_id: node.genesis.chain_id, signer, kind, nonce, deadline_block };
+    SignedIntent::new(intent, key)
+}
+
+fn intent(node: &BoingNode, key: &SigningKey, nonce: u64) -> SignedIntent {
+    intent_until(node, key, nonce, 100)
+}
+
+fn gossip() -> GossipMessage {
+    GossipMessage { id: MessageId::new(b"intent"), source: PeerId::random() }
+}
+
+/// Wait for the next intent `rx` delivers.
+async fn next_intent(rx: &mut tokio::sync::mpsc::Receiver<P2pEvent>) -> (SignedIntent, GossipMessage) {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match rx.recv().await {
+                Some(P2pEvent::IntentReceived(signed, message)) => return (signed, message),
+                Some(_) => {}
+                None => panic!("peer P2P stopped"),
+            }
+        }
+    })
+    .await
+    .expect("intent gossiped to the peer")
+}
+
+#[test]
+fn test_gossiped_intent_reaches_subscribers() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(AccountId(key.verifying_key().to_bytes()), 1_000_000);
+    let mut node = BoingNode::from_genesis(genesis).unwrap();
+    let mut solver = node.intent_pool.subscribe();
+
+    let signed = intent(&node, &key, 0);
+    node.handle_p2p_event(P2pEvent::IntentReceived(signed.clone(), gossip()));
+    assert_eq!(node.intent_pool.len(), 1);
+    assert_eq!(solver.try_recv().unwrap(), (signed.intent.id(), signed.clone()));
+
+    // A second copy from another peer is not pooled or streamed again.
+    assert!(matches!(node.receive_intent(signed), Err(IntentPoolError::Duplicate)));
+    assert!(solver.try_recv().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_submitted_intent_reaches_peer() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(AccountId(key.verifying_key().to_bytes()), 1_000_000);
+    let mut node = BoingNode::from_genesis(genesis.clone()).unwrap();
+    let mut peer = BoingNode::from_genesis(genesis).unwrap();
+
+    let (node_p2p, _node_rx) = P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/0", node.genesis_hash), None).unwrap();
+    let (peer_p2p, mut peer_rx) =
+        P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/47431", peer.genesis_hash), None).unwrap();
+    node.p2p = node_p2p.clone();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    node_p2p.dial("/ip4/127.0.0.1/tcp/47431").unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let signed = intent(&node, &key, 0);
+    node.submit_intent(signed.clone()).unwrap();
+    let (received, message) = next_intent(&mut peer_rx).await;
+    assert_eq!(received, signed);
+    peer.p2p = peer_p2p;
+    peer.handle_p2p_event(P2pEvent::IntentReceived(received, message));
+    assert_eq!(peer.intent_pool.pending(), node.intent_pool.pending());
+}
+
+#[test]
+fn test_intent_pool_bounds() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(AccountId(key.verifying_key().to_bytes()), 1_000_000);
+    let node = BoingNode::from_genesis(genesis).unwrap();
+
+    // Deadlines may not reach past the horizon.
+    let far = intent_until(&node, &key, 0, 1 + MAX_INTENT_HORIZON + 1);
+    let err = node.receive_intent(far).unwrap_err();
+    assert!(matches!(err, IntentPoolError::DeadlineTooFar { max, got } if max == 1 + MAX_INTENT_HORIZON && got == max + 1));
+    assert!(err.is_invalid_gossip());
+    node.receive_intent(intent_until(&node, &key, 0, 1 + MAX_INTENT_HORIZON)).unwrap();
+
+    // A signer has a bounded share of the pool.
+    for nonce in 1..MAX_INTENTS_PER_SIGNER as u64 {
+        node.receive_intent(intent(&node, &key, nonce)).unwrap();
+    }
+    let err = node.receive_intent(intent(&node, &key, MAX_INTENTS_PER_SIGNER as u64)).unwrap_err();
+    assert!(matches!(err, IntentPoolError::SignerLimit(_)) && err.is_invalid_gossip());
+
+    // A signer without funds cannot pool intents at all.
+    let unfunded = SigningKey::from_bytes(&[9u8; 32]);
+    let err = node.receive_intent(intent(&node, &unfunded, 0)).unwrap_err();
+    assert!(matches!(err, IntentPoolError::InsufficientBalance { needed: 1, balance: 0 }) && err.is_invalid_gossip());
+    assert_eq!(node.intent_pool.len(), MAX_INTENTS_PER_SIGNER);
+
+    // Duplicates arrive from every peer in turn; they are not held against the sender.
+    assert!(!node.receive_intent(intent(&node, &key, 1)).unwrap_err().is_invalid_gossip());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rejected_intent_costs_relaying_peer() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(AccountId(key.verifying_key().to_bytes()), 1_000_000);
+    let node = BoingNode::from_genesis(genesis.clone()).unwrap();
+    let mut peer = BoingNode::from_genesis(genesis).unwrap();
+
+    let (node_p2p, _node_rx) = P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/0", node.genesis_hash), None).unwrap();
+    let (peer_p2p, mut peer_rx) =
+        P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/47432", peer.genesis_hash), None).unwrap();
+    peer.p2p = peer_p2p.clone();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    node_p2p.dial("/ip4/127.0.0.1/tcp/47432").unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let node_id = node_p2p.local_peer_id().unwrap();
+    let reliability = |scores: Vec<(PeerId, boing_p2p::PeerScore)>| {
+        scores.into_iter().find(|(p, _)| *p == node_id).map(|(_, s)| s.reliability)
+    };
+
+    // A valid intent is accepted and credits the sender.
+    node_p2p.broadcast_intent(&intent(&node, &key, 0)).unwrap();
+    let (received, message) = next_intent(&mut peer_rx).await;
+    assert_eq!(message.source, node_id);
+    peer.handle_p2p_event(P2pEvent::IntentReceived(received, message));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let before = reliability(peer_p2p.peer_scores().await).expect("sender scored");
+
+    // One from an unfunded signer passes the stateless checks but the pool rejects it.
+    node_p2p.broadcast_intent(&intent(&node, &SigningKey::from_bytes(&[9u8; 32]), 0)).unwrap();
+    let (received, message) = next_intent(&mut peer_rx).await;
+    peer.handle_p2p_event(P2pEvent::IntentReceived(received, message));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(reliability(peer_p2p.peer_scores().await).unwrap() < before);
+    assert_eq!(peer.intent_pool.len(), 1);
+}
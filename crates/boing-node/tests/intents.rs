@@ -0,0 +1,123 @@
+//! Test the solver flow: an intent is pooled, a solver's FulfillIntent is checked against
+//! the pool and state before entering the mempool, and the committed block settles the
+//! intent and drops it from the pool. Intent nonces make older intents stale and let the
+//! signer cancel.
+
+use boing_node::genesis::{GenesisAllocation, GenesisConfig};
+use boing_node::intent_pool::{IntentPoolError, IntentStatus};
+use boing_node::node::{BoingNode, SolutionError};
+use boing_primitives::{
+    AccessList, AccountId, Intent, IntentKind, SignedIntent, SignedTransaction, Transaction,
+    TransactionPayload, NATIVE_ASSET,
+};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+fn solution(node: &BoingNode, solver: &SigningKey, nonce: u64, intent: &SignedIntent, amount_out: u128) -> SignedTransaction {
+    let sender = id(solver);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload: TransactionPayload::FulfillIntent { intent: Box::new(intent.clone()), amount_out },
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    SignedTransaction::new(tx, solver)
+}
+
+#[test]
+fn test_solution_settles_and_prunes_intent() {
+    let (validator, user, solver) =
+        (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]), SigningKey::from_bytes(&[3u8; 32]));
+    let bob = AccountId([8u8; 32]);
+    let mut genesis = GenesisConfig::single_validator(id(&validator), 1_000_000);
+    genesis.allocations.push(GenesisAllocation { account: id(&user), balance: 1_000 });
+    genesis.allocations.push(GenesisAllocation { account: id(&solver), balance: 1_000 });
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(validator);
+
+    let sign = |kind, nonce, deadline_block| {
+        let intent = Intent { chain_id: node.genesis.chain_id, signer: id(&user), kind, nonce, deadline_block };
+        SignedIntent::new(intent, &user)
+    };
+    let transfer = sign(IntentKind::Transfer { to: bob, amount: 250 }, 1, 5);
+    let too_greedy = sign(IntentKind::Swap { from_asset: NATIVE_ASSET, to_asset: NATIVE_ASSET, amount: 1, min_out: Some(5_000) }, 3, 5);
+    let short_lived = sign(IntentKind::Transfer { to: bob, amount: 1 }, 2, 1);
+    assert!(matches!(node.submit_intent(sign(IntentKind::Transfer { to: bob, amount: 1 }, 0, 0)), Err(IntentPoolError::Expired(0))));
+    let intent_id = node.submit_intent(transfer.clone()).unwrap();
+    node.submit_intent(too_greedy.clone()).unwrap();
+    // More than the signer's balance is refused before it reaches the pool.
+    assert!(matches!(
+        node.submit_intent(sign(IntentKind::Transfer { to: bob, amount: 5_000 }, 4, 5)),
+        Err(IntentPoolError::InsufficientBalance { needed: 5_251, balance: 1_000 })
+    ));
+    node.submit_intent(short_lived.clone()).unwrap();
+    assert_eq!(node.intent_pool.pending().first().map(|(_, s)| s.intent.deadline_block), Some(1));
+
+    let unpooled = sign(IntentKind::Transfer { to: bob, amount: 2 }, 4, 5);
+    assert!(matches!(node.submit_solution(solution(&node, &solver, 0, &unpooled, 0)), Err(SolutionError::UnknownIntent)));
+    assert!(matches!(node.submit_solution(solution(&node, &solver, 0, &too_greedy, 0)), Err(SolutionError::Rejected(_))));
+
+    node.submit_solution(solution(&node, &solver, 0, &transfer, 0)).unwrap();
+    node.produce_block_if_ready().unwrap();
+    assert_eq!(node.state.get(&bob).unwrap().balance, 250);
+    assert_eq!(node.state.get(&id(&user)).unwrap().balance, 750);
+    assert!(node.state.intents().is_fulfilled(&intent_id));
+
+    // Settled and expired intents are pruned; the unsettleable one waits out its deadline.
+    let pending: Vec<_> = node.intent_pool.pending().into_iter().map(|(id, _)| id).collect();
+    assert_eq!(pending, vec![too_greedy.intent.id()]);
+    assert!(matches!(node.submit_solution(solution(&node, &solver, 1, &transfer, 0)), Err(SolutionError::UnknownIntent)));
+    assert_eq!(node.intent_pool.lookup(&intent_id).map(|(_, s)| s), Some(IntentStatus::Fulfilled));
+    assert_eq!(node.intent_pool.lookup(&short_lived.intent.id()).map(|(_, s)| s), Some(IntentStatus::Expired));
+}
+
+#[test]
+fn test_intent_nonces_cancel_and_claims() {
+    let (validator, user, solver) =
+        (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]), SigningKey::from_bytes(&[3u8; 32]));
+    let bob = AccountId([8u8; 32]);
+    let mut genesis = GenesisConfig::single_validator(id(&validator), 1_000_000);
+    genesis.allocations.push(GenesisAllocation { account: id(&user), balance: 1_000 });
+    genesis.allocations.push(GenesisAllocation { account: id(&solver), balance: 1_000 });
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(validator);
+
+    let chain_id = node.genesis.chain_id;
+    let sign = |nonce, amount| {
+        let kind = IntentKind::Transfer { to: bob, amount };
+        SignedIntent::new(Intent { chain_id, signer: id(&user), kind, nonce, deadline_block: 10 }, &user)
+    };
+    let first = sign(0, 10);
+    let second = sign(1, 20);
+    node.submit_intent(first.clone()).unwrap();
+    node.submit_intent(second.clone()).unwrap();
+    assert!(matches!(node.submit_intent(first.clone()), Err(IntentPoolError::Duplicate)));
+    assert!(matches!(node.submit_intent(sign(0, 11)), Err(IntentPoolError::NonceInUse(0))));
+
+    // One solution per signer per block.
+    node.submit_solution(solution(&node, &solver, 0, &second, 0)).unwrap();
+    assert!(matches!(node.submit_solution(solution(&node, &solver, 1, &first, 0)), Err(SolutionError::SolutionPending)));
+    node.produce_block_if_ready().unwrap();
+    assert_eq!(node.intent_pool.lookup(&second.intent.id()).map(|(_, s)| s), Some(IntentStatus::Fulfilled));
+    assert_eq!(node.intent_pool.lookup(&first.intent.id()).map(|(_, s)| s), Some(IntentStatus::Cancelled), "nonce 0 went stale");
+    assert!(matches!(node.submit_intent(sign(1, 30)), Err(IntentPoolError::StaleNonce { next: 2, got: 1 })));
+
+    // The signer cancels its pooled intent on-chain.
+    let third = sign(2, 30);
+    node.submit_intent(third.clone()).unwrap();
+    let sender = id(&user);
+    let cancel = Transaction {
+        chain_id,
+        nonce: 0,
+        sender,
+        payload: TransactionPayload::CancelIntent { nonce: 2 },
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    node.submit_transaction(SignedTransaction::new(cancel, &user)).unwrap();
+    node.produce_block_if_ready().unwrap();
+    assert_eq!(node.intent_pool.lookup(&third.intent.id()).map(|(_, s)| s), Some(IntentStatus::Cancelled));
+    assert!(node.intent_pool.is_empty());
+    assert_eq!(node.state.get(&bob).unwrap().balance, 20);
+}
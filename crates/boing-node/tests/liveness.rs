@@ -0,0 +1,129 @@
+//! Test downtime tracking through block production: a validator that never proposes or
+//! votes accumulates misses, is slashed and jailed, and can unjail after the jail period,
+//! while one whose votes always arrive after quorum is credited and never punished.
+
+use std::time::{Duration, Instant};
+
+use boing_governance::SlashReason;
+use boing_node::genesis::{GenesisConfig, GenesisStake};
+use boing_node::node::BoingNode;
+use boing_primitives::{AccessList, AccountId, SignedTransaction, Transaction, TransactionPayload, Vote};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+fn tx(node: &BoingNode, key: &SigningKey, nonce: u64, payload: TransactionPayload) -> SignedTransaction {
+    let sender = id(key);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+/// Commit one block with a transfer from `key`, timing out views led by the offline
+/// validator on the way.
+fn commit_transfer(node: &mut BoingNode, key: &SigningKey, nonce: u64) {
+    let to = AccountId([9u8; 32]);
+    node.submit_transaction(tx(node, key, nonce, TransactionPayload::Transfer { to, amount: 1 })).unwrap();
+    while node.produce_block_if_ready().is_none() {
+        assert!(node.on_tick(Instant::now() + Duration::from_secs(3_600)));
+    }
+}
+
+#[test]
+fn test_offline_validator_slashed_jailed_and_unjailed() {
+    let (k1, k2) = (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]));
+    let (v1, v2) = (id(&k1), id(&k2));
+    let mut genesis = GenesisConfig::single_validator(v1, 1_000_000);
+    genesis.validators.push(v2);
+    genesis.stakes.push(GenesisStake { account: v1, amount: 10_000 });
+    genesis.stakes.push(GenesisStake { account: v2, amount: 1_000 });
+    genesis.params.liveness_window = 20;
+    genesis.params.liveness_max_missed = 3;
+    genesis.params.liveness_slash_bps = 1_000;
+    genesis.params.liveness_jail_blocks = 5;
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(k1.clone());
+
+    // v2 never votes, and the views it leads time out. Its misses build up block by block.
+    commit_transfer(&mut node, &k1, 0);
+    commit_transfer(&mut node, &k1, 1);
+    let missed = node.state.staking().liveness().missed(&v2);
+    let set = node.consensus.validators_at(2).clone();
+    let round = node.chain.get_block_by_height(2).unwrap().header.round;
+    let led = (1..round).filter(|view| set.leader(*view) == v2).count();
+    assert_eq!(missed.proposals.len(), led);
+    assert_eq!(missed.votes.len(), 1);
+    assert!(!node.state.staking().is_jailed(&v2));
+
+    let mut nonce = 2;
+    while !node.state.staking().is_jailed(&v2) {
+        commit_transfer(&mut node, &k1, nonce);
+        nonce += 1;
+    }
+    let height = node.chain.height();
+    assert_eq!(node.state.get(&v2).unwrap().stake, 900);
+    assert_eq!(node.state.staking().jailed_until(&v2), Some(height + 5));
+    let slash = node.state.staking().slashes().get_slash(1).unwrap();
+    assert_eq!((slash.reason.clone(), slash.amount, slash.block_height), (SlashReason::Liveness, 100, height));
+    assert!(!node.state.staking().is_jailed(&v1));
+
+    // Unjail is refused until the jail period is over.
+    let unjail = tx(&node, &k2, 0, TransactionPayload::Unjail);
+    let mut early = node.state.snapshot();
+    let next = boing_execution::BlockContext::at_height(height + 1);
+    assert!(matches!(node.vm.execute(&unjail.tx, &mut early, &next), Err(boing_execution::VmError::StillJailed(_))));
+
+    while node.chain.height() < height + 4 {
+        commit_transfer(&mut node, &k1, nonce);
+        nonce += 1;
+    }
+    node.submit_transaction(unjail).unwrap();
+    commit_transfer(&mut node, &k1, nonce);
+    assert_eq!(node.chain.height(), height + 5);
+    assert!(!node.state.staking().is_jailed(&v2));
+}
+
+#[test]
+fn test_vote_after_quorum_not_missed() {
+    let keys: Vec<SigningKey> = (1..=4).map(|i| SigningKey::from_bytes(&[i as u8; 32])).collect();
+    let ids: Vec<AccountId> = keys.iter().map(id).collect();
+    let mut genesis = GenesisConfig::single_validator(ids[0], 1_000_000);
+    genesis.validators = ids.clone();
+    genesis.stakes.push(GenesisStake { account: ids[0], amount: 10_000 });
+    for v in &ids[1..] {
+        genesis.stakes.push(GenesisStake { account: *v, amount: 1_000 });
+    }
+    genesis.params.liveness_window = 20;
+    genesis.params.liveness_max_missed = 3;
+    genesis.params.liveness_slash_bps = 1_000;
+    genesis.params.liveness_jail_blocks = 5;
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(keys[0].clone());
+
+    // v1 alone holds a quorum. v2 and v3 never vote; v4 votes for every block, but only
+    // once the block is already certified.
+    let (offline, late) = (ids[1], ids[3]);
+    let to = AccountId([9u8; 32]);
+    let mut nonce = 0;
+    while !node.state.staking().is_jailed(&offline) {
+        node.submit_transaction(tx(&node, &keys[0], nonce, TransactionPayload::Transfer { to, amount: 1 })).unwrap();
+        nonce += 1;
+        let hash = loop {
+            if let Some(hash) = node.propose_if_leader() {
+                break hash;
+            }
+            assert!(node.on_tick(Instant::now() + Duration::from_secs(3_600)));
+        };
+        assert!(node.consensus.high_qc().is_some_and(|h| h.block.hash == hash), "certified before v4 votes");
+        assert!(node.handle_vote(Vote::new(hash, &keys[3])).unwrap().is_none());
+        assert!(node.consensus.high_qc().unwrap().qc.signers().contains(&late));
+    }
+    assert!(node.state.staking().liveness().missed(&late).votes.is_empty());
+    assert!(!node.state.staking().is_jailed(&late));
+    assert_eq!(node.state.get(&late).unwrap().stake, 1_000);
+}
@@ -1,15 +1,14 @@
 //! Multi-node testnet: 4 validators sync blocks via P2P.
 
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use boing_p2p::BlockRequest;
-use boing_node::chain::ChainState;
+use boing_p2p::{BlockRequest, P2pConfig};
+use boing_node::genesis::GenesisConfig;
 use boing_node::node::BoingNode;
 use rand::seq::SliceRandom;
 use boing_primitives::{
-    AccessList, Account, AccountId, AccountState, SignedTransaction, Transaction,
-    TransactionPayload,
+    AccessList, AccountId, SignedTransaction, Transaction, TransactionPayload, ValidatorSet,
 };
 use boing_tokenomics::BLOCK_TIME_SECS;
 use ed25519_dalek::SigningKey;
@@ -22,38 +21,15 @@ fn node_with_p2p_and_block_provider(
     p2p_listen: &str,
 ) -> (BoingNode, tokio::sync::mpsc::Receiver<boing_p2p::P2pEvent>) {
     let proposer = AccountId(signing_key.verifying_key().to_bytes());
-    let genesis = ChainState::genesis(proposer);
-    let chain = ChainState::from_genesis(genesis.clone());
-    let chain_for_provider = chain.clone();
+    let mut node = BoingNode::from_genesis(GenesisConfig::single_validator(proposer, balance))
+        .expect("genesis")
+        .with_validator_key(signing_key.clone());
     let (p2p, event_rx) = boing_p2p::P2pNode::new(
-        p2p_listen,
-        Some(Arc::new(boing_node::ChainBlockProvider(chain_for_provider))),
+        P2pConfig::new(p2p_listen, node.genesis_hash),
+        Some(Arc::new(boing_node::ChainBlockProvider(node.chain.clone()))),
     )
     .expect("P2P init");
-
-    let mut consensus = boing_consensus::ConsensusEngine::single_validator(proposer);
-    let _ = consensus.propose_and_commit(genesis);
-
-    let mut state = boing_state::StateStore::new();
-    state.insert(Account {
-        id: proposer,
-        state: AccountState { balance, nonce: 0, stake: 0 },
-    });
-
-    let node = BoingNode {
-        chain,
-        consensus,
-        state,
-        executor: boing_execution::BlockExecutor::new(),
-        producer: boing_node::block_producer::BlockProducer::new(proposer).with_max_txs(100),
-        vm: boing_execution::Vm::new(),
-        scheduler: boing_execution::TransactionScheduler::new(),
-        mempool: boing_node::mempool::Mempool::new(),
-        p2p,
-        dapp_registry: boing_node::dapp_registry::DappRegistry::new(),
-        intent_pool: boing_node::intent_pool::IntentPool::new(),
-        persistence: None,
-    };
+    node.p2p = p2p;
     (node, event_rx)
 }
 
@@ -73,6 +49,7 @@ async fn test_four_validators_sync() {
         let (node, rx) = node_with_p2p_and_block_provider(&key, 1_000_000, addr);
         if i == 0 {
             let tx = Transaction {
+                chain_id: node.genesis.chain_id,
                 nonce: 0,
                 sender: proposer,
                 payload: TransactionPayload::Transfer { to, amount: 100 },
@@ -164,3 +141,175 @@ async fn test_four_validators_sync() {
         heights
     );
 }
+
+/// Start one P2P node per key on consecutive ports from `base_port`, full-mesh dialed,
+/// each applying its P2P events in the background.
+async fn start_validators(
+    keys: &[SigningKey],
+    genesis: &GenesisConfig,
+    base_port: u16,
+) -> Vec<Arc<RwLock<BoingNode>>> {
+    let addrs: Vec<String> = (0..keys.len() as u16)
+        .map(|i| format!("/ip4/127.0.0.1/tcp/{}", base_port + i))
+        .collect();
+
+    let mut node_refs = Vec::new();
+    for (key, addr) in keys.iter().zip(&addrs) {
+        let mut node = BoingNode::from_genesis(genesis.clone())
+            .expect("genesis")
+            .with_validator_key(key.clone());
+        let (p2p, mut rx) = boing_p2p::P2pNode::new(
+            P2pConfig::new(addr, node.genesis_hash),
+            Some(Arc::new(boing_node::ChainBlockProvider(node.chain.clone()))),
+        )
+        .expect("P2P init");
+        node.p2p = p2p;
+        let node_ref = Arc::new(RwLock::new(node));
+        let event_ref = node_ref.clone();
+        tokio::spawn(async move {
+            while let Some(ev) = rx.recv().await {
+                event_ref.write().await.handle_p2p_event(ev);
+            }
+        });
+        node_refs.push(node_ref);
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    for i in 1..node_refs.len() {
+        let p2p = node_refs[i].read().await.p2p.clone();
+        for addr in &addrs[..i] {
+            p2p.dial(addr).expect("dial");
+        }
+    }
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    node_refs
+}
+
+/// Four validators, each with its own key, reach quorum over loopback gossip: the view-1
+/// leader proposes, the next two leaders extend it with empty blocks, and once the
+/// three-chain is certified all four commit the same block.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_four_validators_reach_quorum_over_p2p() {
+    let keys: Vec<SigningKey> = (0..4).map(|_| SigningKey::generate(&mut OsRng)).collect();
+    let validators: Vec<AccountId> = keys
+        .iter()
+        .map(|k| AccountId(k.verifying_key().to_bytes()))
+        .collect();
+    // Fund the view-1 leader so it can send a transfer.
+    let leader = ValidatorSet::equal_weight(validators.clone()).leader(1);
+    let proposer = validators.iter().position(|v| *v == leader).unwrap();
+    let mut genesis = GenesisConfig::single_validator(leader, 1_000_000);
+    genesis.validators = validators.clone();
+    let to = AccountId([2u8; 32]);
+
+    let node_refs = start_validators(&keys, &genesis, 34021).await;
+
+    {
+        let node = node_refs[proposer].read().await;
+        let tx = Transaction {
+            chain_id: node.genesis.chain_id,
+            nonce: 0,
+            sender: leader,
+            payload: TransactionPayload::Transfer { to, amount: 100 },
+            access_list: AccessList::new(vec![leader, to], vec![leader, to]),
+        };
+        node.submit_transaction(SignedTransaction::new(tx, &keys[proposer])).unwrap();
+    }
+
+    let mut heights = vec![0; node_refs.len()];
+    for _ in 0..40 {
+        for node_ref in &node_refs {
+            node_ref.write().await.propose_if_leader();
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        for (h, node_ref) in heights.iter_mut().zip(&node_refs) {
+            *h = node_ref.read().await.chain.height();
+        }
+        if heights.iter().all(|h| *h >= 1) {
+            break;
+        }
+    }
+    assert_eq!(heights, vec![1; 4], "all validators should commit height 1");
+
+    let committed = node_refs[0].read().await.chain.get_block_by_height(1).unwrap();
+    assert_eq!(committed.header.proposer, leader);
+    let qc = committed.qc.as_ref().expect("committed block carries a QC");
+    assert!(qc.len() >= 3, "QC needs 2f+1 = 3 of 4 votes, got {}", qc.len());
+    assert!(qc.verify(&committed.hash(), &genesis.validator_set()).is_ok());
+    for node_ref in &node_refs {
+        let node = node_ref.read().await;
+        assert_eq!(node.chain.latest_hash(), committed.hash());
+        assert_eq!(node.state.get(&to).unwrap().balance, 100);
+    }
+}
+
+/// The view-1 leader is down: the live validators time out, gossip a timeout certificate,
+/// and the block proposed by the view-2 leader commits at height 1.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_crashed_leader_replaced_after_view_timeout() {
+    // Fixed keys: the schedule must give views 1 and 2 different leaders.
+    let keys: Vec<SigningKey> = (1..=4).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+    let validators: Vec<AccountId> = keys
+        .iter()
+        .map(|k| AccountId(k.verifying_key().to_bytes()))
+        .collect();
+    let set = ValidatorSet::equal_weight(validators.clone());
+    let (crashed, next_leader) = (set.leader(1), set.leader(2));
+    assert_ne!(crashed, next_leader);
+    let mut genesis = GenesisConfig::single_validator(next_leader, 1_000_000);
+    genesis.validators = validators.clone();
+    genesis.params.round_timeout_ms = 1_000;
+    let to = AccountId([2u8; 32]);
+
+    // The leader of view 1 never starts.
+    let live_keys: Vec<SigningKey> =
+        keys.iter().filter(|k| AccountId(k.verifying_key().to_bytes()) != crashed).cloned().collect();
+    let node_refs = start_validators(&live_keys, &genesis, 34031).await;
+
+    let tx = Transaction {
+        chain_id: genesis.chain_id,
+        nonce: 0,
+        sender: next_leader,
+        payload: TransactionPayload::Transfer { to, amount: 100 },
+        access_list: AccessList::new(vec![next_leader, to], vec![next_leader, to]),
+    };
+    let next_key = keys.iter().find(|k| AccountId(k.verifying_key().to_bytes()) == next_leader).unwrap();
+    let signed = SignedTransaction::new(tx, next_key);
+    for node_ref in &node_refs {
+        let mut node = node_ref.write().await;
+        node.submit_transaction(signed.clone()).unwrap();
+        // The timers started before the mesh formed; restart them now.
+        node.pacemaker.on_progress(Instant::now());
+    }
+
+    let mut heights = vec![0; node_refs.len()];
+    for _ in 0..60 {
+        for node_ref in &node_refs {
+            let mut node = node_ref.write().await;
+            node.on_tick(Instant::now());
+            node.propose_if_leader();
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        for (h, node_ref) in heights.iter_mut().zip(&node_refs) {
+            *h = node_ref.read().await.chain.height();
+        }
+        if heights.iter().all(|h| *h >= 1) {
+            break;
+        }
+    }
+    assert_eq!(heights, vec![1; 3], "live validators should commit height 1 after a view change");
+
+    let committed = node_refs[0].read().await.chain.get_block_by_height(1).unwrap();
+    assert_eq!(committed.header.proposer, next_leader);
+    assert_eq!(committed.header.round, 2, "view 1 timed out, view 2 committed");
+    let qc = committed.qc.as_ref().expect("committed block carries a QC");
+    assert!(qc.verify(&committed.hash(), &genesis.validator_set()).is_ok());
+    for node_ref in &node_refs {
+        let node = node_ref.read().await;
+        assert_eq!(node.chain.latest_hash(), committed.hash());
+        assert_eq!(node.state.get(&to).unwrap().balance, 100);
+        assert!(node.mempool.is_empty(), "committed tx is pruned from every mempool");
+        // Views 3 and 4 extended the block with empty ones until it had a three-chain.
+        assert_eq!(node.consensus.view(), 5);
+    }
+}
@@ -1,9 +1,13 @@
 //! Test disk persistence: run node, produce block, restart, verify state restored.
 
+use boing_consensus::{BlockRef, CertifiedBlock, SafetyState};
 use boing_node::chain::ChainState;
+use boing_node::genesis::GenesisConfig;
+use boing_node::node::BoingNode;
 use boing_node::persistence::Persistence;
-use boing_primitives::{Account, AccountId, AccountState, Block};
+use boing_primitives::{Account, AccountId, AccountState, Block, Hash, QuorumCertificate, Vote};
 use boing_state::StateStore;
+use ed25519_dalek::SigningKey;
 
 #[test]
 fn test_persistence_roundtrip() {
@@ -23,26 +27,33 @@ fn test_persistence_roundtrip() {
     let p = Persistence::new(&temp);
     p.ensure_dirs().unwrap();
     p.save_block(&genesis).unwrap();
-    p.save_chain_meta(0, genesis.hash()).unwrap();
+    p.save_chain_meta(0, genesis.hash(), (0, genesis.hash())).unwrap();
     p.save_state(&state).unwrap();
 
-    let block1 = Block {
-        header: boing_primitives::BlockHeader {
+    let block1 = Block::new(
+        boing_primitives::BlockHeader {
             parent_hash: genesis.hash(),
             height: 1,
+            round: 1,
             timestamp: 1,
             proposer,
             tx_root: boing_primitives::Hash::ZERO,
             state_root: boing_primitives::Hash::ZERO,
+            validator_set_hash: boing_primitives::Hash::ZERO,
+            vrf: Default::default(),
+            randomness: Hash::ZERO,
+            justify_hash: Hash::ZERO,
         },
-        transactions: vec![],
-    };
+        vec![],
+    );
     chain.append(block1.clone()).unwrap();
     state.get_mut(&proposer).unwrap().nonce = 1;
     state.get_mut(&proposer).unwrap().balance = 999_900;
+    state.staking_mut().queue_unbond(proposer, proposer, 50, 3_001);
+    state.set_beacon(Hash([5u8; 32]));
 
     p.save_block(&block1).unwrap();
-    p.save_chain_meta(1, block1.hash()).unwrap();
+    p.save_chain_meta(1, block1.hash(), (0, genesis.hash())).unwrap();
     p.save_state(&state).unwrap();
 
     let chain2 = p.load_chain().unwrap().expect("chain");
@@ -50,6 +61,39 @@ fn test_persistence_roundtrip() {
 
     assert_eq!(chain2.height(), 1);
     assert_eq!(chain2.latest_hash(), block1.hash());
+    assert_eq!(chain2.finalized(), (0, genesis.hash()));
     assert_eq!(state2.get(&proposer).unwrap().balance, 999_900);
     assert_eq!(state2.get(&proposer).unwrap().nonce, 1);
+    assert_eq!(state2.staking(), state.staking());
+    assert_eq!(state2.beacon(), Hash([5u8; 32]));
+}
+
+/// The last voted view and lock survive a restart, so a validator cannot vote twice in a
+/// view it already voted in before crashing.
+#[test]
+fn test_safety_state_restored_on_restart() {
+    let temp = std::env::temp_dir().join("boing-safety-persistence-test");
+    let _ = std::fs::remove_dir_all(&temp);
+
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let validator = AccountId(key.verifying_key().to_bytes());
+    let genesis = GenesisConfig::single_validator(validator, 1_000);
+    let node = BoingNode::with_data_dir(genesis.clone(), Some(&temp)).unwrap();
+    assert_eq!(node.consensus.safety_state(), &SafetyState::default());
+    drop(node);
+
+    let locked = Hash([7u8; 32]);
+    let safety = SafetyState {
+        last_voted_view: 9,
+        locked_qc: Some(CertifiedBlock {
+            block: BlockRef { hash: locked, height: 4, view: 7 },
+            qc: QuorumCertificate::from_votes(locked, &[Vote::new(locked, &key)]),
+        }),
+    };
+    let p = Persistence::new(&temp);
+    p.save_safety_state(&safety).unwrap();
+    assert_eq!(p.load_safety_state().unwrap().as_ref(), Some(&safety));
+
+    let node = BoingNode::with_data_dir(genesis, Some(&temp)).unwrap();
+    assert_eq!(node.consensus.safety_state(), &safety);
 }
@@ -0,0 +1,48 @@
+//! Test the randomness beacon: every block mixes its proposer's VRF output into the
+//! previous beacon, contracts read it through PREVRANDAO, and it survives a restart.
+
+use boing_node::genesis::{GenesisConfig, GenesisContract};
+use boing_node::node::BoingNode;
+use boing_primitives::{
+    next_beacon, AccessList, AccountId, Hash, SignedTransaction, Transaction, TransactionPayload,
+};
+use ed25519_dalek::SigningKey;
+
+#[test]
+fn test_contract_reads_block_randomness() {
+    let dir = std::env::temp_dir().join("boing-randomness-test");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let contract = AccountId([9u8; 32]);
+    let mut genesis = GenesisConfig::single_validator(sender, 1_000_000);
+    // PREVRANDAO, PUSH1 0, SSTORE, STOP: store the block's randomness at key 0.
+    genesis.contracts.push(GenesisContract { address: contract, code: vec![0x44, 0x60, 0x00, 0x55, 0x00] });
+    let mut node = BoingNode::with_data_dir(genesis.clone(), Some(&dir))
+        .unwrap()
+        .with_validator_key(key.clone());
+
+    for nonce in 0..2 {
+        let tx = Transaction {
+            chain_id: node.genesis.chain_id,
+            nonce,
+            sender,
+            payload: TransactionPayload::ContractCall { contract, calldata: vec![] },
+            access_list: AccessList::new(vec![sender, contract], vec![sender, contract]),
+        };
+        node.submit_transaction(SignedTransaction::new(tx, &key)).unwrap();
+        node.produce_block_if_ready().unwrap();
+    }
+
+    let (b1, b2) = (node.chain.get_block_by_height(1).unwrap(), node.chain.get_block_by_height(2).unwrap());
+    assert_eq!(b1.header.randomness, next_beacon(&Hash::ZERO, &b1.header.vrf));
+    assert_eq!(b2.header.randomness, next_beacon(&b1.header.randomness, &b2.header.vrf));
+    assert_ne!(b1.header.randomness, b2.header.randomness);
+    assert_eq!(node.state.contract_storage.get(&(contract, [0u8; 32])), Some(&b2.header.randomness.0));
+    assert_eq!(node.state.beacon(), b2.header.randomness);
+    drop(node);
+
+    let restarted = BoingNode::with_data_dir(genesis, Some(&dir)).unwrap();
+    assert_eq!(restarted.state.beacon(), b2.header.randomness);
+}
@@ -0,0 +1,61 @@
+//! Test the relay registry through block production: a relay registers, a staked validator
+//! reports its metrics, and the epoch boundary updates its reputation and pays it; a peer
+//! importing the blocks reaches the same state.
+
+use boing_node::genesis::{GenesisAllocation, GenesisConfig, GenesisStake};
+use boing_node::node::BoingNode;
+use boing_primitives::{
+    AccessList, AccountId, SignedTransaction, StunTurnMetrics, Transaction, TransactionPayload,
+};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+fn tx(node: &BoingNode, key: &SigningKey, nonce: u64, payload: TransactionPayload) -> SignedTransaction {
+    let sender = id(key);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+#[test]
+fn test_relay_settled_at_epoch_boundary() {
+    let (k1, k2) = (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]));
+    let (v1, relay) = (id(&k1), id(&k2));
+    let mut genesis = GenesisConfig::single_validator(v1, 1_000_000);
+    genesis.allocations.push(GenesisAllocation { account: relay, balance: 5_000 });
+    genesis.stakes.push(GenesisStake { account: v1, amount: 1_000 });
+    genesis.params.epoch_length = 2;
+    genesis.params.relay_min_stake = 2_000;
+    genesis.params.relay_epoch_reward = 1_000;
+    let mut node = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(k1.clone());
+
+    let register = TransactionPayload::RegisterRelay { multiaddr: "/dns4/relay.example/udp/3478".into(), stake: 2_000 };
+    node.submit_transaction(tx(&node, &k2, 0, register)).unwrap();
+    node.produce_block_if_ready().unwrap();
+    let metrics = StunTurnMetrics { uptime_bps: 3_000, avg_latency_ms: 40, success_rate_bps: 3_000, throughput_mb: 5 };
+    let report = TransactionPayload::ReportRelayMetrics { relay, metrics };
+    node.submit_transaction(tx(&node, &k1, 0, report)).unwrap();
+    node.produce_block_if_ready().unwrap();
+
+    // Score 8000 blended with the starting 10000; the reward is scaled by reputation.
+    let entry = node.state.staking().relays().get(&relay).unwrap().clone();
+    assert_eq!(entry.reputation.score, 9_000);
+    assert_eq!(node.state.get(&relay).unwrap().balance, 3_000 + 900);
+    assert!(node.state.staking().relays().reports(&relay).is_empty());
+
+    let mut peer = BoingNode::from_genesis(genesis).unwrap();
+    for height in 1..=2 {
+        let block = node.chain.get_block_by_height(height).unwrap().clone();
+        peer.import_network_block(&block).unwrap();
+    }
+    assert_eq!(peer.state.state_root(), node.state.state_root());
+    assert_eq!(peer.state.staking().relays().get(&relay), Some(&entry));
+}
@@ -0,0 +1,240 @@
+//! Test native WebRTC signaling: deposit-gated offers, answers releasing deposits, ICE
+//! candidates within a session, rate limiting, expiry, signed posting and reading through the
+//! node, and deposits the sender cannot spend or move away while its offers are open.
+
+use boing_node::genesis::GenesisConfig;
+use boing_node::mempool::MempoolError;
+use boing_node::node::BoingNode;
+use boing_node::signaling::{unix_now, SignalingError, SignalingStore};
+use boing_p2p::{
+    SignalingContract, SignalingDepositConfig, SignalingMessage, SignalingMessageKind, SignalingQuery,
+    SignalingRateLimit, SignedSignalingMessage, MAX_SIGNALING_PAYLOAD,
+};
+use boing_primitives::{AccessList, AccountId, Hash, SignedTransaction, Transaction, TransactionPayload};
+use ed25519_dalek::SigningKey;
+
+const CHAIN_ID: u64 = 7;
+const T0: u64 = 1_000_000;
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn addr(key: &SigningKey) -> [u8; 32] {
+    key.verifying_key().to_bytes()
+}
+
+fn signed(message: SignalingMessage, key: &SigningKey, timestamp: u64) -> SignedSignalingMessage {
+    SignedSignalingMessage::new(message, CHAIN_ID, timestamp, key)
+}
+
+fn store() -> SignalingStore {
+    SignalingStore::new(CHAIN_ID)
+        .with_deposit(SignalingDepositConfig { min_deposit: 100, expiration_secs: 300 })
+        .with_rate_limit(SignalingRateLimit { max_per_window: 3, window_secs: 60 })
+}
+
+#[test]
+fn test_offer_answer_ice_flow() {
+    let (alice, bob) = (key(1), key(2));
+    let s = store();
+
+    // No offer yet: neither an answer nor ICE is accepted.
+    let answer = SignalingMessage::answer(addr(&bob), addr(&alice), b"sdp-answer".to_vec());
+    assert!(matches!(s.post(signed(answer.clone(), &bob, T0), 0, 0, T0), Err(SignalingError::NoPendingOffer)));
+    let ice = SignalingMessage::ice_candidate(addr(&alice), addr(&bob), b"candidate".to_vec());
+    assert!(matches!(s.post(signed(ice.clone(), &alice, T0), 0, 1_000, T0), Err(SignalingError::NoSession)));
+
+    let offer = SignalingMessage::offer(addr(&alice), addr(&bob), b"sdp-offer".to_vec());
+    let offer_id = s.post(signed(offer.clone(), &alice, T0), 150, 1_000, T0).unwrap();
+    assert_eq!(s.locked_deposit(&addr(&alice), T0), 150);
+    let pending = s.pending_offers(&addr(&bob), T0);
+    assert_eq!(pending.len(), 1);
+    assert_eq!((pending[0].id, &pending[0].message), (offer_id, &offer));
+
+    s.post(signed(ice, &alice, T0 + 1), 0, 1_000, T0 + 1).unwrap();
+    s.post(signed(answer, &bob, T0 + 2), 0, 0, T0 + 2).unwrap();
+    assert!(s.pending_offers(&addr(&bob), T0 + 2).is_empty(), "answered");
+    assert_eq!(s.locked_deposit(&addr(&alice), T0 + 2), 0, "deposit released");
+
+    // Bob polls the ICE candidate; Alice polls the answer.
+    let to_bob = s.messages_for(&addr(&bob), T0 + 2);
+    assert_eq!(to_bob.iter().map(|m| m.message.kind.clone()).collect::<Vec<_>>(), vec![
+        SignalingMessageKind::Offer,
+        SignalingMessageKind::IceCandidate,
+    ]);
+    let to_alice = s.messages_for(&addr(&alice), T0 + 2);
+    assert_eq!(to_alice.len(), 1);
+    assert_eq!(to_alice[0].message.kind, SignalingMessageKind::Answer);
+}
+
+#[test]
+fn test_offer_deposit_gated_by_balance() {
+    let (alice, bob, carol) = (key(1), key(2), key(3));
+    let s = store();
+    let offer = |to: &SigningKey, ts| signed(SignalingMessage::offer(addr(&alice), addr(to), b"o".to_vec()), &alice, ts);
+
+    assert!(matches!(
+        s.post(offer(&bob, T0), 99, 1_000, T0),
+        Err(SignalingError::DepositTooLow { min: 100, got: 99 })
+    ));
+    s.post(offer(&bob, T0), 150, 200, T0).unwrap();
+    assert!(matches!(
+        s.post(offer(&carol, T0), 100, 200, T0),
+        Err(SignalingError::InsufficientBalance { locked: 150, deposit: 100, balance: 200 })
+    ));
+
+    // Expiry releases the reservation.
+    let later = T0 + 300;
+    assert!(s.pending_offers(&addr(&bob), later).is_empty());
+    s.post(offer(&carol, later), 100, 200, later).unwrap();
+    assert_eq!(s.locked_deposit(&addr(&alice), later), 100);
+}
+
+#[test]
+fn test_rate_limit_and_replay() {
+    let alice = key(1);
+    let s = store();
+    let offer = |to: u8, ts| {
+        signed(SignalingMessage::offer(addr(&alice), addr(&key(to)), b"o".to_vec()), &alice, ts)
+    };
+
+    let first = offer(2, T0);
+    s.post(first.clone(), 100, 10_000, T0).unwrap();
+    assert!(matches!(s.post(first, 100, 10_000, T0), Err(SignalingError::Duplicate)));
+    s.post(offer(3, T0), 100, 10_000, T0).unwrap();
+    s.post(offer(4, T0), 100, 10_000, T0).unwrap();
+    assert!(matches!(s.post(offer(5, T0), 100, 10_000, T0), Err(SignalingError::RateLimited)));
+    // The window slides.
+    s.post(offer(5, T0 + 60), 100, 10_000, T0 + 60).unwrap();
+
+    // Stale, future, foreign-chain and forged messages are rejected.
+    assert!(matches!(s.post(offer(6, T0 - 300), 100, 10_000, T0 + 60), Err(SignalingError::StaleTimestamp)));
+    assert!(matches!(s.post(offer(6, T0 + 200), 100, 10_000, T0 + 60), Err(SignalingError::StaleTimestamp)));
+    let foreign = SignedSignalingMessage::new(
+        SignalingMessage::offer(addr(&alice), addr(&key(6)), b"o".to_vec()),
+        CHAIN_ID + 1,
+        T0 + 60,
+        &alice,
+    );
+    assert!(matches!(s.post(foreign, 100, 10_000, T0 + 60), Err(SignalingError::WrongChainId { .. })));
+    let mut forged = offer(6, T0 + 60);
+    forged.message.sender = addr(&key(9));
+    assert!(matches!(s.post(forged, 100, 10_000, T0 + 60), Err(SignalingError::InvalidSignature)));
+}
+
+#[test]
+fn test_content_rules() {
+    let (alice, bob) = (key(1), key(2));
+    let s = store();
+    let big = SignalingMessage::offer(addr(&alice), addr(&bob), vec![0u8; MAX_SIGNALING_PAYLOAD + 1]);
+    assert!(matches!(s.post(signed(big, &alice, T0), 100, 1_000, T0), Err(SignalingError::PayloadTooLarge { .. })));
+    let to_self = SignalingMessage::offer(addr(&alice), addr(&alice), b"o".to_vec());
+    assert!(matches!(s.post(signed(to_self, &alice, T0), 100, 1_000, T0), Err(SignalingError::SelfAddressed)));
+    let pointer = SignalingMessage::with_pointer(SignalingMessageKind::Offer, addr(&alice), addr(&bob), "bafy".into());
+    s.post(signed(pointer, &alice, T0), 100, 1_000, T0).unwrap();
+}
+
+#[test]
+fn test_node_signaling_uses_state_balance() {
+    let (alice, bob) = (key(1), key(2));
+    let genesis = GenesisConfig::single_validator(AccountId(addr(&alice)), 150);
+    let node = BoingNode::from_genesis(genesis).unwrap();
+    let chain_id = node.genesis.chain_id;
+    let now = unix_now();
+
+    let offer = SignedSignalingMessage::new(SignalingMessage::offer(addr(&alice), addr(&bob), b"o".to_vec()), chain_id, now, &alice);
+    let id: Hash = node.post_signaling(offer, 100).unwrap();
+    // Bob holds no balance but may answer; a second offer from Alice exceeds her balance.
+    assert!(!node.post_offer(addr(&alice), addr(&key(3)), Some(b"o".to_vec()), None, 100).ok);
+    let pending = node.get_pending_offers(addr(&bob));
+    assert_eq!(pending.len(), 1);
+    assert_eq!(node.signaling.pending_offers(&addr(&bob), now)[0].id, id);
+    let answered = node.post_answer(addr(&bob), addr(&alice), Some(b"a".to_vec()), None);
+    assert!(answered.ok && answered.message_id.is_some());
+    assert!(node.get_pending_offers(addr(&bob)).is_empty());
+    assert!(node.post_offer(addr(&alice), addr(&key(3)), Some(b"o".to_vec()), None, 100).ok);
+}
+
+#[test]
+fn test_reading_requires_recipient_signature() {
+    let (alice, bob, carol) = (key(1), key(2), key(3));
+    let s = store();
+    let offer = SignalingMessage::offer(addr(&alice), addr(&bob), b"sdp-offer".to_vec());
+    s.post(signed(offer, &alice, T0), 100, 1_000, T0).unwrap();
+
+    let query = SignalingQuery::new(CHAIN_ID, T0, &bob);
+    assert_eq!(s.read(&query, true, T0).unwrap().len(), 1);
+    assert_eq!(s.read(&query, false, T0 + 60).unwrap().len(), 1);
+
+    // Carol cannot read Bob's messages, with her own key or by claiming his address.
+    assert!(s.read(&SignalingQuery::new(CHAIN_ID, T0, &carol), false, T0).unwrap().is_empty());
+    let mut forged = SignalingQuery::new(CHAIN_ID, T0, &carol);
+    forged.recipient = addr(&bob);
+    assert!(matches!(s.read(&forged, false, T0), Err(SignalingError::InvalidSignature)));
+
+    // Old or foreign-chain queries cannot be replayed.
+    assert!(matches!(s.read(&query, false, T0 + 61), Err(SignalingError::StaleTimestamp)));
+    let foreign = SignalingQuery::new(CHAIN_ID + 1, T0, &bob);
+    assert!(matches!(s.read(&foreign, false, T0), Err(SignalingError::WrongChainId { .. })));
+}
+
+fn transfer(node: &BoingNode, key: &SigningKey, nonce: u64, amount: u128) -> SignedTransaction {
+    let sender = AccountId(addr(key));
+    let to = AccountId([9u8; 32]);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload: TransactionPayload::Transfer { to, amount },
+        access_list: AccessList::new(vec![sender, to], vec![sender, to]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+#[test]
+fn test_reserved_deposit_not_spendable() {
+    let (alice, bob) = (key(1), key(2));
+    let genesis = GenesisConfig::single_validator(AccountId(addr(&alice)), 1_000);
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(alice.clone());
+    let balance = node.state.get(&AccountId(addr(&alice))).unwrap().balance;
+    let chain_id = node.genesis.chain_id;
+    let now = unix_now();
+
+    // A transfer queued before the offer still settles; the node then withdraws the offer
+    // its sender can no longer cover instead of keeping a deposit that is gone.
+    node.submit_transaction(transfer(&node, &alice, 0, balance - 150)).unwrap();
+    let offer = SignedSignalingMessage::new(SignalingMessage::offer(addr(&alice), addr(&bob), b"o".to_vec()), chain_id, now, &alice);
+    node.post_signaling(offer, 200).unwrap();
+    assert_eq!(node.signaling.locked_deposit(&addr(&alice), now), 200);
+    node.produce_block_if_ready().unwrap();
+    assert!(node.get_pending_offers(addr(&bob)).is_empty(), "offer withdrawn");
+    assert_eq!(node.signaling.locked_deposit(&addr(&alice), unix_now()), 0);
+
+    // With an offer open, only the balance above its deposit can be spent. The block reward
+    // went to Alice as its proposer, so read her balance again.
+    let spendable = node.state.get(&AccountId(addr(&alice))).unwrap().balance - 100;
+    assert!(node.post_offer(addr(&alice), addr(&bob), Some(b"o".to_vec()), None, 100).ok);
+    assert!(matches!(
+        node.submit_transaction(transfer(&node, &alice, 1, spendable + 1)),
+        Err(MempoolError::SignalingDepositLocked { locked: 100 })
+    ));
+    assert!(matches!(
+        node.receive_transaction(transfer(&node, &alice, 1, spendable + 1)),
+        Err(MempoolError::SignalingDepositLocked { locked: 100 })
+    ));
+    node.submit_transaction(transfer(&node, &alice, 1, spendable)).unwrap();
+
+    // A refused gossiped copy is not marked seen: once Bob answers and the deposit is
+    // released, the same transaction is accepted when it arrives again.
+    let gossiped = transfer(&node, &alice, 2, spendable + 1);
+    assert!(matches!(
+        node.receive_transaction(gossiped.clone()),
+        Err(MempoolError::SignalingDepositLocked { locked: 100 })
+    ));
+    let answer = SignalingMessage::answer(addr(&bob), addr(&alice), b"a".to_vec());
+    node.post_signaling(SignedSignalingMessage::new(answer, chain_id, unix_now(), &bob), 0).unwrap();
+    assert_eq!(node.signaling.locked_deposit(&addr(&alice), unix_now()), 0);
+    node.receive_transaction(gossiped.clone()).unwrap();
+    assert!(matches!(node.receive_transaction(gossiped), Err(MempoolError::Duplicate)));
+}
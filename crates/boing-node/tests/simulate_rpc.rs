@@ -1,5 +1,6 @@
 //! Test boing_simulateTransaction RPC.
 
+use boing_node::genesis::GenesisConfig;
 use boing_node::node::BoingNode;
 use boing_primitives::{
     AccessList, Account, AccountId, AccountState, SignedTransaction, Transaction, TransactionPayload,
@@ -9,29 +10,7 @@ use rand::rngs::OsRng;
 
 fn node_with_proposer(signing_key: &SigningKey, balance: u128) -> BoingNode {
     let proposer = AccountId(signing_key.verifying_key().to_bytes());
-    let genesis = boing_node::chain::ChainState::genesis(proposer);
-    let chain = boing_node::chain::ChainState::from_genesis(genesis.clone());
-    let mut consensus = boing_consensus::ConsensusEngine::single_validator(proposer);
-    let _ = consensus.propose_and_commit(genesis);
-    let mut state = boing_state::StateStore::new();
-    state.insert(Account {
-        id: proposer,
-        state: AccountState { balance, nonce: 0, stake: 0 },
-    });
-    BoingNode {
-        chain,
-        consensus,
-        state,
-        executor: boing_execution::BlockExecutor::new(),
-        producer: boing_node::block_producer::BlockProducer::new(proposer).with_max_txs(100),
-        vm: boing_execution::Vm::new(),
-        scheduler: boing_execution::TransactionScheduler::new(),
-        mempool: boing_node::mempool::Mempool::new(),
-        p2p: boing_p2p::P2pNode::default(),
-        dapp_registry: boing_node::dapp_registry::DappRegistry::new(),
-        intent_pool: boing_node::intent_pool::IntentPool::new(),
-        persistence: None,
-    }
+    BoingNode::from_genesis(GenesisConfig::single_validator(proposer, balance)).expect("genesis")
 }
 
 #[test]
@@ -46,6 +25,7 @@ fn test_simulate_success() {
     });
 
     let tx = Transaction {
+        chain_id: node.genesis.chain_id,
         nonce: 0,
         sender: proposer,
         payload: TransactionPayload::Transfer { to, amount: 100 },
@@ -54,7 +34,7 @@ fn test_simulate_success() {
     let signed = SignedTransaction::new(tx, &key);
     let mut state_copy = node.state.snapshot();
     let vm = boing_execution::Vm::new();
-    let result = vm.execute(&signed.tx, &mut state_copy);
+    let result = vm.execute(&signed.tx, &mut state_copy, &boing_execution::BlockContext::at_height(1));
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), 21_000);
     assert_eq!(state_copy.get(&proposer).unwrap().balance, 999_900);
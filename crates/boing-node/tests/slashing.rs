@@ -0,0 +1,113 @@
+//! Test equivocation slashing through the node: conflicting votes seen by consensus become a
+//! queued evidence transaction, and a slashed validator is left out of the next derived set.
+
+use boing_execution::BlockExecutor;
+use boing_node::block_producer::BlockProducer;
+use boing_node::genesis::{GenesisAllocation, GenesisConfig, GenesisStake};
+use boing_node::mempool::Mempool;
+use boing_node::node::BoingNode;
+use boing_primitives::{
+    AccessList, AccountId, BlockHeader, EquivocationEvidence, Hash, SignedTransaction, Transaction,
+    TransactionPayload, Validator, ValidatorSet, Vote,
+};
+use ed25519_dalek::SigningKey;
+
+fn id(key: &SigningKey) -> AccountId {
+    AccountId(key.verifying_key().to_bytes())
+}
+
+fn tx(node: &BoingNode, key: &SigningKey, nonce: u64, payload: TransactionPayload) -> SignedTransaction {
+    let sender = id(key);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+#[test]
+fn test_conflicting_votes_queue_evidence_transaction() {
+    let keys: Vec<SigningKey> = (1..=4).map(|i| SigningKey::from_bytes(&[i as u8; 32])).collect();
+    let mut genesis = GenesisConfig::single_validator(id(&keys[0]), 1_000_000);
+    genesis.validators = keys.iter().map(id).collect();
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(keys[0].clone());
+
+    // The view leader proposes two different blocks in view 1.
+    let leader = node.consensus.leader(1, 1);
+    let leader_key = keys.iter().find(|k| id(k) == leader).unwrap();
+    let producer = BlockProducer::new(leader_key.clone());
+    let mempool = Mempool::new(node.genesis.chain_id);
+    let executor = BlockExecutor::new();
+    let a = producer.build_block(&mempool, &node.state, &executor, &node.consensus, true).unwrap().block;
+    let to = AccountId([9u8; 32]);
+    mempool.insert(tx(&node, &keys[0], 0, TransactionPayload::Transfer { to, amount: 1 })).unwrap();
+    let b = producer.build_block(&mempool, &node.state, &executor, &node.consensus, true).unwrap().block;
+    assert_ne!(a.hash(), b.hash());
+    node.handle_proposal(a.clone()).unwrap();
+    node.handle_proposal(b.clone()).unwrap();
+
+    let offender = keys.iter().find(|k| id(k) != leader && id(k) != id(&keys[0])).unwrap();
+    node.handle_vote(Vote::new(a.hash(), offender)).unwrap();
+    assert!(node.handle_vote(Vote::new(b.hash(), offender)).is_err());
+
+    let queued = node.mempool.drain_for_block(10);
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].tx.sender, id(&keys[0]));
+    let TransactionPayload::ReportEquivocation { evidence } = &queued[0].tx.payload else {
+        panic!("expected an equivocation report");
+    };
+    assert_eq!(evidence.validator, id(offender));
+    assert!(evidence.verify().is_ok());
+}
+
+#[test]
+fn test_slashed_validator_jailed_from_next_set() {
+    let (k1, k2) = (SigningKey::from_bytes(&[1u8; 32]), SigningKey::from_bytes(&[2u8; 32]));
+    let (v1, v2) = (id(&k1), id(&k2));
+    let mut genesis = GenesisConfig::single_validator(v1, 1_000_000);
+    genesis.allocations.push(GenesisAllocation { account: v2, balance: 10_000 });
+    genesis.stakes.push(GenesisStake { account: v1, amount: 1_000 });
+    genesis.params.epoch_length = 2;
+    genesis.params.min_validator_stake = 500;
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(k1.clone());
+
+    node.submit_transaction(tx(&node, &k2, 0, TransactionPayload::Bond { amount: 3_000 })).unwrap();
+    node.produce_block_if_ready().unwrap();
+
+    // v2 signs two headers of one view.
+    let header = |timestamp| BlockHeader {
+        parent_hash: Hash::ZERO,
+        height: 1,
+        round: 1,
+        timestamp,
+        proposer: v1,
+        tx_root: Hash::ZERO,
+        state_root: Hash::ZERO,
+        validator_set_hash: Hash::ZERO,
+        vrf: Default::default(),
+        randomness: Hash::ZERO,
+        justify_hash: Hash::ZERO,
+    };
+    let (h1, h2) = (header(1), header(2));
+    let evidence = EquivocationEvidence::new(
+        (&h1, &Vote::new(h1.hash(), &k2)),
+        (&h2, &Vote::new(h2.hash(), &k2)),
+    );
+    let report = TransactionPayload::ReportEquivocation { evidence: Box::new(evidence) };
+    node.submit_transaction(tx(&node, &k1, 0, report)).unwrap();
+    node.produce_block_if_ready().unwrap();
+    assert_eq!(node.chain.height(), 2);
+
+    assert_eq!(node.state.get(&v2).unwrap().stake, 2_850);
+    assert!(node.state.staking().is_jailed(&v2));
+    let slash = node.state.staking().slashes().get_slash(1).unwrap();
+    assert_eq!((slash.validator, slash.amount, slash.block_height), (v2.0, 150, 2));
+
+    // Without the slash v2 would lead the set derived at height 2.
+    let seed = node.chain.get_block_by_height(2).unwrap().header.vrf.seed();
+    let expected = ValidatorSet::new(vec![Validator { id: v1, stake: 1_000 }]).with_seed(seed);
+    assert_eq!(node.consensus.validators_at(5), &expected);
+}
@@ -0,0 +1,102 @@
+//! Test transaction gossip: signed transactions received from peers enter the mempool once,
+//! and a transaction submitted to one node reaches another over libp2p.
+
+use std::time::Duration;
+
+use boing_node::genesis::GenesisConfig;
+use boing_node::mempool::MempoolError;
+use boing_node::node::BoingNode;
+use boing_p2p::{P2pConfig, P2pEvent, P2pNode};
+use boing_primitives::{AccessList, AccountId, SignedTransaction, Transaction, TransactionPayload};
+use ed25519_dalek::SigningKey;
+
+fn transfer(node: &BoingNode, key: &SigningKey, nonce: u64) -> SignedTransaction {
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let to = AccountId([2u8; 32]);
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload: TransactionPayload::Transfer { to, amount: 1 },
+        access_list: AccessList::new(vec![sender, to], vec![sender, to]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+#[test]
+fn test_gossiped_transaction_enters_mempool_once() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(AccountId(key.verifying_key().to_bytes()), 1_000_000);
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(key.clone());
+
+    let first = transfer(&node, &key, 0);
+    node.handle_p2p_event(P2pEvent::TransactionReceived(first.clone()));
+    assert_eq!(node.mempool.len(), 1);
+    assert!(matches!(node.receive_transaction(first.clone()), Err(MempoolError::Duplicate)));
+
+    // Still a duplicate after it left the pool in a block.
+    node.produce_block_if_ready().unwrap();
+    assert!(node.mempool.is_empty());
+    assert!(matches!(node.receive_transaction(first), Err(MempoolError::Duplicate)));
+
+    // A forged signature neither enters the pool nor shadows the real transaction.
+    let real = transfer(&node, &key, 1);
+    let mut forged = real.clone();
+    forged.signature = transfer(&node, &SigningKey::from_bytes(&[9u8; 32]), 1).signature;
+    assert!(matches!(node.receive_transaction(forged), Err(MempoolError::InvalidSignature)));
+    node.receive_transaction(real).unwrap();
+    assert_eq!(node.mempool.len(), 1);
+}
+
+#[test]
+fn test_committed_nonce_rejected() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(AccountId(key.verifying_key().to_bytes()), 1_000_000);
+    let mut producer = BoingNode::from_genesis(genesis.clone()).unwrap().with_validator_key(key.clone());
+    let tx = transfer(&producer, &key, 0);
+    producer.submit_transaction(tx.clone()).unwrap();
+    producer.produce_block_if_ready().unwrap();
+
+    // A node that imported the block first rejects the late gossip.
+    let mut node = BoingNode::from_genesis(genesis).unwrap();
+    node.import_network_block(&producer.chain.get_block_by_height(1).unwrap()).unwrap();
+    assert!(matches!(
+        node.receive_transaction(tx),
+        Err(MempoolError::NonceTooLow { expected: 1, got: 0 })
+    ));
+    assert!(node.mempool.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_submitted_transaction_reaches_peer() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let genesis = GenesisConfig::single_validator(AccountId(key.verifying_key().to_bytes()), 1_000_000);
+    let mut leader = BoingNode::from_genesis(genesis.clone()).unwrap();
+    let mut follower = BoingNode::from_genesis(genesis).unwrap();
+
+    let (leader_p2p, mut leader_rx) =
+        P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/47411", leader.genesis_hash), None).unwrap();
+    let (follower_p2p, _follower_rx) =
+        P2pNode::new(P2pConfig::new("/ip4/127.0.0.1/tcp/0", follower.genesis_hash), None).unwrap();
+    follower.p2p = follower_p2p.clone();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    follower_p2p.dial("/ip4/127.0.0.1/tcp/47411").unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    let tx = transfer(&follower, &key, 0);
+    follower.submit_transaction(tx.clone()).unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            match leader_rx.recv().await {
+                Some(P2pEvent::TransactionReceived(signed)) => return signed,
+                Some(_) => {}
+                None => panic!("leader P2P stopped"),
+            }
+        }
+    })
+    .await
+    .expect("transaction gossiped to the leader");
+    leader.p2p = leader_p2p;
+    leader.handle_p2p_event(P2pEvent::TransactionReceived(received));
+    assert_eq!(leader.mempool.len(), 1);
+}
@@ -0,0 +1,61 @@
+//! Test the unbonding queue through block production: unbonded stake is locked until the
+//! unbonding delay has passed, then a Withdraw returns it to the balance.
+
+use boing_node::genesis::GenesisConfig;
+use boing_node::node::BoingNode;
+use boing_primitives::{
+    AccessList, AccountId, SignedTransaction, Transaction, TransactionPayload, UnbondingEntry,
+};
+use ed25519_dalek::SigningKey;
+
+fn tx(node: &BoingNode, key: &SigningKey, nonce: u64, payload: TransactionPayload) -> SignedTransaction {
+    let sender = AccountId(key.verifying_key().to_bytes());
+    let tx = Transaction {
+        chain_id: node.genesis.chain_id,
+        nonce,
+        sender,
+        payload,
+        access_list: AccessList::new(vec![sender], vec![sender]),
+    };
+    SignedTransaction::new(tx, key)
+}
+
+#[test]
+fn test_unbonded_stake_withdrawable_after_delay() {
+    let key = SigningKey::from_bytes(&[1u8; 32]);
+    let v = AccountId(key.verifying_key().to_bytes());
+    let mut genesis = GenesisConfig::single_validator(v, 1_000_000);
+    genesis.params.epoch_length = 1;
+    genesis.params.unbonding_delay = 3;
+    let mut node = BoingNode::from_genesis(genesis).unwrap().with_validator_key(key.clone());
+
+    node.submit_transaction(tx(&node, &key, 0, TransactionPayload::Bond { amount: 300 })).unwrap();
+    node.produce_block_if_ready().unwrap();
+    node.submit_transaction(tx(&node, &key, 1, TransactionPayload::Unbond { amount: 100 })).unwrap();
+    node.produce_block_if_ready().unwrap();
+    assert_eq!(node.chain.height(), 2);
+    let balance = node.state.get(&v).unwrap().balance;
+    assert_eq!(node.state.get(&v).unwrap().stake, 200);
+    assert_eq!(
+        node.state.staking().unbonding(&v),
+        &[UnbondingEntry { validator: v, amount: 100, release_height: 5 }]
+    );
+
+    // Heights 3 and 4 are still inside the delay.
+    let to = AccountId([9u8; 32]);
+    for nonce in 2..4 {
+        node.submit_transaction(tx(&node, &key, nonce, TransactionPayload::Transfer { to, amount: 1 }))
+            .unwrap();
+        node.produce_block_if_ready().unwrap();
+    }
+    assert_eq!(node.state.staking().unbonding_total(&v), 100);
+
+    node.submit_transaction(tx(&node, &key, 4, TransactionPayload::Withdraw)).unwrap();
+    node.produce_block_if_ready().unwrap();
+    assert_eq!(node.chain.height(), 5);
+    assert!(node.state.staking().unbonding(&v).is_empty());
+    let reward = boing_tokenomics::block_emission_validators(3)
+        + boing_tokenomics::block_emission_validators(4)
+        + boing_tokenomics::block_emission_validators(5);
+    assert_eq!(node.state.get(&v).unwrap().balance, balance - 2 + reward + 100);
+}
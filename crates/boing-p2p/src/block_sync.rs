@@ -1,19 +1,55 @@
-//! Block sync protocol — request/response for fetching blocks by hash or height.
+//! Block sync protocol — request/response for fetching blocks by hash or height, and
+//! ranges of headers or blocks for catching up.
 
 use serde::{Deserialize, Serialize};
 
-use boing_primitives::{Block, Hash};
+use boing_primitives::{verify_proposal, Block, BlockHeader, Hash, Signature, SignatureError};
 
-/// Request for a block (by hash or height).
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Most blocks served for one `Range` request.
+pub const MAX_RANGE_BLOCKS: u32 = 128;
+/// Most headers served for one `Headers` request.
+pub const MAX_RANGE_HEADERS: u32 = 2048;
+
+/// Request for a block (by hash or height), or a range of consecutive canonical blocks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BlockRequest {
     ByHash([u8; 32]),
     ByHeight(u64),
+    /// Up to `count` blocks from height `start` (at most [`MAX_RANGE_BLOCKS`]).
+    Range { start: u64, count: u32 },
+    /// Up to `count` headers from height `start` (at most [`MAX_RANGE_HEADERS`]).
+    Headers { start: u64, count: u32 },
 }
 
-/// Response: block if found, or unit meaning not found.
+/// Response to a [`BlockRequest`]. Range responses echo the requested start height and
+/// stop early at the responder's head.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct BlockResponse(pub Option<Block>);
+pub enum BlockResponse {
+    /// Block if found, or None meaning not found.
+    Block(Option<Box<Block>>),
+    Blocks { start: u64, blocks: Vec<Block> },
+    Headers { start: u64, headers: Vec<SealedHeader> },
+}
+
+/// A header with its proposer signature, so header-first sync can check who proposed each
+/// block before downloading any body.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedHeader {
+    pub header: BlockHeader,
+    pub signature: Option<Signature>,
+}
+
+impl SealedHeader {
+    pub fn of(block: &Block) -> Self {
+        Self { header: block.header.clone(), signature: block.signature }
+    }
+
+    /// Verify the proposer signature against `header.proposer`.
+    pub fn verify_signature(&self) -> Result<(), SignatureError> {
+        let sig = self.signature.as_ref().ok_or(SignatureError::InvalidSignature)?;
+        verify_proposal(&self.header.hash(), sig, &self.header.proposer)
+    }
+}
 
 impl BlockRequest {
     pub fn by_hash(h: &Hash) -> Self {
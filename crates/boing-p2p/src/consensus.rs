@@ -0,0 +1,19 @@
+//! Consensus messages — HotStuff proposals, signed votes, and view-change messages,
+//! gossiped between validators.
+
+use serde::{Deserialize, Serialize};
+
+use boing_primitives::{Block, TimeoutCertificate, TimeoutVote, Vote};
+
+/// Message on the consensus gossip topic.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ConsensusMessage {
+    /// Leader's signed block proposal for its view, carrying the QC for its parent.
+    Proposal(Box<Block>),
+    /// Validator's signed vote for a proposed block.
+    Vote(Vote),
+    /// Validator's signed timeout for a view that made no progress.
+    Timeout(TimeoutVote),
+    /// Timeout certificate announcing the next view, so its leader can propose.
+    NewView(TimeoutCertificate),
+}
@@ -2,8 +2,23 @@
 //!
 //! See DECENTRALIZATION-STRATEGY.md for full design.
 
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
 
+/// Reliability gained for each valid gossip message.
+pub const VALID_MESSAGE_REWARD: i32 = 1;
+/// Reliability lost for each invalid gossip message.
+pub const INVALID_MESSAGE_PENALTY: i32 = 20;
+/// Most reliability a peer can bank, so good behaviour cannot buy unlimited bad behaviour.
+pub const MAX_RELIABILITY: i32 = 100;
+/// Peers whose rank falls below this are disconnected and banned.
+pub const BAN_THRESHOLD: i64 = -100;
+/// How long a banned peer stays blocked before it may connect again with a fresh score.
+pub const BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
 /// Peer reputation score for Sybil/eclipse resistance.
 /// Higher = more trustworthy; used to prioritize connections.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -31,6 +46,89 @@ impl PeerScore {
             .saturating_sub(self.latency_ms as i64 / 10)
             .saturating_add((self.uptime_blocks as i64).min(1000))
     }
+
+    /// A gossip message from this peer passed validation.
+    pub fn record_valid(&mut self) {
+        self.reliability = self.reliability.saturating_add(VALID_MESSAGE_REWARD).min(MAX_RELIABILITY);
+    }
+
+    /// A gossip message from this peer failed validation.
+    pub fn record_invalid(&mut self) {
+        self.reliability = self.reliability.saturating_sub(INVALID_MESSAGE_PENALTY);
+    }
+
+    /// Whether the peer should be disconnected and banned.
+    pub fn is_banned(&self) -> bool {
+        self.rank() < BAN_THRESHOLD
+    }
+}
+
+/// Scores of connected peers, and the peers banned until some time. A peer's score is
+/// forgotten when it disconnects, unless it is banned; a ban is forgotten when it expires.
+#[derive(Clone, Debug, Default)]
+pub struct PeerScores {
+    scores: HashMap<PeerId, PeerScore>,
+    bans: HashMap<PeerId, Instant>,
+}
+
+impl PeerScores {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, peer: &PeerId) -> Option<&PeerScore> {
+        self.scores.get(peer)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&PeerId, &PeerScore)> {
+        self.scores.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn is_banned(&self, peer: &PeerId) -> bool {
+        self.bans.contains_key(peer)
+    }
+
+    /// A message from `peer` passed validation.
+    pub fn record_valid(&mut self, peer: PeerId) {
+        self.scores.entry(peer).or_default().record_valid();
+    }
+
+    /// A message from `peer` failed validation at `now`. Returns true if this bans it (until
+    /// `now + BAN_DURATION`).
+    pub fn record_invalid(&mut self, peer: PeerId, now: Instant) -> bool {
+        let score = self.scores.entry(peer).or_default();
+        score.record_invalid();
+        if !score.is_banned() || self.bans.contains_key(&peer) {
+            return false;
+        }
+        self.bans.insert(peer, now + BAN_DURATION);
+        true
+    }
+
+    /// `peer` has no connection left: forget its score unless it is banned.
+    pub fn disconnected(&mut self, peer: &PeerId) {
+        if !self.bans.contains_key(peer) {
+            self.scores.remove(peer);
+        }
+    }
+
+    /// Forget bans that ended by `now`, with their scores. Returns the peers to unblock.
+    pub fn expire_bans(&mut self, now: Instant) -> Vec<PeerId> {
+        let expired: Vec<PeerId> = self.bans.iter().filter(|(_, until)| **until <= now).map(|(p, _)| *p).collect();
+        for peer in &expired {
+            self.bans.remove(peer);
+            self.scores.remove(peer);
+        }
+        expired
+    }
 }
 
 /// Bootnode entry for rotation; governance or community-funded.
@@ -69,6 +167,16 @@ impl PeerDiscoveryConfig {
         }
     }
 
+    /// Whether `connected` peers is below the minimum, so discovery should look for more.
+    pub fn needs_peers(&self, connected: usize) -> bool {
+        connected < self.min_peers as usize
+    }
+
+    /// Whether another peer may connect. A `max_peers` of 0 means no limit.
+    pub fn has_room(&self, connected: usize) -> bool {
+        self.max_peers == 0 || connected < self.max_peers as usize
+    }
+
     pub fn default_devnet() -> Self {
         Self {
             use_dht: false,
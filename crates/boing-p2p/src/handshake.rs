@@ -0,0 +1,24 @@
+//! Status handshake — peers exchange genesis hashes on connect.
+//!
+//! A peer whose genesis hash differs from ours is on another network and is disconnected.
+
+use serde::{Deserialize, Serialize};
+
+use boing_primitives::Hash;
+
+/// Status sent by both sides right after a connection is established.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusMessage {
+    pub genesis_hash: [u8; 32],
+}
+
+impl StatusMessage {
+    pub fn new(genesis_hash: &Hash) -> Self {
+        Self { genesis_hash: genesis_hash.0 }
+    }
+
+    /// True if the remote status belongs to the same network as ours.
+    pub fn is_compatible(&self, ours: &Hash) -> bool {
+        self.genesis_hash == ours.0
+    }
+}
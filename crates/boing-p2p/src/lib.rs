@@ -3,16 +3,36 @@
 //! Permissionless peer discovery, transaction propagation, sync.
 
 mod block_sync;
+mod consensus;
 mod discovery;
+mod handshake;
 mod node;
+mod node_key;
+mod validation;
 mod webrtc;
 
-pub use block_sync::{BlockRequest, BlockResponse};
-pub use discovery::{BootnodeEntry, PeerDiscoveryConfig, PeerScore};
+pub use block_sync::{BlockRequest, BlockResponse, SealedHeader, MAX_RANGE_BLOCKS, MAX_RANGE_HEADERS};
+pub use consensus::ConsensusMessage;
+pub use discovery::{
+    BootnodeEntry, PeerDiscoveryConfig, PeerScore, PeerScores, BAN_DURATION, BAN_THRESHOLD,
+    INVALID_MESSAGE_PENALTY, MAX_RELIABILITY, VALID_MESSAGE_REWARD,
+};
+pub use handshake::StatusMessage;
+pub use validation::{
+    validate_block, validate_consensus, validate_intent, validate_transaction, GossipError,
+};
 pub use webrtc::{
     ContentPointer, SignalingContract, SignalingDepositConfig, SignalingMessage,
-    SignalingMessageKind, SignalingPostResult, SignalingRateLimit, StunTurnMetrics,
-    StunTurnRegistryEntry, StunTurnReputation,
+    SignalingMessageKind, SignalingPostResult, SignalingQuery, SignalingRateLimit, SignedSignalingMessage,
+    StunTurnMetrics, StunTurnRegistryEntry, StunTurnReputation, MAX_SIGNALING_PAYLOAD,
+};
+pub use node::{
+    serve_block_request, BlockProvider, ConnectedPeer, GossipMessage, P2pConfig, P2pError, P2pEvent, P2pNode,
+};
+pub use node_key::{
+    load_node_key, load_or_generate_node_key, node_key_from_validator_key, NodeKeyError, NODE_KEY_FILE,
 };
-pub use node::{BlockProvider, P2pError, P2pEvent, P2pNode};
+pub use libp2p::identity::Keypair;
+pub use libp2p::gossipsub::{MessageAcceptance, MessageId};
+pub use libp2p::PeerId;
 pub use boing_primitives::{Block, Transaction};
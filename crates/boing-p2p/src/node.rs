@@ -1,60 +1,209 @@
-//! P2P node — libp2p swarm with gossipsub, mdns, and block request/response.
+//! P2P node — libp2p swarm with gossipsub, Kademlia, mdns, and block request/response.
 //!
-//! Propagates blocks and transactions; discovers peers via mDNS; fetches blocks on demand.
+//! Propagates blocks, transactions, intents and consensus messages; discovers peers via mDNS and,
+//! when enabled, a Kademlia DHT seeded from the bootnodes; fetches blocks on demand. Gossip is
+//! validated before it is relayed, and peers that send invalid messages lose reputation until
+//! they are banned.
 
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use libp2p::futures::StreamExt;
-use libp2p::gossipsub::{IdentTopic, MessageAuthenticity, ValidationMode};
+use libp2p::allow_block_list::{self, BlockedPeers};
+use libp2p::connection_limits::{self, ConnectionLimits};
+use libp2p::gossipsub::{IdentTopic, MessageAcceptance, MessageAuthenticity, MessageId, ValidationMode};
+use libp2p::kad::{self, store::MemoryStore};
+use libp2p::swarm::behaviour::toggle::Toggle;
+use libp2p::identity::Keypair;
+use libp2p::{identify, Multiaddr, PeerId};
 use libp2p::mdns::tokio::Behaviour as Mdns;
 use libp2p::request_response::{self, ProtocolSupport};
 use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
 use libp2p::StreamProtocol;
 use libp2p::{gossipsub, SwarmBuilder};
 use tokio::sync::{mpsc, oneshot};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
-use crate::block_sync::{BlockRequest, BlockResponse};
-use boing_primitives::{Block, Hash, Transaction};
+use crate::block_sync::{BlockRequest, BlockResponse, SealedHeader, MAX_RANGE_BLOCKS, MAX_RANGE_HEADERS};
+use crate::consensus::ConsensusMessage;
+use crate::discovery::{BootnodeEntry, PeerDiscoveryConfig, PeerScore, PeerScores, BAN_DURATION};
+use crate::handshake::StatusMessage;
+use crate::validation::{validate_block, validate_consensus, validate_intent, validate_transaction, GossipError};
+use boing_primitives::{
+    Block, Hash, SignedIntent, SignedTransaction, TimeoutCertificate, TimeoutVote, Vote,
+};
 
 const BLOCKS_TOPIC: &str = "boing/blocks";
 const TRANSACTIONS_TOPIC: &str = "boing/transactions";
+const CONSENSUS_TOPIC: &str = "boing/consensus";
+const INTENTS_TOPIC: &str = "boing/intents";
+/// How often to check the peer count and query the DHT for more peers.
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(30);
 
-/// P2P events (incoming blocks/transactions and block fetch responses).
+/// P2P events (incoming blocks/signed transactions, consensus messages, and block fetch responses).
 #[derive(Debug)]
 pub enum P2pEvent {
     BlockReceived(Block),
-    TransactionReceived(Transaction),
+    /// Signed transaction gossiped by a peer, not yet validated.
+    TransactionReceived(SignedTransaction),
+    /// Signed intent gossiped by a peer: signature checked and not past its deadline. It is
+    /// not relayed until the node checks it against its pool and reports the result with
+    /// [`P2pNode::report_validation`].
+    IntentReceived(SignedIntent, GossipMessage),
     /// Response from request_block (by hash or height).
     BlockFetched(Block),
+    /// Response from `peer` to a `BlockRequest::Range`: consecutive blocks from `start`.
+    BlocksFetched { peer: PeerId, start: u64, blocks: Vec<Block> },
+    /// Response from `peer` to a `BlockRequest::Headers`: consecutive headers from `start`.
+    HeadersFetched { peer: PeerId, start: u64, headers: Vec<SealedHeader> },
+    /// Block proposal from the round leader.
+    ProposalReceived(Block),
+    /// Signed vote from a validator.
+    VoteReceived(Vote),
+    /// Signed timeout vote from a validator.
+    TimeoutReceived(TimeoutVote),
+    /// Timeout certificate moving validators to the next view.
+    NewViewReceived(TimeoutCertificate),
+}
+
+/// A gossiped message awaiting the node's verdict, and the peer that relayed it.
+#[derive(Clone, Debug)]
+pub struct GossipMessage {
+    pub id: MessageId,
+    pub source: PeerId,
 }
 
 enum BroadcastMsg {
-    Block(Block),
-    Transaction(Transaction),
+    Block(Box<Block>),
+    Transaction(SignedTransaction),
+    Intent(SignedIntent),
+    Consensus(ConsensusMessage),
 }
 
 enum Command {
     RequestBlock(libp2p::PeerId, BlockRequest),
+    ReportPeer(libp2p::PeerId),
+    ReportValidation(GossipMessage, MessageAcceptance),
     GetPeers(oneshot::Sender<Vec<libp2p::PeerId>>),
+    GetPeerScores(oneshot::Sender<Vec<(libp2p::PeerId, PeerScore)>>),
+    GetPeerInfo(oneshot::Sender<Vec<ConnectedPeer>>),
     Dial(String),
 }
 
+/// A connected peer whose genesis matches ours (boing_peers).
+#[derive(Clone, Debug)]
+pub struct ConnectedPeer {
+    pub peer_id: PeerId,
+    /// Listen addresses it announced, or the address of our connection to it.
+    pub addresses: Vec<Multiaddr>,
+    pub score: PeerScore,
+}
+
 /// Provides blocks for the request/response protocol.
 pub trait BlockProvider: Send + Sync {
     fn get_block_by_hash(&self, hash: &Hash) -> Option<Block>;
     fn get_block_by_height(&self, height: u64) -> Option<Block>;
+
+    /// Up to `count` consecutive blocks from `start`, stopping at the first missing height.
+    fn get_blocks(&self, start: u64, count: u32) -> Vec<Block> {
+        (start..start.saturating_add(count as u64))
+            .map_while(|h| self.get_block_by_height(h))
+            .collect()
+    }
+
+    /// Up to `count` consecutive headers from `start`, stopping at the first missing height.
+    fn get_headers(&self, start: u64, count: u32) -> Vec<SealedHeader> {
+        self.get_blocks(start, count).iter().map(SealedHeader::of).collect()
+    }
+}
+
+/// Answer a block sync request from `provider`, capping range sizes.
+pub fn serve_block_request(provider: &dyn BlockProvider, request: &BlockRequest) -> BlockResponse {
+    match *request {
+        BlockRequest::ByHash(h) => BlockResponse::Block(provider.get_block_by_hash(&Hash(h)).map(Box::new)),
+        BlockRequest::ByHeight(h) => BlockResponse::Block(provider.get_block_by_height(h).map(Box::new)),
+        BlockRequest::Range { start, count } => BlockResponse::Blocks {
+            start,
+            blocks: provider.get_blocks(start, count.min(MAX_RANGE_BLOCKS)),
+        },
+        BlockRequest::Headers { start, count } => BlockResponse::Headers {
+            start,
+            headers: provider.get_headers(start, count.min(MAX_RANGE_HEADERS)),
+        },
+    }
 }
 
 type BlockSyncBehaviour = request_response::cbor::Behaviour<BlockRequest, BlockResponse>;
+type StatusBehaviour = request_response::cbor::Behaviour<StatusMessage, StatusMessage>;
 
 #[derive(NetworkBehaviour)]
 #[behaviour(prelude = "libp2p_swarm::derive_prelude")]
 struct BoingBehaviour {
+    blocked: allow_block_list::Behaviour<BlockedPeers>,
+    limits: connection_limits::Behaviour,
+    identify: identify::Behaviour,
+    kad: Toggle<kad::Behaviour<MemoryStore>>,
     mdns: Mdns,
     gossipsub: gossipsub::Behaviour,
     block_sync: BlockSyncBehaviour,
+    status: StatusBehaviour,
+}
+
+/// Network configuration for a P2P node.
+#[derive(Clone, Debug)]
+pub struct P2pConfig {
+    /// Listen multiaddr (e.g. /ip4/0.0.0.0/tcp/4001).
+    pub listen_addr: String,
+    /// Our genesis hash; peers announcing a different one are disconnected.
+    pub genesis_hash: Hash,
+    /// Bootnodes, DHT and peer count limits. Defaults to devnet (mDNS only, no DHT).
+    pub discovery: PeerDiscoveryConfig,
+    /// Identity keypair; None generates a fresh one, so the PeerId changes on every start.
+    pub keypair: Option<Keypair>,
+}
+
+impl P2pConfig {
+    pub fn new(listen_addr: &str, genesis_hash: Hash) -> Self {
+        Self {
+            listen_addr: listen_addr.to_string(),
+            genesis_hash,
+            discovery: PeerDiscoveryConfig::default_devnet(),
+            keypair: None,
+        }
+    }
+
+    pub fn with_discovery(mut self, discovery: PeerDiscoveryConfig) -> Self {
+        self.discovery = discovery;
+        self
+    }
+
+    pub fn with_keypair(mut self, keypair: Keypair) -> Self {
+        self.keypair = Some(keypair);
+        self
+    }
+}
+
+/// Multiaddr of a bootnode and its peer id, from `/p2p/<id>` or the entry's `peer_id`.
+fn parse_bootnode(entry: &BootnodeEntry) -> Option<(Multiaddr, Option<PeerId>)> {
+    let addr: Multiaddr = entry.multiaddr.parse().ok()?;
+    let from_addr = addr.iter().find_map(|p| match p {
+        libp2p::multiaddr::Protocol::P2p(id) => Some(id),
+        _ => None,
+    });
+    let peer_id = from_addr.or_else(|| entry.peer_id.as_ref()?.parse().ok());
+    Some((addr, peer_id))
+}
+
+/// Disconnect `peer` and refuse it until its ban expires (its score fell below the ban
+/// threshold).
+fn ban_peer(swarm: &mut libp2p::Swarm<BoingBehaviour>, verified: &mut HashSet<PeerId>, peer: PeerId, scores: &PeerScores) {
+    let rank = scores.get(&peer).map(PeerScore::rank).unwrap_or_default();
+    warn!("P2P: banning peer {} (rank {}) for {:?}", peer, rank, BAN_DURATION);
+    verified.remove(&peer);
+    swarm.behaviour_mut().gossipsub.blacklist_peer(&peer);
+    swarm.behaviour_mut().blocked.block_peer(peer);
 }
 
 /// P2P node handle. Broadcasts blocks/txs; emits P2pEvent for incoming data.
@@ -63,21 +212,32 @@ struct BoingBehaviour {
 pub struct P2pNode {
     broadcast_tx: Option<mpsc::Sender<BroadcastMsg>>,
     cmd_tx: Option<mpsc::Sender<Command>>,
+    local_peer_id: Option<PeerId>,
+    /// Chain height gossip validation checks intent deadlines against.
+    head_height: Arc<AtomicU64>,
 }
 
 impl P2pNode {
     /// Create a P2P node and spawn the swarm task.
     /// Returns the node handle and a receiver for incoming P2pEvent.
     /// When `block_provider` is provided, enables block request/response protocol.
+    /// Peers are only reported by `connected_peers` once their genesis hash matches ours.
     pub fn new(
-        listen_addr: &str,
+        config: P2pConfig,
         block_provider: Option<Arc<dyn BlockProvider>>,
     ) -> Result<(Self, mpsc::Receiver<P2pEvent>), P2pError> {
         let (broadcast_tx, mut broadcast_rx) = mpsc::channel(64);
         let (cmd_tx, mut cmd_rx) = mpsc::channel(32);
         let (event_tx, event_rx) = mpsc::channel(64);
+        let discovery = config.discovery;
+        let max_peers = (discovery.max_peers > 0).then_some(discovery.max_peers);
+        let use_dht = discovery.use_dht;
+        let keypair = config.keypair.unwrap_or_else(Keypair::generate_ed25519);
+        let local_peer_id = keypair.public().to_peer_id();
+        let head_height = Arc::new(AtomicU64::new(0));
+        let task_head_height = head_height.clone();
 
-        let swarm = SwarmBuilder::with_new_identity()
+        let swarm = SwarmBuilder::with_existing_identity(keypair)
             .with_tokio()
             .with_tcp(
                 Default::default(),
@@ -91,20 +251,40 @@ impl P2pNode {
                     .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
                 let gossipsub_config = gossipsub::ConfigBuilder::default()
                     .heartbeat_interval(Duration::from_secs(1))
-                    .validation_mode(ValidationMode::Permissive)
+                    .validation_mode(ValidationMode::Strict)
+                    .validate_messages()
                     .build()
                     .map_err(|e| Box::new(std::io::Error::other(e.to_string())))?;
                 let gossipsub =
                     gossipsub::Behaviour::new(MessageAuthenticity::Signed(key.clone()), gossipsub_config)
                         .map_err(|e| Box::new(std::io::Error::other(e.to_string())))?;
                 let block_sync = BlockSyncBehaviour::new(
-                    [(StreamProtocol::new("/boing/block-sync/1"), ProtocolSupport::Full)],
+                    [(StreamProtocol::new("/boing/block-sync/2"), ProtocolSupport::Full)],
                     request_response::Config::default(),
                 );
+                let status = StatusBehaviour::new(
+                    [(StreamProtocol::new("/boing/status/1"), ProtocolSupport::Full)],
+                    request_response::Config::default(),
+                );
+                let identify = identify::Behaviour::new(identify::Config::new("/boing/id/1".into(), key.public()));
+                // Server mode: answer DHT queries even before an external address is confirmed.
+                let kad = use_dht.then(|| {
+                    let config = kad::Config::new(StreamProtocol::new("/boing/kad/1"));
+                    let mut kad = kad::Behaviour::with_config(peer_id, MemoryStore::new(peer_id), config);
+                    kad.set_mode(Some(kad::Mode::Server));
+                    kad
+                });
                 Ok::<_, Box<dyn std::error::Error + Send + Sync>>(BoingBehaviour {
+                    blocked: Default::default(),
+                    limits: connection_limits::Behaviour::new(
+                        ConnectionLimits::default().with_max_established(max_peers),
+                    ),
+                    identify,
+                    kad: kad.into(),
                     mdns,
                     gossipsub,
                     block_sync,
+                    status,
                 })
             })
             .map_err(|e| P2pError::Network(e.to_string()))?
@@ -113,7 +293,11 @@ impl P2pNode {
 
         let blocks_topic = IdentTopic::new(BLOCKS_TOPIC);
         let txs_topic = IdentTopic::new(TRANSACTIONS_TOPIC);
-        let listen_addr = listen_addr.to_string();
+        let consensus_topic = IdentTopic::new(CONSENSUS_TOPIC);
+        let intents_topic = IdentTopic::new(INTENTS_TOPIC);
+        let listen_addr = config.listen_addr;
+        let genesis_hash = config.genesis_hash;
+        let our_status = StatusMessage::new(&genesis_hash);
         let block_provider = block_provider;
 
         tokio::spawn(async move {
@@ -125,8 +309,25 @@ impl P2pNode {
             );
             swarm.behaviour_mut().gossipsub.subscribe(&blocks_topic).expect("subscribe blocks");
             swarm.behaviour_mut().gossipsub.subscribe(&txs_topic).expect("subscribe txs");
+            swarm.behaviour_mut().gossipsub.subscribe(&consensus_topic).expect("subscribe consensus");
+            swarm.behaviour_mut().gossipsub.subscribe(&intents_topic).expect("subscribe intents");
+
+            info!("P2P: listening on {} peer_id={}", listen_addr, swarm.local_peer_id());
+
+            let bootnodes: Vec<_> = discovery.bootnodes.iter().filter_map(parse_bootnode).collect();
+            for (addr, peer_id) in &bootnodes {
+                if let (Some(kad), Some(peer_id)) = (swarm.behaviour_mut().kad.as_mut(), peer_id) {
+                    kad.add_address(peer_id, addr.clone());
+                }
+            }
+            let mut discovery_tick = tokio::time::interval(DISCOVERY_INTERVAL);
 
-            info!("P2P: listening on {} peer_id={:?}", listen_addr, swarm.local_peer_id());
+            // Peers whose status handshake matched our genesis.
+            let mut verified: HashSet<libp2p::PeerId> = HashSet::new();
+            // Addresses to report for each connected peer.
+            let mut addresses: HashMap<PeerId, Vec<Multiaddr>> = HashMap::new();
+            // Reputation from gossip validation, of connected and banned peers.
+            let mut scores = PeerScores::new();
 
             loop {
                 tokio::select! {
@@ -135,8 +336,48 @@ impl P2pNode {
                             Some(Command::RequestBlock(peer, req)) => {
                                 swarm.behaviour_mut().block_sync.send_request(&peer, req);
                             }
+                            Some(Command::ReportPeer(peer)) => {
+                                if swarm.is_connected(&peer) && scores.record_invalid(peer, Instant::now()) {
+                                    ban_peer(&mut swarm, &mut verified, peer, &scores);
+                                }
+                            }
+                            Some(Command::ReportValidation(message, acceptance)) => {
+                                let reject = matches!(acceptance, MessageAcceptance::Reject);
+                                let accept = matches!(acceptance, MessageAcceptance::Accept);
+                                let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                    &message.id,
+                                    &message.source,
+                                    acceptance,
+                                );
+                                if !swarm.is_connected(&message.source) {
+                                    // Its score was dropped on disconnect; do not start a new one.
+                                } else if accept {
+                                    scores.record_valid(message.source);
+                                } else if reject && scores.record_invalid(message.source, Instant::now()) {
+                                    ban_peer(&mut swarm, &mut verified, message.source, &scores);
+                                }
+                            }
                             Some(Command::GetPeers(tx)) => {
-                                let peers: Vec<_> = swarm.connected_peers().cloned().collect();
+                                let peers: Vec<_> = swarm
+                                    .connected_peers()
+                                    .filter(|p| verified.contains(p))
+                                    .cloned()
+                                    .collect();
+                                let _ = tx.send(peers);
+                            }
+                            Some(Command::GetPeerScores(tx)) => {
+                                let _ = tx.send(scores.iter().map(|(p, s)| (*p, *s)).collect());
+                            }
+                            Some(Command::GetPeerInfo(tx)) => {
+                                let peers = swarm
+                                    .connected_peers()
+                                    .filter(|p| verified.contains(p))
+                                    .map(|p| ConnectedPeer {
+                                        peer_id: *p,
+                                        addresses: addresses.get(p).cloned().unwrap_or_default(),
+                                        score: scores.get(p).copied().unwrap_or_default(),
+                                    })
+                                    .collect();
                                 let _ = tx.send(peers);
                             }
                             Some(Command::Dial(addr)) => {
@@ -149,16 +390,38 @@ impl P2pNode {
                             None => break,
                         }
                     }
+                    _ = discovery_tick.tick() => {
+                        for peer in scores.expire_bans(Instant::now()) {
+                            info!("P2P: ban of peer {} expired", peer);
+                            swarm.behaviour_mut().gossipsub.remove_blacklisted_peer(&peer);
+                            swarm.behaviour_mut().blocked.unblock_peer(peer);
+                        }
+                        let connected = swarm.connected_peers().count();
+                        if connected == 0 {
+                            for (addr, _) in &bootnodes {
+                                if let Err(e) = swarm.dial(addr.clone()) {
+                                    debug!("P2P: bootnode {} dial failed: {:?}", addr, e);
+                                }
+                            }
+                        }
+                        if discovery.needs_peers(connected) {
+                            if let Some(kad) = swarm.behaviour_mut().kad.as_mut() {
+                                // A random key walks the DHT and returns peers spread over it.
+                                kad.get_closest_peers(PeerId::random());
+                            }
+                        }
+                    }
                     msg = broadcast_rx.recv() => {
                         match msg {
                             Some(BroadcastMsg::Block(block)) => {
-                                if let Err(e) = bincode::serialize(&block) {
-                                    warn!("P2P: block serialize error: {}", e);
-                                } else if let Ok(bytes) = bincode::serialize(&block) {
-                                    if let Err(e) = swarm.behaviour_mut().gossipsub.publish(blocks_topic.clone(), bytes) {
-                                        warn!("P2P: block publish error: {}", e);
-                                    } else {
-                                        info!("P2P: broadcast block height={}", block.header.height);
+                                match bincode::serialize(&block) {
+                                    Err(e) => warn!("P2P: block serialize error: {}", e),
+                                    Ok(bytes) => {
+                                        if let Err(e) = swarm.behaviour_mut().gossipsub.publish(blocks_topic.clone(), bytes) {
+                                            warn!("P2P: block publish error: {}", e);
+                                        } else {
+                                            info!("P2P: broadcast block height={}", block.header.height);
+                                        }
                                     }
                                 }
                             }
@@ -167,7 +430,23 @@ impl P2pNode {
                                     if let Err(e) = swarm.behaviour_mut().gossipsub.publish(txs_topic.clone(), bytes) {
                                         warn!("P2P: tx publish error: {}", e);
                                     } else {
-                                        info!("P2P: broadcast tx from {:?}", tx.sender);
+                                        info!("P2P: broadcast tx from {:?}", tx.tx.sender);
+                                    }
+                                }
+                            }
+                            Some(BroadcastMsg::Intent(signed)) => {
+                                if let Ok(bytes) = bincode::serialize(&signed) {
+                                    if let Err(e) = swarm.behaviour_mut().gossipsub.publish(intents_topic.clone(), bytes) {
+                                        warn!("P2P: intent publish error: {}", e);
+                                    } else {
+                                        debug!("P2P: broadcast intent {}", signed.intent.id());
+                                    }
+                                }
+                            }
+                            Some(BroadcastMsg::Consensus(msg)) => {
+                                if let Ok(bytes) = bincode::serialize(&msg) {
+                                    if let Err(e) = swarm.behaviour_mut().gossipsub.publish(consensus_topic.clone(), bytes) {
+                                        debug!("P2P: consensus publish error: {}", e);
                                     }
                                 }
                             }
@@ -176,27 +455,91 @@ impl P2pNode {
                     }
                     ev = swarm.select_next_some() => {
                         if let SwarmEvent::Behaviour(BoingBehaviourEvent::Gossipsub(gossipsub::Event::Message {
-                                message, ..
+                                propagation_source, message_id, message,
                             })) = ev {
+                            // Until a peer has shown it shares our genesis, its gossip is neither
+                            // relayed nor scored: it may simply be on another chain.
+                            if !verified.contains(&propagation_source) {
+                                let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                    &message_id,
+                                    &propagation_source,
+                                    MessageAcceptance::Ignore,
+                                );
+                                continue;
+                            }
                             let topic = message.topic.as_str();
-                            if topic == BLOCKS_TOPIC {
-                                if let Ok(block) = bincode::deserialize(&message.data) {
-                                    let _ = event_tx.send(P2pEvent::BlockReceived(block)).await;
-                                }
+                            let validated = if topic == BLOCKS_TOPIC {
+                                Some(validate_block(&message.data).map(P2pEvent::BlockReceived))
                             } else if topic == TRANSACTIONS_TOPIC {
-                                if let Ok(tx) = bincode::deserialize(&message.data) {
-                                    let _ = event_tx.send(P2pEvent::TransactionReceived(tx)).await;
+                                Some(validate_transaction(&message.data).map(P2pEvent::TransactionReceived))
+                            } else if topic == INTENTS_TOPIC {
+                                let next_height = task_head_height.load(Ordering::Relaxed) + 1;
+                                let gossip = GossipMessage { id: message_id.clone(), source: propagation_source };
+                                Some(validate_intent(&message.data, next_height).map(|signed| P2pEvent::IntentReceived(signed, gossip)))
+                            } else if topic == CONSENSUS_TOPIC {
+                                Some(validate_consensus(&message.data).map(|msg| match msg {
+                                    ConsensusMessage::Proposal(block) => P2pEvent::ProposalReceived(*block),
+                                    ConsensusMessage::Vote(vote) => P2pEvent::VoteReceived(vote),
+                                    ConsensusMessage::Timeout(vote) => P2pEvent::TimeoutReceived(vote),
+                                    ConsensusMessage::NewView(tc) => P2pEvent::NewViewReceived(tc),
+                                }))
+                            } else {
+                                None
+                            };
+                            let mut banned = false;
+                            let (acceptance, event) = match validated {
+                                // The node reports the verdict once it has checked the intent.
+                                Some(Ok(event @ P2pEvent::IntentReceived(..))) => {
+                                    let _ = event_tx.send(event).await;
+                                    continue;
+                                }
+                                Some(Ok(event)) => {
+                                    scores.record_valid(propagation_source);
+                                    (MessageAcceptance::Accept, Some(event))
                                 }
+                                // Not relayed, but no penalty: a block may have overtaken it.
+                                Some(Err(GossipError::IntentExpired(_))) => (MessageAcceptance::Ignore, None),
+                                Some(Err(e)) => {
+                                    debug!("P2P: invalid gossip on {} from {}: {}", topic, propagation_source, e);
+                                    banned = scores.record_invalid(propagation_source, Instant::now());
+                                    (MessageAcceptance::Reject, None)
+                                }
+                                None => (MessageAcceptance::Ignore, None),
+                            };
+                            let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                                &message_id,
+                                &propagation_source,
+                                acceptance,
+                            );
+                            if banned {
+                                ban_peer(&mut swarm, &mut verified, propagation_source, &scores);
+                            }
+                            if let Some(event) = event {
+                                let _ = event_tx.send(event).await;
                             }
                         } else if let SwarmEvent::Behaviour(BoingBehaviourEvent::BlockSync(
                             request_response::Event::Message {
+                                peer,
                                 message: request_response::Message::Response { response, .. },
                                 ..
                             },
                         )) = ev
                         {
-                            if let Some(block) = response.0 {
-                                let _ = event_tx.send(P2pEvent::BlockFetched(block)).await;
+                            if !verified.contains(&peer) {
+                                debug!("P2P: ignoring block response from unverified peer {}", peer);
+                                continue;
+                            }
+                            let event = match response {
+                                BlockResponse::Block(block) => block.map(|b| P2pEvent::BlockFetched(*b)),
+                                BlockResponse::Blocks { start, blocks } => {
+                                    Some(P2pEvent::BlocksFetched { peer, start, blocks })
+                                }
+                                BlockResponse::Headers { start, headers } => {
+                                    Some(P2pEvent::HeadersFetched { peer, start, headers })
+                                }
+                            };
+                            if let Some(event) = event {
+                                let _ = event_tx.send(event).await;
                             }
                         } else if let SwarmEvent::Behaviour(BoingBehaviourEvent::BlockSync(
                             request_response::Event::Message {
@@ -206,20 +549,80 @@ impl P2pNode {
                         )) = ev
                         {
                             if let Some(ref provider) = block_provider {
-                                let block = match &request {
-                                    BlockRequest::ByHash(h) => {
-                                        let hash = Hash(*h);
-                                        provider.get_block_by_hash(&hash)
-                                    }
-                                    BlockRequest::ByHeight(h) => provider.get_block_by_height(*h),
-                                };
-                                let resp = BlockResponse(block);
+                                let resp = serve_block_request(provider.as_ref(), &request);
                                 if let Err(e) = swarm.behaviour_mut().block_sync.send_response(channel, resp) {
                                     warn!("P2P: block response send error: {:?}", e);
                                 }
                             }
+                        } else if let SwarmEvent::Behaviour(BoingBehaviourEvent::Status(
+                            request_response::Event::Message { peer, message },
+                        )) = ev
+                        {
+                            let remote = match message {
+                                request_response::Message::Request { request, channel, .. } => {
+                                    let _ = swarm.behaviour_mut().status.send_response(channel, our_status.clone());
+                                    request
+                                }
+                                request_response::Message::Response { response, .. } => response,
+                            };
+                            if remote.is_compatible(&genesis_hash) {
+                                verified.insert(peer);
+                            } else {
+                                warn!(
+                                    "P2P: peer {} has genesis {} (ours {}), disconnecting",
+                                    peer,
+                                    Hash(remote.genesis_hash),
+                                    genesis_hash
+                                );
+                                verified.remove(&peer);
+                                let _ = swarm.disconnect_peer_id(peer);
+                            }
+                        } else if let SwarmEvent::Behaviour(BoingBehaviourEvent::Identify(
+                            identify::Event::Received { peer_id, info, .. },
+                        )) = ev
+                        {
+                            let needs_peers = discovery.needs_peers(swarm.connected_peers().count());
+                            if let Some(kad) = swarm.behaviour_mut().kad.as_mut() {
+                                for addr in &info.listen_addrs {
+                                    kad.add_address(&peer_id, addr.clone());
+                                }
+                                // Ask the new peer's part of the DHT for more peers right away.
+                                if needs_peers {
+                                    kad.get_closest_peers(PeerId::random());
+                                }
+                            }
+                            if !info.listen_addrs.is_empty() {
+                                addresses.insert(peer_id, info.listen_addrs);
+                            }
+                        } else if let SwarmEvent::Behaviour(BoingBehaviourEvent::Kad(
+                            kad::Event::OutboundQueryProgressed {
+                                result: kad::QueryResult::GetClosestPeers(Ok(ok)), ..
+                            },
+                        )) = ev
+                        {
+                            for peer in ok.peers {
+                                let connected = swarm.connected_peers().count();
+                                if !discovery.has_room(connected) {
+                                    break;
+                                }
+                                if peer.peer_id == *swarm.local_peer_id() || swarm.is_connected(&peer.peer_id) {
+                                    continue;
+                                }
+                                debug!("P2P: dialing {} found via DHT", peer.peer_id);
+                                let _ = swarm.dial(peer.peer_id);
+                            }
+                        } else if let SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } = ev {
+                            addresses.entry(peer_id).or_insert_with(|| vec![endpoint.get_remote_address().clone()]);
+                            if endpoint.is_dialer() {
+                                swarm.behaviour_mut().status.send_request(&peer_id, our_status.clone());
+                            }
+                        } else if let SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } = ev {
+                            verified.remove(&peer_id);
+                            addresses.remove(&peer_id);
+                            scores.disconnected(&peer_id);
                         } else if let SwarmEvent::NewListenAddr { address, .. } = ev {
-                            info!("P2P: listening on {}", address);
+                            // Full address, usable as a bootnode entry by other nodes.
+                            info!("P2P: listening on {}/p2p/{}", address, swarm.local_peer_id());
                         }
                     }
                 }
@@ -230,6 +633,8 @@ impl P2pNode {
             Self {
                 broadcast_tx: Some(broadcast_tx),
                 cmd_tx: Some(cmd_tx),
+                local_peer_id: Some(local_peer_id),
+                head_height,
             },
             event_rx,
         ))
@@ -240,9 +645,16 @@ impl P2pNode {
         Self {
             broadcast_tx: None,
             cmd_tx: None,
+            local_peer_id: None,
+            head_height: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Our PeerId. None for inert nodes.
+    pub fn local_peer_id(&self) -> Option<PeerId> {
+        self.local_peer_id
+    }
+
     /// Returns connected peers. For inert nodes, returns empty vec.
     pub async fn connected_peers(&self) -> Vec<libp2p::PeerId> {
         if let Some(ref ch) = self.cmd_tx {
@@ -256,6 +668,33 @@ impl P2pNode {
         vec![]
     }
 
+    /// Reputation of connected peers that have gossiped to us, and of banned peers. For inert
+    /// nodes, returns empty vec.
+    pub async fn peer_scores(&self) -> Vec<(libp2p::PeerId, PeerScore)> {
+        if let Some(ref ch) = self.cmd_tx {
+            let (tx, rx) = oneshot::channel();
+            if ch.send(Command::GetPeerScores(tx)).await.is_ok() {
+                if let Ok(scores) = rx.await {
+                    return scores;
+                }
+            }
+        }
+        vec![]
+    }
+
+    /// Connected peers with their addresses and scores. For inert nodes, returns empty vec.
+    pub async fn peer_info(&self) -> Vec<ConnectedPeer> {
+        if let Some(ref ch) = self.cmd_tx {
+            let (tx, rx) = oneshot::channel();
+            if ch.send(Command::GetPeerInfo(tx)).await.is_ok() {
+                if let Ok(peers) = rx.await {
+                    return peers;
+                }
+            }
+        }
+        vec![]
+    }
+
     /// Dial a peer by multiaddress (e.g. "/ip4/127.0.0.1/tcp/4001").
     pub fn dial(&self, addr: &str) -> Result<(), P2pError> {
         if let Some(ref ch) = self.cmd_tx {
@@ -265,7 +704,8 @@ impl P2pNode {
         Ok(())
     }
 
-    /// Request a block from a peer. Response arrives via P2pEvent::BlockFetched.
+    /// Request a block or a range from a peer. Responses arrive via P2pEvent::BlockFetched,
+    /// BlocksFetched or HeadersFetched.
     pub fn request_block(&self, peer: libp2p::PeerId, request: BlockRequest) -> Result<(), P2pError> {
         if let Some(ref ch) = self.cmd_tx {
             ch.try_send(Command::RequestBlock(peer, request))
@@ -274,21 +714,86 @@ impl P2pNode {
         Ok(())
     }
 
+    /// Report a peer that served invalid sync data. It loses reputation as for an invalid
+    /// gossip message, and is banned once its score falls below the threshold.
+    pub fn report_peer(&self, peer: PeerId) -> Result<(), P2pError> {
+        if let Some(ref ch) = self.cmd_tx {
+            ch.try_send(Command::ReportPeer(peer))
+                .map_err(|e| P2pError::Network(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Complete the validation of a gossiped message the node checks itself (intents):
+    /// `Accept` relays it and credits the peer that sent it, `Reject` drops it and costs
+    /// that peer reputation as for any invalid message, `Ignore` only drops it.
+    pub fn report_validation(&self, message: GossipMessage, acceptance: MessageAcceptance) -> Result<(), P2pError> {
+        if let Some(ref ch) = self.cmd_tx {
+            ch.try_send(Command::ReportValidation(message, acceptance))
+                .map_err(|e| P2pError::Network(e.to_string()))?;
+        }
+        Ok(())
+    }
+
     pub fn broadcast_block(&self, block: &Block) -> Result<(), P2pError> {
         if let Some(ref ch) = self.broadcast_tx {
-            ch.try_send(BroadcastMsg::Block(block.clone()))
+            ch.try_send(BroadcastMsg::Block(Box::new(block.clone())))
                 .map_err(|e| P2pError::Network(e.to_string()))?;
         }
         Ok(())
     }
 
-    pub fn broadcast_transaction(&self, tx: &Transaction) -> Result<(), P2pError> {
+    /// Gossip a signed transaction so it reaches the leader's mempool.
+    pub fn broadcast_transaction(&self, tx: &SignedTransaction) -> Result<(), P2pError> {
         if let Some(ref ch) = self.broadcast_tx {
             ch.try_send(BroadcastMsg::Transaction(tx.clone()))
                 .map_err(|e| P2pError::Network(e.to_string()))?;
         }
         Ok(())
     }
+
+    /// Gossip a signed intent so solvers on every node see it.
+    pub fn broadcast_intent(&self, signed: &SignedIntent) -> Result<(), P2pError> {
+        if let Some(ref ch) = self.broadcast_tx {
+            ch.try_send(BroadcastMsg::Intent(signed.clone()))
+                .map_err(|e| P2pError::Network(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Set the committed chain height; gossiped intents whose deadline is before the next
+    /// block are dropped.
+    pub fn set_head_height(&self, height: u64) {
+        self.head_height.store(height, Ordering::Relaxed);
+    }
+
+    /// Gossip a signed block proposal to validators.
+    pub fn broadcast_proposal(&self, block: &Block) -> Result<(), P2pError> {
+        self.broadcast_consensus(ConsensusMessage::Proposal(Box::new(block.clone())))
+    }
+
+    /// Gossip a signed vote to validators.
+    pub fn broadcast_vote(&self, vote: &Vote) -> Result<(), P2pError> {
+        self.broadcast_consensus(ConsensusMessage::Vote(vote.clone()))
+    }
+
+    /// Gossip a signed timeout vote to validators.
+    pub fn broadcast_timeout(&self, vote: &TimeoutVote) -> Result<(), P2pError> {
+        self.broadcast_consensus(ConsensusMessage::Timeout(vote.clone()))
+    }
+
+    /// Gossip a timeout certificate (new-view message) to validators.
+    pub fn broadcast_new_view(&self, tc: &TimeoutCertificate) -> Result<(), P2pError> {
+        self.broadcast_consensus(ConsensusMessage::NewView(tc.clone()))
+    }
+
+    fn broadcast_consensus(&self, msg: ConsensusMessage) -> Result<(), P2pError> {
+        if let Some(ref ch) = self.broadcast_tx {
+            ch.try_send(BroadcastMsg::Consensus(msg))
+                .map_err(|e| P2pError::Network(e.to_string()))?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for P2pNode {
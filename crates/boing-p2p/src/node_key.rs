@@ -0,0 +1,71 @@
+//! Node identity — the libp2p keypair that fixes a node's PeerId across restarts.
+//!
+//! The key is an Ed25519 secret stored as hex in the data directory (the same format as
+//! validator keys) and generated on first start. Validators may instead derive it from
+//! their validator key, so the PeerId follows the key rather than the data directory.
+
+use std::path::Path;
+
+use libp2p::identity::Keypair;
+
+/// Default node key file name inside the data directory.
+pub const NODE_KEY_FILE: &str = "node_key";
+
+/// Domain separator for deriving a node key from a validator key.
+const DERIVE_CONTEXT: &[u8] = b"boing/node-key/v1";
+
+/// Load the node key at `path`, or generate one and save it there if the file is missing.
+pub fn load_or_generate_node_key(path: impl AsRef<Path>) -> Result<Keypair, NodeKeyError> {
+    let path = path.as_ref();
+    if path.exists() {
+        return load_node_key(path);
+    }
+    let seed: [u8; 32] = rand::random();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    write_secret(path, &hex::encode(seed))?;
+    tracing::info!("P2P: generated node key at {}", path.display());
+    keypair_from_seed(seed)
+}
+
+/// Load a hex-encoded 32-byte Ed25519 node key.
+pub fn load_node_key(path: impl AsRef<Path>) -> Result<Keypair, NodeKeyError> {
+    let text = std::fs::read_to_string(path)?;
+    let bytes = hex::decode(text.trim().trim_start_matches("0x")).map_err(|_| NodeKeyError::InvalidKey)?;
+    let seed: [u8; 32] = bytes.try_into().map_err(|_| NodeKeyError::InvalidKey)?;
+    keypair_from_seed(seed)
+}
+
+/// Node key derived from a validator's Ed25519 secret key. The validator key itself is not
+/// reused, so a compromised transport key does not expose the signing key.
+pub fn node_key_from_validator_key(validator_secret: &[u8; 32]) -> Keypair {
+    let mut hasher = boing_primitives::hasher();
+    hasher.update(DERIVE_CONTEXT);
+    hasher.update(validator_secret);
+    keypair_from_seed(*hasher.finalize().as_bytes()).expect("32-byte seed is a valid Ed25519 key")
+}
+
+fn keypair_from_seed(mut seed: [u8; 32]) -> Result<Keypair, NodeKeyError> {
+    Keypair::ed25519_from_bytes(&mut seed).map_err(|_| NodeKeyError::InvalidKey)
+}
+
+/// Write the secret readable only by the owner where the platform supports it.
+fn write_secret(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    std::io::Write::write_all(&mut options.open(path)?, contents.as_bytes())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NodeKeyError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Node key must be a hex-encoded 32-byte Ed25519 secret")]
+    InvalidKey,
+}
@@ -0,0 +1,101 @@
+//! Gossip validation — every gossiped message is checked before gossipsub relays it.
+//!
+//! Messages are decoded and checked for what needs no chain state: the signatures on
+//! transactions, intents, blocks and votes, and a block's transaction root, justify QC
+//! commitment and VRF proof.
+//! Intents are also checked against the head height, so expired ones are not relayed; a
+//! valid intent is only relayed once the node has also checked it against its pool and state.
+//! Valid messages are accepted and relayed; invalid ones are rejected and cost the peer that
+//! sent them reputation (see [`crate::PeerScore`]). Checks against state and the validator
+//! set happen when the node imports the message.
+
+use boing_primitives::{tx_root, Block, SignedIntent, SignedTransaction, TimeoutVote};
+
+use crate::consensus::ConsensusMessage;
+
+/// Decode and check a block from the blocks topic.
+pub fn validate_block(data: &[u8]) -> Result<Block, GossipError> {
+    let block: Block = decode(data)?;
+    check_block(&block)?;
+    Ok(block)
+}
+
+/// Decode and check a signed transaction from the transactions topic.
+pub fn validate_transaction(data: &[u8]) -> Result<SignedTransaction, GossipError> {
+    let signed: SignedTransaction = decode(data)?;
+    signed.verify().map_err(|_| GossipError::InvalidSignature)?;
+    Ok(signed)
+}
+
+/// Decode and check a signed intent from the intents topic; it must still be settleable in
+/// the block at `next_height`. An expired intent is [`GossipError::IntentExpired`], which
+/// may just have been overtaken by a block in transit and is not the sender's fault.
+pub fn validate_intent(data: &[u8], next_height: u64) -> Result<SignedIntent, GossipError> {
+    let signed: SignedIntent = decode(data)?;
+    signed.verify().map_err(|_| GossipError::InvalidSignature)?;
+    if signed.intent.deadline_block < next_height {
+        return Err(GossipError::IntentExpired(signed.intent.deadline_block));
+    }
+    Ok(signed)
+}
+
+/// Decode and check a consensus message. Timeout certificates are checked vote by vote;
+/// whether the signers form a quorum depends on the validator set.
+pub fn validate_consensus(data: &[u8]) -> Result<ConsensusMessage, GossipError> {
+    let msg: ConsensusMessage = decode(data)?;
+    match &msg {
+        ConsensusMessage::Proposal(block) => check_block(block)?,
+        ConsensusMessage::Vote(vote) => vote.verify().map_err(|_| GossipError::InvalidSignature)?,
+        ConsensusMessage::Timeout(vote) => vote.verify().map_err(|_| GossipError::InvalidSignature)?,
+        ConsensusMessage::NewView(tc) => {
+            if tc.signatures.is_empty() {
+                return Err(GossipError::EmptyCertificate);
+            }
+            for (validator, signature) in &tc.signatures {
+                let vote = TimeoutVote { view: tc.view, validator: *validator, signature: *signature };
+                vote.verify().map_err(|_| GossipError::InvalidSignature)?;
+            }
+        }
+    }
+    Ok(msg)
+}
+
+/// Header checks that need no chain state.
+fn check_block(block: &Block) -> Result<(), GossipError> {
+    if block.header.height == 0 {
+        return Err(GossipError::Genesis);
+    }
+    block.verify_signature().map_err(|_| GossipError::InvalidSignature)?;
+    if !block.justify_matches() {
+        return Err(GossipError::JustifyMismatch);
+    }
+    if tx_root(&block.transactions) != block.header.tx_root {
+        return Err(GossipError::TxRootMismatch);
+    }
+    block.header.verify_vrf().map_err(|_| GossipError::InvalidVrf)?;
+    Ok(())
+}
+
+fn decode<T: serde::de::DeserializeOwned>(data: &[u8]) -> Result<T, GossipError> {
+    bincode::deserialize(data).map_err(|e| GossipError::Decode(e.to_string()))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GossipError {
+    #[error("Undecodable message: {0}")]
+    Decode(String),
+    #[error("Invalid signature")]
+    InvalidSignature,
+    #[error("Genesis block gossiped")]
+    Genesis,
+    #[error("Transaction root does not match the block's transactions")]
+    TxRootMismatch,
+    #[error("Invalid VRF proof")]
+    InvalidVrf,
+    #[error("Justify QC does not match the one the header commits to")]
+    JustifyMismatch,
+    #[error("Timeout certificate has no signatures")]
+    EmptyCertificate,
+    #[error("Intent expired at block {0}")]
+    IntentExpired(u64),
+}
@@ -4,6 +4,12 @@
 
 use serde::{Deserialize, Serialize};
 
+use boing_primitives::signature::{Signature, SignatureError};
+use boing_primitives::{hasher, Hash};
+
+/// Largest inline payload in bytes; larger SDPs go off-chain behind a content pointer.
+pub const MAX_SIGNALING_PAYLOAD: usize = 4096;
+
 /// On-chain rate limit for signaling messages (spam prevention).
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignalingRateLimit {
@@ -61,7 +67,7 @@ pub struct ContentPointer {
 }
 
 /// WebRTC signaling message — posted to Boing contract or gossip.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SignalingMessage {
     pub kind: SignalingMessageKind,
     /// Sender's Boing account ID (public key).
@@ -122,53 +128,101 @@ impl SignalingMessage {
     }
 }
 
-// --- STUN/TURN Registry & Reputation ---
-
-/// Performance metrics for a STUN/TURN server (reputation inputs).
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
-pub struct StunTurnMetrics {
-    pub uptime_bps: u16,
-    pub avg_latency_ms: u32,
-    pub success_rate_bps: u16,
-    pub throughput_mb: u64,
+/// Signaling message signed by its sender, as posted over RPC. The chain id and timestamp
+/// are signed with it so a captured message cannot be replayed on another network or
+/// after it expires.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedSignalingMessage {
+    pub message: SignalingMessage,
+    pub chain_id: u64,
+    /// Unix seconds when the sender signed the message.
+    pub timestamp_secs: u64,
+    pub signature: Signature,
 }
 
-/// Reputation score for a STUN/TURN server (0–10000 = 0–100%).
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
-pub struct StunTurnReputation {
-    pub score: u16,
+impl SignedSignalingMessage {
+    /// Sign a message with the sender's Ed25519 key (sender must be the key's public key).
+    pub fn new(
+        message: SignalingMessage,
+        chain_id: u64,
+        timestamp_secs: u64,
+        signing_key: &ed25519_dalek::SigningKey,
+    ) -> Self {
+        use ed25519_dalek::Signer;
+        let hash = signable_hash(&message, chain_id, timestamp_secs);
+        let signature = Signature(signing_key.sign(hash.as_bytes()).to_bytes());
+        Self { message, chain_id, timestamp_secs, signature }
+    }
+
+    /// Message id: hash of the signed content (independent of the signature bytes).
+    pub fn id(&self) -> Hash {
+        signable_hash(&self.message, self.chain_id, self.timestamp_secs)
+    }
+
+    /// Verify the signature against `message.sender`.
+    pub fn verify(&self) -> Result<(), SignatureError> {
+        use ed25519_dalek::Verifier;
+        let pk = ed25519_dalek::VerifyingKey::from_bytes(&self.message.sender)
+            .map_err(|_| SignatureError::InvalidPublicKey)?;
+        let sig = ed25519_dalek::Signature::from_bytes(&self.signature.0);
+        pk.verify(self.id().as_bytes(), &sig)
+            .map_err(|_| SignatureError::InvalidSignature)
+    }
 }
 
-impl StunTurnReputation {
-    pub const MAX: u16 = 10_000;
+/// Request to read the signaling messages addressed to `recipient`, signed by the recipient
+/// so no one else can read its SDP and ICE payloads. Nodes accept it only for a short time
+/// around `timestamp_secs`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignalingQuery {
+    pub recipient: [u8; 32],
+    pub chain_id: u64,
+    /// Unix seconds when the recipient signed the query.
+    pub timestamp_secs: u64,
+    pub signature: Signature,
+}
 
-    pub fn new(score: u16) -> Self {
-        Self {
-            score: score.min(Self::MAX),
-        }
+impl SignalingQuery {
+    /// Sign a query for the messages of the key's account.
+    pub fn new(chain_id: u64, timestamp_secs: u64, signing_key: &ed25519_dalek::SigningKey) -> Self {
+        use ed25519_dalek::Signer;
+        let recipient = signing_key.verifying_key().to_bytes();
+        let hash = query_hash(&recipient, chain_id, timestamp_secs);
+        let signature = Signature(signing_key.sign(hash.as_bytes()).to_bytes());
+        Self { recipient, chain_id, timestamp_secs, signature }
     }
 
-    pub fn from_metrics(metrics: &StunTurnMetrics) -> Self {
-        let uptime = metrics.uptime_bps as u32;
-        let success = metrics.success_rate_bps as u32;
-        let latency_penalty = ((metrics.avg_latency_ms.saturating_sub(100)).min(500) / 10) as u16;
-        let base = ((uptime + success) / 2) as u16;
-        let raw = base.saturating_add(5000).saturating_sub(latency_penalty);
-        Self::new(raw.min(Self::MAX))
+    /// Verify the signature against `recipient`.
+    pub fn verify(&self) -> Result<(), SignatureError> {
+        use ed25519_dalek::Verifier;
+        let pk = ed25519_dalek::VerifyingKey::from_bytes(&self.recipient)
+            .map_err(|_| SignatureError::InvalidPublicKey)?;
+        let sig = ed25519_dalek::Signature::from_bytes(&self.signature.0);
+        let hash = query_hash(&self.recipient, self.chain_id, self.timestamp_secs);
+        pk.verify(hash.as_bytes(), &sig)
+            .map_err(|_| SignatureError::InvalidSignature)
     }
 }
 
-/// Registry entry for a registered STUN/TURN server.
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct StunTurnRegistryEntry {
-    pub server_id: [u8; 32],
-    pub multiaddr: String,
-    pub stake: u128,
-    pub reputation: StunTurnReputation,
-    pub registered_at_secs: u64,
+fn query_hash(recipient: &[u8; 32], chain_id: u64, timestamp_secs: u64) -> Hash {
+    let mut h = hasher();
+    h.update(b"boing-signaling-query");
+    h.update(&bincode::serialize(&(chain_id, timestamp_secs, recipient)).unwrap_or_default());
+    Hash(*h.finalize().as_bytes())
+}
+
+fn signable_hash(message: &SignalingMessage, chain_id: u64, timestamp_secs: u64) -> Hash {
+    let mut h = hasher();
+    h.update(&bincode::serialize(&(chain_id, timestamp_secs, message)).unwrap_or_default());
+    Hash(*h.finalize().as_bytes())
 }
 
-// --- Signaling Contract Interface (stub) ---
+// --- STUN/TURN Registry & Reputation ---
+
+// The registry is on-chain state (see boing-primitives `relay`); re-exported for callers here.
+pub use boing_primitives::relay::{StunTurnMetrics, StunTurnRegistryEntry, StunTurnReputation};
+
+// --- Signaling Contract Interface ---
 
 /// Result of posting a signaling message to the contract.
 #[derive(Debug)]
@@ -177,12 +231,11 @@ pub struct SignalingPostResult {
     pub message_id: Option<[u8; 32]>,
 }
 
-/// Stub interface for the on-chain WebRTC signaling contract.
-/// See WEBRTC-SIGNALING.md for full design. Implementations would
-/// interact with the actual Boing smart contract.
-#[allow(unused_variables)]
+/// Interface of the WebRTC signaling contract. See DECENTRALIZATION-AND-NETWORKING.md
+/// for the design; the node implements it natively over its account state.
 pub trait SignalingContract {
-    /// Post an offer. Returns result with message_id if accepted.
+    /// Post an offer, reserving `deposit` from the sender. Returns result with message_id
+    /// if accepted.
     fn post_offer(
         &self,
         sender: [u8; 32],
@@ -190,33 +243,25 @@ pub trait SignalingContract {
         payload: Option<Vec<u8>>,
         content_pointer: Option<ContentPointer>,
         deposit: u128,
-    ) -> SignalingPostResult {
-        SignalingPostResult { ok: false, message_id: None }
-    }
+    ) -> SignalingPostResult;
 
-    /// Post an answer in response to an offer.
+    /// Post an answer in response to an offer from `recipient`.
     fn post_answer(
         &self,
         sender: [u8; 32],
         recipient: [u8; 32],
         payload: Option<Vec<u8>>,
         content_pointer: Option<ContentPointer>,
-    ) -> SignalingPostResult {
-        SignalingPostResult { ok: false, message_id: None }
-    }
+    ) -> SignalingPostResult;
 
-    /// Post an ICE candidate.
+    /// Post an ICE candidate to a peer with an open offer in either direction.
     fn post_ice_candidate(
         &self,
         sender: [u8; 32],
         recipient: [u8; 32],
         payload: Vec<u8>,
-    ) -> SignalingPostResult {
-        SignalingPostResult { ok: false, message_id: None }
-    }
+    ) -> SignalingPostResult;
 
-    /// Fetch pending offers for a recipient (stub).
-    fn get_pending_offers(&self, recipient: [u8; 32]) -> Vec<SignalingMessage> {
-        vec![]
-    }
+    /// Unanswered, unexpired offers for a recipient, oldest first.
+    fn get_pending_offers(&self, recipient: [u8; 32]) -> Vec<SignalingMessage>;
 }
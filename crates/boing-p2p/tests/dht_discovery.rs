@@ -0,0 +1,60 @@
+//! Test Kademlia discovery: two nodes that only know a bootnode find each other.
+
+use std::time::Duration;
+
+use boing_p2p::{BootnodeEntry, P2pConfig, P2pNode, PeerDiscoveryConfig};
+use boing_primitives::Hash;
+
+fn discovery(bootnode: Option<&str>) -> PeerDiscoveryConfig {
+    let mut config = PeerDiscoveryConfig::default_mainnet();
+    config.bootnodes = bootnode
+        .map(|addr| BootnodeEntry { multiaddr: addr.to_string(), peer_id: None })
+        .into_iter()
+        .collect();
+    config
+}
+
+#[test]
+fn test_peer_count_limits() {
+    let config = PeerDiscoveryConfig { min_peers: 2, max_peers: 3, ..Default::default() };
+    assert!(config.needs_peers(1));
+    assert!(!config.needs_peers(2));
+    assert!(config.has_room(2));
+    assert!(!config.has_room(3));
+    assert!(PeerDiscoveryConfig::default().has_room(1_000), "0 max_peers means no limit");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_nodes_find_each_other_through_bootnode() {
+    let genesis = Hash([5u8; 32]);
+    let boot_addr = "/ip4/127.0.0.1/tcp/47521";
+    let (_boot, _boot_rx) =
+        P2pNode::new(P2pConfig::new(boot_addr, genesis).with_discovery(discovery(None)), None).unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let (a, _a_rx) = P2pNode::new(
+        P2pConfig::new("/ip4/127.0.0.1/tcp/0", genesis).with_discovery(discovery(Some(boot_addr))),
+        None,
+    )
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let (b, _b_rx) = P2pNode::new(
+        P2pConfig::new("/ip4/127.0.0.1/tcp/0", genesis).with_discovery(discovery(Some(boot_addr))),
+        None,
+    )
+    .unwrap();
+
+    // Each knows only the bootnode; the DHT introduces them to each other.
+    let found = tokio::time::timeout(Duration::from_secs(15), async {
+        loop {
+            if a.peer_info().await.len() >= 2 && b.peer_info().await.len() >= 2 {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    })
+    .await;
+    assert!(found.is_ok(), "a={:?} b={:?}", a.peer_info().await, b.peer_info().await);
+    let peers = a.peer_info().await;
+    assert!(peers.iter().all(|p| !p.addresses.is_empty()));
+}
@@ -0,0 +1,163 @@
+//! Test gossip validation and the peer reputation it feeds.
+
+use boing_p2p::{
+    validate_block, validate_consensus, validate_intent, validate_transaction, ConsensusMessage,
+    GossipError, PeerId, PeerScore, PeerScores, BAN_DURATION, INVALID_MESSAGE_PENALTY, MAX_RELIABILITY,
+};
+use boing_primitives::{
+    tx_root, AccessList, AccountId, Block, BlockHeader, Hash, Intent, IntentKind, SignedIntent,
+    SignedTransaction, TimeoutCertificate, TimeoutVote, Transaction, TransactionPayload, Vote,
+};
+use ed25519_dalek::SigningKey;
+
+fn key() -> SigningKey {
+    SigningKey::from_bytes(&[1u8; 32])
+}
+
+fn transfer(nonce: u64) -> SignedTransaction {
+    let sender = AccountId(key().verifying_key().to_bytes());
+    let to = AccountId([2u8; 32]);
+    let tx = Transaction {
+        chain_id: 1,
+        nonce,
+        sender,
+        payload: TransactionPayload::Transfer { to, amount: 1 },
+        access_list: AccessList::new(vec![sender, to], vec![sender, to]),
+    };
+    SignedTransaction::new(tx, &key())
+}
+
+fn signed_block() -> Block {
+    let txs = vec![transfer(0).tx];
+    let mut header = BlockHeader {
+        parent_hash: Hash::ZERO,
+        height: 1,
+        round: 1,
+        timestamp: 1,
+        proposer: AccountId(key().verifying_key().to_bytes()),
+        tx_root: tx_root(&txs),
+        state_root: Hash::ZERO,
+        validator_set_hash: Hash::ZERO,
+        vrf: Default::default(),
+        randomness: Hash::ZERO,
+        justify_hash: Hash::ZERO,
+    };
+    header.prove_vrf(&key());
+    let mut block = Block::new(header, txs);
+    block.sign(&key());
+    block
+}
+
+fn encode<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    bincode::serialize(value).unwrap()
+}
+
+#[test]
+fn test_block_checks() {
+    let block = signed_block();
+    assert_eq!(validate_block(&encode(&block)).unwrap(), block);
+    assert!(matches!(validate_block(b"garbage"), Err(GossipError::Decode(_))));
+
+    let mut unsigned = block.clone();
+    unsigned.signature = None;
+    assert!(matches!(validate_block(&encode(&unsigned)), Err(GossipError::InvalidSignature)));
+
+    // Extra transactions keep the signed header but break its transaction root.
+    let mut padded = block.clone();
+    padded.transactions.push(transfer(1).tx);
+    assert!(matches!(validate_block(&encode(&padded)), Err(GossipError::TxRootMismatch)));
+
+    let mut forged_vrf = block.clone();
+    forged_vrf.header.vrf.output[0] ^= 1;
+    forged_vrf.sign(&key());
+    assert!(matches!(validate_block(&encode(&forged_vrf)), Err(GossipError::InvalidVrf)));
+}
+
+#[test]
+fn test_transaction_and_consensus_checks() {
+    let tx = transfer(0);
+    assert_eq!(validate_transaction(&encode(&tx)).unwrap(), tx);
+    let mut forged = tx.clone();
+    forged.tx.nonce = 1;
+    assert!(matches!(validate_transaction(&encode(&forged)), Err(GossipError::InvalidSignature)));
+
+    let vote = Vote::new(Hash([7u8; 32]), &key());
+    assert!(validate_consensus(&encode(&ConsensusMessage::Vote(vote.clone()))).is_ok());
+    let mut bad_vote = vote;
+    bad_vote.block_hash = Hash([8u8; 32]);
+    assert!(matches!(
+        validate_consensus(&encode(&ConsensusMessage::Vote(bad_vote))),
+        Err(GossipError::InvalidSignature)
+    ));
+
+    let timeout = TimeoutVote::new(3, &key());
+    let tc = TimeoutCertificate::from_votes(3, [&timeout]);
+    assert!(validate_consensus(&encode(&ConsensusMessage::NewView(tc.clone()))).is_ok());
+    let wrong_view = TimeoutCertificate { view: 4, ..tc };
+    assert!(validate_consensus(&encode(&ConsensusMessage::NewView(wrong_view))).is_err());
+    let empty = TimeoutCertificate { view: 3, signatures: vec![] };
+    assert!(matches!(
+        validate_consensus(&encode(&ConsensusMessage::NewView(empty))),
+        Err(GossipError::EmptyCertificate)
+    ));
+}
+
+#[test]
+fn test_intent_checks() {
+    let signer = AccountId(key().verifying_key().to_bytes());
+    let kind = IntentKind::Transfer { to: AccountId([2u8; 32]), amount: 5 };
+    let intent = Intent { chain_id: 1, signer, kind, nonce: 0, deadline_block: 10 };
+    let signed = SignedIntent::new(intent, &key());
+    assert_eq!(validate_intent(&encode(&signed), 10).unwrap(), signed);
+    assert!(matches!(validate_intent(&encode(&signed), 11), Err(GossipError::IntentExpired(10))));
+
+    let mut forged = signed;
+    forged.intent.deadline_block = 100;
+    assert!(matches!(validate_intent(&encode(&forged), 11), Err(GossipError::InvalidSignature)));
+    assert!(matches!(validate_intent(&[1, 2, 3], 1), Err(GossipError::Decode(_))));
+}
+
+#[test]
+fn test_invalid_messages_lead_to_ban() {
+    let mut score = PeerScore::default();
+    for _ in 0..1_000 {
+        score.record_valid();
+    }
+    assert_eq!(score.reliability, MAX_RELIABILITY, "credit is capped");
+
+    let mut invalid = 0;
+    while !score.is_banned() {
+        score.record_invalid();
+        invalid += 1;
+    }
+    let expected = (MAX_RELIABILITY as i64 - boing_p2p::BAN_THRESHOLD) / INVALID_MESSAGE_PENALTY as i64 + 1;
+    assert_eq!(invalid, expected);
+    assert!(score.rank() < boing_p2p::BAN_THRESHOLD);
+}
+
+#[test]
+fn test_scores_forgotten_on_disconnect_and_ban_expiry() {
+    let mut scores = PeerScores::new();
+    let now = std::time::Instant::now();
+    let (honest, liar) = (PeerId::random(), PeerId::random());
+    scores.record_valid(honest);
+    while !scores.record_invalid(liar, now) {}
+    assert!(scores.is_banned(&liar));
+    assert!(!scores.record_invalid(liar, now), "banned once");
+
+    // Churned peers leave nothing behind; a banned peer is kept until its ban ends.
+    for _ in 0..100 {
+        let peer = PeerId::random();
+        scores.record_valid(peer);
+        scores.disconnected(&peer);
+    }
+    scores.disconnected(&honest);
+    scores.disconnected(&liar);
+    assert_eq!(scores.len(), 1);
+    assert!(scores.get(&liar).unwrap().is_banned());
+
+    assert!(scores.expire_bans(now + BAN_DURATION / 2).is_empty());
+    assert_eq!(scores.expire_bans(now + BAN_DURATION), vec![liar]);
+    assert!(scores.is_empty());
+    assert!(!scores.is_banned(&liar));
+}
@@ -0,0 +1,48 @@
+//! Test node identity keys: a generated key is saved and reloaded with the same PeerId,
+//! keys derived from a validator key are stable, and the P2P node runs under the given key.
+
+use boing_p2p::{
+    load_node_key, load_or_generate_node_key, node_key_from_validator_key, NodeKeyError, P2pConfig, P2pNode,
+};
+use boing_primitives::Hash;
+
+#[test]
+fn test_generated_key_persists() {
+    let dir = std::env::temp_dir().join(format!("boing-{}-test_generated_key_persists", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("node_key");
+
+    let first = load_or_generate_node_key(&path).unwrap();
+    assert!(path.exists());
+    let again = load_or_generate_node_key(&path).unwrap();
+    assert_eq!(first.public().to_peer_id(), again.public().to_peer_id());
+    assert_eq!(load_node_key(&path).unwrap().public(), first.public());
+
+    std::fs::write(&path, "not hex").unwrap();
+    assert!(matches!(load_or_generate_node_key(&path), Err(NodeKeyError::InvalidKey)));
+    std::fs::write(&path, hex::encode([7u8; 16])).unwrap();
+    assert!(matches!(load_node_key(&path), Err(NodeKeyError::InvalidKey)));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_key_derived_from_validator_key() {
+    let a = node_key_from_validator_key(&[1u8; 32]);
+    assert_eq!(a.public(), node_key_from_validator_key(&[1u8; 32]).public());
+    assert_ne!(a.public(), node_key_from_validator_key(&[2u8; 32]).public());
+
+    // The validator's signing key is not reused as the transport key.
+    let validator = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]);
+    let derived = a.public().try_into_ed25519().unwrap().to_bytes();
+    assert_ne!(derived, validator.verifying_key().to_bytes());
+}
+
+#[tokio::test]
+async fn test_node_uses_configured_key() {
+    let key = node_key_from_validator_key(&[3u8; 32]);
+    let expected = key.public().to_peer_id();
+    let config = P2pConfig::new("/ip4/127.0.0.1/tcp/0", Hash([0u8; 32])).with_keypair(key);
+    let (node, _rx) = P2pNode::new(config, None).unwrap();
+    assert_eq!(node.local_peer_id(), Some(expected));
+    assert_eq!(P2pNode::inert().local_peer_id(), None);
+}
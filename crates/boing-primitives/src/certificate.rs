@@ -0,0 +1,230 @@
+//! Block proposals, validator votes, quorum certificates, and timeout certificates.
+//!
+//! The proposer signs the block hash; validators vote by signing the block hash
+//! under a separate domain. Votes from validators holding more than two thirds of the
+//! stake form a quorum certificate (QC). When a view makes no progress, validators sign
+//! timeout votes for it; a stake quorum of them forms a timeout certificate (TC) that moves
+//! everyone to the next view.
+
+use std::collections::HashSet;
+
+use ed25519_dalek::{Signature as Ed25519Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::hash::{hasher, Hash};
+use crate::signature::{Signature, SignatureError};
+use crate::types::AccountId;
+use crate::validator_set::ValidatorSet;
+
+const PROPOSAL_DOMAIN: &[u8] = b"boing/proposal/v1";
+const VOTE_DOMAIN: &[u8] = b"boing/vote/v1";
+const TIMEOUT_DOMAIN: &[u8] = b"boing/timeout/v1";
+
+fn domain_hash(domain: &[u8], block_hash: &Hash) -> Hash {
+    let mut h = hasher();
+    h.update(domain);
+    h.update(block_hash.as_bytes());
+    Hash(*h.finalize().as_bytes())
+}
+
+fn timeout_hash(view: u64) -> Hash {
+    let mut h = hasher();
+    h.update(TIMEOUT_DOMAIN);
+    h.update(&view.to_le_bytes());
+    Hash(*h.finalize().as_bytes())
+}
+
+fn sign_msg(msg: &Hash, signing_key: &SigningKey) -> Signature {
+    Signature(signing_key.sign(msg.as_bytes()).to_bytes())
+}
+
+fn verify_msg(msg: &Hash, sig: &Signature, signer: &AccountId) -> Result<(), SignatureError> {
+    let pk = VerifyingKey::from_bytes(&signer.0).map_err(|_| SignatureError::InvalidPublicKey)?;
+    let ed_sig = Ed25519Signature::from_bytes(&sig.0);
+    pk.verify_strict(msg.as_bytes(), &ed_sig)
+        .map_err(|_| SignatureError::InvalidSignature)
+}
+
+fn sign_domain(domain: &[u8], block_hash: &Hash, signing_key: &SigningKey) -> Signature {
+    sign_msg(&domain_hash(domain, block_hash), signing_key)
+}
+
+fn verify_domain(
+    domain: &[u8],
+    block_hash: &Hash,
+    sig: &Signature,
+    signer: &AccountId,
+) -> Result<(), SignatureError> {
+    verify_msg(&domain_hash(domain, block_hash), sig, signer)
+}
+
+/// Check that `signatures` are from distinct members of `validators` with valid signatures
+/// over `msg`, together holding a quorum of the stake.
+fn verify_quorum(
+    msg: &Hash,
+    signatures: &[(AccountId, Signature)],
+    validators: &ValidatorSet,
+) -> Result<(), CertificateError> {
+    let mut seen = HashSet::new();
+    for (validator, sig) in signatures {
+        if !validators.contains(validator) {
+            return Err(CertificateError::UnknownValidator(*validator));
+        }
+        if !seen.insert(*validator) {
+            return Err(CertificateError::DuplicateSigner(*validator));
+        }
+        verify_msg(msg, sig, validator).map_err(|_| CertificateError::InvalidSignature(*validator))?;
+    }
+    let have = validators.stake_of_all(&seen);
+    let need = validators.quorum_stake();
+    if have < need {
+        return Err(CertificateError::InsufficientStake { have, need });
+    }
+    Ok(())
+}
+
+/// Proposer signature over a block hash.
+pub fn sign_proposal(block_hash: &Hash, signing_key: &SigningKey) -> Signature {
+    sign_domain(PROPOSAL_DOMAIN, block_hash, signing_key)
+}
+
+/// Verify a proposer signature. `proposer` must be the 32-byte public key.
+pub fn verify_proposal(block_hash: &Hash, sig: &Signature, proposer: &AccountId) -> Result<(), SignatureError> {
+    verify_domain(PROPOSAL_DOMAIN, block_hash, sig, proposer)
+}
+
+/// A validator's signed vote for a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    pub block_hash: Hash,
+    pub validator: AccountId,
+    pub signature: Signature,
+}
+
+impl Vote {
+    /// Sign a vote for `block_hash`. The validator is the key's public key.
+    pub fn new(block_hash: Hash, signing_key: &SigningKey) -> Self {
+        Self {
+            block_hash,
+            validator: AccountId(signing_key.verifying_key().to_bytes()),
+            signature: sign_domain(VOTE_DOMAIN, &block_hash, signing_key),
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), SignatureError> {
+        verify_domain(VOTE_DOMAIN, &self.block_hash, &self.signature, &self.validator)
+    }
+}
+
+/// Quorum certificate — vote signatures over one block hash from a stake quorum.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuorumCertificate {
+    pub block_hash: Hash,
+    pub signatures: Vec<(AccountId, Signature)>,
+}
+
+impl QuorumCertificate {
+    /// Aggregate votes for the same block. Votes are not verified here.
+    pub fn from_votes<'a>(block_hash: Hash, votes: impl IntoIterator<Item = &'a Vote>) -> Self {
+        let signatures = votes
+            .into_iter()
+            .filter(|v| v.block_hash == block_hash)
+            .map(|v| (v.validator, v.signature))
+            .collect();
+        Self { block_hash, signatures }
+    }
+
+    /// Number of signatures.
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Hash of the certificate, signatures included. A block header commits to its justify
+    /// QC this way, so the signer set cannot be swapped once the block is signed.
+    pub fn hash(&self) -> Hash {
+        let mut h = hasher();
+        h.update(&bincode::serialize(self).unwrap_or_default());
+        Hash(*h.finalize().as_bytes())
+    }
+
+    /// Validators whose votes the certificate carries.
+    pub fn signers(&self) -> Vec<AccountId> {
+        self.signatures.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Verify the certificate for `block_hash` against `validators`: distinct signers from the
+    /// set, valid vote signatures, and more than two thirds of the stake among them.
+    pub fn verify(&self, block_hash: &Hash, validators: &ValidatorSet) -> Result<(), CertificateError> {
+        if self.block_hash != *block_hash {
+            return Err(CertificateError::WrongBlock);
+        }
+        verify_quorum(&domain_hash(VOTE_DOMAIN, block_hash), &self.signatures, validators)
+    }
+}
+
+/// A validator's signed statement that view `view` made no progress. Views never repeat
+/// across heights, so the view alone identifies the round.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeoutVote {
+    pub view: u64,
+    pub validator: AccountId,
+    pub signature: Signature,
+}
+
+impl TimeoutVote {
+    /// Sign a timeout for `view`. The validator is the key's public key.
+    pub fn new(view: u64, signing_key: &SigningKey) -> Self {
+        Self {
+            view,
+            validator: AccountId(signing_key.verifying_key().to_bytes()),
+            signature: sign_msg(&timeout_hash(view), signing_key),
+        }
+    }
+
+    pub fn verify(&self) -> Result<(), SignatureError> {
+        verify_msg(&timeout_hash(self.view), &self.signature, &self.validator)
+    }
+}
+
+/// Timeout certificate — timeout votes for one view from a stake quorum. Lets the next
+/// leader propose.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeoutCertificate {
+    pub view: u64,
+    pub signatures: Vec<(AccountId, Signature)>,
+}
+
+impl TimeoutCertificate {
+    /// Aggregate timeout votes for the same view. Votes are not verified here.
+    pub fn from_votes<'a>(view: u64, votes: impl IntoIterator<Item = &'a TimeoutVote>) -> Self {
+        let signatures = votes
+            .into_iter()
+            .filter(|v| v.view == view)
+            .map(|v| (v.validator, v.signature))
+            .collect();
+        Self { view, signatures }
+    }
+
+    /// Verify: distinct signers from `validators`, valid signatures, a stake quorum among them.
+    pub fn verify(&self, validators: &ValidatorSet) -> Result<(), CertificateError> {
+        verify_quorum(&timeout_hash(self.view), &self.signatures, validators)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CertificateError {
+    #[error("Certificate is for a different block")]
+    WrongBlock,
+    #[error("Signer {0:?} is not in the validator set")]
+    UnknownValidator(AccountId),
+    #[error("Duplicate signer {0:?}")]
+    DuplicateSigner(AccountId),
+    #[error("Invalid vote signature from {0:?}")]
+    InvalidSignature(AccountId),
+    #[error("Not enough stake signed: have {have}, need {need}")]
+    InsufficientStake { have: u128, need: u128 },
+}
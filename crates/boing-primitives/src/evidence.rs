@@ -0,0 +1,120 @@
+//! Equivocation evidence — proof that a validator voted for two blocks in one view.
+//!
+//! The evidence carries both block headers and the validator's vote signatures over their
+//! hashes, so anyone can check it without the blocks or the validator set: the headers
+//! share a view, hash differently, and both votes verify under the validator's key.
+
+use serde::{Deserialize, Serialize};
+
+use crate::certificate::Vote;
+use crate::signature::Signature;
+use crate::types::{AccountId, BlockHeader};
+
+/// A header and a vote signature over its hash.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedHeader {
+    pub header: BlockHeader,
+    pub signature: Signature,
+}
+
+impl SignedHeader {
+    fn vote(&self, validator: AccountId) -> Vote {
+        Vote { block_hash: self.header.hash(), validator, signature: self.signature }
+    }
+}
+
+/// Two conflicting votes from one validator in the same view.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquivocationEvidence {
+    pub validator: AccountId,
+    pub first: SignedHeader,
+    pub second: SignedHeader,
+}
+
+impl EquivocationEvidence {
+    /// Evidence from two votes by the same validator and the headers they vote for.
+    pub fn new(first: (&BlockHeader, &Vote), second: (&BlockHeader, &Vote)) -> Self {
+        Self {
+            validator: first.1.validator,
+            first: SignedHeader { header: first.0.clone(), signature: first.1.signature },
+            second: SignedHeader { header: second.0.clone(), signature: second.1.signature },
+        }
+    }
+
+    /// View both votes were cast in.
+    pub fn view(&self) -> u64 {
+        self.first.header.round
+    }
+
+    /// Lowest height of the two blocks (the age of the offence).
+    pub fn height(&self) -> u64 {
+        self.first.header.height.min(self.second.header.height)
+    }
+
+    /// Check that the votes conflict and were both signed by `validator`.
+    pub fn verify(&self) -> Result<(), EvidenceError> {
+        if self.first.header.round != self.second.header.round {
+            return Err(EvidenceError::DifferentViews);
+        }
+        if self.first.header.hash() == self.second.header.hash() {
+            return Err(EvidenceError::SameBlock);
+        }
+        for signed in [&self.first, &self.second] {
+            signed.vote(self.validator).verify().map_err(|_| EvidenceError::InvalidSignature)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::Hash;
+    use ed25519_dalek::SigningKey;
+
+    fn header(round: u64, timestamp: u64) -> BlockHeader {
+        BlockHeader {
+            parent_hash: Hash::ZERO,
+            height: 1,
+            round,
+            timestamp,
+            proposer: AccountId([9u8; 32]),
+            tx_root: Hash::ZERO,
+            state_root: Hash::ZERO,
+            validator_set_hash: Hash::ZERO,
+            vrf: Default::default(),
+            randomness: Hash::ZERO,
+            justify_hash: Hash::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_conflicting_votes_verify() {
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let (a, b) = (header(3, 1), header(3, 2));
+        let (va, vb) = (Vote::new(a.hash(), &key), Vote::new(b.hash(), &key));
+        assert!(EquivocationEvidence::new((&a, &va), (&b, &vb)).verify().is_ok());
+
+        let same = EquivocationEvidence::new((&a, &va), (&a, &va));
+        assert!(matches!(same.verify(), Err(EvidenceError::SameBlock)));
+
+        let c = header(4, 1);
+        let vc = Vote::new(c.hash(), &key);
+        let views = EquivocationEvidence::new((&a, &va), (&c, &vc));
+        assert!(matches!(views.verify(), Err(EvidenceError::DifferentViews)));
+
+        let other = SigningKey::from_bytes(&[2u8; 32]);
+        let forged = EquivocationEvidence::new((&a, &va), (&b, &Vote::new(b.hash(), &other)));
+        assert!(matches!(forged.verify(), Err(EvidenceError::InvalidSignature)));
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EvidenceError {
+    #[error("Votes are for different views")]
+    DifferentViews,
+    #[error("Votes are for the same block")]
+    SameBlock,
+    #[error("Vote signature does not verify for the accused validator")]
+    InvalidSignature,
+}
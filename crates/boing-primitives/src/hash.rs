@@ -5,7 +5,7 @@ use blake3::Hasher as Blake3Hasher;
 use serde::{Deserialize, Serialize};
 
 /// 32-byte hash output.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct Hash(pub [u8; 32]);
 
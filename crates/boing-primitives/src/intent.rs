@@ -7,6 +7,10 @@ use serde::{Deserialize, Serialize};
 use crate::hash::{Hash, hasher};
 use crate::AccountId;
 
+/// Asset id of native BOING in swap intents. It is the only asset settled on-chain until a
+/// token standard exists.
+pub const NATIVE_ASSET: AccountId = AccountId([0u8; 32]);
+
 /// High-level intent kind — what the user wants to achieve.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IntentKind {
@@ -29,6 +33,8 @@ pub enum IntentKind {
 /// Unsigned intent — declarative goal before solver execution.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Intent {
+    /// Chain this intent is valid on (replay protection across networks).
+    pub chain_id: u64,
     pub signer: AccountId,
     pub kind: IntentKind,
     pub nonce: u64,
@@ -46,13 +52,21 @@ impl Intent {
 }
 
 /// Signed intent — user has attested to the goal; solver may execute.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SignedIntent {
     pub intent: Intent,
     pub signature: crate::signature::Signature,
 }
 
 impl SignedIntent {
+    /// Sign an intent with an Ed25519 signing key (signer must be the key's public key).
+    pub fn new(intent: Intent, signing_key: &ed25519_dalek::SigningKey) -> Self {
+        use ed25519_dalek::Signer;
+        let msg_hash = intent_signable_hash(&intent);
+        let signature = crate::signature::Signature(signing_key.sign(msg_hash.as_bytes()).to_bytes());
+        Self { intent, signature }
+    }
+
     /// Human-readable summary for wallet signing UI (intent display).
     pub fn display_for_signing(&self) -> String {
         let kind_str = match &self.intent.kind {
@@ -79,7 +93,8 @@ impl SignedIntent {
             IntentKind::Custom(_) => "Custom intent".into(),
         };
         format!(
-            "From: {} | Nonce: {} | Deadline: #{} | {}",
+            "Chain: {} | From: {} | Nonce: {} | Deadline: #{} | {}",
+            self.intent.chain_id,
             hex::encode(&self.intent.signer.0[..8]),
             self.intent.nonce,
             self.intent.deadline_block,
@@ -89,12 +104,7 @@ impl SignedIntent {
 
     /// Verify the signature over the intent. Uses signer from intent.
     pub fn verify(&self) -> Result<(), crate::signature::SignatureError> {
-        let msg = bincode::serialize(&self.intent).map_err(|_| crate::signature::SignatureError::InvalidSignature)?;
-        let msg_hash = {
-            let mut h = hasher();
-            h.update(&msg);
-            h.finalize()
-        };
+        let msg_hash = intent_signable_hash(&self.intent);
         use ed25519_dalek::Verifier;
         let pk = ed25519_dalek::VerifyingKey::from_bytes(&self.intent.signer.0)
             .map_err(|_| crate::signature::SignatureError::InvalidPublicKey)?;
@@ -104,3 +114,10 @@ impl SignedIntent {
         Ok(())
     }
 }
+
+/// Message hash that gets signed (bincode of the full intent, including chain id).
+fn intent_signable_hash(intent: &Intent) -> blake3::Hash {
+    let mut h = hasher();
+    h.update(&bincode::serialize(intent).unwrap_or_default());
+    h.finalize()
+}
@@ -2,15 +2,24 @@
 //!
 //! Types, hashing, signatures, and shared data structures.
 
+pub mod certificate;
+pub mod evidence;
 pub mod hash;
 pub mod hd;
 pub mod intent;
 pub mod paymaster;
 pub mod randomness;
 pub mod recovery;
+pub mod relay;
 pub mod signature;
 pub mod types;
+pub mod validator_set;
 
+pub use certificate::{
+    sign_proposal, verify_proposal, CertificateError, QuorumCertificate,
+    TimeoutCertificate, TimeoutVote, Vote,
+};
+pub use evidence::{EquivocationEvidence, EvidenceError, SignedHeader};
 pub use hash::{Hash, hasher};
 pub use signature::{
     sign_transaction, verify_signature, Signature, SignatureError, SignedTransaction,
@@ -18,11 +27,13 @@ pub use signature::{
 pub use types::{
     tx_root, AccountId, Block, BlockHeader, Transaction, TransactionPayload, AccessList,
 };
-pub use types::{Account, AccountState};
-pub use intent::{Intent, IntentKind, SignedIntent};
-pub use randomness::{dummy_vrf_output, leader_from_vrf, VdfOutput, VrfOutput};
+pub use types::{Account, AccountState, UnbondingEntry};
+pub use validator_set::{Validator, ValidatorSet};
+pub use intent::{Intent, IntentKind, SignedIntent, NATIVE_ASSET};
+pub use randomness::{next_beacon, VdfOutput, VrfError, VrfOutput};
 pub use paymaster::{PaymasterConfig, SponsoredTransaction};
 pub use recovery::{Guardian, RecoveryRequest};
+pub use relay::{StunTurnMetrics, StunTurnRegistryEntry, StunTurnReputation};
 pub use hd::HdPath;
 
 #[cfg(test)]
@@ -46,6 +57,7 @@ mod tests {
     fn test_transaction_id_deterministic() {
         let id = AccountId::from_bytes([1u8; 32]);
         let tx = Transaction {
+            chain_id: 1,
             nonce: 0,
             sender: id,
             payload: TransactionPayload::Transfer {
@@ -62,6 +74,7 @@ mod tests {
         let key = SigningKey::generate(&mut OsRng);
         let sender = AccountId::from_bytes(key.verifying_key().to_bytes());
         let tx = Transaction {
+            chain_id: 1,
             nonce: 0,
             sender,
             payload: TransactionPayload::Transfer {
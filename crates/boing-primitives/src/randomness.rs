@@ -1,11 +1,30 @@
-//! Verifiable randomness for leader selection (VDF/VRF placeholders).
+//! Verifiable randomness for leader selection.
+//!
+//! Block proposers evaluate ECVRF-EDWARDS25519-SHA512-TAI (RFC 9381) with their Ed25519
+//! validator key over the parent hash and view, and put the proof in the block header.
+//! Anyone with the proposer's public key can check the proof, and the output is unique per
+//! key and input, so a proposer cannot pick a favourable one. The output of each epoch
+//! boundary block seeds leader selection for the epoch it schedules.
+//!
+//! The VRF outputs also feed a per-block randomness beacon: each block hashes its
+//! proposer's output into the previous beacon. Contracts read it (PREVRANDAO-style), and no
+//! proposer can choose it; one can only withhold its block and forfeit the view.
 //!
 //! See DECENTRALIZATION-STRATEGY.md for design.
 
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use ed25519_dalek::SigningKey;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
 
 use crate::{AccountId, Hash};
 
+/// ECVRF-EDWARDS25519-SHA512-TAI suite string.
+const SUITE: u8 = 0x03;
+/// Proof length: Gamma (32) ‖ c (16) ‖ s (32).
+pub const VRF_PROOF_LEN: usize = 80;
+
 /// VDF output — verifiable delay function result for fair ordering.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VdfOutput {
@@ -14,31 +33,214 @@ pub struct VdfOutput {
     pub proof: Vec<u8>,
 }
 
-/// VRF output — verifiable random function for leader election.
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// VRF output — an ECVRF proof and the first 32 bytes of its hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VrfOutput {
     pub proof: Vec<u8>,
     pub output: [u8; 32],
 }
 
-/// Select leader from validator set using VRF output (stub).
-/// Given validators and a VRF output, deterministically returns the elected leader.
-/// Production implementations would verify the VRF proof.
-pub fn leader_from_vrf(validators: &[AccountId], vrf_output: &VrfOutput) -> Option<AccountId> {
-    if validators.is_empty() {
-        return None;
+impl VrfOutput {
+    /// Evaluate the VRF on `alpha` with `key`.
+    pub fn prove(key: &SigningKey, alpha: &[u8]) -> Self {
+        let proof = ecvrf_prove(key, alpha);
+        let beta = ecvrf_proof_to_hash(&proof).expect("fresh proof decodes");
+        Self { proof: proof.to_vec(), output: truncate(&beta) }
+    }
+
+    /// Check that `proof` was made by `public` over `alpha` and `output` is its hash.
+    pub fn verify(&self, public: &AccountId, alpha: &[u8]) -> Result<(), VrfError> {
+        let proof: &[u8; VRF_PROOF_LEN] =
+            self.proof.as_slice().try_into().map_err(|_| VrfError::MalformedProof)?;
+        let beta = ecvrf_verify(&public.0, proof, alpha)?;
+        if truncate(&beta) != self.output {
+            return Err(VrfError::OutputMismatch);
+        }
+        Ok(())
+    }
+
+    /// The output as a hash (e.g. a leader selection seed).
+    pub fn seed(&self) -> Hash {
+        Hash(self.output)
+    }
+}
+
+/// Beacon after a block: the previous beacon mixed with the block's VRF output.
+pub fn next_beacon(previous: &Hash, vrf: &VrfOutput) -> Hash {
+    let mut h = crate::hash::hasher();
+    h.update(b"boing/beacon/v1");
+    h.update(&previous.0);
+    h.update(&vrf.output);
+    Hash(*h.finalize().as_bytes())
+}
+
+fn truncate(beta: &[u8; 64]) -> [u8; 32] {
+    beta[..32].try_into().expect("64-byte hash")
+}
+
+/// ECVRF proof over `alpha` (RFC 9381, section 5.1).
+pub fn ecvrf_prove(key: &SigningKey, alpha: &[u8]) -> [u8; VRF_PROOF_LEN] {
+    let expanded: [u8; 64] = Sha512::digest(key.to_bytes()).into();
+    let x = Scalar::from_bytes_mod_order(clamp(expanded[..32].try_into().expect("32 bytes")));
+    let y = key.verifying_key().to_bytes();
+    let h = encode_to_curve(&y, alpha).expect("try-and-increment finds a point");
+    let h_bytes = h.compress().to_bytes();
+    let gamma = h * x;
+
+    let nonce: [u8; 64] = Sha512::new().chain_update(&expanded[32..]).chain_update(h_bytes).finalize().into();
+    let k = Scalar::from_bytes_mod_order_wide(&nonce);
+    let c = challenge(&[
+        &y,
+        &h_bytes,
+        &gamma.compress().to_bytes(),
+        &EdwardsPoint::mul_base(&k).compress().to_bytes(),
+        &(h * k).compress().to_bytes(),
+    ]);
+    let s = k + c * x;
+
+    let mut pi = [0u8; VRF_PROOF_LEN];
+    pi[..32].copy_from_slice(&gamma.compress().to_bytes());
+    pi[32..48].copy_from_slice(&c.to_bytes()[..16]);
+    pi[48..].copy_from_slice(&s.to_bytes());
+    pi
+}
+
+/// Verify `proof` for public key `y` over `alpha` and return its 64-byte hash (RFC 9381,
+/// section 5.3).
+pub fn ecvrf_verify(y: &[u8; 32], proof: &[u8; VRF_PROOF_LEN], alpha: &[u8]) -> Result<[u8; 64], VrfError> {
+    let y_point = CompressedEdwardsY(*y).decompress().ok_or(VrfError::InvalidKey)?;
+    if y_point.is_small_order() {
+        return Err(VrfError::InvalidKey);
+    }
+    let (gamma, c, s) = decode_proof(proof)?;
+    let h = encode_to_curve(y, alpha).ok_or(VrfError::InvalidProof)?;
+    // U = s·B − c·Y, V = s·H − c·Gamma
+    let u = EdwardsPoint::vartime_double_scalar_mul_basepoint(&-c, &y_point, &s);
+    let v = h * s - gamma * c;
+    let expected = challenge(&[
+        y,
+        &h.compress().to_bytes(),
+        &gamma.compress().to_bytes(),
+        &u.compress().to_bytes(),
+        &v.compress().to_bytes(),
+    ]);
+    if expected != c {
+        return Err(VrfError::InvalidProof);
+    }
+    Ok(gamma_to_hash(&gamma))
+}
+
+/// The 64-byte VRF hash of a proof, without verifying it (RFC 9381, section 5.2).
+pub fn ecvrf_proof_to_hash(proof: &[u8; VRF_PROOF_LEN]) -> Result<[u8; 64], VrfError> {
+    decode_proof(proof).map(|(gamma, _, _)| gamma_to_hash(&gamma))
+}
+
+fn gamma_to_hash(gamma: &EdwardsPoint) -> [u8; 64] {
+    Sha512::new()
+        .chain_update([SUITE, 0x03])
+        .chain_update(gamma.mul_by_cofactor().compress().to_bytes())
+        .chain_update([0x00])
+        .finalize()
+        .into()
+}
+
+fn decode_proof(proof: &[u8; VRF_PROOF_LEN]) -> Result<(EdwardsPoint, Scalar, Scalar), VrfError> {
+    let gamma = CompressedEdwardsY(proof[..32].try_into().expect("32 bytes"))
+        .decompress()
+        .ok_or(VrfError::MalformedProof)?;
+    let mut c = [0u8; 32];
+    c[..16].copy_from_slice(&proof[32..48]);
+    let s: Option<Scalar> = Scalar::from_canonical_bytes(proof[48..].try_into().expect("32 bytes")).into();
+    Ok((gamma, Scalar::from_bytes_mod_order(c), s.ok_or(VrfError::MalformedProof)?))
+}
+
+/// Try-and-increment hash to the prime-order subgroup, salted with the public key.
+fn encode_to_curve(y: &[u8; 32], alpha: &[u8]) -> Option<EdwardsPoint> {
+    (0..=u8::MAX).find_map(|ctr| {
+        let hash = Sha512::new()
+            .chain_update([SUITE, 0x01])
+            .chain_update(y)
+            .chain_update(alpha)
+            .chain_update([ctr, 0x00])
+            .finalize();
+        CompressedEdwardsY(hash[..32].try_into().expect("32 bytes"))
+            .decompress()
+            .map(|p| p.mul_by_cofactor())
+    })
+}
+
+/// Challenge over the encoded points, truncated to 16 bytes.
+fn challenge(points: &[&[u8; 32]]) -> Scalar {
+    let mut hasher = Sha512::new().chain_update([SUITE, 0x02]);
+    for p in points {
+        hasher.update(p);
     }
-    let mut idx = 0u64;
-    for (i, b) in vrf_output.output.iter().take(8).enumerate() {
-        idx |= (*b as u64) << (i * 8);
+    let digest = hasher.chain_update([0x00]).finalize();
+    let mut c = [0u8; 32];
+    c[..16].copy_from_slice(&digest[..16]);
+    Scalar::from_bytes_mod_order(c)
+}
+
+fn clamp(mut bytes: [u8; 32]) -> [u8; 32] {
+    bytes[0] &= 248;
+    bytes[31] &= 127;
+    bytes[31] |= 64;
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex<const N: usize>(s: &str) -> [u8; N] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    /// RFC 9381, appendix B.3, example 16.
+    #[test]
+    fn test_rfc9381_vector() {
+        let key = SigningKey::from_bytes(&unhex("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"));
+        let y = key.verifying_key().to_bytes();
+        assert_eq!(y, unhex::<32>("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"));
+        let pi = ecvrf_prove(&key, b"");
+        assert_eq!(
+            hex::encode(pi),
+            "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805"
+        );
+        let beta = ecvrf_verify(&y, &pi, b"").unwrap();
+        assert_eq!(
+            hex::encode(beta),
+            "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae"
+        );
+    }
+
+    #[test]
+    fn test_proof_binds_key_and_input() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public = AccountId(key.verifying_key().to_bytes());
+        let vrf = VrfOutput::prove(&key, b"round 1");
+        assert!(vrf.verify(&public, b"round 1").is_ok());
+        assert_eq!(VrfOutput::prove(&key, b"round 1"), vrf, "deterministic");
+
+        assert!(matches!(vrf.verify(&public, b"round 2"), Err(VrfError::InvalidProof)));
+        let other = AccountId(SigningKey::from_bytes(&[8u8; 32]).verifying_key().to_bytes());
+        assert!(matches!(vrf.verify(&other, b"round 1"), Err(VrfError::InvalidProof)));
+
+        let mut forged = vrf.clone();
+        forged.output[0] ^= 1;
+        assert!(matches!(forged.verify(&public, b"round 1"), Err(VrfError::OutputMismatch)));
+        assert!(matches!(VrfOutput::default().verify(&public, b"round 1"), Err(VrfError::MalformedProof)));
     }
-    Some(validators[(idx as usize) % validators.len()])
 }
 
-/// Produce a dummy VRF output for a given round (stub for testing).
-/// Production would use real VRF (e.g. ECVRF) with round seed.
-pub fn dummy_vrf_output(round: u64) -> VrfOutput {
-    let mut output = [0u8; 32];
-    output[..8].copy_from_slice(&round.to_le_bytes());
-    VrfOutput { proof: vec![], output }
+#[derive(Debug, thiserror::Error)]
+pub enum VrfError {
+    #[error("Public key is not a valid curve point")]
+    InvalidKey,
+    #[error("Malformed VRF proof")]
+    MalformedProof,
+    #[error("VRF proof does not verify")]
+    InvalidProof,
+    #[error("VRF output does not match the proof")]
+    OutputMismatch,
 }
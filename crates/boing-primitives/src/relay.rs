@@ -0,0 +1,90 @@
+//! STUN/TURN relays — registry entries, performance metrics and reputation.
+//!
+//! Relay operators stake to list a multiaddr in the on-chain registry. Staked accounts
+//! report the metrics they observe; at each epoch boundary the median of the reports is
+//! turned into a reputation score, which decides whether the relay is rewarded or dropped.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest multiaddr a relay may register, in bytes.
+pub const MAX_RELAY_MULTIADDR_LEN: usize = 256;
+
+/// Performance metrics for a STUN/TURN server (reputation inputs).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StunTurnMetrics {
+    pub uptime_bps: u16,
+    pub avg_latency_ms: u32,
+    pub success_rate_bps: u16,
+    pub throughput_mb: u64,
+}
+
+impl StunTurnMetrics {
+    /// Rates are basis points and cannot exceed 100%.
+    pub fn is_valid(&self) -> bool {
+        self.uptime_bps <= 10_000 && self.success_rate_bps <= 10_000
+    }
+
+    /// Field-wise median of `reports` (the lower middle value for an even count), so a
+    /// minority of dishonest reporters cannot move the result past the honest ones. None if
+    /// there are no reports.
+    pub fn median(reports: &[StunTurnMetrics]) -> Option<Self> {
+        if reports.is_empty() {
+            return None;
+        }
+        fn mid<T: Ord + Copy>(mut values: Vec<T>) -> T {
+            values.sort_unstable();
+            values[(values.len() - 1) / 2]
+        }
+        Some(Self {
+            uptime_bps: mid(reports.iter().map(|m| m.uptime_bps).collect()),
+            avg_latency_ms: mid(reports.iter().map(|m| m.avg_latency_ms).collect()),
+            success_rate_bps: mid(reports.iter().map(|m| m.success_rate_bps).collect()),
+            throughput_mb: mid(reports.iter().map(|m| m.throughput_mb).collect()),
+        })
+    }
+}
+
+/// Reputation score for a STUN/TURN server (0–10000 = 0–100%).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StunTurnReputation {
+    pub score: u16,
+}
+
+impl StunTurnReputation {
+    pub const MAX: u16 = 10_000;
+
+    pub fn new(score: u16) -> Self {
+        Self {
+            score: score.min(Self::MAX),
+        }
+    }
+
+    pub fn from_metrics(metrics: &StunTurnMetrics) -> Self {
+        let uptime = metrics.uptime_bps as u32;
+        let success = metrics.success_rate_bps as u32;
+        let latency_penalty = ((metrics.avg_latency_ms.saturating_sub(100)).min(500) / 10) as u16;
+        let base = ((uptime + success) / 2) as u16;
+        let raw = base.saturating_add(5000).saturating_sub(latency_penalty);
+        Self::new(raw.min(Self::MAX))
+    }
+
+    /// Blend an epoch's score into the running reputation (equal weight), so one bad epoch
+    /// halves the distance to it rather than replacing the history.
+    pub fn update(self, epoch: Self) -> Self {
+        Self::new(((self.score as u32 + epoch.score as u32) / 2) as u16)
+    }
+}
+
+/// Registry entry for a registered STUN/TURN server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StunTurnRegistryEntry {
+    /// Operator account; it staked for the entry and receives its rewards.
+    pub server_id: [u8; 32],
+    pub multiaddr: String,
+    pub stake: u128,
+    pub reputation: StunTurnReputation,
+    /// Height of the block that registered the relay.
+    pub registered_at: u64,
+    /// The operator asked to leave; the stake is returned at the next epoch boundary.
+    pub leaving: bool,
+}
@@ -53,6 +53,7 @@ impl std::fmt::Debug for Signature {
 /// Message hash that gets signed (deterministic serialization of tx fields).
 fn signable_hash(tx: &Transaction) -> [u8; 32] {
     let mut h = hasher();
+    h.update(&tx.chain_id.to_le_bytes());
     h.update(&tx.nonce.to_le_bytes());
     h.update(tx.sender.0.as_slice());
     h.update(&bincode::serialize(&tx.payload).unwrap_or_default());
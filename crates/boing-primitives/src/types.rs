@@ -4,10 +4,16 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::certificate::{sign_proposal, verify_proposal, QuorumCertificate};
+use crate::evidence::EquivocationEvidence;
 use crate::hash::{Hash, hasher};
+use crate::intent::SignedIntent;
+use crate::randomness::{VrfError, VrfOutput};
+use crate::relay::StunTurnMetrics;
+use crate::signature::{Signature, SignatureError};
 
 /// Account identifier (32 bytes, typically derived from pubkey).
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct AccountId(pub [u8; 32]);
 
@@ -61,6 +67,8 @@ impl AccessList {
 /// Transaction — the unit of execution.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Transaction {
+    /// Chain this transaction is valid on (replay protection across networks).
+    pub chain_id: u64,
     pub nonce: u64,
     pub sender: AccountId,
     pub payload: TransactionPayload,
@@ -76,13 +84,43 @@ impl Transaction {
             }
             TransactionPayload::Bond { amount } => format!("Bond {} stake", amount),
             TransactionPayload::Unbond { amount } => format!("Unbond {} stake", amount),
+            TransactionPayload::Withdraw => "Withdraw unbonded stake".into(),
+            TransactionPayload::RegisterValidator { commission_bps } => {
+                format!("Register validator with {} bps commission", commission_bps)
+            }
+            TransactionPayload::Delegate { validator, amount } => {
+                format!("Delegate {} to {}", amount, hex::encode(&validator.0[..8]))
+            }
+            TransactionPayload::Undelegate { validator, amount } => {
+                format!("Undelegate {} from {}", amount, hex::encode(&validator.0[..8]))
+            }
+            TransactionPayload::ReportEquivocation { evidence } => {
+                format!("Report equivocation by {}", hex::encode(&evidence.validator.0[..8]))
+            }
+            TransactionPayload::AppealSlash { slash_id, .. } => format!("Appeal slash #{}", slash_id),
+            TransactionPayload::ResolveAppeal { appeal_id, approved } => {
+                format!("{} appeal #{}", if *approved { "Approve" } else { "Reject" }, appeal_id)
+            }
+            TransactionPayload::Unjail => "Unjail validator".into(),
+            TransactionPayload::RegisterRelay { multiaddr, stake } => {
+                format!("Register relay {} with {} stake", multiaddr, stake)
+            }
+            TransactionPayload::ReportRelayMetrics { relay, .. } => {
+                format!("Report metrics of relay {}", hex::encode(&relay.0[..8]))
+            }
+            TransactionPayload::DeregisterRelay => "Deregister relay".into(),
+            TransactionPayload::FulfillIntent { intent, .. } => {
+                format!("Fulfill intent {}", hex::encode(&intent.intent.id().0[..8]))
+            }
+            TransactionPayload::CancelIntent { nonce } => format!("Cancel intents up to nonce {}", nonce),
             TransactionPayload::ContractCall { contract, .. } => {
                 format!("Call contract {}", hex::encode(&contract.0[..8]))
             }
             TransactionPayload::ContractDeploy { .. } => "Deploy contract".into(),
         };
         format!(
-            "From: {} | Nonce: {} | {}",
+            "Chain: {} | From: {} | Nonce: {} | {}",
+            self.chain_id,
             hex::encode(&self.sender.0[..8]),
             self.nonce,
             payload_str
@@ -106,8 +144,40 @@ pub enum TransactionPayload {
     ContractDeploy { bytecode: Vec<u8> },
     /// Bond stake to become/l remain a validator.
     Bond { amount: u128 },
-    /// Unbond stake (with optional unbonding period).
+    /// Unbond stake. The amount leaves the stake at once but only becomes withdrawable
+    /// after the unbonding delay.
     Unbond { amount: u128 },
+    /// Move every matured unbonding entry back to the liquid balance.
+    Withdraw,
+    /// Register the sender as a validator accepting delegations, or change its commission.
+    RegisterValidator { commission_bps: u16 },
+    /// Delegate balance to a registered validator; it adds to the validator's voting power.
+    Delegate { validator: AccountId, amount: u128 },
+    /// Withdraw a delegation. The amount goes through the unbonding queue like `Unbond`.
+    Undelegate { validator: AccountId, amount: u128 },
+    /// Submit proof that a validator voted twice in one view; slashes and jails it.
+    ReportEquivocation { evidence: Box<EquivocationEvidence> },
+    /// Contest a slash of the sender within the appeal window.
+    AppealSlash { slash_id: u64, evidence: Vec<u8> },
+    /// Governance decision on an appeal; an approved appeal refunds the slash.
+    ResolveAppeal { appeal_id: u64, approved: bool },
+    /// Release the sender from jail once its jail period has passed.
+    Unjail,
+    /// Register the sender as a STUN/TURN relay at `multiaddr`, staking `stake` from its
+    /// balance; for a registered relay, add `stake` and update the address.
+    RegisterRelay { multiaddr: String, stake: u128 },
+    /// Metrics a staked account observed for `relay` this epoch. A later report from the
+    /// same sender in the epoch replaces the earlier one.
+    ReportRelayMetrics { relay: AccountId, metrics: StunTurnMetrics },
+    /// Leave the relay registry; the stake is returned at the next epoch boundary.
+    DeregisterRelay,
+    /// Settle a signed intent, sent by the solver. A transfer moves the intent's amount
+    /// from its signer; a swap also pays the signer `amount_out` (at least `min_out`) from
+    /// the sender. Each intent settles at most once, up to its deadline block, and only
+    /// while its nonce is not below the signer's next intent nonce.
+    FulfillIntent { intent: Box<SignedIntent>, amount_out: u128 },
+    /// Cancel the sender's unsettled intents with a nonce up to and including `nonce`.
+    CancelIntent { nonce: u64 },
 }
 
 /// Account state — balance, nonce, and staked amount.
@@ -119,6 +189,16 @@ pub struct AccountState {
     pub stake: u128,
 }
 
+/// Stake waiting out the unbonding delay. Still slashable until withdrawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnbondingEntry {
+    /// Validator the stake was bonded or delegated to.
+    pub validator: AccountId,
+    pub amount: u128,
+    /// First height at which a `Withdraw` can claim the entry.
+    pub release_height: u64,
+}
+
 /// Full account (id + state).
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Account {
@@ -165,25 +245,110 @@ fn hash_pair(a: &Hash, b: &Hash) -> Hash {
 pub struct BlockHeader {
     pub parent_hash: Hash,
     pub height: u64,
+    /// Consensus view the block was proposed in; the proposer must be that view's leader.
+    pub round: u64,
     pub timestamp: u64,
     pub proposer: AccountId,
     pub tx_root: Hash,
     pub state_root: Hash,
+    /// Hash of the validator set of the block's epoch; its QCs are checked against that set.
+    pub validator_set_hash: Hash,
+    /// Proposer's VRF proof over [`Self::vrf_input`]. Empty only for the genesis block.
+    pub vrf: VrfOutput,
+    /// Randomness beacon after this block: the parent's beacon mixed with `vrf`'s output
+    /// (see [`crate::next_beacon`]). Zero for the genesis block.
+    pub randomness: Hash,
+    /// Hash of the block's `justify` QC (see [`Block::justify_hash`]); zero without one.
+    /// Liveness is recorded from that QC's signers, so the proposer signs which ones.
+    pub justify_hash: Hash,
 }
 
-/// Block — header + transactions.
+/// Block — header + transactions, proposer signature, and quorum certificate.
+/// The block hash covers only the header, so the signature and QC (both over the hash)
+/// are carried alongside it. Both are `None` only for the genesis block.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Block {
     pub header: BlockHeader,
     pub transactions: Vec<Transaction>,
+    /// Proposer's signature over the block hash.
+    pub signature: Option<Signature>,
+    /// QC certifying the parent block (chained HotStuff). None only for children of genesis.
+    /// Committed to by `header.justify_hash`, so it cannot be replaced after signing.
+    pub justify: Option<QuorumCertificate>,
+    /// 2f+1 validator votes for this block; attached once the block commits.
+    pub qc: Option<QuorumCertificate>,
 }
 
-impl Block {
+impl BlockHeader {
+    /// Block hash: the hash of the serialized header.
     pub fn hash(&self) -> Hash {
         let mut h = hasher();
-        h.update(&bincode::serialize(&self.header).unwrap_or_default());
+        h.update(&bincode::serialize(self).unwrap_or_default());
         let mut out = [0u8; 32];
         out.copy_from_slice(h.finalize().as_bytes());
         Hash(out)
     }
+
+    /// VRF input: the parent and the view, both fixed before the proposer builds the block.
+    pub fn vrf_input(&self) -> Hash {
+        let mut h = hasher();
+        h.update(b"boing/vrf/v1");
+        h.update(&self.parent_hash.0);
+        h.update(&self.round.to_le_bytes());
+        Hash(*h.finalize().as_bytes())
+    }
+
+    /// Evaluate the VRF for this header with the proposer's key.
+    pub fn prove_vrf(&mut self, key: &ed25519_dalek::SigningKey) {
+        self.vrf = VrfOutput::prove(key, self.vrf_input().as_bytes());
+    }
+
+    /// Check the VRF proof against the proposer's key.
+    pub fn verify_vrf(&self) -> Result<(), VrfError> {
+        self.vrf.verify(&self.proposer, self.vrf_input().as_bytes())
+    }
+}
+
+impl Block {
+    /// Unsigned block without certificates.
+    pub fn new(header: BlockHeader, transactions: Vec<Transaction>) -> Self {
+        Self {
+            header,
+            transactions,
+            signature: None,
+            justify: None,
+            qc: None,
+        }
+    }
+
+    /// Hash `header.justify_hash` must hold for `justify`: zero without one.
+    pub fn justify_hash(justify: Option<&QuorumCertificate>) -> Hash {
+        justify.map_or(Hash::ZERO, |qc| qc.hash())
+    }
+
+    /// Set `justify` and commit to it in the header. Call before [`Self::sign`].
+    pub fn set_justify(&mut self, justify: Option<QuorumCertificate>) {
+        self.header.justify_hash = Self::justify_hash(justify.as_ref());
+        self.justify = justify;
+    }
+
+    /// Whether `justify` is the QC the header commits to.
+    pub fn justify_matches(&self) -> bool {
+        self.header.justify_hash == Self::justify_hash(self.justify.as_ref())
+    }
+
+    /// Sign as proposer. The key must belong to `header.proposer`.
+    pub fn sign(&mut self, signing_key: &ed25519_dalek::SigningKey) {
+        self.signature = Some(sign_proposal(&self.hash(), signing_key));
+    }
+
+    /// Verify the proposer signature against `header.proposer`.
+    pub fn verify_signature(&self) -> Result<(), SignatureError> {
+        let sig = self.signature.as_ref().ok_or(SignatureError::InvalidSignature)?;
+        verify_proposal(&self.hash(), sig, &self.header.proposer)
+    }
+
+    pub fn hash(&self) -> Hash {
+        self.header.hash()
+    }
 }
@@ -0,0 +1,199 @@
+//! Validator set — validators with their voting power (bonded stake).
+//!
+//! Quorums are stake-weighted: a certificate needs signatures from validators holding more
+//! than two thirds of the set's total stake. The set is fixed for an epoch; block headers
+//! commit to its hash so importers check blocks against the right epoch.
+//!
+//! Leaders are drawn by stake: each view hashes the set's seed (the VRF output of the
+//! epoch boundary block that scheduled it) with the view number and picks the validator
+//! whose stake interval contains the result. Every node derives the same leader, nobody
+//! can predict it before the boundary block, and a validator leads in proportion to stake.
+
+use serde::{Deserialize, Serialize};
+
+use crate::hash::{hasher, Hash};
+use crate::types::AccountId;
+
+/// A validator and its voting power.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validator {
+    pub id: AccountId,
+    pub stake: u128,
+}
+
+/// Ordered, non-empty set of validators with the seed for leader selection. The order is
+/// the order of signatures in certificates.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSet {
+    validators: Vec<Validator>,
+    seed: Hash,
+}
+
+impl ValidatorSet {
+    /// Set from validators in order, with a zero seed. Validators without stake or listed twice are
+    /// dropped. Panics if no validator remains.
+    pub fn new(validators: Vec<Validator>) -> Self {
+        let mut out: Vec<Validator> = Vec::with_capacity(validators.len());
+        for v in validators {
+            if v.stake > 0 && !out.iter().any(|o| o.id == v.id) {
+                out.push(v);
+            }
+        }
+        assert!(!out.is_empty(), "Validator set requires at least 1 staked validator");
+        Self { validators: out, seed: Hash::ZERO }
+    }
+
+    /// Use `seed` for leader selection.
+    pub fn with_seed(mut self, seed: Hash) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Seed for leader selection.
+    pub fn seed(&self) -> Hash {
+        self.seed
+    }
+
+    /// Set where every validator has one unit of voting power (unstaked dev chains and tests).
+    pub fn equal_weight(ids: impl IntoIterator<Item = AccountId>) -> Self {
+        Self::new(ids.into_iter().map(|id| Validator { id, stake: 1 }).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.validators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Validator> {
+        self.validators.iter()
+    }
+
+    /// Validator accounts in set order.
+    pub fn accounts(&self) -> Vec<AccountId> {
+        self.validators.iter().map(|v| v.id).collect()
+    }
+
+    pub fn contains(&self, id: &AccountId) -> bool {
+        self.validators.iter().any(|v| v.id == *id)
+    }
+
+    /// Voting power of `id` (0 if not in the set).
+    pub fn stake_of(&self, id: &AccountId) -> u128 {
+        self.validators.iter().find(|v| v.id == *id).map(|v| v.stake).unwrap_or(0)
+    }
+
+    pub fn total_stake(&self) -> u128 {
+        self.validators.iter().map(|v| v.stake).sum()
+    }
+
+    /// Stake a quorum needs: more than two thirds of the total.
+    pub fn quorum_stake(&self) -> u128 {
+        self.total_stake() * 2 / 3 + 1
+    }
+
+    /// Largest stake that can be faulty without breaking safety (total minus a quorum).
+    /// More than this means at least one honest validator is among the signers.
+    pub fn max_faulty_stake(&self) -> u128 {
+        self.total_stake() - self.quorum_stake()
+    }
+
+    /// Combined stake of `signers`; unknown signers count zero and duplicates once.
+    pub fn stake_of_all<'a>(&self, signers: impl IntoIterator<Item = &'a AccountId>) -> u128 {
+        let mut seen: Vec<&AccountId> = Vec::new();
+        signers
+            .into_iter()
+            .filter(|id| {
+                let new = !seen.contains(id);
+                seen.push(id);
+                new
+            })
+            .map(|id| self.stake_of(id))
+            .sum()
+    }
+
+    /// Leader for `view`: drawn by stake from the seed and the view.
+    pub fn leader(&self, view: u64) -> AccountId {
+        let mut h = hasher();
+        h.update(b"boing/leader/v1");
+        h.update(&self.seed.0);
+        h.update(&view.to_le_bytes());
+        let draw = u128::from_le_bytes(h.finalize().as_bytes()[..16].try_into().expect("16 bytes"));
+        let mut point = draw % self.total_stake();
+        for v in &self.validators {
+            if point < v.stake {
+                return v.id;
+            }
+            point -= v.stake;
+        }
+        unreachable!("draw is below the total stake")
+    }
+
+    /// Commitment to the members, their stakes and the seed, recorded in block headers.
+    pub fn hash(&self) -> Hash {
+        let mut h = hasher();
+        h.update(b"boing/validator-set/v2");
+        h.update(&self.seed.0);
+        for v in &self.validators {
+            h.update(&v.id.0);
+            h.update(&v.stake.to_le_bytes());
+        }
+        Hash(*h.finalize().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(i: u8) -> AccountId {
+        AccountId([i; 32])
+    }
+
+    #[test]
+    fn test_quorum_is_stake_weighted() {
+        let set = ValidatorSet::new(vec![
+            Validator { id: id(1), stake: 70 },
+            Validator { id: id(2), stake: 20 },
+            Validator { id: id(3), stake: 10 },
+        ]);
+        assert_eq!(set.quorum_stake(), 67);
+        assert!(set.stake_of_all(&[id(1)]) >= set.quorum_stake());
+        assert!(set.stake_of_all(&[id(2), id(3), id(2)]) < set.quorum_stake());
+        assert_eq!(set.stake_of_all(&[id(9)]), 0);
+    }
+
+    #[test]
+    fn test_equal_weight_matches_two_f_plus_one() {
+        let set = ValidatorSet::equal_weight((1..=4).map(id));
+        assert_eq!(set.quorum_stake(), 3);
+        assert_eq!(set.max_faulty_stake(), 1);
+    }
+
+    #[test]
+    fn test_leader_drawn_by_stake_from_seed() {
+        let set = ValidatorSet::new(vec![
+            Validator { id: id(1), stake: 900 },
+            Validator { id: id(2), stake: 100 },
+        ]);
+        let led = |set: &ValidatorSet, v: AccountId| (0..1000).filter(|view| set.leader(*view) == v).count();
+        let small = led(&set, id(2));
+        assert!((50..150).contains(&small), "id(2) led {small} of 1000 views");
+        assert_eq!(led(&set, id(1)) + small, 1000);
+
+        // Another seed reorders the schedule and changes the set's commitment.
+        let reseeded = set.clone().with_seed(Hash([1u8; 32]));
+        assert_ne!(reseeded.hash(), set.hash());
+        assert!((0..64).any(|view| reseeded.leader(view) != set.leader(view)));
+    }
+
+    #[test]
+    fn test_hash_commits_to_stakes() {
+        let a = ValidatorSet::new(vec![Validator { id: id(1), stake: 10 }]);
+        let b = ValidatorSet::new(vec![Validator { id: id(1), stake: 11 }]);
+        assert_ne!(a.hash(), b.hash());
+        assert_eq!(a.hash(), a.clone().hash());
+    }
+}
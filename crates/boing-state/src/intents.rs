@@ -0,0 +1,72 @@
+//! Intent settlement record — which signed intents have been fulfilled on-chain, and each
+//! signer's next usable intent nonce.
+//!
+//! An intent id is kept until the intent's deadline has passed, after which it could not
+//! be settled again anyway, so the record stays bounded by the intents still live. Settling
+//! or cancelling an intent with nonce `n` moves its signer's next nonce past `n`, so intents
+//! with that nonce or a lower one become stale.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use boing_primitives::{hasher, AccountId, Hash};
+
+/// Reserved state-tree key for the intent commitment.
+pub fn intents_key() -> [u8; 32] {
+    let mut h = hasher();
+    h.update(b"boing/state/intents");
+    *h.finalize().as_bytes()
+}
+
+/// Fulfilled intents, committed to the state root under a reserved key.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntentRegistry {
+    /// Intent id -> deadline block.
+    fulfilled: BTreeMap<[u8; 32], u64>,
+    /// Signer -> lowest intent nonce that can still be settled.
+    nonces: BTreeMap<AccountId, u64>,
+}
+
+impl IntentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fulfilled.is_empty() && self.nonces.is_empty()
+    }
+
+    pub fn is_fulfilled(&self, id: &Hash) -> bool {
+        self.fulfilled.contains_key(&id.0)
+    }
+
+    /// Lowest intent nonce `signer` can still have settled.
+    pub fn next_nonce(&self, signer: &AccountId) -> u64 {
+        self.nonces.get(signer).copied().unwrap_or(0)
+    }
+
+    /// Record `id` (valid up to `deadline`) as fulfilled at `height`, forgetting intents
+    /// whose deadline is before `height`. Returns false if it was already fulfilled.
+    pub fn mark_fulfilled(&mut self, id: Hash, deadline: u64, height: u64) -> bool {
+        self.fulfilled.retain(|_, d| *d >= height);
+        self.fulfilled.insert(id.0, deadline).is_none()
+    }
+
+    /// Use up `signer`'s intent nonces up to and including `nonce`.
+    pub fn consume_nonce(&mut self, signer: AccountId, nonce: u64) {
+        let next = self.nonces.entry(signer).or_insert(0);
+        *next = (*next).max(nonce.saturating_add(1));
+    }
+
+    /// Commitment to the record, or None when empty.
+    pub fn root(&self) -> Option<Hash> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut h = hasher();
+        h.update(b"boing/intents/v1");
+        h.update(&bincode::serialize(self).expect("intent registry serializes"));
+        Some(Hash(*h.finalize().as_bytes()))
+    }
+}
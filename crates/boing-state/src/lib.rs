@@ -2,9 +2,17 @@
 //!
 //! Enables stateless clients and compact proofs.
 
+mod intents;
+mod liveness;
+mod relays;
 mod sparse_merkle;
+mod staking;
 mod store;
 
+pub use intents::IntentRegistry;
+pub use liveness::{LivenessTracker, MissedDuties};
+pub use relays::RelayRegistry;
 pub use sparse_merkle::{MerkleProof, ProofStep, SparseMerkleTree};
+pub use staking::{StakingState, ValidatorRecord};
 pub use store::{ContractStorageEntry, StateCheckpoint, StateStore};
 pub use boing_primitives::{Account, AccountId, AccountState, Hash};
@@ -0,0 +1,140 @@
+//! Liveness tracking — missed proposals and votes per validator over a sliding window.
+//!
+//! Every applied block reveals two kinds of missed duties. Views skipped between the
+//! previous block and this one had leaders that did not get a block through, and the
+//! block's `justify` QC lists who voted for the previous block (including votes that came
+//! in after quorum, which the leader adds to the QC), so every other validator
+//! of that block's set missed its vote. Both are derived from the chain alone, so every
+//! node records the same misses. Misses are kept by height and forgotten once they fall
+//! out of the window.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use boing_primitives::{AccountId, ValidatorSet};
+
+/// Heights at which a validator missed a proposal or a vote, oldest first.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissedDuties {
+    pub proposals: VecDeque<u64>,
+    pub votes: VecDeque<u64>,
+}
+
+impl MissedDuties {
+    /// Missed proposals and votes together.
+    pub fn total(&self) -> u64 {
+        (self.proposals.len() + self.votes.len()) as u64
+    }
+
+    fn prune(&mut self, oldest: u64) {
+        while self.proposals.front().is_some_and(|h| *h < oldest) {
+            self.proposals.pop_front();
+        }
+        while self.votes.front().is_some_and(|h| *h < oldest) {
+            self.votes.pop_front();
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.proposals.is_empty() && self.votes.is_empty()
+    }
+}
+
+/// Sliding-window record of missed duties, part of the staking state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LivenessTracker {
+    /// View of the last applied block.
+    last_view: u64,
+    /// Validator set of the last applied block: who should have voted for it.
+    last_voters: Vec<AccountId>,
+    missed: BTreeMap<AccountId, MissedDuties>,
+}
+
+impl LivenessTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nothing was ever recorded (no block applied since genesis).
+    pub fn is_empty(&self) -> bool {
+        self.last_view == 0 && self.last_voters.is_empty() && self.missed.is_empty()
+    }
+
+    /// Record the block at `height`, proposed in view `round` under `set`, whose `justify`
+    /// QC was signed by `signers`. Misses older than `window` blocks are dropped. Views
+    /// skipped before the block count against their leaders in `set`, at most `window` of
+    /// them.
+    pub fn record_block(
+        &mut self,
+        height: u64,
+        round: u64,
+        set: &ValidatorSet,
+        signers: &[AccountId],
+        window: u64,
+    ) {
+        let first_skipped = (self.last_view + 1).max(round.saturating_sub(window));
+        for view in first_skipped..round {
+            self.missed.entry(set.leader(view)).or_default().proposals.push_back(height);
+        }
+        for voter in std::mem::take(&mut self.last_voters) {
+            if !signers.contains(&voter) {
+                self.missed.entry(voter).or_default().votes.push_back(height);
+            }
+        }
+        self.last_view = round;
+        self.last_voters = set.iter().map(|v| v.id).collect();
+
+        let oldest = (height + 1).saturating_sub(window);
+        self.missed.retain(|_, duties| {
+            duties.prune(oldest);
+            !duties.is_empty()
+        });
+    }
+
+    /// Misses of `id` within the window (empty if none).
+    pub fn missed(&self, id: &AccountId) -> MissedDuties {
+        self.missed.get(id).cloned().unwrap_or_default()
+    }
+
+    /// Validators with more than `max_missed` misses in the window, ordered by id.
+    pub fn over_limit(&self, max_missed: u64) -> Vec<AccountId> {
+        self.missed
+            .iter()
+            .filter(|(_, duties)| duties.total() > max_missed)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Forget the misses of `id` (after it was punished for them).
+    pub fn clear(&mut self, id: &AccountId) {
+        self.missed.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skipped_views_and_missing_signers_are_missed() {
+        let ids: Vec<AccountId> = (1..=3).map(|i| AccountId([i; 32])).collect();
+        let set = ValidatorSet::equal_weight(ids.clone());
+        let mut tracker = LivenessTracker::new();
+
+        tracker.record_block(1, 1, &set, &[], 10);
+        assert_eq!(tracker.missed(&ids[0]).total(), 0);
+
+        // Views 2 and 3 were skipped; only ids[0] and ids[1] signed block 1's QC.
+        tracker.record_block(2, 4, &set, &ids[..2], 10);
+        assert_eq!(tracker.missed(&set.leader(2)).proposals, VecDeque::from([2]));
+        assert_eq!(tracker.missed(&set.leader(3)).proposals, VecDeque::from([2]));
+        assert_eq!(tracker.missed(&ids[2]).votes, VecDeque::from([2]));
+        assert_eq!(tracker.over_limit(1), vec![ids[2]]);
+
+        // Misses fall out of the window.
+        tracker.record_block(12, 5, &set, &ids, 10);
+        assert_eq!(tracker.missed(&ids[2]).total(), 0);
+        assert!(tracker.over_limit(0).is_empty());
+    }
+}
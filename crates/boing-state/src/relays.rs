@@ -0,0 +1,105 @@
+//! Relay registry — staked STUN/TURN servers and the metrics reported for them this epoch.
+//!
+//! A relay's stake is held here, apart from its operator's bonded validator stake, so it
+//! never adds voting power. Reports are keyed by reporter, so each staked account counts
+//! once per relay and epoch; they are taken when the epoch is settled.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use boing_primitives::{AccountId, StunTurnMetrics, StunTurnRegistryEntry, StunTurnReputation};
+
+/// Registered relays and this epoch's reports, part of the staking state.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayRegistry {
+    relays: BTreeMap<AccountId, StunTurnRegistryEntry>,
+    /// Relay -> reporter -> latest metrics this epoch.
+    reports: BTreeMap<AccountId, BTreeMap<AccountId, StunTurnMetrics>>,
+}
+
+impl RelayRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relays.is_empty() && self.reports.is_empty()
+    }
+
+    /// Register `operator` at `multiaddr` with `stake`, starting at full reputation; for a
+    /// registered relay, add `stake`, update the address and cancel a pending exit.
+    /// Returns the relay's total stake.
+    pub fn register(&mut self, operator: AccountId, multiaddr: String, stake: u128, height: u64) -> u128 {
+        let entry = self.relays.entry(operator).or_insert_with(|| StunTurnRegistryEntry {
+            server_id: operator.0,
+            multiaddr: String::new(),
+            stake: 0,
+            reputation: StunTurnReputation::new(StunTurnReputation::MAX),
+            registered_at: height,
+            leaving: false,
+        });
+        entry.multiaddr = multiaddr;
+        entry.stake = entry.stake.saturating_add(stake);
+        entry.leaving = false;
+        entry.stake
+    }
+
+    pub fn get(&self, operator: &AccountId) -> Option<&StunTurnRegistryEntry> {
+        self.relays.get(operator)
+    }
+
+    /// Registered relays, ordered by operator id.
+    pub fn iter(&self) -> impl Iterator<Item = &StunTurnRegistryEntry> {
+        self.relays.values()
+    }
+
+    /// Operators of registered relays, ordered by id.
+    pub fn operators(&self) -> Vec<AccountId> {
+        self.relays.keys().copied().collect()
+    }
+
+    /// Record `metrics` for `relay` from `reporter`, replacing its earlier report this epoch.
+    pub fn report(&mut self, relay: AccountId, reporter: AccountId, metrics: StunTurnMetrics) {
+        self.reports.entry(relay).or_default().insert(reporter, metrics);
+    }
+
+    /// Reports for `relay` this epoch, ordered by reporter id.
+    pub fn reports(&self, relay: &AccountId) -> Vec<StunTurnMetrics> {
+        self.reports
+            .get(relay)
+            .map(|r| r.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Remove and return the reports for `relay` (when settling the epoch).
+    pub fn take_reports(&mut self, relay: &AccountId) -> Vec<StunTurnMetrics> {
+        self.reports
+            .remove(relay)
+            .map(|r| r.into_values().collect())
+            .unwrap_or_default()
+    }
+
+    /// Mark `operator`'s relay as leaving. Returns false if it is not registered.
+    pub fn mark_leaving(&mut self, operator: &AccountId) -> bool {
+        match self.relays.get_mut(operator) {
+            Some(entry) => {
+                entry.leaving = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_reputation(&mut self, operator: &AccountId, reputation: StunTurnReputation) {
+        if let Some(entry) = self.relays.get_mut(operator) {
+            entry.reputation = reputation;
+        }
+    }
+
+    /// Drop `operator`'s relay and its reports, returning the entry (with its stake).
+    pub fn remove(&mut self, operator: &AccountId) -> Option<StunTurnRegistryEntry> {
+        self.reports.remove(operator);
+        self.relays.remove(operator)
+    }
+}
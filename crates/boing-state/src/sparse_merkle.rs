@@ -38,6 +38,13 @@ impl SparseMerkleTree {
         self.root_cache = None;
     }
 
+    /// Insert a leaf with a precomputed value hash (state outside the account map, under a
+    /// reserved key).
+    pub fn insert_leaf(&mut self, key: [u8; 32], value_hash: Hash) {
+        self.leaves.insert(key, value_hash.0);
+        self.root_cache = None;
+    }
+
     pub fn get(&self, key: &AccountId) -> Option<&[u8; 32]> {
         self.leaves.get(&key.0)
     }
@@ -0,0 +1,334 @@
+//! Staking state — registered validators, delegations, unbonding queues and slashing.
+//!
+//! A validator's voting power is its own bonded stake plus what is delegated to it.
+//! Unbonded stake is held here until its release height so that it can still be slashed
+//! for misbehaviour committed while it was bonded. Slashes are recorded in a
+//! [`SlashRegistry`] together with what was taken from whom, so an approved appeal can
+//! refund it; slashed validators are jailed out of future validator sets. Missed proposals
+//! and votes are tracked here too (see [`LivenessTracker`]), as are staked STUN/TURN relays
+//! (see [`RelayRegistry`]). The whole module is committed to the state root as a single
+//! leaf under a reserved key.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use boing_governance::SlashRegistry;
+use boing_primitives::{hasher, AccountId, Hash, UnbondingEntry};
+
+use crate::liveness::LivenessTracker;
+use crate::relays::RelayRegistry;
+
+/// Reserved state-tree key for the staking commitment. Not a valid Ed25519 account in
+/// practice (it is a domain-separated hash).
+pub fn staking_key() -> [u8; 32] {
+    let mut h = hasher();
+    h.update(b"boing/state/staking");
+    *h.finalize().as_bytes()
+}
+
+/// A validator registered to accept delegations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorRecord {
+    /// Share of block rewards the validator keeps before splitting with delegators.
+    pub commission_bps: u16,
+}
+
+/// Consensus state for staking beyond the per-account bonded amount.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakingState {
+    /// Pending unbonds per account, in the order they were queued.
+    unbonding: BTreeMap<AccountId, Vec<UnbondingEntry>>,
+    validators: BTreeMap<AccountId, ValidatorRecord>,
+    /// Validator -> delegator -> delegated amount.
+    delegations: BTreeMap<AccountId, BTreeMap<AccountId, u128>>,
+    slashes: SlashRegistry,
+    /// Stake taken by each slash, per owner (for refunds on appeal).
+    deductions: BTreeMap<u64, Vec<(AccountId, u128)>>,
+    /// (validator, view) pairs already punished for equivocation.
+    punished: BTreeSet<(AccountId, u64)>,
+    /// Jailed validators by the height from which they may be released.
+    jailed: BTreeMap<AccountId, u64>,
+    liveness: LivenessTracker,
+    relays: RelayRegistry,
+}
+
+impl StakingState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unbonding.is_empty()
+            && self.validators.is_empty()
+            && self.delegations.is_empty()
+            && self.slashes.is_empty()
+            && self.jailed.is_empty()
+            && self.liveness.is_empty()
+            && self.relays.is_empty()
+    }
+
+    /// Register `id` as a validator, or update its commission if already registered.
+    pub fn register_validator(&mut self, id: AccountId, commission_bps: u16) {
+        self.validators.insert(id, ValidatorRecord { commission_bps });
+    }
+
+    pub fn validator(&self, id: &AccountId) -> Option<&ValidatorRecord> {
+        self.validators.get(id)
+    }
+
+    /// Registered validators, ordered by id.
+    pub fn validators(&self) -> impl Iterator<Item = (&AccountId, &ValidatorRecord)> {
+        self.validators.iter()
+    }
+
+    /// Add `amount` to the delegation of `delegator` to `validator`.
+    pub fn delegate(&mut self, validator: AccountId, delegator: AccountId, amount: u128) {
+        let entry = self.delegations.entry(validator).or_default().entry(delegator).or_default();
+        *entry = entry.saturating_add(amount);
+    }
+
+    /// Remove `amount` from the delegation of `delegator` to `validator`. Returns false (and
+    /// changes nothing) if the delegation is smaller than `amount`.
+    pub fn undelegate(&mut self, validator: &AccountId, delegator: &AccountId, amount: u128) -> bool {
+        let Some(delegators) = self.delegations.get_mut(validator) else {
+            return false;
+        };
+        let Some(delegated) = delegators.get_mut(delegator) else {
+            return false;
+        };
+        let Some(rest) = delegated.checked_sub(amount) else {
+            return false;
+        };
+        *delegated = rest;
+        if rest == 0 {
+            delegators.remove(delegator);
+        }
+        if delegators.is_empty() {
+            self.delegations.remove(validator);
+        }
+        true
+    }
+
+    /// Amount `delegator` has delegated to `validator`.
+    pub fn delegation(&self, validator: &AccountId, delegator: &AccountId) -> u128 {
+        self.delegations
+            .get(validator)
+            .and_then(|d| d.get(delegator))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Total delegated to `validator`.
+    pub fn delegated_to(&self, validator: &AccountId) -> u128 {
+        self.delegations
+            .get(validator)
+            .map(|d| d.values().sum())
+            .unwrap_or(0)
+    }
+
+    /// Delegators of `validator` with their amounts, ordered by delegator id.
+    pub fn delegators(&self, validator: &AccountId) -> Vec<(AccountId, u128)> {
+        self.delegations
+            .get(validator)
+            .map(|d| d.iter().map(|(id, amount)| (*id, *amount)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Positions of `delegator`: (validator, amount), ordered by validator id.
+    pub fn delegations_of(&self, delegator: &AccountId) -> Vec<(AccountId, u128)> {
+        self.delegations
+            .iter()
+            .filter_map(|(validator, d)| d.get(delegator).map(|amount| (*validator, *amount)))
+            .collect()
+    }
+
+    /// Pending unbonds of `id` (empty if none).
+    pub fn unbonding(&self, id: &AccountId) -> &[UnbondingEntry] {
+        self.unbonding.get(id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Total stake `id` has waiting in the queue.
+    pub fn unbonding_total(&self, id: &AccountId) -> u128 {
+        self.unbonding(id).iter().map(|e| e.amount).sum()
+    }
+
+    /// Queue `amount`, bonded to `validator`, for `id`; withdrawable from `release_height`.
+    pub fn queue_unbond(&mut self, id: AccountId, validator: AccountId, amount: u128, release_height: u64) {
+        self.unbonding
+            .entry(id)
+            .or_default()
+            .push(UnbondingEntry { validator, amount, release_height });
+    }
+
+    /// Remove the entries of `id` released at or before `height` and return their sum.
+    pub fn withdraw_matured(&mut self, id: &AccountId, height: u64) -> u128 {
+        let Some(entries) = self.unbonding.get_mut(id) else {
+            return 0;
+        };
+        let mut released = 0u128;
+        entries.retain(|e| {
+            let matured = e.release_height <= height;
+            if matured {
+                released = released.saturating_add(e.amount);
+            }
+            !matured
+        });
+        if entries.is_empty() {
+            self.unbonding.remove(id);
+        }
+        released
+    }
+
+    /// Take `bps` basis points of every delegation to `validator` and of every unbonding
+    /// entry still bonded to it. Returns what was taken from each owner, ordered by id.
+    /// The validator's own bonded stake lives in its account and is not touched here.
+    pub fn slash_bonded_to(&mut self, validator: &AccountId, bps: u16) -> Vec<(AccountId, u128)> {
+        let cut = |amount: &mut u128| {
+            let taken = *amount * bps as u128 / 10_000;
+            *amount -= taken;
+            taken
+        };
+        let mut taken: BTreeMap<AccountId, u128> = BTreeMap::new();
+        if let Some(delegators) = self.delegations.get_mut(validator) {
+            for (delegator, amount) in delegators.iter_mut() {
+                *taken.entry(*delegator).or_default() += cut(amount);
+            }
+            delegators.retain(|_, amount| *amount > 0);
+            if delegators.is_empty() {
+                self.delegations.remove(validator);
+            }
+        }
+        for (owner, entries) in self.unbonding.iter_mut() {
+            for entry in entries.iter_mut().filter(|e| e.validator == *validator) {
+                *taken.entry(*owner).or_default() += cut(&mut entry.amount);
+            }
+        }
+        taken.into_iter().filter(|(_, amount)| *amount > 0).collect()
+    }
+
+    /// Slashes and appeals.
+    pub fn slashes(&self) -> &SlashRegistry {
+        &self.slashes
+    }
+
+    pub fn slashes_mut(&mut self) -> &mut SlashRegistry {
+        &mut self.slashes
+    }
+
+    /// Remember what `slash_id` took from each owner.
+    pub fn record_deductions(&mut self, slash_id: u64, taken: Vec<(AccountId, u128)>) {
+        self.deductions.insert(slash_id, taken);
+    }
+
+    /// What `slash_id` took from each owner, removed so it is refunded at most once.
+    pub fn take_deductions(&mut self, slash_id: u64) -> Vec<(AccountId, u128)> {
+        self.deductions.remove(&slash_id).unwrap_or_default()
+    }
+
+    /// Record that `validator` was punished for equivocating in `view`. Returns false if it
+    /// already was (the same offence is slashed once).
+    pub fn mark_punished(&mut self, validator: AccountId, view: u64) -> bool {
+        self.punished.insert((validator, view))
+    }
+
+    /// Exclude `validator` from validator sets until it is released, no earlier than
+    /// `release_height` (`u64::MAX`: only an approved appeal releases it).
+    pub fn jail(&mut self, validator: AccountId, release_height: u64) {
+        let entry = self.jailed.entry(validator).or_insert(release_height);
+        *entry = (*entry).max(release_height);
+    }
+
+    pub fn unjail(&mut self, validator: &AccountId) {
+        self.jailed.remove(validator);
+    }
+
+    pub fn is_jailed(&self, validator: &AccountId) -> bool {
+        self.jailed.contains_key(validator)
+    }
+
+    /// Height from which a jailed validator may be released.
+    pub fn jailed_until(&self, validator: &AccountId) -> Option<u64> {
+        self.jailed.get(validator).copied()
+    }
+
+    /// Missed proposals and votes per validator.
+    pub fn liveness(&self) -> &LivenessTracker {
+        &self.liveness
+    }
+
+    pub fn liveness_mut(&mut self) -> &mut LivenessTracker {
+        &mut self.liveness
+    }
+
+    /// Registered STUN/TURN relays and their reports.
+    pub fn relays(&self) -> &RelayRegistry {
+        &self.relays
+    }
+
+    pub fn relays_mut(&mut self) -> &mut RelayRegistry {
+        &mut self.relays
+    }
+
+    /// Commitment to the staking state, or None when there is nothing to commit (genesis
+    /// state, so the genesis root is the plain account root).
+    pub fn root(&self) -> Option<Hash> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut h = hasher();
+        h.update(b"boing/staking/v1");
+        h.update(&bincode::serialize(self).expect("staking state serializes"));
+        Some(Hash(*h.finalize().as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_withdraw_only_matured_entries() {
+        let a = AccountId([1u8; 32]);
+        let mut staking = StakingState::new();
+        staking.queue_unbond(a, a, 100, 10);
+        staking.queue_unbond(a, a, 50, 20);
+        assert_eq!(staking.unbonding_total(&a), 150);
+        assert_eq!(staking.withdraw_matured(&a, 9), 0);
+        assert_eq!(staking.withdraw_matured(&a, 10), 100);
+        assert_eq!(staking.unbonding(&a), &[UnbondingEntry { validator: a, amount: 50, release_height: 20 }]);
+        assert_eq!(staking.withdraw_matured(&a, 25), 50);
+        assert!(staking.is_empty());
+        assert!(staking.root().is_none());
+    }
+
+    #[test]
+    fn test_delegations_by_validator_and_delegator() {
+        let (v, d1, d2) = (AccountId([1u8; 32]), AccountId([2u8; 32]), AccountId([3u8; 32]));
+        let mut staking = StakingState::new();
+        staking.register_validator(v, 500);
+        staking.delegate(v, d1, 100);
+        staking.delegate(v, d2, 50);
+        staking.delegate(v, d1, 10);
+        assert_eq!(staking.delegated_to(&v), 160);
+        assert_eq!(staking.delegators(&v), vec![(d1, 110), (d2, 50)]);
+        assert_eq!(staking.delegations_of(&d2), vec![(v, 50)]);
+
+        assert!(!staking.undelegate(&v, &d2, 51));
+        assert!(staking.undelegate(&v, &d2, 50));
+        assert_eq!(staking.delegations_of(&d2), vec![]);
+        assert_eq!(staking.delegated_to(&v), 110);
+    }
+
+    #[test]
+    fn test_slash_bonded_to_takes_delegations_and_unbonding() {
+        let (v, other, d) = (AccountId([1u8; 32]), AccountId([2u8; 32]), AccountId([3u8; 32]));
+        let mut staking = StakingState::new();
+        staking.delegate(v, d, 1_000);
+        staking.queue_unbond(d, v, 200, 50);
+        staking.queue_unbond(d, other, 300, 50);
+        assert_eq!(staking.slash_bonded_to(&v, 500), vec![(d, 60)]);
+        assert_eq!(staking.delegation(&v, &d), 950);
+        assert_eq!(staking.unbonding_total(&d), 490);
+    }
+}
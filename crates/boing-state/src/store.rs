@@ -2,9 +2,11 @@
 
 use std::collections::HashMap;
 
-use boing_primitives::{Account, AccountId, AccountState, Hash};
+use boing_primitives::{hasher, Account, AccountId, AccountState, Hash};
 
+use crate::intents::{intents_key, IntentRegistry};
 use crate::sparse_merkle::SparseMerkleTree;
+use crate::staking::{staking_key, StakingState};
 
 /// Type alias for persisted contract storage entries: ((contract, key), value).
 pub type ContractStorageEntry = ((AccountId, [u8; 32]), [u8; 32]);
@@ -22,6 +24,16 @@ pub struct StateCheckpoint {
     accounts: HashMap<AccountId, AccountState>,
     contract_code: HashMap<AccountId, Vec<u8>>,
     contract_storage: HashMap<(AccountId, [u8; 32]), [u8; 32]>,
+    staking: StakingState,
+    intents: IntentRegistry,
+    beacon: Hash,
+}
+
+/// Reserved state-tree key for the randomness beacon.
+fn beacon_key() -> [u8; 32] {
+    let mut h = hasher();
+    h.update(b"boing/state/beacon");
+    *h.finalize().as_bytes()
 }
 
 /// State store with Sparse Merkle tree for state_root.
@@ -33,6 +45,12 @@ pub struct StateStore {
     pub contract_code: HashMap<AccountId, Vec<u8>>,
     /// Contract storage: (contract, key) -> value.
     pub contract_storage: HashMap<(AccountId, [u8; 32]), [u8; 32]>,
+    /// Unbonding queues; committed to the state root under a reserved key.
+    staking: StakingState,
+    /// Fulfilled intents; committed under a reserved key.
+    intents: IntentRegistry,
+    /// Randomness beacon of the last applied block; committed under a reserved key.
+    beacon: Hash,
 }
 
 impl StateStore {
@@ -61,14 +79,52 @@ impl StateStore {
         self.contract_code.get(account)
     }
 
+    pub fn staking(&self) -> &StakingState {
+        &self.staking
+    }
+
+    pub fn staking_mut(&mut self) -> &mut StakingState {
+        &mut self.staking
+    }
+
+    pub fn intents(&self) -> &IntentRegistry {
+        &self.intents
+    }
+
+    pub fn intents_mut(&mut self) -> &mut IntentRegistry {
+        &mut self.intents
+    }
+
     /// Compute state root from Sparse Merkle tree. Rebuilds tree from current
     /// accounts to include changes made via get_mut (e.g. by the VM).
     pub fn state_root(&mut self) -> Hash {
+        self.rebuild_tree();
+        self.tree.root()
+    }
+
+    fn rebuild_tree(&mut self) {
         self.tree = SparseMerkleTree::new();
         for (id, state) in &self.accounts {
             self.tree.insert(*id, state);
         }
-        self.tree.root()
+        if let Some(root) = self.staking.root() {
+            self.tree.insert_leaf(staking_key(), root);
+        }
+        if let Some(root) = self.intents.root() {
+            self.tree.insert_leaf(intents_key(), root);
+        }
+        if self.beacon != Hash::ZERO {
+            self.tree.insert_leaf(beacon_key(), self.beacon);
+        }
+    }
+
+    /// Randomness beacon of the last applied block (zero at genesis).
+    pub fn beacon(&self) -> Hash {
+        self.beacon
+    }
+
+    pub fn set_beacon(&mut self, beacon: Hash) {
+        self.beacon = beacon;
     }
 
     /// Merge account state from parallel execution view.
@@ -93,6 +149,9 @@ impl StateStore {
         for ((contract, key), value) in &self.contract_storage {
             out.merge_contract_storage(*contract, *key, *value);
         }
+        out.staking = self.staking.clone();
+        out.intents = self.intents.clone();
+        out.beacon = self.beacon;
         out
     }
 
@@ -102,6 +161,9 @@ impl StateStore {
             accounts: self.accounts.iter().map(|(k, v)| (*k, v.clone())).collect(),
             contract_code: self.contract_code.iter().map(|(k, v)| (*k, v.clone())).collect(),
             contract_storage: self.contract_storage.iter().map(|(k, v)| (*k, *v)).collect(),
+            staking: self.staking.clone(),
+            intents: self.intents.clone(),
+            beacon: self.beacon,
         }
     }
 
@@ -110,10 +172,10 @@ impl StateStore {
         self.accounts = cp.accounts;
         self.contract_code = cp.contract_code;
         self.contract_storage = cp.contract_storage;
-        self.tree = SparseMerkleTree::new();
-        for (id, st) in &self.accounts {
-            self.tree.insert(*id, st);
-        }
+        self.staking = cp.staking;
+        self.intents = cp.intents;
+        self.beacon = cp.beacon;
+        self.rebuild_tree();
     }
 
     /// Generate Merkle proof for an account. Ensures tree is synced with accounts.
@@ -149,11 +211,19 @@ impl StateStore {
         state
     }
 
-    /// Top N accounts by stake (for validator set derivation).
+    /// Voting power of `id`: its own bonded stake plus stake delegated to it.
+    pub fn voting_power(&self, id: &AccountId) -> u128 {
+        let own = self.accounts.get(id).map(|s| s.stake).unwrap_or(0);
+        own.saturating_add(self.staking.delegated_to(id))
+    }
+
+    /// Top N accounts by voting power (for validator set derivation). Ties are broken by
+    /// account id so every node derives the same order.
     pub fn top_stakers(&self, n: usize) -> Vec<AccountId> {
-        let mut accounts: Vec<_> = self.accounts.iter().collect();
-        accounts.sort_by(|a, b| b.1.stake.cmp(&a.1.stake));
-        accounts.into_iter().take(n).map(|(id, _)| *id).collect()
+        let mut accounts: Vec<(AccountId, u128)> =
+            self.accounts.keys().map(|id| (*id, self.voting_power(id))).collect();
+        accounts.sort_by(|(a, pa), (b, pb)| pb.cmp(pa).then_with(|| a.0.cmp(&b.0)));
+        accounts.into_iter().take(n).map(|(id, _)| id).collect()
     }
 }
 
@@ -177,6 +247,20 @@ mod tests {
         assert!(state.get(&AccountId([3u8; 32])).is_none());
     }
 
+    #[test]
+    fn test_unbonding_queue_in_state_root_and_checkpoint() {
+        let mut state = StateStore::new();
+        let a = AccountId([1u8; 32]);
+        state.insert(Account { id: a, state: AccountState { balance: 100, nonce: 0, stake: 50 } });
+        let before = state.state_root();
+        let cp = state.checkpoint();
+        state.staking_mut().queue_unbond(a, a, 50, 10);
+        assert_ne!(state.state_root(), before);
+        state.revert(cp);
+        assert!(state.staking().unbonding(&a).is_empty());
+        assert_eq!(state.state_root(), before);
+    }
+
     #[test]
     fn test_top_stakers() {
         let mut state = StateStore::new();
@@ -190,5 +274,10 @@ mod tests {
         assert_eq!(top.len(), 2);
         assert_eq!(top[0], b);
         assert_eq!(top[1], c);
+
+        // Delegations count toward voting power.
+        state.staking_mut().delegate(a, c, 450);
+        assert_eq!(state.voting_power(&a), 550);
+        assert_eq!(state.top_stakers(1), vec![a]);
     }
 }